@@ -0,0 +1,76 @@
+use crate::detect::{is_target, is_target_file};
+use crate::longpath::extended;
+use crate::units::{age_of, unix_secs};
+use crate::CandidateDir;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use walkdir::WalkDir;
+
+/// Upper bound on the entries inspected per project so huge trees stay cheap.
+pub const MAX_ACTIVITY_SAMPLES: usize = 4000;
+
+/// How recently a folder may have been modified and still be written to by a running build.
+pub const DEFAULT_ACTIVE_WITHIN: Duration = Duration::from_secs(10 * 60);
+
+const IGNORED_DIRS: &[&str] = &[".git", ".hg", ".svn", ".idea", ".vscode"];
+
+/// Estimates when a project was last worked on from the newest source file mtime.
+///
+/// Artifact directories and VCS metadata are skipped, since they change on every
+/// build or fetch rather than when the code itself is edited.
+pub fn last_project_activity(project_dir: &Path) -> Option<u64> {
+    WalkDir::new(project_dir)
+        .into_iter()
+        .filter_entry(|entry| {
+            if entry.file_type().is_symlink() {
+                return false;
+            }
+            if entry.depth() == 0 || !entry.file_type().is_dir() {
+                return true;
+            }
+            let name = entry.file_name().to_string_lossy();
+            !is_target(&name) && !IGNORED_DIRS.contains(&name.as_ref())
+        })
+        .take(MAX_ACTIVITY_SAMPLES)
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && !is_target_file(&entry.file_name().to_string_lossy()))
+        .filter_map(|entry| entry.metadata().ok())
+        .filter_map(|metadata| metadata.modified().ok())
+        .map(unix_secs)
+        .max()
+}
+
+/// [`last_project_activity`] for a candidate: the newest of its project and, for a
+/// workspace's shared folder, of every member it serves.
+pub fn candidate_activity(candidate: &CandidateDir) -> Option<u64> {
+    let project = candidate.path.parent()?;
+    std::iter::once(project).chain(candidate.serves.iter().map(PathBuf::as_path)).filter_map(last_project_activity).max()
+}
+
+/// The newest modification time of `path` itself and its direct entries, which is when a
+/// build or install last touched an artifact folder; `None` when it can't be read.
+pub fn last_modified(path: &Path) -> Option<u64> {
+    let path = extended(path);
+    let metadata = std::fs::symlink_metadata(&path).ok()?;
+    let own = unix_secs(metadata.modified().ok()?);
+    if !metadata.is_dir() {
+        return Some(own);
+    }
+    let entries = match std::fs::read_dir(&path) {
+        Ok(entries) => entries,
+        Err(_) => return Some(own),
+    };
+    let newest = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.metadata().ok())
+        .filter_map(|metadata| metadata.modified().ok())
+        .map(unix_secs)
+        .max();
+    Some(newest.map_or(own, |newest| newest.max(own)))
+}
+
+/// Whether something may still be writing to `candidate`: it or an entry at its top level
+/// was modified within `window`, going by [`last_modified`] as recorded by the scan.
+pub fn recently_modified(candidate: &CandidateDir, window: Duration) -> bool {
+    candidate.modified.is_some_and(|t| age_of(t) < window)
+}
@@ -0,0 +1,58 @@
+//! The scan cache: the last scan of every root, so a later run can be answered without
+//! walking the tree again (see [`ScanScope::answers`]).
+
+use crate::{CandidateDir, ScanScope};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Every cached scan, by the canonical root it was made for, so scanning one tree never
+/// replaces what was cached for another.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanCache {
+    pub roots: BTreeMap<PathBuf, CachedScan>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedScan {
+    /// When the scan ran, as seconds since the Unix epoch.
+    pub scanned_at: u64,
+    /// The scan stopped early (`--find-at-least`), so a full run has to rescan.
+    #[serde(default)]
+    pub partial: bool,
+    /// What the scan looked for; caches written before it was recorded can't be filtered safely.
+    #[serde(default)]
+    pub scope: Option<ScanScope>,
+    pub candidates: Vec<CandidateDir>,
+}
+
+impl ScanCache {
+    /// The key of the scan made for `wanted`'s root, or failing that of the closest scan
+    /// of a folder above it, which is filtered down to the root when used.
+    pub fn entry_for(&self, wanted: &ScanScope) -> Option<PathBuf> {
+        let exact = cache_key(&wanted.root);
+        if self.roots.contains_key(&exact) {
+            return Some(exact);
+        }
+        self.roots.keys().filter(|root| exact.starts_with(root)).max_by_key(|root| root.components().count()).cloned()
+    }
+}
+
+/// The cache key of a scan root: its canonical path, so `~/work`, `~/work/` and a
+/// symlink to it share one entry.
+pub fn cache_key(root: &Path) -> PathBuf {
+    fs::canonicalize(root).unwrap_or_else(|_| std::path::absolute(root).unwrap_or_else(|_| root.to_path_buf()))
+}
+
+/// Caches written before scans were kept per root (one list for whatever was scanned
+/// last) can't be attributed to a root; they read as empty and are replaced.
+pub fn load_cache(path: &Path) -> Option<ScanCache> {
+    serde_json::from_reader(io::BufReader::new(fs::File::open(path).ok()?)).ok()
+}
+
+pub fn write_cache(path: &Path, cached: &ScanCache) -> io::Result<()> {
+    let file = fs::File::create(path)?;
+    serde_json::to_writer(file, cached).map_err(io::Error::other)
+}
@@ -0,0 +1,98 @@
+//! The safety checks a path has to pass before DevPurge would delete it, usable on
+//! their own by scripts that do the deleting themselves.
+
+use crate::detect::{self, is_target_file, target_kind};
+use crate::exec::{self, ExecError};
+use crate::projects::{project_kind, risk_of, Risk};
+use directories::BaseDirs;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+const GIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Whether one path is safe to delete, and why not.
+#[derive(Debug, Clone, Serialize)]
+pub struct Verdict {
+    pub path: PathBuf,
+    pub ok: bool,
+    /// The target rule it matched, e.g. `node_modules`.
+    pub kind: Option<String>,
+    /// `caution` for targets that sometimes hold files put there by hand, like `vendor`.
+    pub risk: Risk,
+    pub reasons: Vec<String>,
+    /// Checks that couldn't be run, e.g. the git check under `--no-exec`; they don't fail the path.
+    pub notes: Vec<String>,
+}
+
+/// Runs every safety check on `path`: it must exist, match a target name, have its
+/// project's marker file next to it, stay clear of system and protected paths, and
+/// contain nothing tracked by git.
+pub fn check_path(path: &Path, protected: &[PathBuf]) -> Verdict {
+    let mut reasons = Vec::new();
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+
+    let metadata = std::fs::symlink_metadata(&path);
+    let is_file = metadata.as_ref().is_ok_and(|m| m.is_file());
+    if metadata.is_err() {
+        reasons.push("does not exist".to_string());
+    }
+
+    let kind = target_kind(&name, is_file).map(str::to_string);
+    if kind.is_none() && !name.is_empty() {
+        reasons.push(format!("'{}' is not a known target name", name));
+    } else if metadata.is_ok() {
+        let marked = if is_file && is_target_file(&name) {
+            detect::is_safe_to_delete_file(&name, &path)
+        } else {
+            detect::is_safe_to_delete(&name, &path)
+        };
+        if !marked {
+            reasons.push("no matching project file next to it".to_string());
+        }
+    }
+
+    if let Some(reason) = system_path_reason(&path) {
+        reasons.push(reason);
+    }
+    if let Some(guard) = protected.iter().find(|p| path.starts_with(p) || p.starts_with(&path)) {
+        reasons.push(format!("protected by {}", guard.display()));
+    }
+    let mut notes = Vec::new();
+    if metadata.is_ok() {
+        match has_tracked_files(&path) {
+            Ok(true) => reasons.push("contains files tracked by git".to_string()),
+            Ok(false) => {}
+            Err(e) => notes.push(format!("git-tracked check unknown: {}", e)),
+        }
+    }
+
+    let risk = kind.as_deref().map_or(Risk::Safe, |kind| risk_of(kind, project_kind(&path, is_file), is_file));
+    Verdict { ok: reasons.is_empty(), path, kind, risk, reasons, notes }
+}
+
+/// Filesystem roots, the home directory and anything above it are never candidates.
+fn system_path_reason(path: &Path) -> Option<String> {
+    if path.parent().is_none() || path.components().count() < 3 {
+        return Some("too close to the filesystem root".to_string());
+    }
+    let home = BaseDirs::new()?.home_dir().to_path_buf();
+    home.starts_with(path).then(|| "is the home directory or one of its parents".to_string())
+}
+
+/// Asks git whether anything inside is tracked; false when it isn't in a repository.
+fn has_tracked_files(path: &Path) -> Result<bool, ExecError> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else { return Ok(false) };
+    let mut git = Command::new("git");
+    git.arg("-C").arg(dir).args(["ls-files", "-z", "--"]).arg(name).env("GIT_TERMINAL_PROMPT", "0");
+    let out = exec::run(&mut git, GIT_TIMEOUT)?;
+    Ok(out.status.success() && !out.stdout.is_empty())
+}
+
+/// Splits `verify --stdin` input: NUL-separated when it contains a NUL, otherwise one path per line.
+pub fn split_paths(input: &str) -> Vec<PathBuf> {
+    let parts: Vec<&str> = if input.contains('\0') { input.split('\0').collect() } else { input.lines().collect() };
+    parts.into_iter().filter(|p| !p.trim().is_empty()).map(PathBuf::from).collect()
+}
@@ -0,0 +1,182 @@
+use crate::confirm::EscalationStyle;
+use crate::detect::{validate_targets, CustomTarget};
+use crate::severity::Severity;
+use crate::units::{parse_duration, parse_size};
+use anyhow::{Context, Result};
+use directories::ProjectDirs;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// User settings read from `config.toml` in the platform config directory.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Same syntax as `--min-size`, e.g. "node_modules=200M,target=1G,default=50M".
+    pub min_size: Option<String>,
+    /// Reflect scan and deletion progress in the terminal title.
+    pub set_title: bool,
+    /// Selections at least this large (e.g. "100G") need the escalated confirmation.
+    pub large_selection_size: Option<String>,
+    /// Selections with more folders than this need the escalated confirmation.
+    pub large_selection_count: Option<usize>,
+    pub large_selection_confirm: Option<EscalationStyle>,
+    /// Folders of one kind below this size (e.g. "5M") share a single row in the list.
+    pub small_below: Option<String>,
+    /// Folders that grew by more than this factor since they were sized are confirmed again.
+    pub grown_factor: Option<f64>,
+    /// Paths that are never scanned into and never pass `check`/`verify`.
+    pub protected: Vec<PathBuf>,
+    /// Offered as the path to scan when `--path` isn't given.
+    pub default_path: Option<PathBuf>,
+    /// Directories that are never scanned into, e.g. tool caches under the home directory.
+    pub excludes: Vec<PathBuf>,
+    /// Folder names never looked inside, on top of `.git`, `.hg` and `.svn`; `*` and `?` work.
+    pub prune: Vec<String>,
+    /// Directories that don't open within this long (e.g. "10s") are skipped; "0" waits forever.
+    pub dir_timeout: Option<String>,
+    /// Folders modified within this long (e.g. "10m") may still be being written to and
+    /// aren't preselected.
+    pub active_within: Option<String>,
+    /// Never run external programs, like `--no-exec` on every run.
+    pub no_exec: bool,
+    /// `false` turns the scan cache off, like `--no-cache` on every run.
+    pub cache: Option<bool>,
+    /// Cached scans older than this (e.g. "12h", "2d") are ignored; "0" keeps them forever.
+    pub max_cache_age: Option<String>,
+    /// Built-in targets to stop looking for, e.g. `["vendor"]`.
+    pub disabled_targets: Vec<String>,
+    pub colors: ColorConfig,
+    /// Extra targets from `[[targets]]` tables, each with the marker files that make it safe.
+    pub targets: Vec<CustomTarget>,
+}
+
+/// Thresholds for coloring sizes and ages, from the `[colors]` table.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorConfig {
+    /// Three sizes separating green, yellow, red and bold red (e.g. `["100M", "1G", "10G"]`).
+    pub size: Option<Vec<String>>,
+    /// Two ages: younger than the first is dimmed, at least the second is green.
+    pub age: Option<Vec<String>>,
+}
+
+impl ColorConfig {
+    pub fn severity(&self) -> Result<Severity> {
+        let mut severity = Severity::default();
+        if let Some(sizes) = &self.size {
+            let parsed = sizes.iter().map(|s| parse_size(s)).collect::<Result<Vec<_>, _>>().map_err(anyhow::Error::msg)?;
+            severity.size = parsed.try_into().map_err(|_| anyhow::anyhow!("colors.size needs exactly three sizes"))?;
+        }
+        if let Some(ages) = &self.age {
+            let parsed = ages.iter().map(|s| parse_duration(s)).collect::<Result<Vec<_>, _>>().map_err(anyhow::Error::msg)?;
+            severity.age = parsed.try_into().map_err(|_| anyhow::anyhow!("colors.age needs exactly two ages"))?;
+        }
+        Ok(severity)
+    }
+}
+
+pub fn default_config_path() -> Option<PathBuf> {
+    match xdg_dir("XDG_CONFIG_HOME") {
+        Some(dir) => Some(dir.join("config.toml")),
+        None => ProjectDirs::from("com", "devpurge", "devpurge").map(|dirs| dirs.config_dir().join("config.toml")),
+    }
+}
+
+/// Our directory under an XDG base directory variable, when it is set.
+///
+/// These are honored on every platform, not only where `directories` consults them;
+/// relative values are ignored, as the XDG spec requires.
+pub fn xdg_dir(var: &str) -> Option<PathBuf> {
+    let base = PathBuf::from(std::env::var_os(var)?);
+    base.is_absolute().then(|| base.join("devpurge"))
+}
+
+/// Loads the config file; a missing file yields the defaults, a broken one is an error.
+pub fn load_config(path: &Path) -> Result<Config> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+        Err(e) => return Err(e).with_context(|| format!("failed to read config {}", path.display())),
+    };
+    parse_config(&text).with_context(|| format!("invalid config {}", path.display()))
+}
+
+/// Quotes a string as a TOML basic string.
+pub fn toml_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Adds `paths` to the top-level array `key` of a config file's text, keeping everything
+/// else as written. A missing key is added above the first table.
+pub fn add_to_list(text: &str, key: &str, paths: &[PathBuf]) -> Result<String> {
+    let items: Vec<String> = paths.iter().map(|p| toml_string(&p.to_string_lossy())).collect();
+    let mut updated = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('[') {
+            break;
+        }
+        let assigns_key = trimmed.strip_prefix(key).is_some_and(|rest| rest.trim_start().starts_with('='));
+        if let Some(close) = assigns_key.then(|| closing_bracket(&text[offset..])).flatten() {
+            let at = offset + close;
+            let before = text[..at].trim_end();
+            let separator = if before.ends_with('[') || before.ends_with(',') { "" } else { ", " };
+            updated = Some(format!("{}{}{}{}", &text[..at], separator, items.join(", "), &text[at..]));
+            break;
+        }
+        offset += line.len();
+    }
+    let updated = updated.unwrap_or_else(|| {
+        let (head, tail) = text.split_at(offset);
+        let newline = if head.is_empty() || head.ends_with('\n') { "" } else { "\n" };
+        format!("{}{}{} = [{}]\n{}", head, newline, key, items.join(", "), tail)
+    });
+    parse_config(&updated).with_context(|| format!("could not add to `{}` without breaking the file", key))?;
+    Ok(updated)
+}
+
+/// Byte offset of the `]` closing the first array in `text`, skipping strings and comments.
+fn closing_bracket(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    let mut chars = text.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (Some('"'), '\\') => {
+                chars.next();
+            }
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => {
+                chars.by_ref().find(|&(_, c)| c == '\n');
+            }
+            (None, '[') => depth += 1,
+            (None, ']') if depth == 1 => return Some(i),
+            (None, ']') => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+pub fn parse_config(text: &str) -> Result<Config> {
+    let value = crate::minitoml::parse(text).map_err(anyhow::Error::msg)?;
+    let config: Config = serde_json::from_value(value)?;
+    validate_targets(&config.targets, &config.disabled_targets).map_err(anyhow::Error::msg)?;
+    Ok(config)
+}
@@ -0,0 +1,46 @@
+use serde::Deserialize;
+
+pub const DEFAULT_LARGE_SELECTION_BYTES: u64 = 100 << 30;
+pub const DEFAULT_LARGE_SELECTION_COUNT: usize = 500;
+
+/// How a large selection has to be confirmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum EscalationStyle {
+    /// Type the selection's total size rounded to whole gigabytes.
+    Size,
+    /// Type the word DELETE.
+    Word,
+}
+
+/// Thresholds above which a selection needs the escalated confirmation.
+#[derive(Debug, Clone, Copy)]
+pub struct LargeSelectionPolicy {
+    pub min_bytes: u64,
+    pub min_count: usize,
+    pub style: EscalationStyle,
+}
+
+impl Default for LargeSelectionPolicy {
+    fn default() -> Self {
+        LargeSelectionPolicy {
+            min_bytes: DEFAULT_LARGE_SELECTION_BYTES,
+            min_count: DEFAULT_LARGE_SELECTION_COUNT,
+            style: EscalationStyle::Size,
+        }
+    }
+}
+
+impl LargeSelectionPolicy {
+    pub fn applies(&self, count: usize, bytes: u64) -> bool {
+        bytes >= self.min_bytes || count > self.min_count
+    }
+
+    /// The exact text the user has to type to go ahead.
+    pub fn expected_answer(&self, bytes: u64) -> String {
+        match self.style {
+            EscalationStyle::Size => ((bytes as f64) / (1u64 << 30) as f64).round().to_string(),
+            EscalationStyle::Word => "DELETE".to_string(),
+        }
+    }
+}
@@ -0,0 +1,582 @@
+use crate::glob::glob_match;
+use crate::limits::{is_fd_exhaustion, note_fd_exhaustion, retry_exhausted};
+use crate::longpath::extended;
+use crate::projects::detect;
+use serde::Deserialize;
+use walkdir::WalkDir;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+pub const TARGET_DIRS: &[&str] = &[
+    "node_modules", // JS/TS
+    "target",       // Rust
+    "build",        // Java/Gradle/C++
+    "dist",         // Web
+    ".gradle",      // Gradle
+    "vendor",       // PHP/Go
+    "__pycache__",  // Python
+    ".venv", "venv", // Python virtualenvs
+    ".tox",         // tox
+    ".pytest_cache", ".mypy_cache", // pytest, mypy
+    "bin", "obj",   // .NET
+    ".dart_tool",   // Dart
+    ".angular",     // Angular
+    ".next",        // Next.js
+    ".nuxt",        // Nuxt.js
+    ".vs",          // Visual Studio
+    ".pio",         // PlatformIO
+    "build-*",      // Qt Creator shadow builds
+    "Pods",         // CocoaPods
+    "Build",        // Carthage (only as Carthage/Build)
+    "DerivedData",  // Xcode
+    ".terraform",   // Terraform
+    "_build", "deps", // Elixir/Erlang
+    ".stack-work",  // Haskell (stack)
+    "dist-newstyle", // Haskell (cabal)
+];
+
+/// Caches outside any project, relative to the home folder, that only `--system-caches`
+/// offers.
+pub const SYSTEM_CACHES: &[&str] = &["Library/Developer/Xcode/DerivedData"];
+
+/// Single regenerable files, offered alongside the directories.
+pub const TARGET_FILES: &[&str] = &[
+    ".eslintcache",          // ESLint
+    ".stylelintcache",       // Stylelint
+    "*.tsbuildinfo",         // TypeScript incremental builds
+    ".phpunit.result.cache", // PHPUnit
+];
+
+/// A target declared in the config or with `--target`: a directory (or with `file`, a
+/// file) name or pattern, and the files next to it that make it safe to delete.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomTarget {
+    pub name: String,
+    pub markers: Vec<String>,
+    #[serde(default)]
+    pub file: bool,
+    /// Matched on the name alone, which only `--target` with `--allow-unmarked` can ask for.
+    #[serde(skip)]
+    pub unmarked: bool,
+}
+
+impl CustomTarget {
+    fn matches(&self, name: &str, is_file: bool) -> bool {
+        self.file == is_file && glob_match(&self.name, name)
+    }
+
+    fn is_marked(&self, path: &Path) -> bool {
+        self.unmarked || path.parent().is_some_and(|parent| self.markers.iter().any(|m| has_file(parent, m)))
+    }
+
+    /// How `--target` writes it, e.g. `coverage:package.json`; the cache records this.
+    pub fn spec(&self) -> String {
+        match self.markers.is_empty() {
+            true => self.name.clone(),
+            false => format!("{}:{}", self.name, self.markers.join(",")),
+        }
+    }
+}
+
+/// Parses a `--target name:marker1,marker2` folder target. A bare name is refused unless
+/// `allow_unmarked`, since a name alone is how the wrong folder gets deleted.
+pub fn parse_ad_hoc_target(spec: &str, allow_unmarked: bool) -> Result<CustomTarget, String> {
+    let (name, markers) = spec.split_once(':').unwrap_or((spec, ""));
+    let name = name.trim().to_string();
+    let markers: Vec<String> = markers.split(',').map(str::trim).filter(|m| !m.is_empty()).map(str::to_string).collect();
+    if markers.is_empty() && !allow_unmarked {
+        return Err(format!(
+            "'{}' has no marker files; name the files that make it safe to delete, e.g. '{}:package.json', or add --allow-unmarked to match the name alone",
+            name, name
+        ));
+    }
+    Ok(CustomTarget { unmarked: markers.is_empty(), name, markers, file: false })
+}
+
+/// The built-in targets merged with the config's, fixed for the whole run.
+struct Rules {
+    custom: Vec<CustomTarget>,
+    disabled: Vec<String>,
+    dirs: Vec<String>,
+    files: Vec<String>,
+}
+
+static RULES: OnceLock<Rules> = OnceLock::new();
+
+fn rules() -> &'static Rules {
+    RULES.get_or_init(|| merge(Vec::new(), Vec::new()))
+}
+
+fn merge(custom: Vec<CustomTarget>, disabled: Vec<String>) -> Rules {
+    let active = |builtin: &[&str], file: bool| -> Vec<String> {
+        let builtin = builtin.iter().filter(|t| !disabled.iter().any(|d| d == *t)).map(|t| t.to_string());
+        let added = custom.iter().filter(|t| t.file == file).map(|t| t.name.clone());
+        let mut names: Vec<String> = builtin.chain(added).collect();
+        let mut seen = std::collections::HashSet::new();
+        names.retain(|n| seen.insert(n.clone()));
+        names
+    };
+    let (dirs, files) = (active(TARGET_DIRS, false), active(TARGET_FILES, true));
+    Rules { custom, disabled, dirs, files }
+}
+
+/// Checks the `targets` and `disabled_targets` of a config without applying them.
+pub fn validate_targets(custom: &[CustomTarget], disabled: &[String]) -> Result<(), String> {
+    for target in custom {
+        if target.name.trim().is_empty() || target.name.contains(['/', '\\']) {
+            return Err(format!("target name '{}' must be a single file or folder name", target.name));
+        }
+        if target.markers.is_empty() && !target.unmarked {
+            return Err(format!("target '{}' needs at least one marker file that makes it safe to delete", target.name));
+        }
+    }
+    if let Some(unknown) = disabled.iter().find(|d| !TARGET_DIRS.contains(&d.as_str()) && !TARGET_FILES.contains(&d.as_str())) {
+        return Err(format!("cannot disable '{}': built-in targets are {}", unknown, [TARGET_DIRS, TARGET_FILES].concat().join(", ")));
+    }
+    Ok(())
+}
+
+/// Adds the config's targets to the built-in ones and drops the disabled ones, for the
+/// rest of the run. Has to happen before anything looks at the targets.
+pub fn configure_targets(custom: &[CustomTarget], disabled: &[String]) -> Result<(), String> {
+    validate_targets(custom, disabled)?;
+    RULES
+        .set(merge(custom.to_vec(), disabled.to_vec()))
+        .map_err(|_| "targets were already in use before they were configured".to_string())
+}
+
+/// Every configured and `--target` rule, as [`CustomTarget::spec`] writes them.
+pub fn custom_target_specs() -> Vec<String> {
+    rules().custom.iter().map(CustomTarget::spec).collect()
+}
+
+/// The directory names and patterns looked for: [`TARGET_DIRS`] and the configured ones.
+pub fn target_dirs() -> &'static [String] {
+    &rules().dirs
+}
+
+/// The file names and patterns looked for: [`TARGET_FILES`] and the configured ones.
+pub fn target_files() -> &'static [String] {
+    &rules().files
+}
+
+/// Parses a `--targets` entry, which must name one of the [`target_dirs`] or [`target_files`] rules.
+pub fn parse_target(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if target_dirs().iter().chain(target_files()).any(|t| t == name) {
+        return Ok(name.to_string());
+    }
+    Err(format!("unknown target '{}'; known targets are {}", name, [target_dirs(), target_files()].concat().join(", ")))
+}
+
+/// Whether `name` matches one of the [`target_dirs`] names or patterns.
+pub fn is_target(name: &str) -> bool {
+    target_dirs().iter().any(|t| glob_match(t, name))
+}
+
+/// A built-in rule that matches `name` and wasn't disabled.
+fn builtin_rule(name: &str, is_file: bool) -> Option<&'static str> {
+    let builtin = if is_file { TARGET_FILES } else { TARGET_DIRS };
+    builtin.iter().copied().find(|t| glob_match(t, name)).filter(|t| !rules().disabled.iter().any(|d| d == t))
+}
+
+/// A configured target that matches `name` and has one of its markers next to `path`.
+fn custom_marked(name: &str, path: &Path, is_file: bool) -> bool {
+    rules().custom.iter().any(|t| t.matches(name, is_file) && t.is_marked(path))
+}
+
+fn has_file(path: &Path, file_name: &str) -> bool {
+    path.join(file_name).exists()
+}
+
+fn has_any_file(path: &Path, files: &[&str]) -> bool {
+    files.iter().any(|f| path.join(f).exists())
+}
+
+pub(crate) fn has_file_with_extension(path: &Path, extension: &str) -> bool {
+    if let Ok(entries) = retry_exhausted(|| std::fs::read_dir(path)) {
+        for entry in entries.flatten() {
+            if let Some(ext) = entry.path().extension() {
+                if ext == extension {
+                    return true;
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Whether the folder has what marks it as regenerable next to it: the built-in rule's
+/// project files, or the markers of a configured target of that name.
+pub fn is_safe_to_delete(dir_name: &str, path: &Path) -> bool {
+    custom_marked(dir_name, path, false) || (builtin_rule(dir_name, false).is_some() && detect(dir_name, path, false).is_some())
+}
+
+/// The rule a candidate was matched by: its name, or the pattern it matched (`build-*`).
+pub fn target_kind(name: &str, is_file: bool) -> Option<&'static str> {
+    let rules = if is_file { target_files() } else { target_dirs() };
+    rules.iter().find(|t| glob_match(t, name)).map(String::as_str)
+}
+
+pub fn is_target_file(name: &str) -> bool {
+    target_files().iter().any(|t| glob_match(t, name))
+}
+
+pub fn is_safe_to_delete_file(file_name: &str, path: &Path) -> bool {
+    custom_marked(file_name, path, true) || (builtin_rule(file_name, true).is_some() && detect(file_name, path, true).is_some())
+}
+
+/// Qt Creator names shadow builds `build-<project>-<kit>-<config>` and puts them next to
+/// the project. A CMake build says so with its `CMakeCache.txt`, so any project beside or
+/// above it will do; a bare `Makefile` is too common for that, so the name has to have
+/// that shape with `<project>` a sibling that is one.
+pub(crate) fn is_shadow_build(path: &Path) -> bool {
+    let (Some(name), Some(parent)) = (path.file_name().and_then(|n| n.to_str()), path.parent()) else { return false };
+    let is_project = |dir: &Path| has_file(dir, "CMakeLists.txt") || has_file_with_extension(dir, "pro");
+    let named_after = |project: &str| !project.is_empty() && is_project(&parent.join(project));
+
+    if has_file(path, "CMakeCache.txt") {
+        return named_after(name.trim_start_matches("build-").split('-').next().unwrap_or(""))
+            || is_project(parent)
+            || retry_exhausted(|| std::fs::read_dir(parent))
+                .map(|entries| entries.flatten().any(|e| e.path() != path && e.path().is_dir() && is_project(&e.path())))
+                .unwrap_or(false);
+    }
+    if !has_file(path, "Makefile") {
+        return false;
+    }
+    // The project name may itself contain dashes, so every split before the kit is tried.
+    let Some((project_and_kit, config)) = name.strip_prefix("build-").and_then(|rest| rest.rsplit_once('-')) else { return false };
+    QT_BUILD_CONFIGS.contains(&config)
+        && project_and_kit.match_indices('-').any(|(at, _)| at + 1 < project_and_kit.len() && named_after(&project_and_kit[..at]))
+}
+
+/// The build configurations Qt Creator ends a shadow build's name with.
+const QT_BUILD_CONFIGS: &[&str] = &["Debug", "Release", "Profile", "RelWithDebInfo", "MinSizeRel"];
+
+fn is_esp_idf_build(path: &Path) -> bool {
+    path.parent().is_some_and(|parent| has_file(parent, "sdkconfig"))
+}
+
+/// Xcode's own DerivedData under the home folder, shared by every project.
+pub(crate) fn is_default_derived_data(path: &Path) -> bool {
+    path.ends_with(SYSTEM_CACHES[0])
+}
+
+/// Targets whose name alone doesn't say what they belong to, so the list names their
+/// ecosystem next to them.
+pub fn shows_ecosystem(dir_name: &str) -> bool {
+    matches!(dir_name, "Pods" | "Build" | "DerivedData")
+}
+
+/// How to get a deleted target back, by its kind: a folder or file name, or the pattern
+/// that matched it, like `build-*`.
+pub fn restore_hint(kind: &str, path: &Path) -> Option<&'static str> {
+    Some(match kind {
+        "build" if is_esp_idf_build(path) => "idf.py build",
+        "node_modules" => "npm install",
+        "target" => "cargo build",
+        "build" | "dist" | ".gradle" | ".angular" | ".next" | ".nuxt" => "rebuild the project",
+        "vendor" => "composer install / go mod vendor / bundle install",
+        "__pycache__" | ".pytest_cache" | ".mypy_cache" => "regenerated on the next run",
+        ".venv" | "venv" => "python -m venv, then reinstall the requirements",
+        ".tox" => "tox recreates its environments on the next run",
+        "bin" | "obj" => "dotnet build",
+        ".dart_tool" => "dart pub get",
+        ".vs" => "reopen the solution",
+        ".pio" => "pio run",
+        name if name.starts_with("build-") => "rebuild in Qt Creator",
+        "Pods" => "pod install",
+        "Build" => "carthage build",
+        "DerivedData" => "Xcode rebuilds it on the next build",
+        ".terraform" => "terraform init",
+        "_build" | "deps" if has_file(path.parent()?, "mix.exs") => "mix deps.get && mix compile",
+        "_build" => "rebar3 compile",
+        ".stack-work" => "stack build",
+        "dist-newstyle" => "cabal build",
+        ".eslintcache" | ".stylelintcache" => "regenerated on the next lint run",
+        name if name.ends_with(".tsbuildinfo") => "regenerated on the next tsc build",
+        ".phpunit.result.cache" => "regenerated on the next test run",
+        _ => return None,
+    })
+}
+
+/// Caches inside a target that rebuild quickly and can go on their own while the rest
+/// stays, as paths relative to it where `*` matches any one name.
+pub fn partial_paths(dir_name: &str) -> &'static [&'static str] {
+    match dir_name {
+        "node_modules" => &[".cache", ".vite"],
+        "target" => &["*/incremental"],
+        ".next" => &["cache"],
+        _ => &[],
+    }
+}
+
+/// The ecosystem's own command for cleaning a target, run in the project next to it.
+pub fn native_clean(dir_name: &str, path: &Path) -> Option<&'static [&'static str]> {
+    let parent = path.parent()?;
+    match dir_name {
+        "target" if has_file(parent, "Cargo.toml") => Some(&["cargo", "clean"]),
+        "build" if has_any_file(parent, &["build.gradle", "build.gradle.kts"]) => Some(&["gradle", "clean"]),
+        _ => None,
+    }
+}
+
+/// Something to do before deleting a target folder, if deleting it while in use fails.
+pub fn deletion_warning(dir_name: &str) -> Option<&'static str> {
+    match dir_name {
+        ".vs" => Some("close Visual Studio first; it keeps files in .vs open while the solution is loaded"),
+        ".venv" | "venv" => Some("packages installed by hand and not in a requirements file are lost; `pip freeze` lists them"),
+        "DerivedData" => Some("quit Xcode first; it writes to DerivedData while a project is open"),
+        _ => None,
+    }
+}
+
+pub fn calculate_size(path: &Path) -> u64 {
+    calculate_size_with_progress(path, |_, _| {})
+}
+
+/// Whether an entry is a symbolic link, or on Windows any reparse point such as a junction.
+/// Their targets live elsewhere, so they are neither counted nor descended into.
+pub fn is_link_entry(file_type: std::fs::FileType, metadata: &std::fs::Metadata) -> bool {
+    file_type.is_symlink() || is_reparse_point(metadata)
+}
+
+#[cfg(windows)]
+fn is_reparse_point(metadata: &std::fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+    metadata.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0
+}
+
+#[cfg(not(windows))]
+fn is_reparse_point(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// Every entry at or below `path` that isn't a link or junction; those are neither listed
+/// nor entered, `path` itself included, so nothing outside the folder is ever counted.
+fn entries_without_links(path: &Path) -> impl Iterator<Item = walkdir::DirEntry> {
+    WalkDir::new(extended(path))
+        .follow_root_links(false)
+        .into_iter()
+        .filter_entry(|entry| entry.metadata().is_ok_and(|metadata| !is_link_entry(entry.file_type(), &metadata)))
+        .filter_map(|entry| entry.ok())
+}
+
+/// Bytes the path actually occupies on disk, counting whole blocks rather than file lengths.
+pub fn allocated_size(path: &Path) -> u64 {
+    let mut hard_linked = HashSet::new();
+    entries_without_links(path)
+        .filter_map(|entry| entry.metadata().ok().map(|metadata| (entry, metadata)))
+        .filter(|(entry, metadata)| metadata.is_file() && hard_link_id(entry.path(), metadata).is_none_or(|id| hard_linked.insert(id)))
+        .map(|(_, metadata)| allocated_bytes(&metadata))
+        .sum()
+}
+
+/// The identity of a file with more than one hard link, so it is counted once however
+/// many of its names a folder holds; `None` for the usual file with a single name.
+#[cfg(unix)]
+pub(crate) fn hard_link_id(_path: &Path, metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    (metadata.nlink() > 1).then(|| (metadata.dev(), metadata.ino()))
+}
+
+/// Windows only tells the link count and file index of an open file, so this opens each
+/// one; a file that can't be opened is counted as if it had a single name.
+#[cfg(windows)]
+pub(crate) fn hard_link_id(path: &Path, _metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::ffi::c_void;
+    use std::os::windows::io::AsRawHandle;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct ByHandleFileInformation {
+        attributes: u32,
+        creation_time: [u32; 2],
+        last_access_time: [u32; 2],
+        last_write_time: [u32; 2],
+        volume_serial_number: u32,
+        size_high: u32,
+        size_low: u32,
+        number_of_links: u32,
+        index_high: u32,
+        index_low: u32,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetFileInformationByHandle(file: *mut c_void, info: *mut ByHandleFileInformation) -> i32;
+    }
+
+    let file = std::fs::File::open(path).ok()?;
+    let mut info = ByHandleFileInformation::default();
+    if unsafe { GetFileInformationByHandle(file.as_raw_handle(), &mut info) } == 0 {
+        return None;
+    }
+    (info.number_of_links > 1).then(|| (info.volume_serial_number as u64, (info.index_high as u64) << 32 | info.index_low as u64))
+}
+
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn hard_link_id(_path: &Path, _metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(unix)]
+fn allocated_bytes(metadata: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blocks() * 512
+}
+
+#[cfg(not(unix))]
+fn allocated_bytes(metadata: &std::fs::Metadata) -> u64 {
+    metadata.len()
+}
+
+/// Bytes deleting the path would actually free on APFS, where clones share blocks with
+/// other files: the sum of each file's private (unshared) size. `None` elsewhere.
+#[cfg(all(target_os = "macos", feature = "apfs"))]
+pub fn private_size(path: &Path) -> Option<u64> {
+    let mut total = 0;
+    for entry in entries_without_links(path) {
+        if entry.file_type().is_file() {
+            total += private_bytes(entry.path())?;
+        }
+    }
+    Some(total)
+}
+
+#[cfg(not(all(target_os = "macos", feature = "apfs")))]
+pub fn private_size(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// `ATTR_CMNEXT_PRIVATESIZE` of one file; the volume doesn't support it when this fails.
+#[cfg(all(target_os = "macos", feature = "apfs"))]
+fn private_bytes(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    #[repr(C, packed(4))]
+    struct AttrBuf {
+        length: u32,
+        private_size: libc::off_t,
+    }
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: attrlist is plain old data; all-zero is a valid (empty) request.
+    let mut request: libc::attrlist = unsafe { std::mem::zeroed() };
+    request.bitmapcount = libc::ATTR_BIT_MAP_COUNT;
+    request.forkattr = libc::ATTR_CMNEXT_PRIVATESIZE;
+    let mut buf = AttrBuf { length: 0, private_size: 0 };
+    // SAFETY: both pointers reference live, correctly sized values for the duration of the call.
+    let rc = unsafe {
+        libc::getattrlist(
+            c_path.as_ptr(),
+            (&mut request as *mut libc::attrlist).cast(),
+            (&mut buf as *mut AttrBuf).cast(),
+            std::mem::size_of::<AttrBuf>(),
+            libc::FSOPT_ATTR_CMN_EXTENDED | libc::FSOPT_NOFOLLOW,
+        )
+    };
+    let (length, private_size) = (buf.length, buf.private_size);
+    if rc != 0 || (length as usize) < std::mem::size_of::<AttrBuf>() {
+        return None;
+    }
+    Some(private_size.max(0) as u64)
+}
+
+/// How often the sizing progress callback may fire, at most.
+pub const SIZING_PROGRESS_INTERVAL: Duration = Duration::from_millis(150);
+const SIZING_PROGRESS_BATCH: u64 = 4096;
+
+/// Like [`calculate_size`], reporting the running `(bytes, files)` total while it works.
+///
+/// The clock is only consulted every few thousand entries and the callback fires at
+/// most every [`SIZING_PROGRESS_INTERVAL`], so reporting stays cheap on fast disks.
+pub fn calculate_size_with_progress(path: &Path, progress: impl FnMut(u64, u64)) -> u64 {
+    measure(path, progress).bytes
+}
+
+/// What sizing a candidate found.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Measured {
+    pub bytes: u64,
+    /// Links and junctions inside, whose targets were left out of `bytes`.
+    pub links: u64,
+    /// Entries that couldn't be read (e.g. permission denied); their size is missing from `bytes`.
+    pub unreadable: u64,
+}
+
+/// Sizes `path` like [`calculate_size_with_progress`], also counting the links inside it.
+pub fn measure(path: &Path, mut progress: impl FnMut(u64, u64)) -> Measured {
+    let mut tally = Tally { measured: Measured::default(), files: 0, entries: 0, last_report: Instant::now(), hard_linked: HashSet::new() };
+    measure_into(path, &mut progress, &mut tally, true);
+    tally.measured
+}
+
+/// The running totals of [`measure`], kept across the walks it starts again.
+struct Tally {
+    measured: Measured,
+    files: u64,
+    entries: u64,
+    last_report: Instant,
+    /// Files with several hard links inside the candidate, so each is counted once.
+    hard_linked: HashSet<(u64, u64)>,
+}
+
+/// Adds everything at or below `path` to `tally`. A directory that couldn't be opened
+/// because other threads held every descriptor is walked on its own once one frees up,
+/// unless it is `path` itself and that was already a second try (`retry` unset).
+fn measure_into(path: &Path, progress: &mut dyn FnMut(u64, u64), tally: &mut Tally, retry: bool) {
+    // A candidate that is itself a link is measured as the link: deleting it frees nothing
+    // but the link, so its target is not followed either. One directory is open at a time,
+    // the rest of its parents' entries kept in memory, so walks that are waiting for a
+    // descriptor hold none and can't keep each other waiting.
+    let mut walk = WalkDir::new(extended(path)).follow_root_links(false).max_open(1).into_iter();
+    while let Some(entry) = walk.next() {
+        let (entry, metadata) = match entry.and_then(|entry| {
+            let metadata = entry.metadata()?;
+            Ok((entry, metadata))
+        }) {
+            Ok(found) => found,
+            Err(e) => {
+                if e.io_error().is_some_and(is_fd_exhaustion) {
+                    match e.path().filter(|_| retry || e.depth() > 0) {
+                        Some(dir) if retry_exhausted(|| std::fs::read_dir(dir).map(drop)).is_ok() => {
+                            measure_into(dir, progress, tally, false);
+                            continue;
+                        }
+                        Some(_) => {}
+                        None => note_fd_exhaustion(),
+                    }
+                }
+                tally.measured.unreadable += 1;
+                continue;
+            }
+        };
+        let (depth, file_type) = (entry.depth(), entry.file_type());
+        tally.entries += 1;
+        if is_link_entry(file_type, &metadata) {
+            // The candidate itself being a link is not a link inside it.
+            if depth > 0 {
+                tally.measured.links += 1;
+            }
+            // A junction walkdir would still enter; anything else is left alone already.
+            if file_type.is_dir() {
+                walk.skip_current_dir();
+            }
+            continue;
+        }
+        if metadata.is_file() && hard_link_id(entry.path(), &metadata).is_none_or(|id| tally.hard_linked.insert(id)) {
+            tally.measured.bytes += metadata.len();
+            tally.files += 1;
+        }
+        if tally.entries.is_multiple_of(SIZING_PROGRESS_BATCH) && tally.last_report.elapsed() >= SIZING_PROGRESS_INTERVAL {
+            tally.last_report = Instant::now();
+            progress(tally.measured.bytes, tally.files);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::longpath::friendly;
+use crate::CandidateDir;
+use directories::BaseDirs;
+use std::path::{Path, PathBuf};
+
+/// How candidate paths are shown in the selection list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum PathStyle {
+    /// Relative to the scan root.
+    Relative,
+    /// With the home directory abbreviated to `~`.
+    #[default]
+    Home,
+    /// The full absolute path.
+    Absolute,
+}
+
+/// Renders paths in a [`PathStyle`]; paths outside the root are shown from home, and
+/// paths outside home in full.
+pub struct PathDisplay {
+    style: PathStyle,
+    root: PathBuf,
+    home: Option<PathBuf>,
+}
+
+impl PathDisplay {
+    pub fn new(style: PathStyle, root: &Path) -> Self {
+        PathDisplay {
+            style,
+            root: friendly(root).into_owned(),
+            home: BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf()),
+        }
+    }
+
+    pub fn show(&self, path: &Path) -> String {
+        // A root given in the extended-length form is shown without its prefix.
+        let path: &Path = &friendly(path);
+        match self.style {
+            PathStyle::Relative => match path.strip_prefix(&self.root) {
+                Ok(rel) if rel.as_os_str().is_empty() => ".".to_string(),
+                Ok(rel) => rel.display().to_string(),
+                // Outside the root, e.g. --system-caches: at least the home prefix goes.
+                Err(_) => self.home_relative(path),
+            },
+            PathStyle::Home => self.home_relative(path),
+            PathStyle::Absolute => path.display().to_string(),
+        }
+    }
+
+    fn home_relative(&self, path: &Path) -> String {
+        match self.home.as_deref().map(|home| path.strip_prefix(home)) {
+            Some(Ok(rel)) if rel.as_os_str().is_empty() => "~".to_string(),
+            Some(Ok(rel)) => format!("~{}{}", std::path::MAIN_SEPARATOR, rel.display()),
+            _ => path.display().to_string(),
+        }
+    }
+}
+
+/// Characters of the path kept before the suffix is cut short as well.
+const MIN_PATH_WIDTH: usize = 16;
+
+/// Fits `path` followed by `suffix` into `max_width` characters by eliding the
+/// middle of the path (or its start, when there is very little room). A suffix too
+/// long to leave the path [`MIN_PATH_WIDTH`] characters loses its end instead, so the
+/// row never wraps.
+///
+/// The suffix may carry styling; only its visible width counts.
+pub fn fit_row(path: &str, suffix: &str, max_width: usize) -> String {
+    let path_len = path.chars().count();
+    let suffix_len = console::measure_text_width(suffix);
+    if path_len + suffix_len <= max_width {
+        return format!("{}{}", path, suffix);
+    }
+
+    let path_room = MIN_PATH_WIDTH.min(path_len);
+    let suffix = match suffix_len + path_room > max_width {
+        true => console::truncate_str(suffix, max_width.saturating_sub(path_room), "..."),
+        false => suffix.into(),
+    };
+    let suffix_len = console::measure_text_width(&suffix);
+    if path_len + suffix_len <= max_width {
+        return format!("{}{}", path, suffix);
+    }
+
+    let available_space = max_width.saturating_sub(suffix_len + 3);
+    if available_space < 10 {
+        let end: String = path.chars().skip(path_len.saturating_sub(max_width.saturating_sub(suffix_len + 3))).collect();
+        format!("...{}{}", end, suffix)
+    } else {
+        let keep = available_space / 2;
+        let start: String = path.chars().take(keep).collect();
+        let end: String = path.chars().skip(path_len - keep).collect();
+        format!("{}...{}{}", start, end, suffix)
+    }
+}
+
+/// Short markers for candidates that deserve a second look before deleting.
+pub fn badges(candidate: &CandidateDir, unicode: bool) -> Vec<&'static str> {
+    let mut badges = Vec::new();
+    if candidate.is_link {
+        badges.push(if unicode { "↪ link" } else { "-> link" });
+    }
+    if candidate.on_network {
+        badges.push(if unicode { "⇅ network" } else { "<> network" });
+    }
+    if candidate.is_mount_point {
+        badges.push(if unicode { "◆ mount" } else { "[mount]" });
+    }
+    if candidate.cloud_synced {
+        badges.push(if unicode { "☁ cloud" } else { "(cloud)" });
+    }
+    badges
+}
+
+/// Marker for cached candidates whose volume isn't mounted.
+pub fn offline_badge(unicode: bool) -> &'static str {
+    if unicode { "⏏ offline" } else { "[offline]" }
+}
+
+/// One-line explanations of each badge, for the detail view.
+pub fn badge_explanations(candidate: &CandidateDir) -> Vec<&'static str> {
+    let mut lines = Vec::new();
+    if candidate.is_link {
+        lines.push("reached through a symbolic link; the real folder lives elsewhere");
+    }
+    if candidate.on_network {
+        lines.push("on a network filesystem; deleting may be slow and affects other machines");
+    }
+    if candidate.is_mount_point {
+        lines.push("a mount point; its contents belong to another filesystem");
+    }
+    if candidate.cloud_synced {
+        lines.push("inside a cloud-synced folder; deletion will propagate to other devices");
+    }
+    lines
+}
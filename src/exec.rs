@@ -0,0 +1,101 @@
+//! The one way DevPurge runs external programs.
+//!
+//! Every subprocess goes through [`run`], which refuses to spawn anything once
+//! `--no-exec` is set and kills programs that outlive their timeout (a `git` waiting
+//! for credentials would otherwise hang the run).
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+static NO_EXEC: AtomicBool = AtomicBool::new(false);
+
+/// Turns spawning external programs off (or back on) for the rest of the process.
+pub fn set_no_exec(disabled: bool) {
+    NO_EXEC.store(disabled, Ordering::Relaxed);
+}
+
+pub fn exec_allowed() -> bool {
+    !NO_EXEC.load(Ordering::Relaxed)
+}
+
+#[derive(Debug)]
+pub enum ExecError {
+    /// `--no-exec` is in effect.
+    Disabled,
+    Spawn(io::Error),
+    TimedOut(Duration),
+}
+
+impl fmt::Display for ExecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecError::Disabled => write!(f, "external commands are disabled (--no-exec)"),
+            ExecError::Spawn(e) => write!(f, "could not run it: {}", e),
+            ExecError::TimedOut(after) => write!(f, "gave up after {:.1}s", after.as_secs_f64()),
+        }
+    }
+}
+
+impl std::error::Error for ExecError {}
+
+/// Runs `command` to completion with no stdin, collecting its output, unless
+/// `--no-exec` is set or it takes longer than `timeout`, in which case it is killed.
+pub fn run(command: &mut Command, timeout: Duration) -> Result<Output, ExecError> {
+    run_with_input(command, None, timeout)
+}
+
+/// [`run`], writing `input` to the program's stdin instead of leaving it empty.
+pub fn run_with_input(command: &mut Command, input: Option<Vec<u8>>, timeout: Duration) -> Result<Output, ExecError> {
+    if !exec_allowed() {
+        return Err(ExecError::Disabled);
+    }
+    let mut child = command
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(ExecError::Spawn)?;
+
+    // Written from its own thread, so a program that answers before reading everything
+    // can't deadlock against us; dropping the pipe afterwards closes its stdin.
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        });
+    }
+
+    // Drain both pipes while waiting so a chatty program can't block on a full pipe.
+    let drain = |pipe: Option<Box<dyn Read + Send>>| {
+        thread::spawn(move || {
+            let mut bytes = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut bytes);
+            }
+            bytes
+        })
+    };
+    let stdout = drain(child.stdout.take().map(|p| Box::new(p) as Box<dyn Read + Send>));
+    let stderr = drain(child.stderr.take().map(|p| Box::new(p) as Box<dyn Read + Send>));
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait().map_err(ExecError::Spawn)? {
+            break status;
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(ExecError::TimedOut(timeout));
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
@@ -0,0 +1,135 @@
+use crate::detect::allocated_size;
+use crate::projects::Risk;
+use crate::summary::{summarize, Summary};
+use crate::units::unix_secs;
+use crate::CandidateDir;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+// ncdu's export format version; every ncdu release since 1.9 reads 1.x files.
+const NCDU_MAJOR_VERSION: u64 = 1;
+const NCDU_MINOR_VERSION: u64 = 2;
+
+#[derive(Default)]
+struct Node {
+    children: BTreeMap<String, Node>,
+    /// `(apparent, allocated)` bytes when this node is a candidate.
+    sizes: Option<(u64, u64)>,
+    is_file: bool,
+}
+
+/// Writes the candidates as an ncdu JSON export (`ncdu -f <file>`) rooted at `root`.
+///
+/// Folders between the root and a candidate appear empty, so ncdu's totals are the
+/// reclaimable space. Candidates outside `root` are left out.
+pub fn write_ncdu(root: &Path, candidates: &[CandidateDir], mut writer: impl Write) -> io::Result<()> {
+    let mut tree = Node::default();
+    for candidate in candidates {
+        let Ok(relative) = candidate.path.strip_prefix(root) else { continue };
+        let mut node = &mut tree;
+        for component in relative.components() {
+            node = node.children.entry(component.as_os_str().to_string_lossy().into_owned()).or_default();
+        }
+        node.sizes = Some((candidate.size, allocated_size(&candidate.path)));
+        node.is_file = candidate.is_file;
+    }
+
+    let header = json!({
+        "progname": env!("CARGO_PKG_NAME"),
+        "progver": env!("CARGO_PKG_VERSION"),
+        "timestamp": unix_secs(SystemTime::now()),
+    });
+    let export = json!([NCDU_MAJOR_VERSION, NCDU_MINOR_VERSION, header, to_value(&root.to_string_lossy(), &tree)]);
+    serde_json::to_writer(&mut writer, &export).map_err(io::Error::other)?;
+    writer.flush()
+}
+
+fn to_value(name: &str, node: &Node) -> Value {
+    let mut info = json!({ "name": name });
+    if let Some((apparent, allocated)) = node.sizes {
+        info["asize"] = json!(apparent);
+        info["dsize"] = json!(allocated);
+    }
+    if node.is_file {
+        return info;
+    }
+
+    let mut entries = vec![info];
+    entries.extend(node.children.iter().map(|(name, child)| to_value(name, child)));
+    Value::Array(entries)
+}
+
+/// One candidate in the `--format` outputs.
+#[derive(Debug, Serialize)]
+struct Row<'a> {
+    path: String,
+    kind: &'a str,
+    size: u64,
+    reclaimable: u64,
+    is_file: bool,
+    /// Some entries couldn't be read, so `size` is a lower bound.
+    incomplete: bool,
+    /// `caution` when it may hold files put there by hand; JSON only, so the CSV
+    /// columns stay as they were.
+    risk: Risk,
+}
+
+impl<'a> From<&'a CandidateDir> for Row<'a> {
+    fn from(c: &'a CandidateDir) -> Self {
+        Row {
+            path: c.path.to_string_lossy().into_owned(),
+            kind: &c.kind,
+            size: c.size,
+            reclaimable: c.reclaimable(),
+            is_file: c.is_file,
+            incomplete: c.is_incomplete(),
+            risk: c.risk,
+        }
+    }
+}
+
+/// The `--format json` document.
+#[derive(Serialize)]
+struct Export<'a> {
+    candidates: Vec<Row<'a>>,
+    summary: Summary,
+}
+
+/// Writes the candidates as a JSON object: a `candidates` array of objects and their
+/// `summary` by kind.
+pub fn write_json(candidates: &[CandidateDir], mut writer: impl Write) -> io::Result<()> {
+    let export = Export { candidates: candidates.iter().map(Row::from).collect(), summary: summarize(candidates) };
+    serde_json::to_writer_pretty(&mut writer, &export).map_err(io::Error::other)?;
+    writeln!(writer)?;
+    writer.flush()
+}
+
+/// Writes the candidates as CSV with a header row, quoting fields as RFC 4180 asks.
+pub fn write_csv(candidates: &[CandidateDir], mut writer: impl Write) -> io::Result<()> {
+    writeln!(writer, "path,kind,size,reclaimable,is_file,incomplete")?;
+    for row in candidates.iter().map(Row::from) {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{}",
+            csv_field(&row.path),
+            csv_field(row.kind),
+            row.size,
+            row.reclaimable,
+            row.is_file,
+            row.incomplete
+        )?;
+    }
+    writer.flush()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,97 @@
+use crate::units::unix_secs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Keeps the history from growing without bound; the oldest entries go first.
+pub const MAX_FAILED_ENTRIES: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedDeletion {
+    pub path: PathBuf,
+    pub error: String,
+    pub timestamp: u64,
+    pub attempts: u32,
+}
+
+/// Deletions that failed on earlier runs, persisted in the data directory.
+#[derive(Debug, Default)]
+pub struct FailureLog {
+    file: Option<PathBuf>,
+    entries: Vec<FailedDeletion>,
+}
+
+impl FailureLog {
+    pub fn load(file: Option<PathBuf>) -> Self {
+        let entries = file
+            .as_deref()
+            .and_then(|f| fs::File::open(f).ok())
+            .and_then(|f| serde_json::from_reader(f).ok())
+            .unwrap_or_default();
+        FailureLog { file, entries }
+    }
+
+    pub fn entries(&self) -> &[FailedDeletion] {
+        &self.entries
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&FailedDeletion> {
+        self.entries.iter().find(|e| e.path == path)
+    }
+
+    pub fn record_failure(&mut self, path: &Path, error: &io::Error) {
+        let timestamp = unix_secs(SystemTime::now());
+        let error = describe_error(error);
+        match self.entries.iter_mut().find(|e| e.path == path) {
+            Some(entry) => {
+                entry.error = error;
+                entry.timestamp = timestamp;
+                entry.attempts += 1;
+            }
+            None => self.entries.push(FailedDeletion { path: path.to_path_buf(), error, timestamp, attempts: 1 }),
+        }
+        if self.entries.len() > MAX_FAILED_ENTRIES {
+            self.entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+            self.entries.truncate(MAX_FAILED_ENTRIES);
+        }
+    }
+
+    pub fn record_success(&mut self, path: &Path) {
+        self.entries.retain(|e| e.path != path);
+    }
+
+    /// Forgets entries whose path no longer exists, e.g. removed by hand.
+    pub fn prune_missing(&mut self) {
+        self.entries.retain(|e| e.path.exists());
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(file) = &self.file else { return Ok(()) };
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let writer = fs::File::create(file)?;
+        serde_json::to_writer(writer, &self.entries).map_err(io::Error::other)
+    }
+}
+
+/// A short human-readable reason for a failed deletion.
+pub fn describe_error(error: &io::Error) -> String {
+    #[cfg(windows)]
+    if error.raw_os_error() == Some(32) {
+        return "sharing violation".to_string();
+    }
+    #[cfg(unix)]
+    if error.raw_os_error() == Some(libc::EBUSY) {
+        return "resource busy".to_string();
+    }
+    match error.kind() {
+        // Deletion names the entry that was still denied after a retry.
+        io::ErrorKind::PermissionDenied if error.get_ref().is_some() => error.to_string(),
+        io::ErrorKind::PermissionDenied => "permission denied".to_string(),
+        io::ErrorKind::NotFound => "not found".to_string(),
+        _ => error.to_string(),
+    }
+}
@@ -0,0 +1,161 @@
+//! The filesystem calls deletion makes, behind a trait so tests can make them fail the
+//! way real disks do: a locked file halfway through a tree, a folder another program
+//! holds open for a moment.
+
+use std::cell::{Cell, RefCell};
+use std::fs::{self, Metadata, Permissions, ReadDir};
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub trait FileSystem {
+    fn read_dir(&self, path: &Path) -> io::Result<ReadDir>;
+    fn remove_dir(&self, path: &Path) -> io::Result<()>;
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+    fn set_permissions(&self, path: &Path, permissions: Permissions) -> io::Result<()>;
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata>;
+}
+
+/// The real filesystem, through `std::fs`.
+pub struct RealFs;
+
+impl FileSystem for RealFs {
+    fn read_dir(&self, path: &Path) -> io::Result<ReadDir> {
+        fs::read_dir(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn set_permissions(&self, path: &Path, permissions: Permissions) -> io::Result<()> {
+        fs::set_permissions(path, permissions)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        fs::symlink_metadata(path)
+    }
+}
+
+/// One call made through a [`FaultyFs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    ReadDir,
+    RemoveDir,
+    RemoveFile,
+    SetPermissions,
+    Metadata,
+}
+
+#[derive(Debug)]
+enum Fault {
+    /// Removing the nth file (counting from 1) is denied.
+    DenyNthFile(usize),
+    /// Listing this folder is denied.
+    DenyListing(PathBuf),
+    /// The next call on this path fails as if another program had it open.
+    BusyOnce(PathBuf),
+}
+
+/// The real filesystem with failures injected, for tests.
+///
+/// Deletion walks a tree one entry at a time through these calls, so a failure partway
+/// leaves it half deleted like a real one would. Every fault fires once.
+#[derive(Debug, Default)]
+pub struct FaultyFs {
+    faults: RefCell<Vec<Fault>>,
+    files_removed: Cell<usize>,
+    calls: RefCell<Vec<(Op, PathBuf)>>,
+}
+
+impl FaultyFs {
+    pub fn new() -> Self {
+        FaultyFs::default()
+    }
+
+    /// Denies removing the nth file, counted across all calls.
+    pub fn deny_nth_file(self, n: usize) -> Self {
+        self.faults.borrow_mut().push(Fault::DenyNthFile(n));
+        self
+    }
+
+    /// Denies listing the folder at `path`.
+    pub fn deny_listing(self, path: impl Into<PathBuf>) -> Self {
+        self.faults.borrow_mut().push(Fault::DenyListing(path.into()));
+        self
+    }
+
+    /// Fails the next call on `path` with a sharing violation (`EBUSY` outside Windows).
+    pub fn busy_once(self, path: impl Into<PathBuf>) -> Self {
+        self.faults.borrow_mut().push(Fault::BusyOnce(path.into()));
+        self
+    }
+
+    /// Every call made so far, in order.
+    pub fn calls(&self) -> Vec<(Op, PathBuf)> {
+        self.calls.borrow().clone()
+    }
+
+    fn enter(&self, op: Op, path: &Path) -> io::Result<()> {
+        self.calls.borrow_mut().push((op, path.to_path_buf()));
+        if op == Op::RemoveFile {
+            self.files_removed.set(self.files_removed.get() + 1);
+        }
+        let mut faults = self.faults.borrow_mut();
+        let fired = faults.iter().position(|fault| match fault {
+            Fault::DenyNthFile(n) => op == Op::RemoveFile && self.files_removed.get() == *n,
+            Fault::DenyListing(dir) => op == Op::ReadDir && dir == path,
+            Fault::BusyOnce(busy) => busy == path,
+        });
+        match fired.map(|i| faults.remove(i)) {
+            Some(Fault::DenyNthFile(_) | Fault::DenyListing(_)) => Err(io::Error::from(io::ErrorKind::PermissionDenied)),
+            Some(Fault::BusyOnce(_)) => Err(sharing_violation()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(windows)]
+fn sharing_violation() -> io::Error {
+    io::Error::from_raw_os_error(32)
+}
+
+#[cfg(unix)]
+fn sharing_violation() -> io::Error {
+    io::Error::from_raw_os_error(libc::EBUSY)
+}
+
+#[cfg(not(any(windows, unix)))]
+fn sharing_violation() -> io::Error {
+    io::Error::from(io::ErrorKind::ResourceBusy)
+}
+
+impl FileSystem for FaultyFs {
+    fn read_dir(&self, path: &Path) -> io::Result<ReadDir> {
+        self.enter(Op::ReadDir, path)?;
+        fs::read_dir(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        self.enter(Op::RemoveDir, path)?;
+        fs::remove_dir(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.enter(Op::RemoveFile, path)?;
+        fs::remove_file(path)
+    }
+
+    fn set_permissions(&self, path: &Path, permissions: Permissions) -> io::Result<()> {
+        self.enter(Op::SetPermissions, path)?;
+        fs::set_permissions(path, permissions)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.enter(Op::Metadata, path)?;
+        fs::symlink_metadata(path)
+    }
+}
@@ -0,0 +1,274 @@
+//! Synthetic project trees for tests, benchmarks and `devpurge gen-fixture`.
+//!
+//! A [`FixtureSpec`] lists projects by ecosystem, where they go, how big their artifact
+//! folder is and how many files it holds, plus links and unreadable directories.
+//! [`generate`] writes the tree (with sparse files, so gigabytes cost nothing) and returns
+//! the candidates a scan has to find, which makes "finds exactly these" one assertion.
+
+use crate::units::parse_size;
+use crate::{ScanIter, ScanOptions};
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Ecosystem name, marker file, artifact folder, and whether the folder needs the marker.
+const ECOSYSTEMS: &[(&str, &str, &str, bool)] = &[
+    ("node", "package.json", "node_modules", true),
+    ("rust", "Cargo.toml", "target", true),
+    ("gradle", "build.gradle", "build", true),
+    ("cmake", "CMakeLists.txt", "build", true),
+    ("php", "composer.json", "vendor", true),
+    ("go", "go.mod", "vendor", true),
+    ("python", "main.py", "__pycache__", false),
+    ("dotnet", "App.csproj", "bin", true),
+    ("dart", "pubspec.yaml", ".dart_tool", true),
+    ("next", "next.config.js", ".next", true),
+    ("platformio", "platformio.ini", ".pio", true),
+];
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FixtureSpec {
+    pub projects: Vec<ProjectSpec>,
+    pub links: Vec<LinkSpec>,
+    /// Directories created without read permission, relative to the tree. Has no effect
+    /// on Windows or when running as root.
+    pub unreadable: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectSpec {
+    /// Where the project goes, relative to the tree; `{n}` is replaced by the copy number.
+    pub path: String,
+    /// One of `node`, `rust`, `gradle`, `cmake`, `php`, `go`, `python`, `dotnet`, `dart`,
+    /// `next` or `platformio`.
+    pub ecosystem: String,
+    /// Total size of the artifact folder, e.g. "200M" or "4B"; bare numbers are megabytes.
+    #[serde(default)]
+    pub size: Option<String>,
+    /// How many files the size is spread over.
+    #[serde(default = "one")]
+    pub files: u64,
+    /// How many directories deep the files sit inside the artifact folder.
+    #[serde(default)]
+    pub depth: usize,
+    /// How many copies of the project to create.
+    #[serde(default = "one")]
+    pub copies: usize,
+    /// Leave out the marker file, so the artifact folder must not be found.
+    #[serde(default)]
+    pub unmarked: bool,
+}
+
+fn one<T: From<u8>>() -> T {
+    T::from(1)
+}
+
+/// A symbolic link (a junction on Windows) to a directory, both relative to the tree.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LinkSpec {
+    pub path: String,
+    pub target: String,
+}
+
+/// A candidate a scan of the generated tree has to find.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Expected {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+pub fn parse_spec(text: &str) -> Result<FixtureSpec, String> {
+    let value = crate::minitoml::parse(text)?;
+    let spec: FixtureSpec = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    for project in &spec.projects {
+        ecosystem(&project.ecosystem)?;
+        if let Some(size) = &project.size {
+            parse_size(size).map_err(|e| format!("{} in project {}", e, project.path))?;
+        }
+        if project.files == 0 && project.size.is_some() {
+            return Err(format!("project {} has a size but no files to hold it", project.path));
+        }
+    }
+    Ok(spec)
+}
+
+fn ecosystem(name: &str) -> Result<(&'static str, &'static str, bool), String> {
+    ECOSYSTEMS
+        .iter()
+        .find(|e| e.0 == name)
+        .map(|&(_, marker, artifact, needs_marker)| (marker, artifact, needs_marker))
+        .ok_or_else(|| format!("unknown ecosystem '{}'; known are {}", name, ECOSYSTEMS.iter().map(|e| e.0).collect::<Vec<_>>().join(", ")))
+}
+
+/// Writes the tree described by `spec` under `dir` and returns, sorted by path, the
+/// candidates a full scan has to find. Artifact folders inside another candidate are
+/// left out, since scans never descend into candidates.
+pub fn generate(spec: &FixtureSpec, dir: &Path) -> io::Result<Vec<Expected>> {
+    let mut expected = Vec::new();
+    // Every file written with its length, to size candidates holding nested projects.
+    let mut written: Vec<(PathBuf, u64)> = Vec::new();
+    for project in &spec.projects {
+        let (marker, artifact, needs_marker) = ecosystem(&project.ecosystem).map_err(io::Error::other)?;
+        let size = project.size.as_deref().map(parse_size).transpose().map_err(io::Error::other)?.unwrap_or(0);
+        for n in 1..=project.copies {
+            let root = dir.join(project.path.replace("{n}", &n.to_string()));
+            fs::create_dir_all(&root)?;
+            if !project.unmarked {
+                let contents = marker_contents(marker);
+                fs::write(root.join(marker), contents)?;
+                written.push((root.join(marker), contents.len() as u64));
+            }
+            let artifact_dir = root.join(artifact);
+            written.extend(fill(&artifact_dir, size, project.files, project.depth)?);
+            if !project.unmarked || !needs_marker {
+                expected.push(Expected { path: artifact_dir, size: 0 });
+            }
+        }
+    }
+    for link in &spec.links {
+        let path = dir.join(&link.path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        link_dir(&dir.join(&link.target), &path)?;
+    }
+    for unreadable in &spec.unreadable {
+        let path = dir.join(unreadable);
+        fs::create_dir_all(&path)?;
+        set_readable(&path, false)?;
+    }
+
+    expected.sort();
+    let nested: Vec<PathBuf> = expected
+        .iter()
+        .filter(|e| expected.iter().any(|other| other.path != e.path && e.path.starts_with(&other.path)))
+        .map(|e| e.path.clone())
+        .collect();
+    expected.retain(|e| !nested.contains(&e.path));
+    for candidate in &mut expected {
+        candidate.size = written.iter().filter(|(file, _)| file.starts_with(&candidate.path)).map(|(_, len)| len).sum();
+    }
+    Ok(expected)
+}
+
+fn marker_contents(marker: &str) -> &'static str {
+    match marker {
+        "package.json" | "composer.json" => "{}\n",
+        "Cargo.toml" => "[package]\nname = \"fixture\"\n",
+        _ => "",
+    }
+}
+
+/// Creates `dir` with `size` bytes spread over `files` sparse files, `depth` levels down,
+/// and returns the files with their lengths.
+fn fill(dir: &Path, size: u64, files: u64, depth: usize) -> io::Result<Vec<(PathBuf, u64)>> {
+    let mut inner = dir.to_path_buf();
+    for level in 0..depth {
+        inner.push(format!("d{}", level));
+    }
+    fs::create_dir_all(&inner)?;
+    let mut filled = Vec::new();
+    for i in 0..files {
+        let mut len = size / files;
+        if i == files - 1 {
+            len += size % files;
+        }
+        let file = inner.join(format!("f{}.bin", i));
+        fs::File::create(&file)?.set_len(len)?;
+        filled.push((file, len));
+    }
+    Ok(filled)
+}
+
+#[cfg(unix)]
+fn link_dir(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn link_dir(target: &Path, link: &Path) -> io::Result<()> {
+    let status = std::process::Command::new("cmd").args(["/C", "mklink", "/J"]).arg(link).arg(target).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("mklink /J failed for {}", link.display())))
+    }
+}
+
+#[cfg(unix)]
+fn set_readable(path: &Path, readable: bool) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(if readable { 0o755 } else { 0o000 }))
+}
+
+#[cfg(not(unix))]
+fn set_readable(_path: &Path, _readable: bool) -> io::Result<()> {
+    Ok(())
+}
+
+/// A generated tree in a fresh directory under the system temp dir, removed on drop.
+pub struct Tree {
+    pub root: PathBuf,
+    unreadable: Vec<PathBuf>,
+}
+
+impl Tree {
+    pub fn new(name: &str) -> Self {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let root = std::env::temp_dir().join(format!("devpurge-{}-{}-{}", name, std::process::id(), NEXT.fetch_add(1, Ordering::Relaxed)));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).expect("could not create the fixture directory");
+        Tree { root, unreadable: Vec::new() }
+    }
+
+    /// Generates `spec` into the tree; see [`generate`].
+    pub fn generate(&mut self, spec: &FixtureSpec) -> Vec<Expected> {
+        self.unreadable.extend(spec.unreadable.iter().map(|u| self.root.join(u)));
+        generate(spec, &self.root).expect("could not generate the fixture")
+    }
+
+    /// Adds a small file, creating its directories.
+    pub fn file(&self, rel: &str) -> &Self {
+        let path = self.root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, b"x").unwrap();
+        self
+    }
+
+    pub fn dir(&self, rel: &str) -> &Self {
+        fs::create_dir_all(self.root.join(rel)).unwrap();
+        self
+    }
+
+    /// Adds a link at `rel` pointing to `target`, both relative to the tree.
+    pub fn link(&self, rel: &str, target: &str) -> &Self {
+        link_dir(&self.root.join(target), &self.root.join(rel)).unwrap();
+        self
+    }
+
+    /// What a scan with the default options finds, relative to the tree and sorted.
+    pub fn found(&self) -> Vec<String> {
+        let mut found: Vec<String> = ScanIter::new(&self.root, ScanOptions::default()).filter_map(Result::ok).map(|c| self.relative(&c.path)).collect();
+        found.sort();
+        found
+    }
+
+    /// `path` relative to the tree, with `/` separators.
+    pub fn relative(&self, path: &Path) -> String {
+        path.strip_prefix(&self.root).unwrap_or(path).to_string_lossy().replace('\\', "/")
+    }
+}
+
+impl Drop for Tree {
+    fn drop(&mut self) {
+        for dir in &self.unreadable {
+            let _ = set_readable(dir, true);
+        }
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::detect::{target_dirs, target_files};
+use crate::units::parse_size;
+use crate::CandidateDir;
+use human_bytes::human_bytes;
+use std::fmt;
+
+/// Minimum sizes below which candidates are dropped, optionally per target kind,
+/// e.g. `node_modules=200M,target=1G,default=50M`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SizeFloors {
+    /// Applies to kinds without a rule of their own.
+    pub default: u64,
+    pub kinds: Vec<(String, u64)>,
+}
+
+/// Parses a comma-separated list of `KIND=SIZE` rules plus an optional `default=SIZE`;
+/// a lone size (`500`, `1G`) is the default floor. Bare numbers are megabytes.
+pub fn parse_size_floors(input: &str) -> Result<SizeFloors, String> {
+    let mut floors = SizeFloors::default();
+    for rule in input.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+        let Some((kind, size)) = rule.split_once('=') else {
+            floors.default = parse_size(rule)?;
+            continue;
+        };
+        let kind = kind.trim();
+        let bytes = parse_size(size).map_err(|e| format!("{} in '{}'", e, rule))?;
+        if kind == "default" {
+            floors.default = bytes;
+        } else if target_dirs().iter().chain(target_files()).any(|t| t == kind) {
+            floors.kinds.retain(|(k, _)| k != kind);
+            floors.kinds.push((kind.to_string(), bytes));
+        } else {
+            return Err(format!(
+                "unknown target '{}' in '{}'; use default or one of {}",
+                kind,
+                rule,
+                [target_dirs(), target_files()].concat().join(", ")
+            ));
+        }
+    }
+    Ok(floors)
+}
+
+impl SizeFloors {
+    pub fn is_empty(&self) -> bool {
+        self.default == 0 && self.kinds.is_empty()
+    }
+
+    /// The rules written the way [`parse_size_floors`] reads them, e.g.
+    /// `node_modules=200M,default=50M`.
+    pub fn rules(&self) -> String {
+        let mut rules: Vec<String> = self.kinds.iter().map(|(k, b)| format!("{}={}", k, size_rule(*b))).collect();
+        if self.default > 0 {
+            rules.push(format!("default={}", size_rule(self.default)));
+        }
+        rules.join(",")
+    }
+
+    /// The rule that applies to `kind`: its own, or `default`.
+    pub fn rule_for(&self, kind: &str) -> (&str, u64) {
+        match self.kinds.iter().find(|(k, _)| k == kind) {
+            Some((k, bytes)) => (k, *bytes),
+            None => ("default", self.default),
+        }
+    }
+
+    pub fn admits(&self, candidate: &CandidateDir) -> bool {
+        candidate.size >= self.rule_for(&candidate.kind).1
+    }
+
+    /// Drops candidates below their floor, returning how many each rule removed
+    /// (rules that removed nothing are left out).
+    pub fn apply(&self, candidates: &mut Vec<CandidateDir>) -> Vec<(String, usize)> {
+        let mut removed: Vec<(String, usize)> = Vec::new();
+        candidates.retain(|c| {
+            if self.admits(c) {
+                return true;
+            }
+            let rule = self.rule_for(&c.kind).0;
+            match removed.iter_mut().find(|(r, _)| r == rule) {
+                Some((_, count)) => *count += 1,
+                None => removed.push((rule.to_string(), 1)),
+            }
+            false
+        });
+        removed
+    }
+}
+
+/// `bytes` in the largest unit that divides it evenly.
+fn size_rule(bytes: u64) -> String {
+    for (unit, size) in [("T", 1u64 << 40), ("G", 1 << 30), ("M", 1 << 20), ("K", 1 << 10)] {
+        if bytes.is_multiple_of(size) {
+            return format!("{}{}", bytes / size, unit);
+        }
+    }
+    format!("{}B", bytes)
+}
+
+impl fmt::Display for SizeFloors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rules: Vec<String> = self.kinds.iter().map(|(k, b)| format!("{} {}", k, human_bytes(*b as f64))).collect();
+        if self.default > 0 {
+            rules.push(format!("{}{}", if rules.is_empty() { "" } else { "others " }, human_bytes(self.default as f64)));
+        }
+        write!(f, "{}", rules.join(", "))
+    }
+}
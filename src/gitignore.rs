@@ -0,0 +1,79 @@
+//! Whether candidates are ignored by git in the repository around them. An artifact
+//! folder normally is; one that isn't may well be committed on purpose, like a
+//! `vendor/` checked in with the code.
+
+use crate::exec;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+const GIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitStatus {
+    /// The repository's ignore rules cover it.
+    Ignored,
+    /// Inside a repository that doesn't ignore it.
+    NotIgnored,
+    /// Inside a repository, but git couldn't be asked, e.g. under `--no-exec`.
+    Unknown(String),
+}
+
+/// The working tree `path` is in: the nearest folder above it with a `.git` directory,
+/// or the `.git` file worktrees and submodules have, so nested repositories win.
+pub fn repository_of(path: &Path) -> Option<&Path> {
+    path.ancestors().skip(1).find(|dir| dir.join(".git").exists())
+}
+
+/// The status of every path inside a repository; paths outside any are left out. Paths
+/// are grouped by repository and each repository is asked once, for all of them.
+pub fn statuses<'a>(paths: impl IntoIterator<Item = &'a Path>) -> HashMap<PathBuf, GitStatus> {
+    let mut by_repository: BTreeMap<PathBuf, Vec<(&Path, PathBuf)>> = BTreeMap::new();
+    for path in paths {
+        let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        if let Some(repository) = repository_of(&absolute) {
+            by_repository.entry(repository.to_path_buf()).or_default().push((path, absolute.clone()));
+        }
+    }
+
+    let mut found = HashMap::new();
+    for (repository, members) in by_repository {
+        let absolute: Vec<&Path> = members.iter().map(|(_, absolute)| absolute.as_path()).collect();
+        let ignored = ignored_in(&repository, &absolute);
+        for (path, absolute) in members {
+            let status = match &ignored {
+                Ok(ignored) if ignored.contains(&absolute) => GitStatus::Ignored,
+                Ok(_) => GitStatus::NotIgnored,
+                Err(e) => GitStatus::Unknown(e.clone()),
+            };
+            found.insert(path.to_path_buf(), status);
+        }
+    }
+    found
+}
+
+/// Which of `paths`, all inside `repository`, its ignore rules cover, asked in a single
+/// `git check-ignore`.
+fn ignored_in(repository: &Path, paths: &[&Path]) -> Result<HashSet<PathBuf>, String> {
+    let mut input = Vec::new();
+    for path in paths {
+        input.extend_from_slice(path.strip_prefix(repository).unwrap_or(path).to_string_lossy().as_bytes());
+        input.push(0);
+    }
+    let mut git = Command::new("git");
+    git.arg("-C").arg(repository).args(["check-ignore", "-z", "--stdin"]).env("GIT_TERMINAL_PROMPT", "0");
+    let out = exec::run_with_input(&mut git, Some(input), GIT_TIMEOUT).map_err(|e| format!("git: {}", e))?;
+    // 0 when some are ignored and 1 when none are; anything else is an error.
+    if !matches!(out.status.code(), Some(0 | 1)) {
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        let reason = stderr.lines().rfind(|line| !line.trim().is_empty()).unwrap_or("no output").trim().to_string();
+        return Err(format!("git check-ignore failed ({}): {}", out.status, reason));
+    }
+    Ok(out
+        .stdout
+        .split(|&byte| byte == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| repository.join(String::from_utf8_lossy(name).as_ref()))
+        .collect())
+}
@@ -0,0 +1,76 @@
+/// Matches a file name against a shell-style pattern where `*` stands for any run of
+/// characters and `?` for exactly one. Patterns without wildcards match exactly.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    // Where the last `*` was seen, and how much of the name it had consumed.
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, consumed)) => {
+                    p = star + 1;
+                    n = consumed + 1;
+                    backtrack = Some((star, consumed + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+pub fn is_pattern(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// Matches a path against an exclude pattern. Patterns are split at `/` and matched one
+/// component at a time with [`glob_match`], where a `**` component stands for any number
+/// of components. A pattern that doesn't start at the root may match anywhere in the path,
+/// and a path below a match matches too, so `legacy` excludes every directory of that
+/// name and its contents, the same as `**/legacy/**`.
+pub fn path_glob_match(pattern: &str, path: &std::path::Path) -> bool {
+    let pattern = pattern.replace('\\', "/");
+    let pattern = pattern.trim_end_matches('/');
+    let mut parts: Vec<&str> = pattern.split('/').collect();
+    let anchored = std::path::Path::new(pattern).is_absolute() || pattern.starts_with('/');
+    if !anchored && parts.first() != Some(&"**") {
+        parts.insert(0, "**");
+    }
+    // Whatever a pattern matches is excluded with everything below it.
+    if parts.last() != Some(&"**") {
+        parts.push("**");
+    }
+    // The root is an empty first component, as in the split pattern; after a Windows
+    // drive prefix (`C:`) it has no counterpart in the pattern and is dropped.
+    let components: Vec<String> = path
+        .components()
+        .enumerate()
+        .filter(|&(i, c)| i == 0 || c != std::path::Component::RootDir)
+        .map(|(_, c)| match c {
+            std::path::Component::RootDir => String::new(),
+            other => other.as_os_str().to_string_lossy().into_owned(),
+        })
+        .collect();
+    match_components(&parts, &components)
+}
+
+fn match_components(parts: &[&str], components: &[String]) -> bool {
+    match parts.split_first() {
+        None => components.is_empty(),
+        Some((&"**", rest)) => (0..=components.len()).any(|skip| match_components(rest, &components[skip..])),
+        Some((part, rest)) => components
+            .split_first()
+            .is_some_and(|(component, others)| glob_match(part, component) && match_components(rest, others)),
+    }
+}
@@ -0,0 +1,29 @@
+//! What to run to get cleaned folders back, grouped by the project they were in, for
+//! the summary after a cleanup.
+
+use crate::CandidateDir;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// The hints for one project folder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectHints {
+    pub project: PathBuf,
+    /// Each cleaned kind with how it comes back, in the order they were cleaned; a kind
+    /// cleaned twice in one project is listed once.
+    pub restores: Vec<(String, &'static str)>,
+}
+
+/// Groups the hints for `cleaned` by the folder each candidate sat in, sorted by that
+/// folder. Candidates whose kind has no hint are left out.
+pub fn regeneration_hints<'a>(cleaned: impl IntoIterator<Item = &'a CandidateDir>) -> Vec<ProjectHints> {
+    let mut by_project: BTreeMap<PathBuf, Vec<(String, &'static str)>> = BTreeMap::new();
+    for candidate in cleaned {
+        let (Some(hint), Some(project)) = (candidate.restore_hint(), candidate.path.parent()) else { continue };
+        let restores = by_project.entry(project.to_path_buf()).or_default();
+        if !restores.iter().any(|(kind, _)| *kind == candidate.kind) {
+            restores.push((candidate.kind.clone(), hint));
+        }
+    }
+    by_project.into_iter().map(|(project, restores)| ProjectHints { project, restores }).collect()
+}
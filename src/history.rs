@@ -0,0 +1,133 @@
+//! Append-only log of cleanup runs, safe to write from several runs at once.
+//!
+//! Each record is one line, `{"sum":"<fnv-1a hex>","entry":{...}}`, written with a
+//! single append so concurrent writers never interleave. A torn or corrupted line
+//! (e.g. after a crash mid-write) fails its checksum and is skipped on read.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// What one run deleted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub timestamp: u64,
+    /// The scan root; for runs that didn't scan one, like `--apply`, see [`common_root`].
+    pub root: PathBuf,
+    pub folders_deleted: u64,
+    pub bytes_reclaimed: u64,
+    /// The same by ecosystem (see [`crate::CandidateDir::ecosystem`]), or by kind when it has none.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub by_ecosystem: BTreeMap<String, Totals>,
+}
+
+/// The root recorded for a run that didn't scan one: the deepest folder holding every
+/// one of `paths`.
+pub fn common_root<'a>(paths: impl IntoIterator<Item = &'a Path>) -> PathBuf {
+    let mut parents = paths.into_iter().map(|path| path.parent().unwrap_or(path));
+    let Some(first) = parents.next() else { return PathBuf::new() };
+    parents.fold(first.to_path_buf(), |root, parent| {
+        root.components().zip(parent.components()).take_while(|(a, b)| a == b).map(|(a, _)| a).collect()
+    })
+}
+
+/// Folders cleaned and the bytes they gave back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Totals {
+    pub folders: u64,
+    pub bytes: u64,
+}
+
+/// What happened to one folder during a cleanup, appended as soon as it is known so a
+/// run that crashes halfway still leaves a record of what went before.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeletionRecord {
+    pub timestamp: u64,
+    pub path: PathBuf,
+    /// The target kind, e.g. `node_modules`.
+    pub kind: String,
+    /// How it was cleaned, e.g. `delete` or `trash`.
+    pub action: String,
+    /// Bytes reclaimed, including what went before a failure.
+    pub size: u64,
+    /// The folder is gone (or cleaned) entirely.
+    pub complete: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One line as read back: the entry is kept as written, since the checksum covers its
+/// exact text, not what serializing it again would give.
+#[derive(Deserialize)]
+struct Line {
+    sum: String,
+    entry: Box<RawValue>,
+}
+
+/// 64-bit FNV-1a; enough to tell a damaged line from an intact one.
+pub fn checksum(bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &byte in bytes {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Appends one record to the log at `file`, creating it (and its directory) if needed.
+pub fn append_record<T: Serialize>(file: &Path, entry: &T) -> io::Result<()> {
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let body = serde_json::to_string(entry).map_err(io::Error::other)?;
+    let mut line = format!("{{\"sum\":\"{}\",\"entry\":{}}}", checksum(body.as_bytes()), body);
+    line.push('\n');
+    let mut log = OpenOptions::new().create(true).read(true).append(true).open(file)?;
+    // A line torn by a crash would swallow this record too; start a fresh line after it.
+    if ends_mid_line(&mut log)? {
+        line.insert(0, '\n');
+    }
+    // One write of the whole line: with O_APPEND it lands after every earlier record.
+    log.write_all(line.as_bytes())
+}
+
+fn ends_mid_line(log: &mut fs::File) -> io::Result<bool> {
+    let len = log.metadata()?.len();
+    if len == 0 {
+        return Ok(false);
+    }
+    let mut last = [0u8];
+    log.seek(SeekFrom::Start(len - 1))?;
+    log.read_exact(&mut last)?;
+    Ok(last[0] != b'\n')
+}
+
+/// Every intact record in the log, plus how many lines were skipped as damaged.
+pub fn read_records<T: DeserializeOwned>(file: &Path) -> io::Result<(Vec<T>, usize)> {
+    let log = match fs::File::open(file) {
+        Ok(log) => log,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((Vec::new(), 0)),
+        Err(e) => return Err(e),
+    };
+    let mut records = Vec::new();
+    let mut damaged = 0;
+    for line in io::BufReader::new(log).split(b'\n') {
+        let line = line?;
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        let entry = serde_json::from_slice::<Line>(&line)
+            .ok()
+            .filter(|parsed| checksum(parsed.entry.get().as_bytes()) == parsed.sum)
+            .and_then(|parsed| serde_json::from_str(parsed.entry.get()).ok());
+        match entry {
+            Some(entry) => records.push(entry),
+            None => damaged += 1,
+        }
+    }
+    Ok((records, damaged))
+}
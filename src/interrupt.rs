@@ -0,0 +1,120 @@
+//! Ctrl-C. While a scan or a deletion is running, the first one asks it to stop at the
+//! next safe point, so the scan keeps what it found and a deletion finishes the folder it
+//! is on; anywhere else, or the second time, the run ends at once with the terminal put
+//! back the way it was.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Exit status of a run stopped by Ctrl-C, as shells report one killed by SIGINT.
+pub const INTERRUPTED_EXIT: i32 = 130;
+
+static INTERRUPTS: AtomicUsize = AtomicUsize::new(0);
+static STOPPABLE: AtomicUsize = AtomicUsize::new(0);
+static STDOUT_IS_TERM: AtomicBool = AtomicBool::new(false);
+static STDERR_IS_TERM: AtomicBool = AtomicBool::new(false);
+
+/// The error a run returns once it stopped for Ctrl-C, after saying what it kept.
+#[derive(Debug)]
+pub struct Interrupted;
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("interrupted")
+    }
+}
+
+impl std::error::Error for Interrupted {}
+
+/// Makes the first Ctrl-C a request to stop for as long as it is alive.
+pub struct Stoppable(());
+
+impl Drop for Stoppable {
+    fn drop(&mut self) {
+        STOPPABLE.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Lets a long step stop early: check [`interrupted`] at each safe point while it lives.
+pub fn stoppable() -> Stoppable {
+    STOPPABLE.fetch_add(1, Ordering::SeqCst);
+    Stoppable(())
+}
+
+/// Whether Ctrl-C was pressed since the run started.
+pub fn interrupted() -> bool {
+    INTERRUPTS.load(Ordering::SeqCst) > 0
+}
+
+/// Takes over Ctrl-C for the rest of the process.
+pub fn install() {
+    use std::io::IsTerminal;
+    STDOUT_IS_TERM.store(std::io::stdout().is_terminal(), Ordering::SeqCst);
+    STDERR_IS_TERM.store(std::io::stderr().is_terminal(), Ordering::SeqCst);
+    install_handler();
+}
+
+/// Shows the cursor again on whichever of stdout and stderr is a terminal.
+pub fn restore_terminal() {
+    for (term, is_term) in [(console::Term::stdout(), &STDOUT_IS_TERM), (console::Term::stderr(), &STDERR_IS_TERM)] {
+        if is_term.load(Ordering::SeqCst) {
+            let _ = term.show_cursor();
+        }
+    }
+}
+
+/// Counts the Ctrl-C and says whether the run should end now rather than at a safe point.
+fn must_exit_now() -> bool {
+    let count = INTERRUPTS.fetch_add(1, Ordering::SeqCst) + 1;
+    count > 1 || STOPPABLE.load(Ordering::SeqCst) == 0
+}
+
+#[cfg(unix)]
+fn install_handler() {
+    const SHOW_CURSOR: &str = "\x1b[?25h";
+
+    extern "C" fn on_interrupt(_signal: libc::c_int) {
+        if !must_exit_now() {
+            return;
+        }
+        // Only what is safe inside a signal handler: raw writes, then _exit.
+        for (fd, is_term) in [(libc::STDOUT_FILENO, &STDOUT_IS_TERM), (libc::STDERR_FILENO, &STDERR_IS_TERM)] {
+            if is_term.load(Ordering::SeqCst) {
+                unsafe { libc::write(fd, SHOW_CURSOR.as_ptr() as *const libc::c_void, SHOW_CURSOR.len()) };
+            }
+        }
+        crate::title::restore_from_signal();
+        unsafe { libc::_exit(INTERRUPTED_EXIT) };
+    }
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        libc::sigemptyset(&mut action.sa_mask);
+        // Reads and waits carry on after the first Ctrl-C; the step notices at its next check.
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigaction(libc::SIGINT, &action, std::ptr::null_mut());
+    }
+}
+
+/// Windows runs the handler on a thread of its own; returning 0 lets the default one end
+/// the process, which puts the console back itself.
+#[cfg(windows)]
+fn install_handler() {
+    const CTRL_C_EVENT: u32 = 0;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn SetConsoleCtrlHandler(handler: Option<unsafe extern "system" fn(u32) -> i32>, add: i32) -> i32;
+    }
+
+    unsafe extern "system" fn on_control(event: u32) -> i32 {
+        (event == CTRL_C_EVENT && !must_exit_now()) as i32
+    }
+    unsafe {
+        SetConsoleCtrlHandler(Some(on_control), 1);
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn install_handler() {}
+
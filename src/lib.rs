@@ -0,0 +1,57 @@
+pub mod activity;
+pub mod cache;
+pub mod check;
+pub mod config;
+pub mod confirm;
+pub mod detect;
+pub mod display;
+pub mod exec;
+pub mod export;
+pub mod failures;
+pub mod filesystem;
+#[doc(hidden)]
+pub mod fixtures;
+pub mod floors;
+pub mod gitignore;
+pub mod glob;
+pub mod hints;
+pub mod history;
+pub mod interrupt;
+pub mod limits;
+pub mod longpath;
+pub mod metrics;
+pub mod minitoml;
+pub mod monorepo;
+pub mod moves;
+pub mod notes;
+pub mod onboarding;
+pub mod order;
+pub mod owner;
+pub mod pathinput;
+pub mod plan;
+pub mod preselect;
+pub mod progress;
+pub mod projects;
+pub mod prompt;
+pub mod refine;
+pub mod remove;
+pub mod report;
+pub mod resume;
+pub mod rows;
+pub mod scanner;
+pub mod severity;
+mod sizing;
+pub mod snapshot;
+pub mod stats;
+pub mod summary;
+pub mod title;
+pub mod trash;
+pub mod ui;
+pub mod units;
+pub mod volume;
+mod walk;
+mod watchdog;
+pub mod workspaces;
+pub mod wsl;
+
+pub use scanner::{scan, CandidateDir, Fingerprint, ScanError, ScanIter, ScanOptions, ScanScope};
@@ -0,0 +1,133 @@
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+static FD_EXHAUSTED: AtomicUsize = AtomicUsize::new(0);
+
+/// How often an operation that ran out of descriptors is tried again, waiting twice as
+/// long each time from [`FIRST_WAIT`]: about two seconds in all before it is given up.
+const RETRIES: u32 = 11;
+const FIRST_WAIT: Duration = Duration::from_millis(1);
+
+/// Sizing walks running, and how many may run at once; see [`throttled`].
+struct Throttle {
+    running: usize,
+    allowed: usize,
+}
+
+static THROTTLE: Mutex<Throttle> = Mutex::new(Throttle { running: 0, allowed: usize::MAX });
+static SLOT_FREED: Condvar = Condvar::new();
+
+/// Whether an error means the process (or system) ran out of file descriptors.
+pub fn is_fd_exhaustion(error: &io::Error) -> bool {
+    #[cfg(unix)]
+    {
+        matches!(error.raw_os_error(), Some(libc::EMFILE) | Some(libc::ENFILE))
+    }
+    #[cfg(not(unix))]
+    {
+        // ERROR_TOO_MANY_OPEN_FILES
+        error.raw_os_error() == Some(4)
+    }
+}
+
+/// Counts a directory that could not be read for lack of descriptors.
+pub fn note_fd_exhaustion() {
+    FD_EXHAUSTED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Runs `op`, and again while it fails for lack of descriptors, waiting a little longer
+/// each time for other threads to close theirs. The first failure also halves how many
+/// sizing walks run at once ([`back_off`]); only the last one is counted
+/// ([`fd_exhaustion_count`]), as a directory that was given up.
+pub fn retry_exhausted<T>(mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    let mut wait = FIRST_WAIT;
+    for attempt in 0..=RETRIES {
+        match op() {
+            Err(e) if is_fd_exhaustion(&e) && attempt < RETRIES => {
+                if attempt == 0 {
+                    back_off();
+                }
+                std::thread::sleep(wait);
+                wait *= 2;
+            }
+            Err(e) => {
+                if is_fd_exhaustion(&e) {
+                    note_fd_exhaustion();
+                }
+                return Err(e);
+            }
+            ok => return ok,
+        }
+    }
+    unreachable!("the last attempt always returns")
+}
+
+/// Runs a sizing walk once there is room for it: without limit until one runs out of
+/// descriptors, then only as many at once as [`back_off`] left room for.
+pub fn throttled<T>(work: impl FnOnce() -> T) -> T {
+    let mut throttle = THROTTLE.lock().unwrap_or_else(|e| e.into_inner());
+    while throttle.running >= throttle.allowed {
+        throttle = SLOT_FREED.wait(throttle).unwrap_or_else(|e| e.into_inner());
+    }
+    throttle.running += 1;
+    drop(throttle);
+
+    struct Slot;
+    impl Drop for Slot {
+        fn drop(&mut self) {
+            THROTTLE.lock().unwrap_or_else(|e| e.into_inner()).running -= 1;
+            SLOT_FREED.notify_one();
+        }
+    }
+    let _slot = Slot;
+    work()
+}
+
+/// Halves how many sizing walks may run at once, down to one, for the rest of the run;
+/// the ones running finish, and no other starts until they are fewer than that.
+pub fn back_off() {
+    let mut throttle = THROTTLE.lock().unwrap_or_else(|e| e.into_inner());
+    throttle.allowed = (throttle.running.min(throttle.allowed) / 2).max(1);
+}
+
+/// Directories skipped so far because descriptors ran out.
+pub fn fd_exhaustion_count() -> usize {
+    FD_EXHAUSTED.load(Ordering::Relaxed)
+}
+
+/// Raises the soft open-file limit toward the hard limit, returning the new soft limit.
+#[cfg(unix)]
+pub fn raise_fd_limit() -> Option<u64> {
+    let mut limit: libc::rlimit = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        return None;
+    }
+
+    #[allow(unused_mut)]
+    let mut wanted = limit.rlim_max;
+    // macOS reports an unlimited hard limit but refuses anything above OPEN_MAX.
+    #[cfg(target_os = "macos")]
+    {
+        wanted = wanted.min(10240);
+    }
+    if wanted <= limit.rlim_cur {
+        #[allow(clippy::unnecessary_cast)]
+        return Some(limit.rlim_cur as u64);
+    }
+
+    let raised = libc::rlimit { rlim_cur: wanted, rlim_max: limit.rlim_max };
+    if unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &raised) } == 0 {
+        #[allow(clippy::unnecessary_cast)]
+        Some(wanted as u64)
+    } else {
+        #[allow(clippy::unnecessary_cast)]
+        Some(limit.rlim_cur as u64)
+    }
+}
+
+#[cfg(not(unix))]
+pub fn raise_fd_limit() -> Option<u64> {
+    None
+}
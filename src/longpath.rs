@@ -0,0 +1,55 @@
+//! Windows refuses paths longer than MAX_PATH (260 characters) unless they are written in
+//! the extended-length form, `\\?\C:\...`, and deep `node_modules` trees are often longer.
+//! Sizing and deletion hand the filesystem that form; what is shown keeps the usual one.
+
+use std::borrow::Cow;
+use std::path::Path;
+
+const VERBATIM: &str = r"\\?\";
+const VERBATIM_UNC: &str = r"\\?\UNC\";
+
+/// `path` as the filesystem calls should get it: absolute and extended-length on Windows,
+/// so everything below it can be reached however deep it is; unchanged elsewhere, and
+/// when it can't be made absolute.
+#[cfg(windows)]
+pub fn extended(path: &Path) -> Cow<'_, Path> {
+    use std::ffi::OsString;
+    use std::path::PathBuf;
+
+    let text = path.as_os_str().to_string_lossy();
+    if text.starts_with(VERBATIM) || text.starts_with(r"\\.\") {
+        return Cow::Borrowed(path);
+    }
+    // `absolute` also turns `/` into `\` and resolves `.` and `..`, which the
+    // extended-length form takes literally.
+    let Ok(absolute) = std::path::absolute(path) else { return Cow::Borrowed(path) };
+    let mut extended = OsString::new();
+    match absolute.to_str().and_then(|a| a.strip_prefix(r"\\")) {
+        Some(share) => {
+            extended.push(VERBATIM_UNC);
+            extended.push(share);
+        }
+        None => {
+            extended.push(VERBATIM);
+            extended.push(absolute.as_os_str());
+        }
+    }
+    Cow::Owned(PathBuf::from(extended))
+}
+
+#[cfg(not(windows))]
+pub fn extended(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
+/// `path` without the extended-length prefix, as the user would write it.
+pub fn friendly(path: &Path) -> Cow<'_, Path> {
+    let Some(text) = path.to_str() else { return Cow::Borrowed(path) };
+    if let Some(share) = text.strip_prefix(VERBATIM_UNC) {
+        return Cow::Owned(format!(r"\\{}", share).into());
+    }
+    match text.strip_prefix(VERBATIM) {
+        Some(local) => Cow::Owned(local.into()),
+        None => Cow::Borrowed(path),
+    }
+}
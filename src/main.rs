@@ -1,351 +1,2629 @@
-use walkdir::WalkDir;
-use dialoguer::{theme::SimpleTheme, MultiSelect, Input};
+use dialoguer::{theme::SimpleTheme, Confirm, Input};
 use indicatif::{ProgressBar, ProgressStyle};
 use human_bytes::human_bytes;
 use std::path::{Path, PathBuf};
 use std::fs;
-use anyhow::Result;
-use std::time::Duration;
-use clap::Parser;
-use serde::{Serialize, Deserialize};
-use directories::ProjectDirs;
-use console::Term;
-
-const TARGET_DIRS: &[&str] = &[
-    "node_modules", // JS/TS
-    "target",       // Rust
-    "build",        // Java/Gradle/C++
-    "dist",         // Web
-    ".gradle",      // Gradle
-    "vendor",       // PHP/Go
-    "__pycache__",  // Python
-    "bin", "obj",   // .NET
-    ".dart_tool",   // Dart
-    ".angular",     // Angular
-    ".next",        // Next.js
-    ".nuxt",        // Nuxt.js
-];
+use std::io::{self, IsTerminal, Read};
+use anyhow::{bail, Context, Result};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
+use devpurge::activity::{candidate_activity, last_modified, last_project_activity, recently_modified, DEFAULT_ACTIVE_WITHIN};
+use devpurge::check::{check_path, split_paths};
+use devpurge::confirm::{EscalationStyle, LargeSelectionPolicy};
+use devpurge::detect::{calculate_size, configure_targets, deletion_warning, is_safe_to_delete, is_safe_to_delete_file, parse_ad_hoc_target, parse_target, private_size, target_dirs, target_files, TARGET_DIRS, TARGET_FILES};
+use devpurge::display::{offline_badge, PathDisplay, PathStyle};
+use devpurge::rows::CandidateRows;
+use devpurge::exec::set_no_exec;
+use devpurge::export::{write_csv, write_json, write_ncdu};
+use devpurge::failures::{describe_error, FailureLog};
+use devpurge::floors::{parse_size_floors, SizeFloors};
+use devpurge::refine::{self, kinds, Refinement};
+use devpurge::hints::regeneration_hints;
+use devpurge::cache::{cache_key, load_cache, write_cache, CachedScan, ScanCache};
+use devpurge::interrupt::{self, interrupted, Interrupted, INTERRUPTED_EXIT};
+use devpurge::history::{append_record, common_root, DeletionRecord, RunRecord, Totals};
+use devpurge::stats::{read_stats, record_run, Stats};
+use devpurge::summary::summarize;
+use devpurge::metrics::{write_textfile, RunMetrics};
+use devpurge::owner::{only_mine_by_default, top_level, OwnerCache};
+use devpurge::order::{compare_paths, sort_candidates_by, PathOrder, SortKey};
+use devpurge::limits::{fd_exhaustion_count, raise_fd_limit};
+use devpurge::config::{add_to_list, default_config_path, load_config, parse_config, xdg_dir, Config};
+use devpurge::moves::{KnownProjects, Migration};
+use devpurge::notes::{note_label, Notes};
+use devpurge::onboarding::{self, config_text};
+use devpurge::pathinput::{clean_path_input, suggest_similar};
+use devpurge::plan::{hostname, parse_path_mapping, PathMapping, Plan};
+use devpurge::preselect::{parse_preselect, Preselect};
+use devpurge::progress::{parse_progress_target, ProgressEvents, ProgressTarget};
+use devpurge::projects::Risk;
+use devpurge::prompt::read_line_timeout;
+use devpurge::filesystem::RealFs;
+use devpurge::fixtures::{generate, parse_spec};
+use devpurge::gitignore::{self, GitStatus};
+use devpurge::trash;
+use devpurge::remove::{apply_action_with_progress, available_actions, Action};
+use devpurge::report::Report;
+use devpurge::resume::{SavedSelection, SavedSelections};
+use devpurge::title::{short_count, TerminalTitle};
+use devpurge::severity::Severity;
+use devpurge::snapshot::{diff, previous_snapshot, record_snapshot, Change, Snapshot};
+use devpurge::workspaces::{list_workspaces, parse_job_names, Workspace, WorkspaceState, DEFAULT_WORKSPACE_IDLE};
+use devpurge::wsl::{running_in_wsl, windows_drive_of};
+use devpurge::volume::{annotate_location, group_by_volume, mounted_ids, presence, Presence, VolumeTable, VolumeUsage};
+use devpurge::ui::{SelectOutcome, Selection};
+use devpurge::units::{age_of, format_age, format_duration, format_timestamp, parse_duration, parse_hours, parse_size, unix_secs};
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use serde_json::json;
+use directories::{BaseDirs, ProjectDirs};
+use console::{style, Term};
+use devpurge::scanner::DEFAULT_DIR_TIMEOUT;
+use devpurge::scanner::{ignore_patterns, system_caches, IGNORE_FILE};
+use devpurge::{CandidateDir, Fingerprint, ScanError, ScanIter, ScanOptions, ScanScope};
+
+/// Folders smaller than this are collapsed per kind unless `--small-below` says otherwise.
+const DEFAULT_SMALL_BELOW: u64 = 5 << 20;
+const DEFAULT_GROWN_FACTOR: f64 = 1.5;
+/// More threads than this only make deletions fight over the disk.
+const DEFAULT_MAX_JOBS: usize = 4;
+/// Cached scans older than this are ignored unless `--max-cache-age` says otherwise.
+const DEFAULT_MAX_CACHE_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+/// When scanning through WSL, how long to measure before reporting the scan rate.
+const WSL_THROUGHPUT_AFTER: Duration = Duration::from_secs(3);
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[command(group(ArgGroup::new("scan_root").args(["path", "ci_workspaces"])))]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[arg(short, long)]
     path: Option<String>,
 
-    #[arg(short, long, default_value_t = 0)]
-    min_size: u64,
+    /// Minimum size, in MB or with a unit, optionally per kind: node_modules=200M,target=1G,default=50M
+    #[arg(short, long, value_name = "SIZE|KIND=SIZE,...")]
+    min_size: Option<String>,
+
+    /// Only look for these kinds of folders and files, e.g. node_modules,target (repeatable)
+    #[arg(long, visible_alias = "only", value_name = "KIND,...", value_delimiter = ',', long_help = kinds_help("Only look for these kinds of folders and files, e.g. node_modules,target (repeatable)"))]
+    targets: Vec<String>,
+
+    /// Don't look for these kinds of folders and files, e.g. vendor,bin (repeatable)
+    #[arg(long, value_name = "KIND,...", value_delimiter = ',', long_help = kinds_help("Don't look for these kinds of folders and files, e.g. vendor,bin (repeatable)"))]
+    skip: Vec<String>,
+
+    /// Also look for folders named NAME with one of the MARKER files next to it, for this run only, e.g. coverage:package.json (repeatable)
+    #[arg(long = "target", value_name = "NAME:MARKER,...")]
+    ad_hoc_targets: Vec<String>,
+
+    /// Let a --target without markers match folders on their name alone
+    #[arg(long)]
+    allow_unmarked: bool,
+
+    /// Never walk into paths matching this glob, e.g. '**/legacy/**' or '/mnt/share' (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<String>,
 
     #[arg(long)]
     scan: bool,
 
     #[arg(long)]
     no_cache: bool,
+
+    /// Don't read scan results from the cache
+    #[arg(long)]
+    no_cache_read: bool,
+
+    /// Don't write scan results to the cache
+    #[arg(long)]
+    no_cache_write: bool,
+
+    /// Don't log each deleted folder to deletions.jsonl in the data directory
+    #[arg(long)]
+    no_log: bool,
+
+    /// Don't print what to run to restore the deleted folders
+    #[arg(long)]
+    no_hints: bool,
+
+    /// Rescan instead of using cached results older than this many hours, or a duration like 2d (default 24, 0 never expires)
+    #[arg(long, value_name = "HOURS", value_parser = parse_hours)]
+    max_cache_age: Option<Duration>,
+
+    /// Directory for the scan cache (also DEVPURGE_CACHE_DIR)
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+
+    /// Read settings from this file instead of config.toml in the config directory
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+
+    /// Only offer folders whose project sources haven't changed for this long (e.g. 90d, 6mo)
+    #[arg(long, value_parser = parse_duration)]
+    project_idle: Option<Duration>,
+
+    /// Only offer folders not modified for at least this many days (or a duration like 6mo)
+    #[arg(long, value_name = "DAYS", value_parser = parse_duration)]
+    older_than: Option<Duration>,
+
+    /// Folders modified within this long may still be written to by a build; they are marked and not preselected (default 10m)
+    #[arg(long, value_parser = parse_duration)]
+    active_within: Option<Duration>,
+
+    /// Leave out folders modified within --active-within altogether
+    #[arg(long)]
+    skip_active: bool,
+
+    /// Only offer folders that are always safe to delete, leaving out those marked caution (like vendor or a .NET bin)
+    #[arg(long)]
+    safe_only: bool,
+
+    /// Only offer folders their git repository ignores; folders outside any repository are kept
+    #[arg(long)]
+    gitignored_only: bool,
+
+    /// Also offer caches outside the scan root that live at fixed places in the home folder, like Xcode's DerivedData
+    #[arg(long)]
+    system_caches: bool,
+
+    /// Show scan and deletion progress in the terminal title
+    #[arg(long)]
+    set_title: bool,
+
+    /// Resolve unanswered prompts automatically after this long (e.g. 10m)
+    #[arg(long, value_parser = parse_duration)]
+    prompt_timeout: Option<Duration>,
+
+    /// What to do when a prompt times out
+    #[arg(long, value_enum, default_value_t = OnTimeout::Cancel, requires = "prompt_timeout")]
+    on_timeout: OnTimeout,
+
+    /// Selections at least this large need an extra confirmation (default 100G)
+    #[arg(long, value_parser = parse_size)]
+    large_selection: Option<u64>,
+
+    /// Selections with more folders than this need an extra confirmation (default 500)
+    #[arg(long)]
+    large_selection_count: Option<usize>,
+
+    /// How large selections are confirmed
+    #[arg(long, value_enum)]
+    large_confirm: Option<EscalationStyle>,
+
+    /// Let --yes delete a selection above the large-selection thresholds, which it otherwise refuses
+    #[arg(long, requires = "yes")]
+    allow_large_selection: bool,
+
+    /// Group the selection list
+    #[arg(long, value_enum)]
+    group_by: Option<GroupBy>,
+
+    /// Write the results as an ncdu JSON export (open with `ncdu -f FILE`)
+    #[arg(long, value_name = "FILE")]
+    export_ncdu: Option<PathBuf>,
+
+    /// Print the folders found to stdout as json or csv instead of offering them for deletion
+    #[arg(long, value_enum, conflicts_with_all = ["apply", "save_plan", "ci_workspaces", "yes"])]
+    format: Option<OutputFormat>,
+
+    /// Write node_exporter textfile metrics about the run to this file
+    #[arg(long, value_name = "FILE")]
+    metrics_file: Option<PathBuf>,
+
+    /// Order equally sized folders naturally (v2 before v10) instead of byte by byte
+    #[arg(long)]
+    natural_sort: bool,
+
+    /// Order the list by size (largest first), path, project name or age (oldest first)
+    #[arg(long, value_enum, default_value_t = SortKey::Size)]
+    sort: SortKey,
+
+    /// Reverse the order chosen with --sort
+    #[arg(long)]
+    reverse: bool,
+
+    /// Collapse folders of one kind smaller than this into a single row (default 5M, 0 to disable)
+    #[arg(long, value_parser = parse_size)]
+    small_below: Option<u64>,
+
+    /// Ask again before deleting a folder that grew by more than this factor since it was sized (default 1.5)
+    #[arg(long)]
+    grown_factor: Option<f64>,
+
+    /// How paths are shown in the selection list and the selected folders
+    #[arg(long, value_enum, default_value_t = PathStyle::Home)]
+    paths: PathStyle,
+
+    /// Show paths relative to the scan root, which is shown once above them; same as --paths relative
+    #[arg(long, conflicts_with = "paths")]
+    relative: bool,
+
+    /// Send NDJSON progress events to pipe:<fd|handle>, pipe:<named pipe> or a file
+    #[arg(long, value_name = "TARGET", value_parser = parse_progress_target)]
+    progress: Option<ProgressTarget>,
+
+    /// Only preselect folders matching this policy, e.g. idle>90d, age>30d or size>=1G
+    #[arg(long, value_name = "POLICY", value_parser = parse_preselect)]
+    preselect: Option<Preselect>,
+
+    /// Stop scanning once the folders found add up to this much (e.g. 20G), walking breadth-first
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    find_at_least: Option<u64>,
+
+    /// Write the found folders to a plan file instead of selecting and deleting them
+    #[arg(long, value_name = "FILE", conflicts_with = "apply")]
+    save_plan: Option<PathBuf>,
+
+    /// Path prefix rewrite stored in the plan for applying it elsewhere (FROM=TO, repeatable)
+    #[arg(long, value_name = "FROM=TO", value_parser = parse_path_mapping, requires = "save_plan")]
+    plan_map: Vec<PathMapping>,
+
+    /// Delete the folders listed in a plan file
+    #[arg(long, value_name = "FILE")]
+    apply: Option<PathBuf>,
+
+    /// Apply plan entries even when this host and volume don't match the plan's
+    #[arg(long, requires = "apply")]
+    ignore_host_check: bool,
+
+    /// Mark folders as new, grown or known compared to the previous scan of the same root
+    #[arg(long)]
+    diff_previous: bool,
+
+    /// Skip folders in trees owned by other users (on by default for roots outside your home owned by someone else)
+    #[arg(long, conflicts_with = "all_owners")]
+    only_mine: bool,
+
+    /// Offer folders in every user's trees, overriding the --only-mine default
+    #[arg(long)]
+    all_owners: bool,
+
+    /// Skip directories that don't open within this long, e.g. dead network mounts (default 10s, 0 waits forever)
+    #[arg(long, value_name = "DUR", value_parser = parse_duration)]
+    dir_timeout: Option<Duration>,
+
+    /// Find folders without measuring them; only the ones selected are sized, before the confirmation
+    #[arg(long, conflicts_with_all = ["min_size", "yes", "format", "export_ncdu", "save_plan", "find_at_least", "ci_workspaces"])]
+    fast: bool,
+
+    /// Threads sizing found folders while the scan goes on (default: one per CPU; 1 sizes them one at a time)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    scan_threads: Option<u16>,
+
+    /// Look for folders at most this many levels below the scan root: 0 is the root itself, 1
+    /// its direct children. Found folders are always sized in full
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Never run external programs (git); checks that need them report "unknown" instead
+    #[arg(long)]
+    no_exec: bool,
+
+    /// Skip the first-run questions (where to scan, what to skip, caching)
+    #[arg(long)]
+    no_onboarding: bool,
+
+    /// Delete every folder that passes the filters without asking (needs --path and a minimum size, or retry-failed)
+    #[arg(short = 'y', long, visible_alias = "non-interactive")]
+    yes: bool,
+
+    /// Treat every directory under this CI workspace root (e.g. /var/lib/jenkins/workspace) as its own project
+    #[arg(long, value_name = "ROOT", conflicts_with_all = ["apply", "save_plan"])]
+    ci_workspaces: Option<PathBuf>,
+
+    /// With --ci-workspaces, offer the artifacts of workspaces idle this long (default 14d)
+    #[arg(long, value_name = "DUR", value_parser = parse_duration, requires = "ci_workspaces")]
+    workspace_idle: Option<Duration>,
+
+    /// With --ci-workspaces, read the active job names from stdin and offer other jobs' workspaces whole
+    #[arg(long, requires = "ci_workspaces")]
+    active_jobs_stdin: bool,
+
+    /// Scan and select as usual, then report what would be deleted instead of deleting it
+    #[arg(long, conflicts_with = "apply")]
+    dry_run: bool,
+
+    /// How selected folders are cleaned, unless changed per folder with `a` in the list
+    #[arg(long, value_enum, default_value = "delete")]
+    action: Action,
+
+    /// Move folders to the trash (Recycle Bin on Windows) instead of deleting them permanently
+    #[arg(long, conflicts_with = "action")]
+    trash: bool,
+
+    /// On Unix, give yourself write permission on folders whose entries can't be removed,
+    /// and try again (Windows always clears read-only attributes)
+    #[arg(long)]
+    force: bool,
+
+    /// Folders deleted at the same time (default: one per CPU, at most 4); 1 deletes them one
+    /// after another, which suits spinning disks
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    jobs: Option<u16>,
+
+    /// Also write the run's report (summary, candidates, deletion results) to this file as plain text
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct CandidateDir {
-    path: PathBuf,
-    size: u64,
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Retry deleting folders that failed on earlier runs
+    RetryFailed,
+    /// Run the safety checks on one path without deleting anything
+    Check {
+        path: PathBuf,
+    },
+    /// Run the safety checks on paths read from stdin, printing a JSON verdict per path
+    Verify {
+        /// Read paths from stdin, one per line or NUL-separated
+        #[arg(long, required = true)]
+        stdin: bool,
+    },
+    /// Show how much DevPurge has reclaimed over all runs
+    Stats,
+    /// Inspect the scan cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
+    },
+    /// List or clear the notes attached to folders (n in the selection list)
+    Notes {
+        #[command(subcommand)]
+        action: Option<NotesCommand>,
+    },
+    /// Build a synthetic project tree from a fixture spec, for manual testing and benchmarks
+    #[command(hide = true)]
+    GenFixture {
+        spec: PathBuf,
+        dir: PathBuf,
+    },
 }
 
-fn is_target(name: &str) -> bool {
-    TARGET_DIRS.contains(&name)
+#[derive(Subcommand, Debug)]
+enum NotesCommand {
+    /// Print every note with its folder (the default)
+    List,
+    /// Remove the notes on these folders, or every note with --all
+    Clear {
+        #[arg(required_unless_present = "all")]
+        paths: Vec<PathBuf>,
+        #[arg(long, conflicts_with = "paths")]
+        all: bool,
+    },
 }
 
-fn has_file(path: &Path, file_name: &str) -> bool {
-    path.join(file_name).exists()
+#[derive(Subcommand, Debug)]
+enum CacheCommand {
+    /// Print where the cache is stored and whether it is writable
+    Path,
+    /// Count cached folders that are present, on unmounted volumes, or gone
+    Stats,
 }
 
-fn has_any_file(path: &Path, files: &[&str]) -> bool {
-    files.iter().any(|f| path.join(f).exists())
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OnTimeout {
+    Cancel,
+    ProceedWithPreselection,
 }
 
-fn has_file_with_extension(path: &Path, extension: &str) -> bool {
-    if let Ok(entries) = std::fs::read_dir(path) {
-        for entry in entries.flatten() {
-            if let Some(ext) = entry.path().extension() {
-                if ext == extension {
-                    return true;
-                }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum GroupBy {
+    Volume,
+    Owner,
+    Ecosystem,
+}
+
+/// The `--help` text of a flag taking kinds, listing the built-in ones.
+fn kinds_help(summary: &str) -> String {
+    format!("{}\n\nBuilt-in kinds: {}. Targets added in the config file work too.", summary, [TARGET_DIRS, TARGET_FILES].concat().join(", "))
+}
+
+fn print_volume_summary(report: &mut Report, groups: &[VolumeUsage], severity: &Severity) {
+    for group in groups {
+        let name = group.mount_point.as_ref().map(|m| m.display().to_string()).unwrap_or_else(|| "?".to_string());
+        let free = match group.free {
+            Some(free) => format!("free {} -> {}", human_bytes(free as f64), human_bytes((free + group.bytes) as f64)),
+            None => "free space unknown".to_string(),
+        };
+        let size = severity.size(group.bytes, &format!("{:>10}", human_bytes(group.bytes as f64)));
+        report.line(format!("  {:<20} {:>4} folders  {}  {}", name, group.count, size, free));
+    }
+}
+
+impl Args {
+    fn read_cache(&self) -> bool {
+        !self.scan && !self.no_cache && !self.no_cache_read
+    }
+
+    /// Where each deleted folder is logged, unless `--no-log`, how many are deleted at once,
+    /// whether anyone is there to ask about a folder that grew, and `--force`.
+    fn delete_settings(&self) -> DeleteSettings {
+        let jobs = self.jobs.map_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()).min(DEFAULT_MAX_JOBS), usize::from);
+        DeleteSettings { log: get_data_path("deletions.jsonl").filter(|_| !self.no_log), jobs, ask_grown: !self.yes, force: self.force }
+    }
+
+    fn path_style(&self) -> PathStyle {
+        if self.relative { PathStyle::Relative } else { self.paths }
+    }
+
+    fn write_cache(&self) -> bool {
+        !self.no_cache && !self.no_cache_write
+    }
+
+    /// The action selected folders get unless changed in the list.
+    fn default_action(&self) -> Action {
+        if self.trash { Action::Trash } else { self.action }
+    }
+
+    /// How folders are removed where no other action applies: retries, plans, workspaces.
+    fn removal(&self) -> Action {
+        if self.default_action() == Action::Trash { Action::Trash } else { Action::Delete }
+    }
+}
+
+/// The cache directory and where the choice came from.
+fn resolve_cache_dir(override_dir: Option<&Path>) -> Option<(PathBuf, &'static str)> {
+    if let Some(dir) = override_dir {
+        return Some((dir.to_path_buf(), "--cache-dir"));
+    }
+    match std::env::var_os("DEVPURGE_CACHE_DIR") {
+        Some(dir) if !dir.is_empty() => return Some((PathBuf::from(dir), "DEVPURGE_CACHE_DIR")),
+        _ => {}
+    }
+    if let Some(dir) = xdg_dir("XDG_CACHE_HOME") {
+        return Some((dir, "XDG_CACHE_HOME"));
+    }
+    ProjectDirs::from("com", "devpurge", "devpurge").map(|dirs| (dirs.cache_dir().to_path_buf(), "platform default"))
+}
+
+fn get_cache_path(override_dir: Option<&Path>) -> Option<PathBuf> {
+    let Some((cache_dir, _)) = resolve_cache_dir(override_dir) else {
+        eprintln!("Warning: no cache directory could be determined. Caching is disabled for this run.");
+        return None;
+    };
+    if let Err(e) = fs::create_dir_all(&cache_dir) {
+        eprintln!("Warning: could not create cache directory {}: {}. Caching is disabled for this run.", cache_dir.display(), e);
+        return None;
+    }
+    Some(cache_dir.join("scan_cache.json"))
+}
+
+/// `devpurge stats`: the lifetime totals, by ecosystem, largest first.
+fn print_lifetime_stats() {
+    let Some(file) = get_data_path("stats.json") else {
+        println!("No data directory on this system, so nothing has been recorded.");
+        return;
+    };
+    let stats = read_stats(&file).unwrap_or_else(|e| {
+        eprintln!("Warning: {}; it starts again with the next run", e);
+        Stats::default()
+    });
+    if stats.runs == 0 {
+        println!("Nothing deleted yet.");
+        return;
+    }
+    match stats.first_run {
+        Some(first) => println!("Since {}:", format_timestamp(first)),
+        None => println!("All runs:"),
+    }
+    println!("  Space reclaimed: {}", human_bytes(stats.bytes_reclaimed as f64));
+    println!("  Folders deleted: {}", stats.folders_deleted);
+    println!("  Runs: {}", stats.runs);
+    if let Some(last) = stats.last_run {
+        println!("  Last run: {}", format_timestamp(last));
+    }
+    let mut ecosystems: Vec<_> = stats.by_ecosystem.iter().collect();
+    ecosystems.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then(a.0.cmp(b.0)));
+    if !ecosystems.is_empty() {
+        println!("By ecosystem:");
+        for (name, totals) in ecosystems {
+            println!("  {}: {} ({} folders)", name, human_bytes(totals.bytes as f64), totals.folders);
+        }
+    }
+}
+
+/// `devpurge cache stats`: how many cached folders could be offered right now.
+fn print_cache_stats(override_dir: Option<&Path>) {
+    let cache = resolve_cache_dir(override_dir).and_then(|(dir, _)| load_cache(&dir.join("scan_cache.json"))).unwrap_or_default();
+    if cache.roots.is_empty() {
+        println!("No cached scan.");
+        return;
+    }
+    let volumes = VolumeTable::load();
+    let mounted = mounted_ids(&volumes);
+    for (i, (root, cached)) in cache.roots.iter().enumerate() {
+        let mut counts = [(Presence::Present, "present", 0, 0u64), (Presence::Offline, "offline", 0, 0), (Presence::Stale, "stale", 0, 0)];
+        for c in &cached.candidates {
+            let found = presence(c, &volumes, &mounted);
+            if let Some((_, _, count, bytes)) = counts.iter_mut().find(|(p, ..)| *p == found) {
+                *count += 1;
+                *bytes += c.size;
             }
         }
+        if i > 0 {
+            println!();
+        }
+        println!("Scan root: {} (scanned {})", root.display(), ago(cached.scanned_at));
+        for (_, name, count, bytes) in counts {
+            println!("{:<8} {:>6} folders, {}", format!("{}:", name), count, human_bytes(bytes as f64));
+        }
     }
-    false
 }
 
-fn is_safe_to_delete(dir_name: &str, path: &Path) -> bool {
-    let parent = match path.parent() {
-        Some(p) => p,
-        None => return false,
+/// `devpurge cache path`: where the cache lives and whether it can be written.
+fn print_cache_path(override_dir: Option<&Path>) {
+    let Some((cache_dir, source)) = resolve_cache_dir(override_dir) else {
+        println!("No cache directory could be determined; set DEVPURGE_CACHE_DIR or pass --cache-dir.");
+        return;
     };
+    println!("{} (from {})", cache_dir.join("scan_cache.json").display(), source);
 
-    match dir_name {
-         "node_modules" => has_file(parent, "package.json"),
-         "target" => has_file(parent, "Cargo.toml"),
-         "build" => has_any_file(parent, &["pom.xml", "build.gradle", "build.gradle.kts", "Makefile", "CMakeLists.txt", "angular.json"]),
-         "dist" => has_any_file(parent, &["package.json", "angular.json", "tsconfig.json", "vite.config.js", "vite.config.ts"]),
-         ".gradle" => has_any_file(parent, &["build.gradle", "build.gradle.kts", "settings.gradle", "settings.gradle.kts"]),
-         "vendor" => has_any_file(parent, &["composer.json", "go.mod", "Gemfile"]),
-         "bin" | "obj" => has_file_with_extension(parent, "csproj") || has_file_with_extension(parent, "fsproj") || has_file_with_extension(parent, "sln"),
-         "__pycache__" => true, // Usually safe to delete if found
-         ".dart_tool" => has_file(parent, "pubspec.yaml"),
-         ".angular" => has_file(parent, "angular.json"),
-         ".next" => has_file(parent, "next.config.js") || has_file(parent, "next.config.ts"),
-         ".nuxt" => has_file(parent, "nuxt.config.js") || has_file(parent, "nuxt.config.ts"),
-         _ => false,
+    let probe = cache_dir.join(".devpurge-write-test");
+    let writable = fs::create_dir_all(&cache_dir).and_then(|_| fs::write(&probe, b"")).and_then(|_| fs::remove_file(&probe));
+    match writable {
+        Ok(()) => println!("Writable: yes"),
+        Err(e) => println!("Writable: no ({})", e),
     }
 }
 
-fn calculate_size(path: &Path) -> u64 {
-    WalkDir::new(path)
-        .into_iter()
-        .filter_map(|entry| entry.ok())
-        .filter_map(|entry| entry.metadata().ok())
-        .filter(|metadata| metadata.is_file())
-        .map(|metadata| metadata.len())
-        .sum()
+fn get_failures_path() -> Option<PathBuf> {
+    get_data_path("failed_deletions.json")
+}
+
+fn get_data_path(file_name: &str) -> Option<PathBuf> {
+    ProjectDirs::from("com", "devpurge", "devpurge").map(|dirs| dirs.data_dir().join(file_name))
 }
 
-fn get_cache_path() -> Option<PathBuf> {
-    if let Some(proj_dirs) = ProjectDirs::from("com", "devpurge", "devpurge") {
-        let cache_dir = proj_dirs.cache_dir();
-        if !cache_dir.exists() {
-            let _ = fs::create_dir_all(cache_dir);
+/// Appends the run to the history log and adds it to the lifetime stats. Only what was
+/// cleaned successfully counts, with the bytes each target gave back.
+fn record_history(root: &Path, cleaned: &[(CandidateDir, u64)]) {
+    if cleaned.is_empty() {
+        return;
+    }
+    let mut by_ecosystem: BTreeMap<String, Totals> = BTreeMap::new();
+    for (candidate, freed) in cleaned {
+        let totals = by_ecosystem.entry(candidate.ecosystem().to_string()).or_default();
+        totals.folders += 1;
+        totals.bytes += freed;
+    }
+    let run = RunRecord {
+        timestamp: unix_secs(SystemTime::now()),
+        root: root.to_path_buf(),
+        folders_deleted: cleaned.len() as u64,
+        bytes_reclaimed: cleaned.iter().map(|(_, freed)| freed).sum(),
+        by_ecosystem,
+    };
+    let written = match (get_data_path("history.jsonl"), get_data_path("stats.json")) {
+        (Some(log), Some(totals)) => {
+            if let Err(e) = read_stats(&totals) {
+                eprintln!("Warning: {}; the lifetime stats start again from this run", e);
+            }
+            append_record(&log, &run).and_then(|_| record_run(&totals, &run).map(drop))
         }
-        return Some(cache_dir.join("scan_cache.json"));
+        _ => Ok(()),
+    };
+    if let Err(e) = written {
+        eprintln!("Warning: could not record this run in the history: {}", e);
     }
-    None
 }
 
-fn load_cache(path: &Path) -> Option<Vec<CandidateDir>> {
-    if let Ok(file) = fs::File::open(path) {
-        if let Ok(candidates) = serde_json::from_reader(file) {
-            return Some(candidates);
+/// Writes the cache, warning once per run when it can't be.
+fn save_cache(path: &Path, cached: &ScanCache) -> bool {
+    if let Err(e) = write_cache(path, cached) {
+        static WARNED: AtomicBool = AtomicBool::new(false);
+        if !WARNED.swap(true, Ordering::Relaxed) {
+            eprintln!("Warning: could not write cache {}: {}. Results will not be cached.", path.display(), e);
         }
+        return false;
     }
-    None
+    true
 }
 
-fn save_cache(path: &Path, candidates: &[CandidateDir]) {
-    if let Ok(file) = fs::File::create(path) {
-        let _ = serde_json::to_writer(file, candidates);
+/// Whether a cached candidate would still be found: its project still has the marker
+/// files that make it safe to delete.
+fn still_a_target(candidate: &CandidateDir) -> bool {
+    let name = dir_name(&candidate.path);
+    match candidate.is_file {
+        true => is_safe_to_delete_file(name, &candidate.path),
+        false => is_safe_to_delete(name, &candidate.path),
     }
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+/// Reads a cached candidate's modification time again when its top level changed since the
+/// scan, or when the cache predates recording it.
+fn recheck_modified(candidate: &mut CandidateDir) {
+    if candidate.modified.is_none() || Fingerprint::of(&candidate.path) != candidate.fingerprint {
+        candidate.modified = last_modified(&candidate.path);
+    }
+}
 
-    println!("DevPurge - Developer Dependency Cleaner");
-    
-    let path = match args.path {
-        Some(p) => PathBuf::from(p),
-        None => {
-            let default_path = std::env::current_dir()?;
-            let path_str: String = Input::with_theme(&SimpleTheme)
+/// Heads the list of selected folders, naming the root once when they are shown relative to it.
+fn selected_heading(style: PathStyle, root: &Path) -> String {
+    match style {
+        PathStyle::Relative => format!("\nSelected folders under {}:", root.display()),
+        _ => "\nSelected folders:".to_string(),
+    }
+}
+
+/// Tail of a path, at most 50 characters, for spinner messages.
+fn short_path(path: &Path) -> String {
+    let display_path = path.to_string_lossy();
+    let char_count = display_path.chars().count();
+    if char_count > 50 {
+        let end_part: String = display_path.chars().skip(char_count - 47).collect();
+        format!("...{}", end_part)
+    } else {
+        display_path.to_string()
+    }
+}
+
+fn resolve_scan_path(arg: Option<&str>, configured: Option<&Path>) -> Result<Option<PathBuf>> {
+    let interactive = Term::stdout().is_term();
+    let mut pending = arg.map(str::to_string);
+    let mut default_path = match configured {
+        Some(path) => path.to_path_buf(),
+        None => std::env::current_dir()?,
+    };
+
+    loop {
+        let input = match pending.take() {
+            Some(input) => input,
+            None if interactive => Input::with_theme(&SimpleTheme)
                 .with_prompt("Enter path to scan")
                 .default(default_path.to_string_lossy().to_string())
-                .interact_text()?;
-            PathBuf::from(path_str)
-        }
-    };
-    
-    if !path.exists() {
-        eprintln!("Path does not exist!");
-        return Ok(());
-    }
+                .interact_text()?,
+            None => return Ok(None),
+        };
+        let path = clean_path_input(&input);
 
-    let cache_file_path = get_cache_path();
-    let mut candidates: Vec<CandidateDir> = Vec::new();
-    let mut from_cache = false;
+        if path.is_dir() {
+            return Ok(Some(path));
+        }
 
-    if !args.scan && !args.no_cache {
-        if let Some(ref cache_path) = cache_file_path {
-            if let Some(cached) = load_cache(cache_path) {
-                 println!("Loaded {} results from cache.", cached.len());
-                 candidates = cached.into_iter().filter(|c| c.path.exists()).collect();
-                 from_cache = true;
+        if path.exists() {
+            let parent = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+            eprintln!("{} is a file, not a directory.", path.display());
+            if interactive && Confirm::with_theme(&SimpleTheme)
+                .with_prompt(format!("Scan its parent directory {} instead?", parent.display()))
+                .default(true)
+                .interact()?
+            {
+                return Ok(Some(parent.to_path_buf()));
             }
+            default_path = parent.to_path_buf();
+            continue;
         }
-    }
-
-    if !from_cache {
-        println!("Scanning {} for dependency folders... This may take a while.", path.display());
-        
-        let spinner = ProgressBar::new_spinner();
-        spinner.set_style(ProgressStyle::default_spinner().template("{spinner:.green} {msg}").unwrap());
-        spinner.enable_steady_tick(Duration::from_millis(100));
 
-        let mut total_found_size = 0;
-        let mut it = WalkDir::new(&path).into_iter();
-        
-        loop {
-            let entry = match it.next() {
-                None => break,
-                Some(Err(_)) => continue,
-                Some(Ok(entry)) => entry,
-            };
-            
-            if entry.file_type().is_dir() {
-                let file_name = entry.file_name().to_string_lossy();
-                
-                let display_path = entry.path().to_string_lossy();
-                let char_count = display_path.chars().count();
-                let short_display = if char_count > 50 {
-                    let end_part: String = display_path.chars().skip(char_count - 47).collect();
-                    format!("...{}", end_part)
-                } else {
-                    display_path.to_string()
-                };
-                spinner.set_message(format!("Scanning: {}", short_display));
-
-                if is_target(&file_name) && is_safe_to_delete(&file_name, entry.path()) {
-                    let size = calculate_size(entry.path());
-                    total_found_size += size;
-                    
-                    candidates.push(CandidateDir {
-                        path: entry.path().to_path_buf(),
-                        size,
-                    });
-                    
-                    it.skip_current_dir();
-                }
+        eprintln!("Path does not exist: {}", path.display());
+        let suggestions = suggest_similar(&path);
+        if !suggestions.is_empty() {
+            eprintln!("Did you mean:");
+            for suggestion in &suggestions {
+                eprintln!("  {}", suggestion.display());
             }
+            default_path = suggestions[0].clone();
         }
-        
-        spinner.finish_and_clear();
+    }
+}
 
-        if !args.no_cache {
-             if let Some(ref cache_path) = cache_file_path {
-                 save_cache(cache_path, &candidates);
-                 println!("Scan results cached.");
-             }
-        }
+/// Extra friction for very large selections. These are never confirmed by a prompt timeout.
+fn confirm_large_selection(report: &mut Report, policy: &LargeSelectionPolicy, severity: &Severity, plan: &[(&CandidateDir, Action)], timeout: Option<Duration>) -> Result<bool> {
+    let total: u64 = plan.iter().map(|(c, _)| c.reclaimable()).sum();
+    println!("\nThis is a large selection: {} folders, {} in total.", plan.len(), human_bytes(total as f64));
+
+    let mut largest: Vec<&CandidateDir> = plan.iter().map(|&(c, _)| c).collect();
+    largest.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| compare_paths(&a.path, &b.path, PathOrder::Bytewise)));
+    println!("Largest selected folders:");
+    for candidate in largest.iter().take(10) {
+        println!("  {} ({})", candidate.path.display(), severity.size(candidate.size, &human_bytes(candidate.size as f64)));
     }
 
-    if candidates.is_empty() {
-        println!("No dependency folders found.");
-        return Ok(());
+    let expected = policy.expected_answer(total);
+    match policy.style {
+        EscalationStyle::Size => println!("\nType the total size in GB, rounded to a whole number ({}), to confirm:", expected),
+        EscalationStyle::Word => println!("\nType {} to confirm:", expected),
+    }
+    // Unlike the plain confirmation, running out of time never goes ahead here, whatever
+    // --on-timeout says.
+    let answer: String = match timeout {
+        Some(timeout) => match read_line_timeout(timeout) {
+            Some(line) => line?,
+            None => {
+                report.line(format!("Confirmation timed out after {}.", format_duration(timeout)));
+                return Ok(false);
+            }
+        },
+        None => Input::new().allow_empty(true).interact_text()?,
+    };
+    if answer.trim() != expected {
+        return Ok(false);
     }
 
-    let min_bytes = args.min_size * 1024 * 1024;
-    let original_count = candidates.len();
-    
-    if min_bytes > 0 {
-        candidates.retain(|c| c.size >= min_bytes);
-        println!("Filtered out {} folders smaller than {} MB.", original_count - candidates.len(), args.min_size);
+    for remaining in (1..=5).rev() {
+        print!("\rStarting in {}... (Ctrl-C to abort) ", remaining);
+        let _ = std::io::Write::flush(&mut std::io::stdout());
+        std::thread::sleep(Duration::from_secs(1));
     }
-    
-    if candidates.is_empty() {
-        println!("No dependency folders found matching criteria.");
-        return Ok(());
+    println!();
+    Ok(true)
+}
+
+/// What `--yes` deletes of the `checked` folders: never one that couldn't be read in full,
+/// since its size is only a lower bound and nobody is there to look at why.
+fn unattended(report: &mut Report, candidates: &[CandidateDir], checked: impl Iterator<Item = usize>) -> Vec<usize> {
+    let (incomplete, complete): (Vec<usize>, Vec<usize>) = checked.partition(|&idx| candidates[idx].is_incomplete());
+    if !incomplete.is_empty() {
+        report.line(format!("Leaving out {} folders that couldn't be read in full; --yes only deletes folders it measured completely.", incomplete.len()));
     }
+    complete
+}
 
-    let total_size: u64 = candidates.iter().map(|c| c.size).sum();
-    println!("Found {} folders. Total size: {}", candidates.len(), human_bytes(total_size as f64));
+/// Asks whether to go ahead with deleting the selection.
+fn confirm_deletion(args: &Args, report: &mut Report, large_policy: &LargeSelectionPolicy, severity: &Severity, plan: &[(&CandidateDir, Action)]) -> Result<bool> {
+    let selected_count = plan.len();
+    let selected_size: u64 = plan.iter().map(|(c, _)| c.reclaimable()).sum();
+    if args.yes {
+        if large_policy.applies(selected_count, selected_size) && !args.allow_large_selection {
+            bail!(
+                "the selection is large ({} folders, {}), which --yes doesn't delete on its own; add --allow-large-selection, or raise --large-selection and --large-selection-count",
+                selected_count,
+                human_bytes(selected_size as f64)
+            );
+        }
+        report.line(format!("\n{} without confirmation (--yes).", plan_summary(plan, &format!("{} folders ({})", selected_count, human_bytes(selected_size as f64)))));
+        return Ok(true);
+    }
 
-    candidates.sort_by(|a, b| b.size.cmp(&a.size));
+    if large_policy.applies(selected_count, selected_size) {
+        if !confirm_large_selection(report, large_policy, severity, plan, args.prompt_timeout)? {
+            report.line("Operation cancelled.");
+            return Ok(false);
+        }
+        return Ok(true);
+    }
 
-    let term = Term::stdout();
-    let _ = term.clear_screen();
-
-    let term_cols = term.size().1 as usize;
-    let max_width = if term_cols > 15 { term_cols - 15 } else { 60 };
-
-    let options: Vec<String> = candidates.iter()
-        .map(|c| {
-            let size_str = human_bytes(c.size as f64);
-            let raw_path = c.path.to_string_lossy();
-            let full_str = format!("{} ({})", raw_path, size_str);
-            
-            if full_str.chars().count() > max_width {
-                let extra_chars = 6;
-                let available_space = max_width.saturating_sub(size_str.len() + extra_chars);
-                
-                if available_space < 10 {
-                    let p_str = raw_path.to_string();
-                    let chars_count = p_str.chars().count();
-                    let end: String = p_str.chars().skip(chars_count.saturating_sub(max_width - size_str.len() - 5)).collect();
-                    format!("...{} ({})", end, size_str)
-                } else {
-                    let keep = (available_space) / 2;
-                    let p_str = raw_path.to_string();
-                    let start: String = p_str.chars().take(keep).collect();
-                    let end: String = p_str.chars().rev().take(keep).collect::<String>().chars().rev().collect();
-                    format!("{}...{} ({})", start, end, size_str)
-                }
-            } else {
-                full_str
+    match plan_action(plan) {
+        Some(Action::Delete) => println!("\nAre you sure you want to delete {} folders? (type 'yes' to confirm)", selected_count),
+        Some(Action::Trash) => println!("\nAre you sure you want to move {} folders to the trash? (type 'yes' to confirm)", selected_count),
+        _ => println!("\nAre you sure you want to clean {} folders as listed by action above? (type 'yes' to confirm)", selected_count),
+    }
+    let confirmation: String = match args.prompt_timeout {
+        Some(timeout) => match read_line_timeout(timeout) {
+            Some(line) => line?,
+            None if args.on_timeout == OnTimeout::ProceedWithPreselection => {
+                report.line(format!("Confirmation timed out after {}. Proceeding.", format_duration(timeout)));
+                "yes".to_string()
             }
-        })
-        .collect();
+            None => {
+                report.line(format!("Confirmation timed out after {}. Operation cancelled.", format_duration(timeout)));
+                return Ok(false);
+            }
+        },
+        None => Input::new().interact_text()?,
+    };
+    if confirmation.trim().to_lowercase() != "yes" {
+        report.line("Operation cancelled.");
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// Keeps the checkboxes of a selection that wasn't carried out, so the next run can offer them.
+fn remember_selection(file: Option<&Path>, saved: SavedSelection) {
+    if let Some(file) = file {
+        let mut selections = SavedSelections::load(file);
+        selections.put(saved);
+        if let Err(e) = selections.save(file) {
+            eprintln!("Warning: could not save the selection for next time: {}", e);
+        }
+    }
+}
 
-    let defaults = vec![true; options.len()];
+/// The first run asks a few questions: only when nothing was configured or cached
+/// yet, someone is at the terminal, and no path or plan was given.
+fn wants_onboarding(args: &Args, config_path: &Path) -> bool {
+    let cached = resolve_cache_dir(args.cache_dir.as_deref()).is_some_and(|(dir, _)| dir.join("scan_cache.json").exists());
+    !args.no_onboarding
+        && args.command.is_none()
+        && args.path.is_none()
+        && args.apply.is_none()
+        && Term::stdout().is_term()
+        && !config_path.exists()
+        && !cached
+}
 
-    println!("Select folders to DELETE (Up/Down to move, Space to toggle, Enter to confirm)");
+fn onboard(config_path: &Path) -> Result<Config> {
+    let home = BaseDirs::new().context("could not determine the home directory")?.home_dir().to_path_buf();
+    let text = config_text(&onboarding::ask(&home)?);
+    if let Some(dir) = config_path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("could not create {}", dir.display()))?;
+    }
+    fs::write(config_path, &text).with_context(|| format!("could not write {}", config_path.display()))?;
+    println!("Saved your choices to {}; edit or delete it to change them.\n", config_path.display());
+    parse_config(&text)
+}
 
-    let selections = MultiSelect::with_theme(&SimpleTheme)
-        .with_prompt("")
-        .items_checked(&options.iter().zip(defaults.iter()).map(|(s, &b)| (s.as_str(), b)).collect::<Vec<_>>())
-        .max_length(8)
-        .clear(true)
+/// Lists protected and excluded paths whose project seems to have moved and asks whether
+/// to add the new locations next to the old ones. Returns the answer, or `None` when
+/// nobody could be asked: no terminal, or `--yes` (which passes no config path).
+fn carry_over(report: &mut Report, config_path: Option<&Path>, migrations: &[Migration]) -> Result<Option<bool>> {
+    report.line(format!("{} protected or excluded paths are gone; their projects seem to have moved:", migrations.len()));
+    for m in migrations {
+        report.line(format!("  {:<9}  {} -> {}", m.list, m.from.display(), m.to.display()));
+    }
+    let Some(config_path) = config_path.filter(|_| Term::stdout().is_term()) else {
+        report.line("Run devpurge interactively to add the new paths to the config.");
+        return Ok(None);
+    };
+    let accepted = Confirm::with_theme(&SimpleTheme)
+        .with_prompt(format!("Add the new paths to the same lists in {}?", config_path.display()))
+        .default(false)
         .interact()?;
+    if !accepted {
+        return Ok(Some(false));
+    }
 
-    if selections.is_empty() {
-        println!("No folders selected. Exiting.");
-        return Ok(());
+    let mut text = fs::read_to_string(config_path).with_context(|| format!("could not read {}", config_path.display()))?;
+    for list in ["protected", "excludes"] {
+        let paths: Vec<PathBuf> = migrations.iter().filter(|m| m.list == list).map(|m| m.to.clone()).collect();
+        if !paths.is_empty() {
+            text = add_to_list(&text, list, &paths)?;
+        }
     }
+    fs::write(config_path, text).with_context(|| format!("could not write {}", config_path.display()))?;
+    report.line(format!("Added {} paths to {}:", migrations.len(), config_path.display()));
+    for m in migrations {
+        report.line(format!("  {} += {}", m.list, m.to.display()));
+    }
+    Ok(Some(true))
+}
 
-    println!("\nSelected folders:");
-    for &idx in &selections {
-        println!("  {}", options[idx]);
+/// Drops the selection saved for `root` once it has been carried out; other roots keep theirs.
+fn forget_selection(file: Option<&Path>, root: &Path) {
+    if let Some(file) = file {
+        let mut selections = SavedSelections::load(file);
+        if selections.remove(root) {
+            let _ = if selections.selections.is_empty() { fs::remove_file(file) } else { selections.save(file) };
+        }
     }
+}
 
-    let selected_count = selections.len();
-    println!("\nAre you sure you want to delete {} folders? (type 'yes' to confirm)", selected_count);
-    
-    let confirmation: String = Input::new().interact_text()?;
-    if confirmation.trim().to_lowercase() != "yes" {
-        println!("Operation cancelled.");
-        return Ok(());
+/// How long ago a unix timestamp was, e.g. "3 days ago" or "just now".
+fn ago(timestamp: u64) -> String {
+    match format_age(age_of(timestamp)) {
+        age if age == "just now" => age,
+        age => format!("{} ago", age),
     }
+}
 
-    println!("Deleting {} folders...", selected_count);
-    
-    let delete_bar = ProgressBar::new(selected_count as u64);
-    delete_bar.set_style(ProgressStyle::default_bar()
-        .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} {msg}")
-        .unwrap()
-        .progress_chars("##-"));
+fn manage_notes(action: &NotesCommand) -> Result<()> {
+    let mut notes = Notes::load(get_data_path("notes.json"));
+    match action {
+        NotesCommand::List => {
+            if notes.entries().next().is_none() {
+                println!("No notes. Press n on a folder in the selection list to add one.");
+            }
+            for (path, note) in notes.entries() {
+                println!("{}  {} ({})", path.display(), note_label(&note.text), ago(note.added));
+            }
+            return Ok(());
+        }
+        NotesCommand::Clear { all: true, .. } => println!("Removed {} notes.", notes.clear()),
+        NotesCommand::Clear { paths, .. } => {
+            for path in paths {
+                match notes.remove(path) {
+                    true => println!("Removed the note on {}.", path.display()),
+                    false => println!("No note on {}.", path.display()),
+                }
+            }
+        }
+    }
+    notes.save().context("could not save the notes")
+}
 
-    let mut reclaimed_space = 0;
+fn check_one(path: &Path, protected: &[PathBuf]) -> Result<()> {
+    let verdict = check_path(path, protected);
+    match verdict.ok {
+        true => println!("{}: safe to delete ({})", verdict.path.display(), verdict.kind.as_deref().unwrap_or("?")),
+        false => println!("{}: not safe to delete", verdict.path.display()),
+    }
+    for reason in &verdict.reasons {
+        println!("  - {}", reason);
+    }
+    for note in &verdict.notes {
+        println!("  note: {}", note);
+    }
+    if verdict.ok {
+        return Ok(());
+    }
+    bail!("{} failed the safety checks", verdict.path.display())
+}
 
-    let mut deleted_paths = Vec::new();
+/// `gen-fixture`: writes the tree `spec` describes into `dir` and lists what a full scan
+/// of it should find.
+fn gen_fixture(spec: &Path, dir: &Path) -> Result<()> {
+    let text = fs::read_to_string(spec).with_context(|| format!("could not read {}", spec.display()))?;
+    let spec = parse_spec(&text).map_err(|e| anyhow::anyhow!("invalid fixture spec {}: {}", spec.display(), e))?;
+    let expected = generate(&spec, dir).with_context(|| format!("could not generate the fixture in {}", dir.display()))?;
+    let total: u64 = expected.iter().map(|e| e.size).sum();
+    println!("A full scan of {} should find {} folders, {} in total:", dir.display(), expected.len(), human_bytes(total as f64));
+    for candidate in &expected {
+        println!("  {} ({})", candidate.path.display(), human_bytes(candidate.size as f64));
+    }
+    Ok(())
+}
 
-    for &idx in &selections {
-        let candidate = &candidates[idx];
-        delete_bar.set_message(format!("Deleting {}", candidate.path.display()));
-        
-        if let Err(e) = fs::remove_dir_all(&candidate.path) {
-            delete_bar.println(format!("Failed to delete {}: {}", candidate.path.display(), e));
-        } else {
-            reclaimed_space += candidate.size;
-            deleted_paths.push(candidate.path.clone());
+/// Prints one JSON verdict per path on stdin; fails if any path did.
+fn verify_stdin(protected: &[PathBuf]) -> Result<()> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input).context("could not read paths from stdin")?;
+    let paths = split_paths(&input);
+    let mut failed = 0;
+    for path in &paths {
+        let verdict = check_path(path, protected);
+        if !verdict.ok {
+            failed += 1;
         }
-        delete_bar.inc(1);
+        println!("{}", serde_json::to_string(&verdict)?);
     }
-    
-    delete_bar.finish_with_message("Done!");
-    
-    if !args.no_cache && !deleted_paths.is_empty() {
-        if let Some(ref cache_path) = cache_file_path {
-            if let Some(mut full_cache) = load_cache(cache_path) {
-                 full_cache.retain(|c| !deleted_paths.contains(&c.path));
-                 save_cache(cache_path, &full_cache);
-            }
-        }
+    if failed > 0 {
+        bail!("{} of {} paths failed verification", failed, paths.len());
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    raise_fd_limit();
+    interrupt::install();
+
+    let mut report = Report::new(args.output.clone());
+    if args.format.is_some() {
+        report.use_stderr();
+    }
+    let result = run(&args, &mut report);
+    if result.is_err() {
+        // A prompt given up on, e.g. with Ctrl-C, can leave the cursor hidden.
+        interrupt::restore_terminal();
+    }
+    if let (Some(output), Err(e)) = (&args.output, report.finish()) {
+        eprintln!("Warning: could not write the report to {}: {}", output.display(), e);
+    }
+    match result {
+        Err(e) if e.is::<Interrupted>() => std::process::exit(INTERRUPTED_EXIT),
+        result => result,
+    }
+}
+
+fn run(args: &Args, report: &mut Report) -> Result<()> {
+    let config_path = args.config.clone().or_else(default_config_path);
+    if let Some(path) = args.config.as_deref().filter(|p| !p.exists()) {
+        bail!("config file {} does not exist", path.display());
+    }
+    let mut config = match &config_path {
+        Some(config_path) => load_config(config_path)?,
+        None => Config::default(),
+    };
+    let ad_hoc = args.ad_hoc_targets.iter().map(|t| parse_ad_hoc_target(t, args.allow_unmarked)).collect::<Result<Vec<_>, _>>().map_err(|e| anyhow::anyhow!("invalid --target: {}", e))?;
+    configure_targets(&[config.targets.clone(), ad_hoc].concat(), &config.disabled_targets).map_err(|e| anyhow::anyhow!("invalid targets: {}", e))?;
+    // Kinds can only be checked once the config's targets are known.
+    let min_size = args.min_size.as_deref().map(parse_size_floors).transpose().map_err(|e| anyhow::anyhow!("invalid --min-size: {}", e))?;
+    let mut targets = args.targets.iter().map(|t| parse_target(t)).collect::<Result<Vec<_>, _>>().map_err(|e| anyhow::anyhow!("invalid --targets: {}", e))?;
+    let skipped = args.skip.iter().map(|t| parse_target(t)).collect::<Result<Vec<_>, _>>().map_err(|e| anyhow::anyhow!("invalid --skip: {}", e))?;
+    if !skipped.is_empty() {
+        if targets.is_empty() {
+            targets = target_dirs().iter().chain(target_files()).cloned().collect();
+        }
+        targets.retain(|t| !skipped.contains(t));
+        if targets.is_empty() {
+            bail!("--skip leaves no kinds of folders to look for");
+        }
+    }
+    set_no_exec(args.no_exec || config.no_exec);
+    if args.default_action() == Action::Trash {
+        if let Err(e) = trash::check_available() {
+            bail!("cannot move folders to the trash: {}. Nothing was deleted; run without --trash to delete permanently", e);
+        }
+    }
+
+    if args.yes && args.path.is_none() && args.ci_workspaces.is_none() && !matches!(args.command, Some(Command::RetryFailed)) {
+        bail!("--yes needs --path (or --ci-workspaces) to say what to delete, unless it is given to retry-failed");
+    }
+
+    if let Some(Command::Cache { action: CacheCommand::Path }) = args.command {
+        print_cache_path(args.cache_dir.as_deref());
+        return Ok(());
+    }
+    if let Some(Command::Cache { action: CacheCommand::Stats }) = args.command {
+        print_cache_stats(args.cache_dir.as_deref());
+        return Ok(());
+    }
+    if let Some(Command::Stats) = args.command {
+        print_lifetime_stats();
+        return Ok(());
+    }
+    if let Some(Command::Notes { action }) = &args.command {
+        return manage_notes(action.as_ref().unwrap_or(&NotesCommand::List));
+    }
+    if let Some(Command::GenFixture { spec, dir }) = &args.command {
+        return gen_fixture(spec, dir);
+    }
+    if let Some(Command::Check { path }) = &args.command {
+        return check_one(path, &config.protected);
+    }
+    if let Some(Command::Verify { .. }) = args.command {
+        return verify_stdin(&config.protected);
+    }
+
+    if let Some(config_path) = config_path.as_deref().filter(|p| wants_onboarding(args, p)) {
+        config = onboard(config_path)?;
+    }
+
+    report.line("DevPurge - Developer Dependency Cleaner");
+
+    let events = match &args.progress {
+        Some(target) => ProgressEvents::open(target).context("could not open the --progress target")?,
+        None => ProgressEvents::disabled(),
+    };
+
+    let cache_file_path = if config.cache != Some(false) && (args.read_cache() || args.write_cache()) {
+        get_cache_path(args.cache_dir.as_deref())
+    } else {
+        None
+    };
+
+    let mut large_policy = LargeSelectionPolicy::default();
+    if let Some(size) = &config.large_selection_size {
+        large_policy.min_bytes = parse_size(size).map_err(|e| anyhow::anyhow!("invalid large_selection_size in config: {}", e))?;
+    }
+    large_policy.min_bytes = args.large_selection.unwrap_or(large_policy.min_bytes);
+    large_policy.min_count = args.large_selection_count.or(config.large_selection_count).unwrap_or(large_policy.min_count);
+    large_policy.style = args.large_confirm.or(config.large_selection_confirm).unwrap_or(large_policy.style);
+    let grown_factor = args.grown_factor.or(config.grown_factor).unwrap_or(DEFAULT_GROWN_FACTOR);
+    let severity = config.colors.severity().map_err(|e| anyhow::anyhow!("invalid [colors] in config: {}", e))?;
+
+    let mut failures = FailureLog::load(get_failures_path());
+    failures.prune_missing();
+    if matches!(args.command, Some(Command::RetryFailed)) || args.apply.is_some() {
+        let title = TerminalTitle::new(args.set_title || config.set_title);
+        let cleanup = Cleanup { events: &events, title: &title, large_policy: &large_policy, severity: &severity, grown_factor };
+        return match &args.apply {
+            Some(plan_path) => apply_plan(args, report, plan_path, &config.protected, cache_file_path.as_deref(), &cleanup, &mut failures),
+            None => retry_failed(args, report, &config.protected, cache_file_path.as_deref(), &cleanup, &mut failures),
+        };
     }
     
-    println!("Cleanup complete! Reclaimed space: {}", human_bytes(reclaimed_space as f64));
+    let path = match &args.ci_workspaces {
+        Some(root) => root.clone(),
+        None => match resolve_scan_path(args.path.as_deref(), config.default_path.as_deref())? {
+            Some(path) => path,
+            None => return Ok(()),
+        },
+    };
+
+    let small_below = match (args.small_below, &config.small_below) {
+        (Some(bytes), _) => bytes,
+        (None, Some(size)) => parse_size(size).map_err(|e| anyhow::anyhow!("invalid small_below in config: {}", e))?,
+        (None, None) => DEFAULT_SMALL_BELOW,
+    };
+    let mut floors = match (min_size, &config.min_size) {
+        (Some(floors), _) => floors,
+        (None, Some(rules)) => parse_size_floors(rules).map_err(|e| anyhow::anyhow!("invalid min_size in config: {}", e))?,
+        (None, None) => SizeFloors::default(),
+    };
+    if args.fast && !floors.is_empty() {
+        report.line("Note: --fast doesn't measure folders, so min_size from the config isn't applied.");
+        floors = SizeFloors::default();
+    }
+    let dir_timeout = match (args.dir_timeout, &config.dir_timeout) {
+        (Some(timeout), _) => timeout,
+        (None, Some(timeout)) => parse_duration(timeout).map_err(|e| anyhow::anyhow!("invalid dir_timeout in config: {}", e))?,
+        (None, None) => DEFAULT_DIR_TIMEOUT,
+    };
+    if args.yes && args.ci_workspaces.is_none() && args.min_size.is_none() && config.min_size.is_none() {
+        bail!("--yes deletes every folder that passes the filters; set a minimum size with --min-size (or min_size in the config) as well");
+    }
+    let active_within = match (args.active_within, &config.active_within) {
+        (Some(window), _) => window,
+        (None, Some(window)) => parse_duration(window).map_err(|e| anyhow::anyhow!("invalid active_within in config: {}", e))?,
+        (None, None) => DEFAULT_ACTIVE_WITHIN,
+    };
+    let max_cache_age = match (args.max_cache_age, &config.max_cache_age) {
+        (Some(age), _) => age,
+        (None, Some(age)) => parse_hours(age).map_err(|e| anyhow::anyhow!("invalid max_cache_age in config: {}", e))?,
+        (None, None) => DEFAULT_MAX_CACHE_AGE,
+    };
+
+    let volumes = VolumeTable::load();
+    let title = Rc::new(TerminalTitle::new(args.set_title || config.set_title));
+
+    let mut candidates: Vec<CandidateDir> = Vec::new();
+    let mut from_cache = false;
+    let mut scanned_at = unix_secs(SystemTime::now());
+    let mut scan_duration = None;
+    let mut partial = false;
+
+    let mut scan_options = ScanOptions {
+        breadth_first: args.find_at_least.is_some(),
+        max_depth: args.max_depth,
+        excludes: config.protected.iter().chain(&config.excludes).cloned().collect(),
+        dir_timeout: Some(dir_timeout).filter(|t| !t.is_zero()),
+        size_threads: match args.scan_threads {
+            Some(threads) => threads as usize,
+            None => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        },
+        defer_sizing: args.fast,
+        ..ScanOptions::default()
+    };
+    scan_options.pruned.extend(config.prune.iter().cloned());
+    scan_options.exclude_patterns = args.exclude.clone();
+    match ignore_patterns(&path) {
+        Ok(patterns) => scan_options.exclude_patterns.extend(patterns),
+        Err(e) => eprintln!("Warning: could not read {}: {}", path.join(IGNORE_FILE).display(), e),
+    }
+    if !targets.is_empty() {
+        scan_options.targets = targets.iter().filter(|t| target_dirs().contains(t)).cloned().collect();
+        scan_options.target_files = targets.iter().filter(|t| target_files().contains(t)).cloned().collect();
+    }
+    let scope = ScanScope::new(&path, &scan_options);
+    let exclude_count = scope.excludes.len() + scope.exclude_patterns.len();
+
+    if args.ci_workspaces.is_some() {
+        let cleanup = Cleanup { events: &events, title: &title, large_policy: &large_policy, severity: &severity, grown_factor };
+        return clean_ci_workspaces(args, report, &path, &scan_options, &floors, &cleanup, &mut failures);
+    }
+
+    if args.read_cache() {
+        if let Some(ref cache_path) = cache_file_path {
+            let mut cache = load_cache(cache_path).unwrap_or_default();
+            let key = cache.entry_for(&scope);
+            match key.as_ref().and_then(|key| cache.roots.get_mut(key)) {
+                Some(cached) if cached.partial && args.find_at_least.is_none() => {
+                    report.line("Cached results come from a partial scan; rescanning.");
+                }
+                Some(cached) if !max_cache_age.is_zero() && age_of(cached.scanned_at) > max_cache_age => {
+                    report.line(format!("Cached results are from {}, older than {} (--max-cache-age); rescanning.", ago(cached.scanned_at), format_duration(max_cache_age)));
+                }
+                Some(cached) => match cached.scope.as_ref().map(|cached_scope| cached_scope.answers(&scope)) {
+                    None => report.line("Cached results don't record what was scanned; rescanning."),
+                    Some(Err(reason)) => report.line(format!("Cached results can't answer this scan ({}); rescanning.", reason)),
+                    Some(Ok(())) => {
+                        let total = cached.candidates.len();
+                        partial = cached.partial;
+                        let mounted = mounted_ids(&volumes);
+                        let presences: Vec<Presence> = cached.candidates.iter().map(|c| presence(c, &volumes, &mounted)).collect();
+                        let stale = presences.iter().filter(|&&p| p == Presence::Stale).count();
+                        let mut offline = Vec::new();
+                        let mut not_targets = Vec::new();
+                        let mut remeasured = Vec::new();
+                        let mut filled_in = 0;
+                        let spinner = ProgressBar::new_spinner();
+                        spinner.set_style(ProgressStyle::default_spinner().template("{spinner:.green} {msg}").unwrap());
+                        spinner.enable_steady_tick(Duration::from_millis(100));
+                        spinner.set_message("Checking cached folders...");
+                        for (mut candidate, presence) in cached.candidates.iter().cloned().zip(presences) {
+                            candidate.ensure_kind();
+                            match presence {
+                                _ if !scope.includes(&candidate) => {}
+                                Presence::Present if !still_a_target(&candidate) => not_targets.push(candidate.path),
+                                Presence::Present => {
+                                    // Only what changed at the top level since the scan is measured again,
+                                    // and what a --fast scan left unmeasured once sizes are wanted.
+                                    let changed = Fingerprint::of(&candidate.path) != candidate.fingerprint;
+                                    if changed || (candidate.size_unknown && !args.fast) {
+                                        spinner.set_message(format!("Measuring {}...", short_path(&candidate.path)));
+                                        if let Some(fresh) = candidate.remeasure() {
+                                            filled_in += usize::from(!changed);
+                                            candidate = fresh;
+                                            remeasured.push(candidate.clone());
+                                        }
+                                    }
+                                    candidates.push(candidate);
+                                }
+                                Presence::Offline => offline.push(candidate),
+                                Presence::Stale => {}
+                            }
+                        }
+                        spinner.finish_and_clear();
+                        scanned_at = cached.scanned_at;
+                        if remeasured.len() > filled_in {
+                            report.line(format!("Measured {} cached folders again; they changed since the scan.", remeasured.len() - filled_in));
+                        }
+                        if filled_in > 0 {
+                            report.line(format!("Measured {} cached folders a --fast scan had left unmeasured.", filled_in));
+                        }
+                        if !not_targets.is_empty() {
+                            report.line(format!("Dropped {} cached folders whose project no longer marks them safe to delete.", not_targets.len()));
+                        }
+                        if (stale > 0 || !not_targets.is_empty() || !remeasured.is_empty()) && args.write_cache() {
+                            cached.candidates.retain(|c| presence(c, &volumes, &mounted) != Presence::Stale && !not_targets.contains(&c.path));
+                            for c in cached.candidates.iter_mut() {
+                                if let Some(fresh) = remeasured.iter().find(|fresh| fresh.path == c.path) {
+                                    *c = fresh.clone();
+                                }
+                            }
+                            if save_cache(cache_path, &cache) && stale > 0 {
+                                report.line(format!("Pruned {} cache entries whose folders are gone.", stale));
+                            }
+                        }
+                        if !offline.is_empty() {
+                            report.line(format!("{} cached folders are on volumes that aren't mounted; they stay cached but can't be deleted now:", offline.len()));
+                            for c in &offline {
+                                let badge = offline_badge(Term::stdout().features().wants_emoji());
+                                report.line(style(format!("  {} ({})  {}", c.path.display(), human_bytes(c.size as f64), badge)).dim().to_string());
+                            }
+                        }
+                        let matching = candidates.iter().filter(|c| floors.admits(c)).count();
+                        report.line(format!("Cache: {} entries from {}, {} match current filters.", total, ago(scanned_at), matching));
+                        for candidate in candidates.iter_mut() {
+                            annotate_location(candidate, &volumes);
+                            recheck_modified(candidate);
+                        }
+                        from_cache = true;
+                    }
+                },
+                None => {}
+            }
+        }
+    }
+
+    if !from_cache {
+        report.line(format!("Scanning {} for dependency folders... This may take a while.", path.display()));
+        let through_wsl = running_in_wsl() && windows_drive_of(&volumes, &path).is_some();
+        if through_wsl {
+            report.line("Note: scanning a Windows drive through WSL; this will be slow. Consider running devpurge.exe from Windows instead.");
+        }
+        
+        let spinner = ProgressBar::new_spinner();
+        spinner.set_style(ProgressStyle::default_spinner().template("{spinner:.green} {msg}").unwrap());
+        spinner.enable_steady_tick(Duration::from_millis(100));
+
+        let scan_started = Instant::now();
+        let dirs_seen = Rc::new(Cell::new(0usize));
+        let found = Rc::new(Cell::new(0usize));
+        let found_bytes = Rc::new(Cell::new(0u64));
+        let depth_reached = Rc::new(Cell::new(0usize));
+        let mut last_title = Instant::now();
+        let mut throughput_reported = !through_wsl;
+
+        let progress = spinner.clone();
+        let (scan_title, scan_dirs, scan_found, scan_events) = (title.clone(), dirs_seen.clone(), found.clone(), events.clone());
+        let (scan_root, scan_depth, scan_bytes) = (path.clone(), depth_reached.clone(), found_bytes.clone());
+        events.emit("scan_start", json!({ "root": path }));
+        let stoppable = interrupt::stoppable();
+        let scan = ScanIter::new(&path, scan_options).stop_when(interrupted).on_dir(move |dir| {
+            scan_dirs.set(scan_dirs.get() + 1);
+            scan_depth.set(dir.strip_prefix(&scan_root).map(|rel| rel.components().count()).unwrap_or(0));
+            if last_title.elapsed() >= Duration::from_millis(100) {
+                last_title = Instant::now();
+                scan_title.set(&format!("devpurge: scanning ({} dirs, {} found)", short_count(scan_dirs.get()), scan_found.get()));
+                scan_events.emit("scan_progress", json!({ "path": dir, "dirs": scan_dirs.get(), "found": scan_found.get() }));
+            }
+
+            if !throughput_reported && scan_started.elapsed() >= WSL_THROUGHPUT_AFTER {
+                throughput_reported = true;
+                let rate = scan_dirs.get() as f64 / scan_started.elapsed().as_secs_f64();
+                progress.println(format!("Scanning at {:.0} directories/s through WSL.", rate));
+            }
+
+            match scan_found.get() {
+                0 => progress.set_message(format!("Scanning: {}", short_path(dir))),
+                n => progress.set_message(format!("Scanning: {} — {} found, {} so far", short_path(dir), n, human_bytes(scan_bytes.get() as f64))),
+            }
+        });
+        let (sizing_progress, sizing_events) = (spinner.clone(), events.clone());
+        let scan = scan.on_sizing(move |dir, bytes, files| {
+            sizing_progress.set_message(format!("Sizing {} — {}, {} files…", short_path(dir), human_bytes(bytes as f64), short_count(files as usize)));
+            sizing_events.emit("heartbeat", json!({ "phase": "sizing", "path": dir, "bytes": bytes, "files": files }));
+        });
+
+        let budget = args.find_at_least.unwrap_or(u64::MAX);
+        let mut slow_dirs = Vec::new();
+        for result in scan {
+            let candidate = match result {
+                Ok(candidate) => candidate,
+                Err(ScanError::Slow { path, timeout }) => {
+                    events.emit("skipped_slow", json!({ "path": path, "timeout_secs": timeout.as_secs_f64() }));
+                    slow_dirs.push(path);
+                    continue;
+                }
+                Err(_) => continue,
+            };
+            found.set(found.get() + 1);
+            let size = (!candidate.size_unknown).then_some(candidate.size);
+            events.emit("candidate", json!({ "path": candidate.path, "size": size, "kind": candidate.kind, "risk": candidate.risk, "unreadable": candidate.unreadable }));
+            found_bytes.set(found_bytes.get() + candidate.reclaimable());
+            candidates.push(candidate);
+            if found_bytes.get() >= budget {
+                partial = true;
+                break;
+            }
+        }
+        let stopped = interrupted();
+        drop(stoppable);
+        partial |= stopped;
+        events.emit("scan_done", json!({ "dirs": dirs_seen.get(), "found": found.get(), "skipped_slow": slow_dirs.len(), "interrupted": stopped }));
+        
+        spinner.finish_and_clear();
+        scan_duration = Some(scan_started.elapsed());
+        if stopped {
+            report.line(format!("Interrupted after {} directories: kept the {} folders found so far ({}).", dirs_seen.get(), found.get(), human_bytes(found_bytes.get() as f64)));
+        } else if partial {
+            // Walking breadth-first, every level above the current one has been seen in full.
+            report.line(format!(
+                "Stopped after finding {} in {} directories (complete down to depth {}); results are partial.",
+                human_bytes(found_bytes.get() as f64),
+                dirs_seen.get(),
+                depth_reached.get().saturating_sub(1)
+            ));
+        }
+
+        if !slow_dirs.is_empty() {
+            report.line(format!("Skipped {} directories that did not respond within {}:", slow_dirs.len(), format_duration(dir_timeout)));
+            for dir in &slow_dirs {
+                report.line(format!("  {}", dir.display()));
+            }
+        }
+
+        let exhausted = fd_exhaustion_count();
+        if exhausted > 0 {
+            eprintln!("Warning: {} directories could not be read because the process ran out of file descriptors, even after waiting for them.", exhausted);
+            eprintln!("Results are incomplete; raise the limit (e.g. `ulimit -n 4096`) and rescan with --scan.");
+        }
+
+        if args.write_cache() {
+             if let Some(ref cache_path) = cache_file_path {
+                 // Read again, so results another run cached for other roots meanwhile are kept.
+                 let mut cache = load_cache(cache_path).unwrap_or_default();
+                 cache.roots.insert(cache_key(&path), CachedScan { scanned_at, partial, scope: Some(scope), candidates: candidates.clone() });
+                 if save_cache(cache_path, &cache) {
+                     report.line("Scan results cached.");
+                 }
+             }
+        }
+        if stopped {
+            return Err(Interrupted.into());
+        }
+    }
+
+    let snapshot_dir = get_data_path("snapshots");
+    // A --fast scan has no sizes to compare later ones with.
+    if let (Some(dir), false, false, false) = (&snapshot_dir, from_cache, partial, args.fast) {
+        if let Err(e) = record_snapshot(dir, &path, Snapshot::new(scanned_at, &candidates)) {
+            eprintln!("Warning: could not record this scan for --diff-previous: {}", e);
+        }
+    }
+    let previous = snapshot_dir.as_deref().filter(|_| args.diff_previous).and_then(|dir| previous_snapshot(dir, &path, scanned_at));
+    if args.diff_previous && previous.is_none() {
+        report.line(format!("No earlier scan of {} to compare with.", path.display()));
+    }
+    let gone = previous.as_ref().map(|p| diff(p, &candidates).1.len()).unwrap_or(0);
+
+    if args.system_caches {
+        let spinner = ProgressBar::new_spinner();
+        spinner.set_style(ProgressStyle::default_spinner().template("{spinner:.green} {msg}").unwrap());
+        spinner.enable_steady_tick(Duration::from_millis(100));
+        let home = BaseDirs::new().context("could not determine the home directory for --system-caches")?.home_dir().to_path_buf();
+        let found = system_caches(&home, |dir, bytes, files| {
+            spinner.set_message(format!("Sizing {} — {}, {} files…", short_path(dir), human_bytes(bytes as f64), short_count(files as usize)));
+        });
+        spinner.finish_and_clear();
+        for mut candidate in found {
+            if candidates.iter().any(|known| known.path == candidate.path) {
+                continue;
+            }
+            annotate_location(&mut candidate, &volumes);
+            report.line(format!("Found {} ({}) outside the scan root.", candidate.path.display(), human_bytes(candidate.size as f64)));
+            candidates.push(candidate);
+        }
+    }
+
+    let known_file = get_data_path("projects.json").filter(|_| !config.protected.is_empty() || !config.excludes.is_empty());
+    if let Some(file) = known_file {
+        let mut known = KnownProjects::load(&file);
+        let rules: Vec<(&'static str, &Path)> = config.protected.iter().map(|p| ("protected", p.as_path()))
+            .chain(config.excludes.iter().map(|p| ("excludes", p.as_path())))
+            .collect();
+        let migrations = known.follow(&rules, &candidates);
+        if !migrations.is_empty() {
+            let answer = carry_over(report, config_path.as_deref().filter(|_| !args.yes), &migrations)?;
+            let withheld = known.settle(&migrations, answer, &mut candidates);
+            if answer != Some(true) && withheld > 0 {
+                report.line(format!("Left out {} folders in the moved projects for this run.", withheld));
+            }
+            for migration in migrations.iter().filter(|_| answer == Some(true)) {
+                match migration.list {
+                    "protected" => config.protected.push(migration.to.clone()),
+                    _ => config.excludes.push(migration.to.clone()),
+                }
+            }
+        }
+        if let Err(e) = known.save(&file) {
+            eprintln!("Warning: could not remember the projects behind protected and excluded paths: {}", e);
+        }
+    }
+
+    let mut notes = Notes::load(get_data_path("notes.json"));
+    let moved_notes = notes.follow_moves(&candidates);
+    if !moved_notes.is_empty() {
+        report.line(format!("Moved {} notes along with their projects:", moved_notes.len()));
+        for (from, to) in &moved_notes {
+            report.line(format!("  {} -> {}", from.display(), to.display()));
+        }
+        if let Err(e) = notes.save() {
+            eprintln!("Warning: could not save the notes: {}", e);
+        }
+    }
+
+    if candidates.is_empty() && args.format.is_none() {
+        write_metrics(args.metrics_file.as_deref(), &RunMetrics::new(&candidates, scan_duration));
+        report.line("No dependency folders found.");
+        return Ok(());
+    }
+
+    let mut owners = OwnerCache::default();
+    let home = BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+    if args.only_mine || (!args.all_owners && only_mine_by_default(&path, home.as_deref())) {
+        let before = candidates.len();
+        candidates.retain(|c| owners.is_mine(&top_level(&path, &c.path)));
+        if before > candidates.len() {
+            report.line(format!("Skipped {} folders in other users' trees (--all-owners includes them).", before - candidates.len()));
+        }
+    }
+
+    if let Some(idle) = args.project_idle {
+        let spinner = ProgressBar::new_spinner();
+        spinner.set_style(ProgressStyle::default_spinner().template("{spinner:.green} {msg}").unwrap());
+        spinner.enable_steady_tick(Duration::from_millis(100));
+        spinner.set_message("Estimating project activity...");
+        for candidate in candidates.iter_mut() {
+            candidate.project_modified = candidate_activity(candidate);
+        }
+        spinner.finish_and_clear();
+
+        let before = candidates.len();
+        candidates.retain(|c| c.project_modified.is_none_or(|t| age_of(t) >= idle));
+        report.line(format!("Filtered out {} folders in projects active within the last {}.", before - candidates.len(), format_duration(idle)));
+    }
+
+    if let Some(min_age) = args.older_than {
+        let before = candidates.len();
+        candidates.retain(|c| c.modified.is_none_or(|t| age_of(t) >= min_age));
+        report.line(format!("Filtered out {} folders modified within the last {}.", before - candidates.len(), format_duration(min_age)));
+        let unknown = candidates.iter().filter(|c| c.modified.is_none()).count();
+        if unknown > 0 {
+            report.line(format!("Kept {} folders whose modification time couldn't be read; their age shows as \"?\".", unknown));
+        }
+    }
+
+    if args.safe_only {
+        let before = candidates.len();
+        candidates.retain(|c| c.risk == Risk::Safe);
+        report.line(format!("Filtered out {} folders marked caution.", before - candidates.len()));
+    }
+
+    if args.skip_active {
+        let before = candidates.len();
+        candidates.retain(|c| !recently_modified(c, active_within));
+        report.line(format!("Filtered out {} folders modified within the last {}.", before - candidates.len(), format_duration(active_within)));
+    }
+
+    let git_status = gitignore::statuses(candidates.iter().map(|c| c.path.as_path()));
+    if args.gitignored_only {
+        let before = candidates.len();
+        candidates.retain(|c| git_status.get(&c.path).is_none_or(|status| *status == GitStatus::Ignored));
+        report.line(format!("Filtered out {} folders their git repository doesn't ignore.", before - candidates.len()));
+        if let Some(GitStatus::Unknown(reason)) = git_status.values().find(|status| matches!(status, GitStatus::Unknown(_))) {
+            report.line(format!("Some of them were dropped because git couldn't be asked ({}).", reason));
+        }
+    }
+
+    // Kept whole, so the refinement step can loosen the size floor again.
+    let refinable = Term::stdout().is_term() && !args.yes && args.format.is_none() && args.save_plan.is_none() && !args.fast;
+    let unfiltered = refinable.then(|| candidates.clone());
+    if !floors.is_empty() {
+        let removed = floors.apply(&mut candidates);
+        let total: usize = removed.iter().map(|(_, count)| count).sum();
+        let breakdown: Vec<String> = removed.iter().map(|(rule, count)| format!("{}: {}", rule, count)).collect();
+        match breakdown.is_empty() {
+            true => report.line("Filtered out 0 folders below their minimum size."),
+            false => report.line(format!("Filtered out {} folders below their minimum size ({}).", total, breakdown.join(", "))),
+        }
+    }
     
-    Ok(())
+    write_metrics(args.metrics_file.as_deref(), &RunMetrics::new(&candidates, scan_duration));
+
+    if candidates.is_empty() && args.format.is_none() && unfiltered.is_none() {
+        report.line("No dependency folders found matching criteria.");
+        return Ok(());
+    }
+
+    let total_size: u64 = candidates.iter().map(|c| c.size).sum();
+    let unmeasured = candidates.iter().filter(|c| c.size_unknown).count();
+    match unmeasured {
+        0 => report.line(format!("Found {} folders. Total size: {}", candidates.len(), human_bytes(total_size as f64))),
+        _ => report.line(format!("Found {} folders; {} of them aren't measured yet (--fast).", candidates.len(), unmeasured)),
+    }
+    for group in summarize(&candidates).by_kind {
+        let folders = if group.folders == 1 { "1 folder".to_string() } else { format!("{} folders", group.folders) };
+        match unmeasured {
+            0 => report.line(format!("  {}: {}, {}", group.label(), folders, severity.size(group.size, &human_bytes(group.size as f64)))),
+            _ => report.line(format!("  {}: {}", group.label(), folders)),
+        }
+    }
+
+    let path_order = if args.natural_sort { PathOrder::Natural } else { PathOrder::Bytewise };
+    // Without sizes to go by, folders of one project tree stay together.
+    let sort = if unmeasured > 0 && args.sort == SortKey::Size { SortKey::Path } else { args.sort };
+    sort_candidates_by(&mut candidates, sort, path_order);
+    if args.reverse {
+        candidates.reverse();
+    }
+
+    if let Some(all) = &unfiltered {
+        let mut refinement = Refinement { floors, hidden: Default::default(), sort: args.sort, reverse: args.reverse, path_order };
+        candidates = refine::ask(all, &mut refinement)?;
+        floors = refinement.floors;
+        targets.retain(|kind| !refinement.hidden.contains(kind));
+        if !refinement.hidden.is_empty() && targets.is_empty() {
+            targets = kinds(all).into_iter().map(|(kind, _, _)| kind).filter(|kind| !refinement.hidden.contains(kind)).collect();
+        }
+        if candidates.is_empty() {
+            report.line("No dependency folders found matching criteria.");
+            return Ok(());
+        }
+    }
+
+    if let Some(format) = args.format {
+        let stdout = io::stdout().lock();
+        match format {
+            OutputFormat::Json => write_json(&candidates, stdout),
+            OutputFormat::Csv => write_csv(&candidates, stdout),
+        }
+        .context("could not write the results to stdout")?;
+        return Ok(());
+    }
+
+    let mut changes = HashMap::new();
+    if let Some(previous) = &previous {
+        let (found, _) = diff(previous, &candidates);
+        changes = candidates.iter().map(|c| c.path.clone()).zip(found).collect();
+        // Stable, so new and grown folders keep their largest-first order at the top.
+        candidates.sort_by_key(|c| changes[&c.path].rank());
+        let new: Vec<&CandidateDir> = candidates.iter().filter(|c| changes[&c.path] == Change::New).collect();
+        let when = ago(previous.taken);
+        report.line(format!(
+            "{} new candidates ({}) since the last run {}, {} candidates disappeared.",
+            new.len(),
+            human_bytes(new.iter().map(|c| c.size).sum::<u64>() as f64),
+            when,
+            gone
+        ));
+    }
+
+    if let Some(export_path) = &args.export_ncdu {
+        let file = fs::File::create(export_path).with_context(|| format!("could not create {}", export_path.display()))?;
+        write_ncdu(&path, &candidates, io::BufWriter::new(file)).with_context(|| format!("could not write {}", export_path.display()))?;
+        report.line(format!("Exported {} folders to {} (open with `ncdu -f {}`).", candidates.len(), export_path.display(), export_path.display()));
+    }
+
+    if let Some(plan_path) = &args.save_plan {
+        Plan::new(&candidates, &volumes, args.plan_map.clone()).save(plan_path)?;
+        report.line(format!("Wrote a plan for {} folders to {} (apply with --apply).", candidates.len(), plan_path.display()));
+        return Ok(());
+    }
+
+    let volume_groups = group_by_volume(&volumes, &candidates);
+    if unmeasured == 0 {
+        report.line("By volume:");
+        print_volume_summary(report, &volume_groups, &severity);
+    }
+
+    if args.group_by == Some(GroupBy::Volume) {
+        let rank = |c: &CandidateDir| {
+            let mount_point = volumes.volume_of(&c.path).map(|m| m.mount_point.clone());
+            volume_groups.iter().position(|g| g.mount_point == mount_point)
+        };
+        candidates.sort_by_key(|c| rank(c));
+    }
+
+    let mut group_labels = HashMap::new();
+    if let Some(group_by @ (GroupBy::Owner | GroupBy::Ecosystem)) = args.group_by {
+        let mut totals: Vec<(String, usize, u64)> = Vec::new();
+        for c in &candidates {
+            let name = match group_by {
+                GroupBy::Owner => {
+                    let uid = owners.owner(&top_level(&path, &c.path));
+                    owners.name(uid)
+                }
+                _ => c.ecosystem().to_string(),
+            };
+            match totals.iter_mut().find(|(n, _, _)| *n == name) {
+                Some((_, count, bytes)) => {
+                    *count += 1;
+                    *bytes += c.reclaimable();
+                }
+                None => totals.push((name.clone(), 1, c.reclaimable())),
+            }
+            group_labels.insert(c.path.clone(), name);
+        }
+        totals.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+        report.line(if group_by == GroupBy::Owner { "By owner:" } else { "By ecosystem:" });
+        for (name, count, bytes) in &totals {
+            report.line(format!("  {}: {} folders, {}", name, count, severity.size(*bytes, &human_bytes(*bytes as f64))));
+        }
+        candidates.sort_by_key(|c| totals.iter().position(|(name, _, _)| Some(name) == group_labels.get(&c.path)));
+    }
+
+    let term = Term::stdout();
+
+    let unicode = term.features().wants_emoji();
+    let failed = candidates.iter().filter_map(|c| failures.get(&c.path)).map(|f| (f.path.clone(), f.clone())).collect();
+    let labels = match args.group_by {
+        Some(GroupBy::Volume) => candidates
+            .iter()
+            .filter_map(|c| volumes.volume_of(&c.path).map(|mount| (c.path.clone(), mount.mount_point.display().to_string())))
+            .collect(),
+        Some(GroupBy::Owner | GroupBy::Ecosystem) => group_labels,
+        None => HashMap::new(),
+    };
+
+    let selection_file = resolve_cache_dir(args.cache_dir.as_deref()).map(|(dir, _)| dir.join("selection.json"));
+    let mut defaults: Vec<bool> = match &args.preselect {
+        Some(policy) => candidates.iter().map(|c| policy.matches(c)).collect(),
+        None => vec![true; candidates.len()],
+    };
+    let saved = selection_file.as_deref().filter(|_| term.is_term() && !args.yes).map(SavedSelections::load);
+    if let Some(saved) = saved.as_ref().and_then(|saved| saved.get(&path)) {
+        if let Some(restored) = saved.restore(&path, &candidates, &defaults) {
+            let resume = Confirm::with_theme(&SimpleTheme)
+                .with_prompt(format!(
+                    "Resume previous selection from {}? {} of {} items were selected",
+                    ago(saved.saved_at),
+                    saved.selected_count(),
+                    saved.items.len()
+                ))
+                .default(true)
+                .interact()?;
+            if resume {
+                defaults = restored;
+            }
+        }
+    }
+
+    let actions: Vec<Vec<Action>> = candidates.iter().map(available_actions).collect();
+    let mut without_action = 0;
+    for (checked, available) in defaults.iter_mut().zip(&actions) {
+        if !available.contains(&args.default_action()) {
+            *checked = false;
+            without_action += 1;
+        }
+    }
+    // A build may still be writing to these; deleting under it leaves both half done.
+    let active: HashSet<PathBuf> = candidates.iter().filter(|c| recently_modified(c, active_within)).map(|c| c.path.clone()).collect();
+    let cautious = candidates.iter().filter(|c| c.risk == Risk::Caution).count();
+    for (checked, candidate) in defaults.iter_mut().zip(&candidates) {
+        if active.contains(&candidate.path) || candidate.risk == Risk::Caution {
+            *checked = false;
+        }
+    }
+
+    let mut filters = Vec::new();
+    if !floors.is_empty() {
+        filters.push(format!("min size {}", floors));
+    }
+    if let Some(idle) = args.project_idle {
+        filters.push(format!("project idle for {}", format_duration(idle)));
+    }
+    filters.push(match targets.len() {
+        0 => "targets: all".to_string(),
+        n if n > 3 => format!("targets: {} kinds", n),
+        _ => format!("targets: {}", targets.join(", ")),
+    });
+    filters.push(match exclude_count {
+        0 => "excludes: none".to_string(),
+        n => format!("excludes: {}", n),
+    });
+
+    let sizes = candidates.iter().map(|c| c.reclaimable()).collect();
+    let kinds = candidates.iter().map(|c| c.kind.clone()).collect();
+    let workspaces = candidates.iter().map(|c| c.workspace_root().map(|root| root.display().to_string())).collect();
+    let candidate_notes = candidates.iter().map(|c| notes.get(&c.path).map(str::to_string)).collect();
+    // Shared with the rows, which replace a candidate when `r` measures it again.
+    let candidates = Rc::new(RefCell::new(candidates));
+    let mut rows = CandidateRows::new(candidates.clone(), PathDisplay::new(args.path_style(), &path), severity, unicode);
+    rows.show_idle = args.project_idle.is_some();
+    rows.active = active.clone();
+    rows.not_ignored = git_status.into_iter().filter(|(_, status)| *status == GitStatus::NotIgnored).map(|(path, _)| path).collect();
+    rows.changes = changes;
+    rows.failed = failed;
+    rows.labels = labels;
+    let mut selection = Selection::with_rows(Box::new(rows), sizes, defaults.clone());
+    selection.header = vec![
+        format!("Scan root: {}", path.display()),
+        match (from_cache, partial) {
+            (true, false) => format!("Results: from cache ({} old)", format_age(age_of(scanned_at))),
+            (true, true) => format!("Results: partial, from cache ({} old)", format_age(age_of(scanned_at))),
+            (false, false) => format!("Results: scanned {}", format_timestamp(scanned_at)),
+            (false, true) => format!("Results: partial scan {} (stopped at --find-at-least)", format_timestamp(scanned_at)),
+        },
+        format!("Filters: {}", filters.join(", ")),
+    ];
+    if args.dry_run {
+        selection.header.push("Dry run: nothing will be deleted".to_string());
+    }
+    if let Some(policy) = &args.preselect {
+        selection.header.push(format!("Preselected: {} ({} of {})", policy, defaults.iter().filter(|&&d| d).count(), defaults.len()));
+    }
+    if without_action > 0 {
+        let message = format!("--action {} doesn't apply to {} folders; they aren't preselected and default to delete", args.default_action(), without_action);
+        if args.yes {
+            report.line(&message);
+        }
+        selection.header.push(message);
+    }
+    if cautious > 0 {
+        let message = format!("{} folders are marked caution, since they sometimes hold files put there by hand; they aren't preselected", cautious);
+        if args.yes {
+            report.line(&message);
+        }
+        selection.header.push(message);
+    }
+    if !active.is_empty() {
+        let message = format!("{} folders were modified within the last {}, so a build may still be writing to them; they aren't preselected", active.len(), format_duration(active_within));
+        if args.yes {
+            report.line(&message);
+        }
+        selection.header.push(message);
+    }
+    selection.actions = actions.iter().map(|available| available.iter().map(|a| a.label().to_string()).collect()).collect();
+    for (i, available) in actions.iter().enumerate() {
+        selection.set_action(i, available.iter().position(|&a| a == args.default_action()).unwrap_or(0));
+    }
+    selection.idle_timeout = args.prompt_timeout;
+    selection.kinds = kinds;
+    selection.workspaces = workspaces;
+    if selection.workspaces.iter().any(Option::is_some) {
+        selection.prompt = selection.prompt.replace(", Enter to confirm", ", w for the whole workspace, Enter to confirm");
+    }
+    // Unmeasured folders all look empty, so none of them share a row.
+    selection.small_below = if unmeasured > 0 { 0 } else { small_below };
+    selection.notes = candidate_notes;
+    if report.records() {
+        report.record("Candidates:");
+        for i in 0..selection.len() {
+            let note = selection.note(i).map(|n| format!("  {}", note_label(n))).unwrap_or_default();
+            report.record(format!("  {}{}", selection.row(i, usize::MAX), note));
+        }
+    }
+
+    if !args.yes && !io::stdin().is_terminal() {
+        bail!("stdin is not a terminal, so nothing can be selected; pass --yes to delete every folder listed without asking");
+    }
+    let outcome = if args.yes {
+        let checked = defaults.iter().enumerate().filter(|(_, &checked)| checked).map(|(idx, _)| idx);
+        Ok(SelectOutcome::Selected(unattended(report, &candidates.borrow(), checked)))
+    } else {
+        match selection.interact() {
+            Err(e) if term.is_term() => {
+                eprintln!("The selection list failed ({}); switching to a numbered list.", e);
+                selection.interact_numbered(io::stdin().lock(), io::stdout())
+            }
+            outcome => outcome,
+        }
+    };
+    let shared = candidates;
+    let mut candidates = shared.borrow();
+    let mut notes_changed = false;
+    for (i, c) in candidates.iter().enumerate() {
+        notes_changed |= notes.set(&c.path, selection.note(i).unwrap_or_default());
+    }
+    if notes_changed {
+        if let Err(e) = notes.save() {
+            eprintln!("Warning: could not save the notes: {}", e);
+        }
+    }
+    // Kept until the deletion is done, so a declined confirmation or a crash before it
+    // leaves the checkboxes for the next run.
+    if term.is_term() && !args.yes {
+        remember_selection(selection_file.as_deref(), SavedSelection::new(&path, &candidates, &selection.checked));
+    }
+    let selections = match outcome? {
+        SelectOutcome::Selected(selections) => selections,
+        SelectOutcome::Cancelled => {
+            report.line("Selection cancelled.");
+            return Ok(());
+        }
+        SelectOutcome::TimedOut => {
+            let timeout = format_duration(args.prompt_timeout.unwrap_or_default());
+            if args.on_timeout == OnTimeout::Cancel {
+                report.line(format!("Selection prompt timed out after {}. Operation cancelled.", timeout));
+                return Ok(());
+            }
+            report.line(format!("Selection prompt timed out after {}. Proceeding with the preselected folders.", timeout));
+            defaults.iter().enumerate().filter(|(_, &checked)| checked).map(|(idx, _)| idx).collect()
+        }
+    };
+
+    if selections.is_empty() {
+        report.line("No folders selected. Exiting.");
+        return Ok(());
+    }
+
+    if selections.iter().any(|&idx| candidates[idx].size_unknown) {
+        drop(candidates);
+        let cache_path = cache_file_path.as_deref().filter(|_| args.write_cache());
+        measure_selected(report, &mut shared.borrow_mut(), &selections, cache_path);
+        candidates = shared.borrow();
+    }
+
+    let plan: Vec<(&CandidateDir, Action)> = selections.iter().map(|&idx| (&candidates[idx], actions[idx][selection.action(idx)])).collect();
+    report.line(selected_heading(args.path_style(), &path));
+    for (&idx, (_, action)) in selections.iter().zip(&plan) {
+        let action = if *action == Action::Delete { String::new() } else { format!("  [{}]", action) };
+        let note = selection.note(idx).map(|n| format!("  {}", note_label(n))).unwrap_or_default();
+        report.line(format!("  {}{}{}", selection.row(idx, usize::MAX), action, note));
+    }
+
+    let mut warnings: Vec<&str> = selections.iter().filter_map(|&idx| deletion_warning(dir_name(&candidates[idx].path))).collect();
+    warnings.sort();
+    warnings.dedup();
+    for warning in warnings {
+        report.line(format!("Note: {}.", warning));
+    }
+
+    report.line("\nBy volume:");
+    print_volume_summary(report, &group_by_volume(&volumes, selections.iter().map(|&idx| &candidates[idx])), &severity);
+    if plan.iter().any(|(_, action)| *action != Action::Delete) {
+        report.line("\nBy action:");
+        print_action_summary(report, &plan);
+    }
+
+    if args.dry_run {
+        let total: u64 = selections.iter().map(|&idx| candidates[idx].reclaimable()).sum();
+        let would = match plan_action(&plan) {
+            Some(Action::Delete) => format!("delete {} folders", selections.len()),
+            Some(Action::Trash) => format!("move {} folders to the trash", selections.len()),
+            _ => format!("clean {} folders", selections.len()),
+        };
+        report.line(format!("\nDry run: would {}, reclaiming {}. Nothing was removed.", would, human_bytes(total as f64)));
+        return Ok(());
+    }
+
+    let still_active = plan.iter().filter(|(c, _)| active.contains(&c.path)).count();
+    if still_active > 0 {
+        report.line(format!(
+            "\nWarning: {} selected folders were modified within the last {}. If a build is still writing to one, deleting it races the build and can leave both half done.",
+            still_active,
+            format_duration(active_within)
+        ));
+    }
+    if !confirm_deletion(args, report, &large_policy, &severity, &plan)? {
+        return Ok(());
+    }
+
+    let deletion = delete_folders(report, &events, &plan, grown_factor, &title, &mut failures, &args.delete_settings());
+    if !deletion.interrupted {
+        forget_selection(selection_file.as_deref(), &path);
+    }
+    if args.metrics_file.is_some() {
+        let remaining: Vec<CandidateDir> = candidates.iter().filter(|c| !deletion.deleted.contains(&c.path)).cloned().collect();
+        let mut run_metrics = RunMetrics::new(&remaining, scan_duration);
+        run_metrics.reclaimed = deletion.reclaimed;
+        write_metrics(args.metrics_file.as_deref(), &run_metrics);
+    }
+    save_failures(&failures);
+    record_history(&path, &deletion.cleaned);
+
+    if args.write_cache() {
+        update_cached(cache_file_path.as_deref(), &deletion);
+    }
+
+    finish_deletion(report, &deletion, "Cleanup complete!", !args.no_hints)
+}
+
+/// What deleting needs besides the targets, for the flows outside `run`'s own.
+struct Cleanup<'a> {
+    events: &'a ProgressEvents,
+    title: &'a TerminalTitle,
+    large_policy: &'a LargeSelectionPolicy,
+    severity: &'a Severity,
+    grown_factor: f64,
+}
+
+/// `--ci-workspaces`: scans every workspace under `root` on its own, offers the artifacts
+/// of idle ones and, given the active jobs, the whole workspaces of jobs that are gone.
+fn clean_ci_workspaces(args: &Args, report: &mut Report, root: &Path, scan_options: &ScanOptions, floors: &SizeFloors, cleanup: &Cleanup, failures: &mut FailureLog) -> Result<()> {
+    let active_jobs = if args.active_jobs_stdin {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text).context("could not read the active jobs from stdin")?;
+        let jobs = parse_job_names(&text);
+        if jobs.is_empty() {
+            bail!("no job names on stdin; refusing to treat every workspace as orphaned");
+        }
+        Some(jobs)
+    } else {
+        None
+    };
+    let idle = args.workspace_idle.unwrap_or(DEFAULT_WORKSPACE_IDLE);
+
+    let dirs = list_workspaces(root).with_context(|| format!("could not list {}", root.display()))?;
+    report.line(format!("Scanning {} CI workspaces under {}...", dirs.len(), root.display()));
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(ProgressStyle::default_spinner().template("{spinner:.green} {msg}").unwrap());
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    let count = dirs.len();
+    let mut workspaces = Vec::new();
+    for (done, dir) in dirs.into_iter().enumerate() {
+        spinner.set_message(format!("Scanning workspace {}/{}: {}", done + 1, count, short_path(&dir)));
+        cleanup.title.set(&format!("devpurge: scanning workspaces ({}/{})", done + 1, count));
+        let mut artifacts: Vec<CandidateDir> = ScanIter::new(&dir, scan_options.clone()).filter_map(Result::ok).collect();
+        floors.apply(&mut artifacts);
+        let last_activity = last_project_activity(&dir);
+        workspaces.push(Workspace { dir, last_activity, artifacts });
+    }
+    spinner.finish_and_clear();
+
+    let mut candidates = Vec::new();
+    let mut whole = Vec::new();
+    report.line(format!("\n{:<40} {:>10}  {:<16} State", "Workspace", "Artifacts", "Last activity"));
+    for ws in &workspaces {
+        let state = ws.state(idle, active_jobs.as_ref());
+        let activity = ws.last_activity.map(ago).unwrap_or_else(|| "no sources".to_string());
+        report.line(format!("{:<40} {:>10}  {:<16} {}", ws.name(), human_bytes(ws.purgeable() as f64), activity, state.label()));
+        match state {
+            WorkspaceState::Active => {}
+            WorkspaceState::Idle => {
+                whole.extend(ws.artifacts.iter().map(|_| false));
+                candidates.extend(ws.artifacts.iter().cloned());
+            }
+            WorkspaceState::Orphaned => {
+                whole.push(true);
+                candidates.push(CandidateDir { kind: "workspace".to_string(), ..CandidateDir::new(ws.dir.clone(), calculate_size(&ws.dir)) });
+            }
+        }
+    }
+
+    report.line("");
+    for state in [WorkspaceState::Active, WorkspaceState::Idle, WorkspaceState::Orphaned] {
+        let members: Vec<&Workspace> = workspaces.iter().filter(|ws| ws.state(idle, active_jobs.as_ref()) == state).collect();
+        if members.is_empty() {
+            continue;
+        }
+        let detail = match state {
+            WorkspaceState::Active => format!("worked in within {}, left alone", format_duration(idle)),
+            WorkspaceState::Idle => format!("{} in artifacts", human_bytes(members.iter().map(|ws| ws.purgeable()).sum::<u64>() as f64)),
+            WorkspaceState::Orphaned => {
+                let bytes: u64 = candidates.iter().zip(&whole).filter(|(_, &w)| w).map(|(c, _)| c.size).sum();
+                format!("{} as whole directories (job not in the active list)", human_bytes(bytes as f64))
+            }
+        };
+        report.line(format!("{}: {} workspaces, {}", state.label(), members.len(), detail));
+    }
+
+    if candidates.is_empty() {
+        report.line("Nothing to clean.");
+        return Ok(());
+    }
+    let paths = PathDisplay::new(args.path_style(), root);
+    let items: Vec<String> = candidates
+        .iter()
+        .zip(&whole)
+        .map(|(c, &w)| match w {
+            true => format!("{} ({}, entire workspace)", paths.show(&c.path), human_bytes(c.size as f64)),
+            false => format!("{} ({})", paths.show(&c.path), human_bytes(c.reclaimable() as f64)),
+        })
+        .collect();
+
+    let selections: Vec<usize> = if args.yes {
+        unattended(report, &candidates, 0..candidates.len())
+    } else {
+        if !Term::stdout().is_term() {
+            bail!("stdout is not a terminal, so nothing can be selected; pass --yes to delete everything the policy offers");
+        }
+        let mut selection = Selection::new(items.clone(), candidates.iter().map(|c| c.reclaimable()).collect(), vec![true; items.len()]);
+        selection.header = vec![
+            format!("CI workspaces: {}", root.display()),
+            format!("Offered: artifacts of workspaces idle for {}{}", format_duration(idle), if active_jobs.is_some() { ", whole workspaces of inactive jobs" } else { "" }),
+        ];
+        match selection.interact()? {
+            SelectOutcome::Selected(selections) => selections,
+            SelectOutcome::Cancelled | SelectOutcome::TimedOut => {
+                report.line("Selection cancelled.");
+                return Ok(());
+            }
+        }
+    };
+    if selections.is_empty() {
+        report.line("No folders selected. Exiting.");
+        return Ok(());
+    }
+    report.line(selected_heading(args.path_style(), root));
+    for &idx in &selections {
+        report.line(format!("  {}", items[idx]));
+    }
+
+    let (entire, mut chosen): (Vec<usize>, Vec<usize>) = selections.into_iter().partition(|&idx| whole[idx]);
+    if !entire.is_empty() && confirm_whole_workspaces(args, report, &candidates, &entire)? {
+        chosen.extend(entire);
+    }
+    let targets: Vec<(&CandidateDir, Action)> = chosen.iter().map(|&idx| (&candidates[idx], args.removal())).collect();
+    if targets.is_empty() || !confirm_deletion(args, report, cleanup.large_policy, cleanup.severity, &targets)? {
+        return Ok(());
+    }
+
+    let deletion = delete_folders(report, cleanup.events, &targets, cleanup.grown_factor, cleanup.title, failures, &args.delete_settings());
+    save_failures(failures);
+    record_history(root, &deletion.cleaned);
+    finish_deletion(report, &deletion, "Cleanup complete!", !args.no_hints)
+}
+
+/// Deleting a whole workspace takes its sources with it, so it needs its own typed
+/// confirmation; `--yes` never covers it.
+fn confirm_whole_workspaces(args: &Args, report: &mut Report, candidates: &[CandidateDir], entire: &[usize]) -> Result<bool> {
+    let bytes: u64 = entire.iter().map(|&idx| candidates[idx].size).sum();
+    if args.yes {
+        report.line(format!("Keeping {} entire workspaces ({}): deleting whole workspaces needs a typed confirmation, which --yes doesn't give.", entire.len(), human_bytes(bytes as f64)));
+        return Ok(false);
+    }
+    println!("\n{} of the selected folders are entire workspaces ({}), sources included.", entire.len(), human_bytes(bytes as f64));
+    println!("Type the number of workspaces ({}) to delete them too, or press Enter to keep them:", entire.len());
+    let answer: String = Input::new().allow_empty(true).interact_text()?;
+    if answer.trim() == entire.len().to_string() {
+        return Ok(true);
+    }
+    report.line("Keeping the entire workspaces.");
+    Ok(false)
+}
+
+enum Recheck {
+    Gone,
+    /// Still there, but couldn't be looked at, e.g. permission denied or out of file descriptors.
+    Unreadable(io::Error),
+    Delete(u64),
+    Skip,
+}
+
+/// Looks at a candidate again right before deleting it: gone folders are skipped, ones
+/// that changed significantly are re-sized, and ones that grew past `grown_factor` need a
+/// fresh confirmation unless `ask` is off (`--yes`), in which case they are skipped. Only
+/// `NotFound` means gone; a folder that can't be read right now is still there, and is
+/// reported as a failure instead.
+fn recheck(candidate: &CandidateDir, grown_factor: f64, bar: &ProgressBar, ask: bool) -> Recheck {
+    let now = match Fingerprint::read(&candidate.path) {
+        Ok(now) => now,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Recheck::Gone,
+        Err(e) => return Recheck::Unreadable(e),
+    };
+    if candidate.fingerprint.is_none_or(|before| !now.changed_since(&before)) {
+        return Recheck::Delete(candidate.reclaimable());
+    }
+
+    let size = calculate_size(&candidate.path);
+    let reclaimable = private_size(&candidate.path).filter(|&physical| physical < size).unwrap_or(size);
+    if (size as f64) <= candidate.size as f64 * grown_factor {
+        return Recheck::Delete(reclaimable);
+    }
+    let prompt = format!(
+        "{} grew from {} to {} since it was scanned. Delete it anyway?",
+        candidate.path.display(),
+        human_bytes(candidate.size as f64),
+        human_bytes(size as f64)
+    );
+    if !ask {
+        return Recheck::Skip;
+    }
+    let confirmed = bar.suspend(|| Confirm::with_theme(&SimpleTheme).with_prompt(prompt).default(false).interact());
+    if confirmed.unwrap_or(false) { Recheck::Delete(reclaimable) } else { Recheck::Skip }
+}
+
+/// What applying the targets' actions did.
+struct Deletion {
+    reclaimed: u64,
+    /// Every path no longer on disk (or emptied), including ones that had already
+    /// disappeared by the time they came up.
+    deleted: Vec<PathBuf>,
+    /// The targets this run cleaned itself, with the bytes each gave back.
+    cleaned: Vec<(CandidateDir, u64)>,
+    failed: Vec<FailedTarget>,
+    /// Ctrl-C stopped it before every target came up.
+    interrupted: bool,
+}
+
+/// A target whose action failed, possibly partway.
+struct FailedTarget {
+    path: PathBuf,
+    error: String,
+    /// Reclaimed before the failure.
+    freed: u64,
+    /// What is left, measured again.
+    remaining: CandidateDir,
+}
+
+/// How deletion goes, from the command line.
+struct DeleteSettings {
+    /// Where each deleted folder is logged, unless `--no-log`.
+    log: Option<PathBuf>,
+    /// Folders deleted at the same time.
+    jobs: usize,
+    /// Whether a folder that grew since the scan is asked about; under `--yes` it is skipped.
+    ask_grown: bool,
+    /// Make entries Unix refuses to remove writable and try them again (`--force`).
+    force: bool,
+}
+
+/// What became of one target on a deletion thread.
+enum Outcome {
+    /// It was gone before its turn came.
+    Gone,
+    /// It grew since the scan and the user kept it.
+    Skipped,
+    Failed { error: io::Error, freed: u64, remaining: Option<Box<CandidateDir>> },
+    Cleaned { freed: u64, gone: bool },
+}
+
+/// An [`Outcome`] with the messages printed while getting there, which go into the report
+/// afterwards in the targets' order, as if they had been deleted one after another.
+struct Finished {
+    outcome: Outcome,
+    messages: Vec<String>,
+}
+
+/// Applies each target's action, `settings.jobs` at a time; a target that fails partway is
+/// measured again, so what it freed is counted and what is left can be cached.
+fn delete_folders(report: &mut Report, events: &ProgressEvents, targets: &[(&CandidateDir, Action)], grown_factor: f64, title: &TerminalTitle, failures: &mut FailureLog, settings: &DeleteSettings) -> Deletion {
+    report.line(format!("{}...", plan_summary(targets, &format!("{} folders", targets.len()))));
+    events.emit("delete_start", json!({ "count": targets.len() }));
+    
+    // Measured in bytes, so a large folder moves the bar while it is being removed.
+    let delete_bar = ProgressBar::new(targets.iter().map(|(c, _)| c.reclaimable()).sum());
+    delete_bar.set_style(ProgressStyle::default_bar()
+        .template("[{elapsed_precise}] {bar:40.cyan/blue} {bytes}/{total_bytes} reclaimed, {prefix} {msg}")
+        .unwrap()
+        .progress_chars("##-"));
+    delete_bar.set_prefix(format!("0/{}", targets.len()));
+
+    let mut log_failed = false;
+    let mut log = |candidate: &CandidateDir, action: Action, size: u64, complete: bool, error: Option<String>| {
+        let Some(file) = settings.log.as_deref().filter(|_| !log_failed) else { return };
+        let record = DeletionRecord {
+            timestamp: unix_secs(SystemTime::now()),
+            path: candidate.path.clone(),
+            kind: candidate.kind.clone(),
+            action: action.label().to_string(),
+            size,
+            complete,
+            error,
+        };
+        if let Err(e) = append_record(file, &record) {
+            // Once is enough; the rest of the run would fail the same way.
+            delete_bar.println(format!("Warning: could not log deletions to {}: {}", file.display(), e));
+            log_failed = true;
+        }
+    };
+
+    let _stoppable = interrupt::stoppable();
+    // Every target is looked at again one after another before any is deleted, so a
+    // question about one that grew isn't printed over by the deletion threads.
+    let mut rechecks = Vec::with_capacity(targets.len());
+    for &(candidate, _) in targets {
+        if interrupted() {
+            break;
+        }
+        delete_bar.set_message(format!("checking {}", candidate.path.display()));
+        rechecks.push(Mutex::new(Some(recheck(candidate, grown_factor, &delete_bar, settings.ask_grown))));
+    }
+
+    let next = AtomicUsize::new(0);
+    let mut finished: Vec<Option<Finished>> = targets.iter().map(|_| None).collect();
+    std::thread::scope(|scope| {
+        let (sender, results) = mpsc::channel();
+        for _ in 0..settings.jobs.clamp(1, targets.len().max(1)) {
+            let sender = sender.clone();
+            let (next, rechecks, delete_bar) = (&next, &rechecks, &delete_bar);
+            // After Ctrl-C each thread finishes the folder it is on and takes no other.
+            scope.spawn(move || while !interrupted() {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(checked) = rechecks.get(index).and_then(|checked| checked.lock().unwrap().take()) else { break };
+                let (candidate, action) = targets[index];
+                let done = delete_target(candidate, action, checked, delete_bar, events, settings.force);
+                if sender.send((index, done)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // The bookkeeping that needs `failures` and the log happens here, as each finishes.
+        for (completed, (index, done)) in results.into_iter().enumerate() {
+            let (candidate, action) = targets[index];
+            title.set(&format!("devpurge: deleting {}/{}", completed + 1, targets.len()));
+            delete_bar.set_prefix(format!("{}/{}", completed + 1, targets.len()));
+            match &done.outcome {
+                Outcome::Gone | Outcome::Skipped => {}
+                Outcome::Failed { error, freed, remaining } => {
+                    failures.record_failure(&candidate.path, error);
+                    events.emit("delete_failed", json!({ "path": candidate.path, "action": action.label(), "error": describe_error(error) }));
+                    log(candidate, action, *freed, remaining.is_none(), Some(describe_error(error)));
+                }
+                Outcome::Cleaned { freed, .. } => {
+                    failures.record_success(&candidate.path);
+                    log(candidate, action, *freed, true, None);
+                    events.emit("deleted", json!({ "path": candidate.path, "action": action.label(), "size": freed, "done": completed + 1, "total": targets.len() }));
+                }
+            }
+            finished[index] = Some(done);
+        }
+    });
+
+    let left_alone = finished.iter().filter(|done| done.is_none()).count();
+    let mut reclaimed_space = 0;
+    let mut deleted_paths = Vec::new();
+    let mut cleaned = Vec::new();
+    let mut failed = Vec::new();
+    for (&(candidate, action), done) in targets.iter().zip(finished) {
+        let Some(Finished { outcome, messages }) = done else { continue };
+        for message in messages {
+            report.record(message);
+        }
+        match outcome {
+            Outcome::Gone => deleted_paths.push(candidate.path.clone()),
+            Outcome::Skipped => {}
+            Outcome::Failed { error, freed, remaining } => {
+                // Whatever went before the failure is gone all the same.
+                reclaimed_space += freed;
+                match remaining {
+                    Some(remaining) => failed.push(FailedTarget { path: candidate.path.clone(), error: describe_error(&error), freed, remaining: *remaining }),
+                    None => deleted_paths.push(candidate.path.clone()),
+                }
+            }
+            Outcome::Cleaned { freed, gone } => {
+                reclaimed_space += freed;
+                report.record(format!("{}: {} ({})", action, candidate.path.display(), human_bytes(freed as f64)));
+                if action.removes_contents() || gone {
+                    deleted_paths.push(candidate.path.clone());
+                }
+                cleaned.push((candidate.clone(), freed));
+            }
+        }
+    }
+    
+    let stopped = left_alone > 0;
+    if stopped {
+        delete_bar.abandon_with_message("Interrupted.");
+        report.line(format!("Interrupted: {} of {} folders were left alone.", left_alone, targets.len()));
+    } else {
+        delete_bar.finish_with_message("Done!");
+    }
+    events.emit("delete_done", json!({ "deleted": deleted_paths.len(), "reclaimed": reclaimed_space, "failed": failed.len(), "interrupted": stopped }));
+    Deletion { reclaimed: reclaimed_space, deleted: deleted_paths, cleaned, failed, interrupted: stopped }
+}
+
+/// Applies one target's action on a deletion thread, as its [`recheck`] allows, moving the
+/// shared bar as it goes.
+fn delete_target(candidate: &CandidateDir, action: Action, checked: Recheck, delete_bar: &ProgressBar, events: &ProgressEvents, force: bool) -> Finished {
+    let mut messages = Vec::new();
+    let mut say = |message: String| {
+        delete_bar.println(&message);
+        messages.push(message);
+    };
+    delete_bar.set_message(plan_summary(&[(candidate, action)], &candidate.path.display().to_string()));
+    // The bar's end moves as each folder turns out bigger or smaller than estimated; other
+    // threads move it too, so only by this folder's share.
+    let estimated = candidate.reclaimable();
+    let size = match checked {
+        Recheck::Delete(size) => size,
+        Recheck::Gone => {
+            delete_bar.dec_length(estimated);
+            return Finished { outcome: Outcome::Gone, messages };
+        }
+        Recheck::Skip => {
+            say(format!("Skipped {}: it grew since it was scanned", candidate.path.display()));
+            delete_bar.dec_length(estimated);
+            return Finished { outcome: Outcome::Skipped, messages };
+        }
+        Recheck::Unreadable(error) => {
+            say(format!("Failed to check {} before deleting it: {}; it was left in place", candidate.path.display(), describe_error(&error)));
+            delete_bar.dec_length(estimated);
+            let remaining = Some(Box::new(candidate.clone()));
+            return Finished { outcome: Outcome::Failed { error, freed: 0, remaining }, messages };
+        }
+    };
+    delete_bar.inc_length(size);
+    delete_bar.dec_length(estimated);
+
+    let mut removed_bytes = 0;
+    let removed = events.with_heartbeat("deleting", &candidate.path, || {
+        apply_action_with_progress(&RealFs, candidate, action, force, &mut |bytes| {
+            removed_bytes += bytes;
+            delete_bar.inc(bytes);
+        })
+    });
+    // Clones sharing blocks elsewhere free less than their files' lengths add up to.
+    let removed_bytes = if candidate.physical_size.is_some() { removed_bytes.min(size) } else { removed_bytes };
+    let (outcome, filled) = match removed {
+        Err(e) => {
+            say(match action {
+                Action::Trash => format!("Failed to move {} to the trash: {}; it was left in place", candidate.path.display(), e),
+                _ => format!("Failed to {} {}: {}", action.verb(), candidate.path.display(), e),
+            });
+            if let Some(warning) = deletion_warning(dir_name(&candidate.path)) {
+                say(format!("  ({}: {})", describe_error(&e), warning));
+            }
+            let remaining = candidate.remeasure().map(Box::new);
+            if let Some(remaining) = remaining.as_ref().filter(|_| removed_bytes > 0) {
+                say(format!("  Freed {} before it failed; {} is left.", human_bytes(removed_bytes as f64), human_bytes(remaining.size as f64)));
+            }
+            (Outcome::Failed { error: e, freed: removed_bytes, remaining }, removed_bytes)
+        }
+        Ok(()) => {
+            let gone = !candidate.path.exists();
+            let freed = match action {
+                Action::Delete | Action::KeepDir | Action::Partial => removed_bytes,
+                Action::Trash => size,
+                Action::Native if gone => size,
+                Action::Native => size.saturating_sub(calculate_size(&candidate.path)),
+            };
+            if matches!(action, Action::Trash | Action::Native) {
+                delete_bar.inc(freed);
+            }
+            (Outcome::Cleaned { freed, gone }, freed)
+        }
+    };
+    // The folder's share of the bar becomes what was actually reclaimed from it.
+    delete_bar.inc_length(filled);
+    delete_bar.dec_length(size);
+    Finished { outcome, messages }
+}
+
+/// Reports the reclaimed total, then every target that couldn't be fully removed, which
+/// also fails the run so scripts notice.
+fn finish_deletion(report: &mut Report, deletion: &Deletion, done: &str, hints: bool) -> Result<()> {
+    let done = if deletion.interrupted { "Stopped." } else { done };
+    report.line(format!("{} Reclaimed space: {}", done, human_bytes(deletion.reclaimed as f64)));
+    let projects = if hints { regeneration_hints(deletion.cleaned.iter().map(|(candidate, _)| candidate)) } else { Vec::new() };
+    if !projects.is_empty() {
+        let paths = PathDisplay::new(PathStyle::Home, Path::new(""));
+        report.line("\nTo get them back when needed:");
+        for project in projects {
+            let restores: Vec<String> = project.restores.iter().map(|(kind, hint)| format!("{}: {}", kind, hint)).collect();
+            report.line(format!("  {} — {}", paths.show(&project.project), restores.join("; ")));
+        }
+    }
+    if !deletion.failed.is_empty() {
+        report_failed(report, &deletion.failed);
+    }
+    if deletion.interrupted {
+        return Err(Interrupted.into());
+    }
+    if deletion.failed.is_empty() {
+        return Ok(());
+    }
+    bail!("{} folders could not be fully removed; `devpurge retry-failed` tries them again", deletion.failed.len())
+}
+
+fn report_failed(report: &mut Report, failed: &[FailedTarget]) {
+    report.line(format!("\n{} folders could not be fully removed:", failed.len()));
+    for failed in failed {
+        let freed = match failed.freed {
+            0 => String::new(),
+            bytes => format!("freed {}, ", human_bytes(bytes as f64)),
+        };
+        report.line(format!("  {}: {} ({}{} left)", failed.path.display(), failed.error, freed, human_bytes(failed.remaining.size as f64)));
+    }
+}
+
+/// The action every target gets, if they all get the same one.
+fn plan_action(plan: &[(&CandidateDir, Action)]) -> Option<Action> {
+    let first = plan.first()?.1;
+    plan.iter().all(|(_, action)| *action == first).then_some(first)
+}
+
+/// "Deleting {what}", "Moving {what} to the trash" or "Cleaning {what}".
+fn plan_summary(plan: &[(&CandidateDir, Action)], what: &str) -> String {
+    match plan_action(plan) {
+        Some(Action::Delete) => format!("Deleting {}", what),
+        Some(Action::Trash) => format!("Moving {} to the trash", what),
+        _ => format!("Cleaning {}", what),
+    }
+}
+
+/// How many selected folders get each action, and how much they hold.
+fn print_action_summary(report: &mut Report, plan: &[(&CandidateDir, Action)]) {
+    for action in Action::value_variants() {
+        let folders: Vec<&CandidateDir> = plan.iter().filter(|(_, a)| a == action).map(|&(c, _)| c).collect();
+        if folders.is_empty() {
+            continue;
+        }
+        let total: u64 = folders.iter().map(|c| c.reclaimable()).sum();
+        let frees = if action.removes_contents() { "frees" } else { "frees up to" };
+        report.line(format!("  {:<9} {} folders, {} {}", action.label(), folders.len(), frees, human_bytes(total as f64)));
+    }
+}
+
+fn write_metrics(path: Option<&Path>, metrics: &RunMetrics) {
+    if let Some(path) = path {
+        if let Err(e) = write_textfile(path, metrics) {
+            eprintln!("Warning: could not write metrics to {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn dir_name(path: &Path) -> &str {
+    path.file_name().and_then(|n| n.to_str()).unwrap_or("")
+}
+
+/// `--fast`: sizes the selected folders the scan left unmeasured, so the confirmation says
+/// what deleting them frees, and keeps the sizes in the cache for later runs.
+fn measure_selected(report: &mut Report, candidates: &mut [CandidateDir], selections: &[usize], cache_path: Option<&Path>) {
+    let unmeasured: Vec<usize> = selections.iter().copied().filter(|&idx| candidates[idx].size_unknown).collect();
+    let bar = ProgressBar::new(unmeasured.len() as u64);
+    bar.set_style(ProgressStyle::default_bar().template("{bar:40.cyan/blue} {pos}/{len} {msg}").unwrap());
+    let mut measured = Vec::new();
+    for &idx in &unmeasured {
+        bar.set_message(format!("Measuring {}...", short_path(&candidates[idx].path)));
+        if let Some(fresh) = candidates[idx].remeasure() {
+            candidates[idx] = fresh.clone();
+            measured.push(fresh);
+        }
+        bar.inc(1);
+    }
+    bar.finish_and_clear();
+    let total: u64 = measured.iter().map(|c| c.reclaimable()).sum();
+    report.line(format!("Measured {} selected folders: {}.", measured.len(), human_bytes(total as f64)));
+
+    if let Some(cache_path) = cache_path.filter(|_| !measured.is_empty()) {
+        if let Some(mut cache) = load_cache(cache_path) {
+            for c in cache.roots.values_mut().flat_map(|cached| cached.candidates.iter_mut()) {
+                if let Some(fresh) = measured.iter().find(|fresh| fresh.path == c.path) {
+                    *c = fresh.clone();
+                }
+            }
+            save_cache(cache_path, &cache);
+        }
+    }
+}
+
+/// Drops deleted folders from the cache and puts in what is left of the ones that failed
+/// partway, so a later run neither offers nor mis-sizes them.
+fn update_cached(cache_path: Option<&Path>, deletion: &Deletion) {
+    if deletion.deleted.is_empty() && deletion.failed.is_empty() {
+        return;
+    }
+    if let Some(cache_path) = cache_path {
+        if let Some(mut cache) = load_cache(cache_path) {
+             for cached in cache.roots.values_mut() {
+                 cached.candidates.retain(|c| !deletion.deleted.contains(&c.path));
+                 for c in cached.candidates.iter_mut() {
+                     if let Some(failed) = deletion.failed.iter().find(|f| f.path == c.path) {
+                         *c = failed.remaining.clone();
+                     }
+                 }
+             }
+             save_cache(cache_path, &cache);
+        }
+    }
+}
+
+fn save_failures(failures: &FailureLog) {
+    if let Err(e) = failures.save() {
+        eprintln!("Warning: could not record failed deletions: {}", e);
+    }
+}
+
+/// `retry-failed`: offers the folders earlier runs failed to delete again, each of which
+/// has to pass the same [`check_path`] checks as a folder in a plan.
+fn retry_failed(args: &Args, report: &mut Report, protected: &[PathBuf], cache_path: Option<&Path>, cleanup: &Cleanup, failures: &mut FailureLog) -> Result<()> {
+    if failures.entries().is_empty() {
+        report.line("No failed deletions to retry.");
+        save_failures(failures);
+        return Ok(());
+    }
+
+    // What the scan recorded, so the recheck before deleting still sees any growth since.
+    let cached = cache_path.and_then(load_cache);
+    let mut retries = Vec::new();
+    report.line("Folders that failed to delete on earlier runs:");
+    for entry in failures.entries() {
+        let verdict = check_path(&entry.path, protected);
+        if !verdict.ok {
+            report.line(format!("  skip {}: {}", entry.path.display(), verdict.reasons.join("; ")));
+            continue;
+        }
+        let attempts = if entry.attempts == 1 { "1 attempt".to_string() } else { format!("{} attempts", entry.attempts) };
+        report.line(format!("  {} ({}, {}, {} ago)", entry.path.display(), entry.error, attempts, format_age(age_of(entry.timestamp))));
+        let scanned = cached.iter().flat_map(|cache| cache.roots.values()).flat_map(|scan| &scan.candidates).find(|c| c.path == entry.path);
+        retries.push(match scanned {
+            Some(scanned) => scanned.clone(),
+            None => {
+                let size = calculate_size(&entry.path);
+                let mut candidate = if entry.path.is_file() { CandidateDir::file(entry.path.clone(), size) } else { CandidateDir::new(entry.path.clone(), size) };
+                // Measured just now; there is no earlier size to have grown from.
+                candidate.fingerprint = None;
+                candidate
+            }
+        });
+    }
+    if retries.is_empty() {
+        report.line("None of them can be retried.");
+        save_failures(failures);
+        return Ok(());
+    }
+
+    let chosen: Vec<usize> = if args.yes { unattended(report, &retries, 0..retries.len()) } else { (0..retries.len()).collect() };
+    let targets: Vec<(&CandidateDir, Action)> = chosen.iter().map(|&idx| (&retries[idx], args.removal())).collect();
+    if targets.is_empty() || !confirm_deletion(args, report, cleanup.large_policy, cleanup.severity, &targets)? {
+        return Ok(());
+    }
+
+    let deletion = delete_folders(report, cleanup.events, &targets, cleanup.grown_factor, cleanup.title, failures, &args.delete_settings());
+    save_failures(failures);
+    record_history(&common_root(deletion.cleaned.iter().map(|(c, _)| c.path.as_path())), &deletion.cleaned);
+
+    if args.write_cache() {
+        update_cached(cache_path, &deletion);
+    }
+
+    finish_deletion(report, &deletion, "Retry complete!", !args.no_hints)
+}
+
+fn apply_plan(args: &Args, report: &mut Report, plan_path: &Path, protected: &[PathBuf], cache_path: Option<&Path>, cleanup: &Cleanup, failures: &mut FailureLog) -> Result<()> {
+    let plan = Plan::load(plan_path)?;
+    let volumes = VolumeTable::load();
+    let here = hostname();
+    report.line(format!(
+        "Plan {} from {} (made {}), {} folders:",
+        plan_path.display(),
+        plan.host,
+        format_timestamp(plan.created),
+        plan.entries.len()
+    ));
+
+    let mut targets = Vec::new();
+    for entry in &plan.entries {
+        match plan.verify(entry, &volumes, &here, args.ignore_host_check, protected) {
+            Ok(local) => {
+                report.line(format!("  {} ({})", local.display(), human_bytes(entry.size as f64)));
+                let mut candidate = if entry.is_file { CandidateDir::file(local, entry.size) } else { CandidateDir::new(local, entry.size) };
+                // Sized on the planning machine; any growth since is not this run's to judge.
+                candidate.fingerprint = None;
+                targets.push(candidate);
+            }
+            Err(reason) => report.line(format!("  skip {}: {}", entry.path.display(), reason)),
+        }
+    }
+    if targets.is_empty() {
+        report.line("Nothing in the plan can be applied here.");
+        return Ok(());
+    }
+
+    println!("\nDelete {} of {} planned folders? (type 'yes' to confirm)", targets.len(), plan.entries.len());
+    let confirmation: String = Input::new().interact_text()?;
+    if confirmation.trim().to_lowercase() != "yes" {
+        report.line("Operation cancelled.");
+        return Ok(());
+    }
+
+    let targets: Vec<(&CandidateDir, Action)> = targets.iter().map(|c| (c, args.removal())).collect();
+    let deletion = delete_folders(report, cleanup.events, &targets, cleanup.grown_factor, cleanup.title, failures, &args.delete_settings());
+    save_failures(failures);
+    record_history(&common_root(deletion.cleaned.iter().map(|(c, _)| c.path.as_path())), &deletion.cleaned);
+
+    if args.write_cache() {
+        update_cached(cache_path, &deletion);
+    }
+
+    finish_deletion(report, &deletion, "Plan applied!", !args.no_hints)
 }
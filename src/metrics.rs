@@ -0,0 +1,100 @@
+use crate::units::unix_secs;
+use crate::CandidateDir;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+const RECLAIMED_TOTAL: &str = "devpurge_reclaimed_bytes_total";
+
+/// Figures for one run, in node_exporter's textfile-collector format.
+#[derive(Debug, Default)]
+pub struct RunMetrics {
+    /// `(bytes, count)` of candidates per kind.
+    pub by_kind: BTreeMap<String, (u64, usize)>,
+    /// How long the scan took; `None` when the results came from the cache.
+    pub scan_duration: Option<Duration>,
+    /// Bytes deleted by this run.
+    pub reclaimed: u64,
+}
+
+impl RunMetrics {
+    pub fn new(candidates: &[CandidateDir], scan_duration: Option<Duration>) -> Self {
+        let mut by_kind: BTreeMap<String, (u64, usize)> = BTreeMap::new();
+        for candidate in candidates {
+            let entry = by_kind.entry(candidate.kind.clone()).or_default();
+            entry.0 += candidate.size;
+            entry.1 += 1;
+        }
+        RunMetrics { by_kind, scan_duration, reclaimed: 0 }
+    }
+
+    /// Renders the metrics; `reclaimed_before` carries the counter over from earlier runs.
+    pub fn render(&self, reclaimed_before: u64) -> String {
+        let mut out = String::new();
+        out.push_str("# HELP devpurge_purgeable_bytes Bytes in deletable candidates, by kind.\n");
+        out.push_str("# TYPE devpurge_purgeable_bytes gauge\n");
+        for (kind, (bytes, _)) in &self.by_kind {
+            let _ = writeln!(out, "devpurge_purgeable_bytes{{kind=\"{}\"}} {}", escape_label(kind), bytes);
+        }
+        out.push_str("# HELP devpurge_candidate_count Deletable candidates, by kind.\n");
+        out.push_str("# TYPE devpurge_candidate_count gauge\n");
+        for (kind, (_, count)) in &self.by_kind {
+            let _ = writeln!(out, "devpurge_candidate_count{{kind=\"{}\"}} {}", escape_label(kind), count);
+        }
+        if let Some(duration) = self.scan_duration {
+            out.push_str("# HELP devpurge_scan_duration_seconds How long the last scan took.\n");
+            out.push_str("# TYPE devpurge_scan_duration_seconds gauge\n");
+            let _ = writeln!(out, "devpurge_scan_duration_seconds {:.3}", duration.as_secs_f64());
+        }
+        out.push_str("# HELP devpurge_last_run_timestamp Unix time of the last run.\n");
+        out.push_str("# TYPE devpurge_last_run_timestamp gauge\n");
+        let _ = writeln!(out, "devpurge_last_run_timestamp {}", unix_secs(SystemTime::now()));
+        let _ = writeln!(out, "# HELP {} Bytes deleted across all runs.", RECLAIMED_TOTAL);
+        let _ = writeln!(out, "# TYPE {} counter", RECLAIMED_TOTAL);
+        let _ = writeln!(out, "{} {}", RECLAIMED_TOTAL, reclaimed_before + self.reclaimed);
+        out
+    }
+}
+
+/// Writes the metrics to `path` atomically, keeping the reclaimed counter from the previous file.
+///
+/// The collector may read the file at any moment, so it is written next to the target and
+/// renamed into place.
+pub fn write_textfile(path: &Path, metrics: &RunMetrics) -> io::Result<()> {
+    let reclaimed_before = fs::read_to_string(path).ok().and_then(|s| previous_reclaimed(&s)).unwrap_or(0);
+
+    let file_name = path.file_name().ok_or_else(|| io::Error::other("metrics path has no file name"))?;
+    let tmp = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+    {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(metrics.render(reclaimed_before).as_bytes())?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+fn previous_reclaimed(contents: &str) -> Option<u64> {
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix(RECLAIMED_TOTAL)?.strip_prefix(' '))
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// Escapes a label value as the exposition format requires.
+pub fn escape_label(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,333 @@
+//! A small TOML reader covering the subset DevPurge's config files use: tables,
+//! arrays of tables, dotted keys, strings, integers, floats, booleans, arrays and
+//! inline tables. The result is a JSON value so it can be fed to serde directly.
+
+use serde_json::{Map, Value};
+
+pub fn parse(input: &str) -> Result<Value, String> {
+    let mut root = Map::new();
+    let mut current: Vec<String> = Vec::new();
+    let mut lines = input.lines().enumerate().peekable();
+
+    while let Some((index, raw)) = lines.next() {
+        let line_no = index + 1;
+        let line = strip_comment(raw).trim().to_string();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix("[[") {
+            let header = header
+                .strip_suffix("]]")
+                .ok_or_else(|| format!("line {}: unterminated table header", line_no))?;
+            current = parse_key(header).map_err(|e| format!("line {}: {}", line_no, e))?;
+            let (last, parents) = current.split_last().expect("parse_key never returns an empty key");
+            let table = table_at(&mut root, parents).map_err(|e| format!("line {}: {}", line_no, e))?;
+            let array = table.entry(last.clone()).or_insert_with(|| Value::Array(Vec::new()));
+            match array {
+                Value::Array(items) => items.push(Value::Object(Map::new())),
+                _ => return Err(format!("line {}: '{}' is not an array of tables", line_no, last)),
+            }
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header
+                .strip_suffix(']')
+                .ok_or_else(|| format!("line {}: unterminated table header", line_no))?;
+            current = parse_key(header).map_err(|e| format!("line {}: {}", line_no, e))?;
+            table_at(&mut root, &current).map_err(|e| format!("line {}: {}", line_no, e))?;
+            continue;
+        }
+
+        let eq = find_unquoted(&line, '=').ok_or_else(|| format!("line {}: expected 'key = value'", line_no))?;
+        let key = parse_key(&line[..eq]).map_err(|e| format!("line {}: {}", line_no, e))?;
+        let mut value_text = line[eq + 1..].trim().to_string();
+
+        // Arrays may span several lines; keep reading until the brackets balance.
+        while !brackets_balanced(&value_text) {
+            match lines.next() {
+                Some((_, more)) => {
+                    value_text.push(' ');
+                    value_text.push_str(strip_comment(more).trim());
+                }
+                None => return Err(format!("line {}: unterminated array", line_no)),
+            }
+        }
+
+        let mut parser = ValueParser { chars: value_text.chars().collect(), pos: 0 };
+        let value = parser.value().map_err(|e| format!("line {}: {}", line_no, e))?;
+        parser.skip_ws();
+        if parser.pos != parser.chars.len() {
+            return Err(format!("line {}: unexpected trailing characters", line_no));
+        }
+
+        let mut path = current.clone();
+        path.extend(key);
+        let (last, parents) = path.split_last().expect("parse_key never returns an empty key");
+        let table = table_at(&mut root, parents).map_err(|e| format!("line {}: {}", line_no, e))?;
+        if table.insert(last.clone(), value).is_some() {
+            return Err(format!("line {}: duplicate key '{}'", line_no, last));
+        }
+    }
+
+    Ok(Value::Object(root))
+}
+
+/// Descends into (creating as needed) the table at `path`; a path that ends in
+/// an array of tables resolves to its last element like TOML specifies.
+fn table_at<'a>(root: &'a mut Map<String, Value>, path: &[String]) -> Result<&'a mut Map<String, Value>, String> {
+    let mut table = root;
+    for key in path {
+        let entry = table.entry(key.clone()).or_insert_with(|| Value::Object(Map::new()));
+        table = match entry {
+            Value::Object(map) => map,
+            Value::Array(items) => match items.last_mut() {
+                Some(Value::Object(map)) => map,
+                _ => return Err(format!("'{}' is not a table", key)),
+            },
+            _ => return Err(format!("'{}' is not a table", key)),
+        };
+    }
+    Ok(table)
+}
+
+fn strip_comment(line: &str) -> &str {
+    match find_unquoted(line, '#') {
+        Some(pos) => &line[..pos],
+        None => line,
+    }
+}
+
+fn find_unquoted(text: &str, needle: char) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (pos, c) in text.char_indices() {
+        match quote {
+            Some(q) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' && q == '"' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == needle => return Some(pos),
+            None => {}
+        }
+    }
+    None
+}
+
+fn brackets_balanced(text: &str) -> bool {
+    let mut depth = 0i32;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for c in text.chars() {
+        match quote {
+            Some(q) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' && q == '"' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '[' | '{' => depth += 1,
+                ']' | '}' => depth -= 1,
+                _ => {}
+            },
+        }
+    }
+    depth <= 0
+}
+
+fn parse_key(text: &str) -> Result<Vec<String>, String> {
+    let mut parser = ValueParser { chars: text.trim().chars().collect(), pos: 0 };
+    let key = parser.key()?;
+    parser.skip_ws();
+    if parser.pos != parser.chars.len() {
+        return Err(format!("invalid key '{}'", text.trim()));
+    }
+    Ok(key)
+}
+
+struct ValueParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl ValueParser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_ws();
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected '{}'", expected))
+        }
+    }
+
+    fn key(&mut self) -> Result<Vec<String>, String> {
+        let mut parts = Vec::new();
+        loop {
+            self.skip_ws();
+            let part = match self.peek() {
+                Some('"') => self.basic_string()?,
+                Some('\'') => self.literal_string()?,
+                _ => {
+                    let start = self.pos;
+                    while matches!(self.peek(), Some(c) if c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                        self.pos += 1;
+                    }
+                    if start == self.pos {
+                        return Err("expected a key".to_string());
+                    }
+                    self.chars[start..self.pos].iter().collect()
+                }
+            };
+            parts.push(part);
+            self.skip_ws();
+            if self.peek() == Some('.') {
+                self.pos += 1;
+            } else {
+                return Ok(parts);
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_ws();
+        match self.peek() {
+            Some('"') => self.basic_string().map(Value::String),
+            Some('\'') => self.literal_string().map(Value::String),
+            Some('[') => self.array(),
+            Some('{') => self.inline_table(),
+            Some(_) => self.bare_value(),
+            None => Err("missing value".to_string()),
+        }
+    }
+
+    fn basic_string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let c = self.peek().ok_or("unterminated string")?;
+            self.pos += 1;
+            match c {
+                '"' => return Ok(out),
+                '\\' => {
+                    let escape = self.peek().ok_or("unterminated string")?;
+                    self.pos += 1;
+                    match escape {
+                        'n' => out.push('\n'),
+                        't' => out.push('\t'),
+                        'r' => out.push('\r'),
+                        '"' => out.push('"'),
+                        '\\' => out.push('\\'),
+                        'u' | 'U' => {
+                            let len = if escape == 'u' { 4 } else { 8 };
+                            let hex: String = self.chars.iter().skip(self.pos).take(len).collect();
+                            self.pos += len;
+                            let code = u32::from_str_radix(&hex, 16).map_err(|_| "invalid unicode escape")?;
+                            out.push(char::from_u32(code).ok_or("invalid unicode escape")?);
+                        }
+                        other => return Err(format!("invalid escape '\\{}'", other)),
+                    }
+                }
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let start = self.pos;
+        while self.peek().ok_or("unterminated string")? != '\'' {
+            self.pos += 1;
+        }
+        let out = self.chars[start..self.pos].iter().collect();
+        self.pos += 1;
+        Ok(out)
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        loop {
+            self.skip_ws();
+            if self.peek() == Some(']') {
+                self.pos += 1;
+                return Ok(Value::Array(items));
+            }
+            items.push(self.value()?);
+            self.skip_ws();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {}
+                _ => return Err("expected ',' or ']' in array".to_string()),
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value, String> {
+        self.pos += 1;
+        let mut map = Map::new();
+        loop {
+            self.skip_ws();
+            if self.peek() == Some('}') {
+                self.pos += 1;
+                return Ok(Value::Object(map));
+            }
+            let key = self.key()?;
+            self.expect('=')?;
+            let value = self.value()?;
+            let (last, parents) = key.split_last().expect("key never returns an empty path");
+            table_at(&mut map, parents)?.insert(last.clone(), value);
+            self.skip_ws();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {}
+                _ => return Err("expected ',' or '}' in inline table".to_string()),
+            }
+        }
+    }
+
+    fn bare_value(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if !c.is_whitespace() && c != ',' && c != ']' && c != '}') {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        match text.as_str() {
+            "true" => return Ok(Value::Bool(true)),
+            "false" => return Ok(Value::Bool(false)),
+            _ => {}
+        }
+        let number = text.replace('_', "");
+        if let Ok(int) = number.parse::<i64>() {
+            return Ok(Value::from(int));
+        }
+        if let Ok(float) = number.parse::<f64>() {
+            if let Some(n) = serde_json::Number::from_f64(float) {
+                return Ok(Value::Number(n));
+            }
+        }
+        Err(format!("invalid value '{}'", text))
+    }
+}
@@ -0,0 +1,174 @@
+//! Cargo, npm/yarn and pnpm workspaces: a `target` or hoisted `node_modules` at the
+//! workspace root is shared by every member package, so it is attributed to the root
+//! and lists the members it serves.
+
+use crate::glob::glob_match;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directories never searched for members, even by a `**` pattern.
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", ".git"];
+
+/// How deep a `**` member pattern looks.
+const MAX_GLOB_DEPTH: usize = 6;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    /// `cargo`, `npm` (which yarn shares) or `pnpm`.
+    pub tool: &'static str,
+    /// Member package directories, sorted.
+    pub members: Vec<PathBuf>,
+}
+
+/// The workspace whose shared artifact folder `candidate` is, if any: a `target` next to
+/// a Cargo manifest with a `[workspace]`, or a `node_modules` next to a package.json with
+/// `workspaces` or a pnpm-workspace.yaml.
+pub fn shared_by(candidate: &Path) -> Option<Workspace> {
+    let root = candidate.parent()?;
+    match candidate.file_name()?.to_str()? {
+        "target" => cargo_workspace(root),
+        "node_modules" => pnpm_workspace(root).or_else(|| npm_workspace(root)),
+        _ => None,
+    }
+}
+
+/// The root of the npm/yarn or pnpm workspace whose member package `candidate`, a
+/// `node_modules`, belongs to: the nearest folder above the package listing it as a
+/// member. Such a folder mostly holds links into the root's, and the workspace only
+/// works again once both are reinstalled together.
+pub fn member_of(candidate: &Path) -> Option<PathBuf> {
+    if candidate.file_name()? != "node_modules" {
+        return None;
+    }
+    let package = candidate.parent()?;
+    package
+        .ancestors()
+        .skip(1)
+        .filter(|root| root.join("pnpm-workspace.yaml").is_file() || root.join("package.json").is_file())
+        .find(|root| pnpm_workspace(root).or_else(|| npm_workspace(root)).is_some_and(|w| w.members.iter().any(|m| m == package)))
+        .map(Path::to_path_buf)
+}
+
+fn cargo_workspace(root: &Path) -> Option<Workspace> {
+    let manifest = fs::read_to_string(root.join("Cargo.toml")).ok()?;
+    if !manifest.lines().any(|line| line.trim() == "[workspace]") {
+        return None;
+    }
+    // Only the [workspace] table is parsed, so the rest of a manifest never has to be
+    // understood by the small TOML reader.
+    let mut section = String::new();
+    let mut inside = false;
+    for line in manifest.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') && !trimmed.starts_with("[[") && trimmed.ends_with(']') && !trimmed.contains('=') {
+            inside = trimmed == "[workspace]";
+            continue;
+        }
+        if inside {
+            section.push_str(line);
+            section.push('\n');
+        }
+    }
+    let table = crate::minitoml::parse(&section).ok()?;
+    let strings = |key: &str| -> Vec<String> {
+        table.get(key).and_then(|v| v.as_array()).map(|items| items.iter().filter_map(|i| i.as_str().map(str::to_string)).collect()).unwrap_or_default()
+    };
+    let mut members = expand(root, &strings("members"), &strings("exclude"), "Cargo.toml");
+    // A root manifest with a [package] is a member of its own workspace.
+    if manifest.lines().any(|line| line.trim() == "[package]") && !members.contains(&root.to_path_buf()) {
+        members.insert(0, root.to_path_buf());
+    }
+    Some(Workspace { tool: "cargo", members })
+}
+
+fn npm_workspace(root: &Path) -> Option<Workspace> {
+    let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(root.join("package.json")).ok()?).ok()?;
+    // Either a list of globs, or yarn's `{ "packages": [...] }`.
+    let globs = match manifest.get("workspaces")? {
+        serde_json::Value::Object(map) => map.get("packages")?.as_array()?.clone(),
+        serde_json::Value::Array(items) => items.clone(),
+        _ => return None,
+    };
+    let (include, exclude) = split_negated(globs.iter().filter_map(|g| g.as_str()));
+    Some(Workspace { tool: "npm", members: expand(root, &include, &exclude, "package.json") })
+}
+
+fn pnpm_workspace(root: &Path) -> Option<Workspace> {
+    let text = fs::read_to_string(root.join("pnpm-workspace.yaml")).ok()?;
+    // The `packages:` list is all that matters: `- 'packages/*'` lines below it.
+    let mut globs = Vec::new();
+    let mut inside = false;
+    for line in text.lines() {
+        let trimmed = line.split(" #").next().unwrap_or("").trim();
+        if !line.starts_with([' ', '\t', '-']) && !trimmed.is_empty() {
+            inside = trimmed == "packages:";
+            continue;
+        }
+        if let Some(item) = trimmed.strip_prefix('-').filter(|_| inside) {
+            globs.push(item.trim().trim_matches(['\'', '"']).to_string());
+        }
+    }
+    let (include, exclude) = split_negated(globs.iter().map(String::as_str));
+    Some(Workspace { tool: "pnpm", members: expand(root, &include, &exclude, "package.json") })
+}
+
+/// Separates `!pattern` exclusions from the patterns that add members.
+fn split_negated<'a>(globs: impl Iterator<Item = &'a str>) -> (Vec<String>, Vec<String>) {
+    let (exclude, include): (Vec<&str>, Vec<&str>) = globs.partition(|g| g.starts_with('!'));
+    (include.into_iter().map(str::to_string).collect(), exclude.into_iter().map(|g| g[1..].to_string()).collect())
+}
+
+/// The directories under `root` matching any of `include` and none of `exclude` that
+/// hold `manifest`, sorted.
+fn expand(root: &Path, include: &[String], exclude: &[String], manifest: &str) -> Vec<PathBuf> {
+    let excluded: Vec<PathBuf> = exclude.iter().flat_map(|pattern| matching_dirs(root, pattern)).collect();
+    let mut members: Vec<PathBuf> = include
+        .iter()
+        .flat_map(|pattern| matching_dirs(root, pattern))
+        .filter(|dir| dir.join(manifest).is_file() && !excluded.contains(dir))
+        .collect();
+    members.sort();
+    members.dedup();
+    members
+}
+
+/// Directories matching a `/`-separated pattern relative to `root`, where `*` matches
+/// within one name and `**` any number of directories.
+fn matching_dirs(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let mut current = vec![root.to_path_buf()];
+    for part in pattern.trim_start_matches("./").trim_end_matches('/').split('/').filter(|p| !p.is_empty() && *p != ".") {
+        current = match part {
+            ".." => current.iter().filter_map(|dir| dir.parent().map(Path::to_path_buf)).collect(),
+            "**" => current.iter().flat_map(|dir| descendants(dir, MAX_GLOB_DEPTH)).collect(),
+            _ if !part.contains(['*', '?']) => current.iter().map(|dir| dir.join(part)).filter(|dir| dir.is_dir()).collect(),
+            _ => current.iter().flat_map(|dir| subdirs(dir).into_iter().filter(|sub| name_of(sub).is_some_and(|n| glob_match(part, n)))).collect(),
+        };
+    }
+    current
+}
+
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| entry.path())
+        .filter(|path| name_of(path).is_some_and(|n| !SKIPPED_DIRS.contains(&n)))
+        .collect()
+}
+
+/// `dir` and every directory below it, down to `depth` levels.
+fn descendants(dir: &Path, depth: usize) -> Vec<PathBuf> {
+    let mut found = vec![dir.to_path_buf()];
+    let mut level = vec![dir.to_path_buf()];
+    for _ in 0..depth {
+        level = level.iter().flat_map(|d| subdirs(d)).collect();
+        found.extend(level.iter().cloned());
+    }
+    found
+}
+
+fn name_of(path: &Path) -> Option<&str> {
+    path.file_name().and_then(|n| n.to_str())
+}
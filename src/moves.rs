@@ -0,0 +1,148 @@
+//! Following protected and excluded paths when the project they point at moves.
+//!
+//! Config rules are plain paths, so moving a project leaves its rule pointing at nothing
+//! while the new location shows up as an ordinary candidate. Every run remembers which
+//! project each existing rule belongs to, identified by the contents of its manifest
+//! files; when a rule's path disappears and exactly one candidate's project has the same
+//! identity, that candidate's project is where it most likely went.
+
+use crate::history::checksum;
+use crate::CandidateDir;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Files whose contents identify a project: they travel with it unchanged and rarely
+/// match another project byte for byte.
+const PROJECT_MARKERS: &[&str] = &[
+    "package.json", "Cargo.toml", "pom.xml", "build.gradle", "build.gradle.kts", "settings.gradle",
+    "settings.gradle.kts", "CMakeLists.txt", "composer.json", "go.mod", "Gemfile", "pubspec.yaml",
+    "angular.json", "platformio.ini", "pyproject.toml", "setup.py",
+];
+
+/// A checksum of the marker files in `dir`, or `None` when it has none.
+pub fn project_identity(dir: &Path) -> Option<String> {
+    let mut bytes = Vec::new();
+    for marker in PROJECT_MARKERS {
+        if let Ok(contents) = fs::read(dir.join(marker)) {
+            bytes.extend_from_slice(marker.as_bytes());
+            bytes.push(0);
+            bytes.extend_from_slice(&contents);
+        }
+    }
+    (!bytes.is_empty()).then(|| checksum(&bytes))
+}
+
+/// The distinct projects the candidates are in, grouped by [`project_identity`].
+pub fn projects_by_identity(candidates: &[CandidateDir]) -> HashMap<String, Vec<&Path>> {
+    let mut projects: HashMap<String, Vec<&Path>> = HashMap::new();
+    let mut seen = HashSet::new();
+    for project in candidates.iter().filter_map(|c| c.path.parent()) {
+        if !seen.insert(project) {
+            continue;
+        }
+        if let Some(identity) = project_identity(project) {
+            projects.entry(identity).or_default().push(project);
+        }
+    }
+    projects
+}
+
+/// The project a rule path belongs to: the path itself when it is a project, or its
+/// parent when it names something inside one (`app/node_modules`).
+fn project_of(rule: &Path) -> Option<(PathBuf, String)> {
+    std::iter::once(rule)
+        .chain(rule.parent())
+        .find_map(|dir| project_identity(dir).map(|id| (dir.to_path_buf(), id)))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KnownRule {
+    project: PathBuf,
+    identity: String,
+}
+
+/// A rule whose path is gone, and where its project seems to be now.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    /// The config list the rule is in, `protected` or `excludes`.
+    pub list: &'static str,
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// The project behind each config rule, as of the last run its path existed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct KnownProjects {
+    rules: BTreeMap<PathBuf, KnownRule>,
+}
+
+impl KnownProjects {
+    pub fn load(file: &Path) -> Self {
+        fs::read(file).ok().and_then(|bytes| serde_json::from_slice(&bytes).ok()).unwrap_or_default()
+    }
+
+    pub fn save(&self, file: &Path) -> io::Result<()> {
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        serde_json::to_writer(fs::File::create(file)?, self).map_err(io::Error::other)
+    }
+
+    /// Remembers the project of every rule that exists, and for rules that vanished, finds
+    /// the one candidate whose project has the same identity. Rules whose project matches
+    /// no candidate, or several, are left alone.
+    pub fn follow(&mut self, rules: &[(&'static str, &Path)], candidates: &[CandidateDir]) -> Vec<Migration> {
+        let mut vanished = Vec::new();
+        for &(list, rule) in rules {
+            if rule.exists() {
+                match project_of(rule) {
+                    Some((project, identity)) => self.rules.insert(rule.to_path_buf(), KnownRule { project, identity }),
+                    None => self.rules.remove(rule),
+                };
+            } else if let Some(known) = self.rules.get(rule) {
+                vanished.push((list, rule, known.clone()));
+            }
+        }
+        if vanished.is_empty() {
+            return Vec::new();
+        }
+
+        let projects = projects_by_identity(candidates);
+        vanished
+            .into_iter()
+            .filter_map(|(list, rule, known)| match projects.get(&known.identity).map(Vec::as_slice) {
+                Some([project]) => {
+                    let inside = rule.strip_prefix(&known.project).ok()?;
+                    let to = if inside.as_os_str().is_empty() { project.to_path_buf() } else { project.join(inside) };
+                    Some(Migration { list, from: rule.to_path_buf(), to })
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Forgets a vanished rule once its migration was answered, so it isn't offered again.
+    pub fn forget(&mut self, rule: &Path) {
+        self.rules.remove(rule);
+    }
+
+    /// Applies the user's answer to `migrations`: `None` when nobody could be asked.
+    ///
+    /// The candidates under each new location are left out of this run whatever the
+    /// answer, since their project was protected or excluded before it moved; only an
+    /// answered migration is forgotten, so an unanswered one is offered again next time.
+    /// Returns how many candidates were left out.
+    pub fn settle(&mut self, migrations: &[Migration], answer: Option<bool>, candidates: &mut Vec<CandidateDir>) -> usize {
+        let before = candidates.len();
+        for migration in migrations {
+            if answer.is_some() {
+                self.forget(&migration.from);
+            }
+            candidates.retain(|c| !c.path.starts_with(&migration.to));
+        }
+        before - candidates.len()
+    }
+}
@@ -0,0 +1,124 @@
+//! Free-text notes on candidates ("ask Sam first"), kept in the data directory by path
+//! so triage can span several runs.
+
+use crate::moves::{project_identity, projects_by_identity};
+use crate::units::unix_secs;
+use crate::CandidateDir;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{self, Path, PathBuf};
+use std::time::SystemTime;
+
+/// Longer notes are cut to this many characters; they have to fit in a list row.
+pub const MAX_NOTE_CHARS: usize = 80;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Note {
+    pub text: String,
+    /// When it was written, in unix seconds.
+    pub added: u64,
+    /// Identity of the folder's project when the note was written, to follow it if the
+    /// project moves.
+    #[serde(default)]
+    pub project: Option<String>,
+}
+
+/// How a note is shown after a row or report line.
+pub fn note_label(text: &str) -> String {
+    format!("[note: {}]", text)
+}
+
+#[derive(Debug, Default)]
+pub struct Notes {
+    file: Option<PathBuf>,
+    notes: BTreeMap<PathBuf, Note>,
+}
+
+/// Notes are keyed by absolute path, whichever way the scan root was given.
+fn key(path: &Path) -> PathBuf {
+    path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+impl Notes {
+    pub fn load(file: Option<PathBuf>) -> Self {
+        let notes = file
+            .as_deref()
+            .and_then(|f| fs::read(f).ok())
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Notes { file, notes }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(file) = &self.file else { return Ok(()) };
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        serde_json::to_writer_pretty(fs::File::create(file)?, &self.notes).map_err(io::Error::other)
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&str> {
+        self.notes.get(&key(path)).map(|n| n.text.as_str())
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&PathBuf, &Note)> {
+        self.notes.iter()
+    }
+
+    /// Sets the note on `path`, or removes it when `text` is blank. Returns whether
+    /// anything changed.
+    pub fn set(&mut self, path: &Path, text: &str) -> bool {
+        let text: String = text.trim().chars().take(MAX_NOTE_CHARS).collect();
+        if text.is_empty() {
+            return self.remove(path);
+        }
+        if self.get(path) == Some(text.as_str()) {
+            return false;
+        }
+        let project = path.parent().and_then(project_identity);
+        self.notes.insert(key(path), Note { text, added: unix_secs(SystemTime::now()), project });
+        true
+    }
+
+    pub fn remove(&mut self, path: &Path) -> bool {
+        self.notes.remove(&key(path)).is_some()
+    }
+
+    /// Removes every note, returning how many there were.
+    pub fn clear(&mut self) -> usize {
+        let count = self.notes.len();
+        self.notes.clear();
+        count
+    }
+
+    /// Moves notes on folders that are gone to the same folder of the one candidate
+    /// project with the same identity, returning the moves made.
+    pub fn follow_moves(&mut self, candidates: &[CandidateDir]) -> Vec<(PathBuf, PathBuf)> {
+        let gone: Vec<(PathBuf, String)> = self
+            .notes
+            .iter()
+            .filter(|(path, _)| !path.exists())
+            .filter_map(|(path, note)| Some((path.clone(), note.project.clone()?)))
+            .collect();
+        if gone.is_empty() {
+            return Vec::new();
+        }
+
+        let projects = projects_by_identity(candidates);
+        let mut moved = Vec::new();
+        for (from, identity) in gone {
+            let (Some([project]), Some(name)) = (projects.get(&identity).map(Vec::as_slice), from.file_name()) else {
+                continue;
+            };
+            let to = key(&project.join(name));
+            if to.exists() && !self.notes.contains_key(&to) {
+                let note = self.notes.remove(&from).expect("collected from the notes above");
+                self.notes.insert(to.clone(), note);
+                moved.push((from, to));
+            }
+        }
+        moved
+    }
+}
@@ -0,0 +1,96 @@
+//! The questions asked on the very first run, before anything is scanned.
+
+use crate::config::toml_string;
+use dialoguer::{theme::SimpleTheme, Confirm, MultiSelect, Select};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Folders under the home directory where projects usually live.
+const PROJECT_ROOTS: &[&str] = &["src", "code", "projects", "Projects", "dev", "Developer", "workspace", "repos", "git"];
+
+/// Large folders under the home directory that hold tool caches and application data
+/// rather than projects; scanning them is slow and finds little worth deleting.
+const HEAVY_FOLDERS: &[&str] = &[
+    ".cache", ".local/share", ".cargo", ".rustup", ".npm", ".gradle", ".m2", "go/pkg", "snap",
+    "Library", "AppData", ".Trash",
+];
+
+/// What the user chose, written to the config file so later runs don't ask again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Answers {
+    pub default_path: PathBuf,
+    pub excludes: Vec<PathBuf>,
+    pub cache: bool,
+}
+
+/// Project folders that exist under `home`, followed by `home` itself.
+pub fn suggested_roots(home: &Path) -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = PROJECT_ROOTS.iter().map(|r| home.join(r)).filter(|p| p.is_dir()).collect();
+    roots.push(home.to_path_buf());
+    roots
+}
+
+/// The [`HEAVY_FOLDERS`] that exist under `home`.
+pub fn heavy_folders(home: &Path) -> Vec<PathBuf> {
+    HEAVY_FOLDERS.iter().map(|f| home.join(f)).filter(|p| p.is_dir()).collect()
+}
+
+/// Asks where to scan by default, what to skip and whether to cache results.
+pub fn ask(home: &Path) -> io::Result<Answers> {
+    println!("Welcome to DevPurge!");
+    println!("It looks for dependency and build folders (node_modules, target, build, ...) that");
+    println!("can be regenerated, shows how much space they take and lets you pick which to delete.");
+    println!("Nothing is deleted without your confirmation. Scanning a whole home directory can take");
+    println!("a while, so start somewhere smaller if your projects live in one place.\n");
+
+    let roots = suggested_roots(home);
+    let labels: Vec<String> = roots
+        .iter()
+        .map(|r| if r == home { format!("{} (everything in your home directory)", r.display()) } else { r.display().to_string() })
+        .collect();
+    let root = Select::with_theme(&SimpleTheme)
+        .with_prompt("Where should DevPurge look by default?")
+        .items(&labels)
+        .default(0)
+        .interact()
+        .map_err(io::Error::other)?;
+
+    let heavy = heavy_folders(home);
+    let skipped = if heavy.is_empty() {
+        Vec::new()
+    } else {
+        let labels: Vec<String> = heavy.iter().map(|p| p.display().to_string()).collect();
+        MultiSelect::with_theme(&SimpleTheme)
+            .with_prompt("Skip these tool and application folders? (Space to toggle, Enter to confirm)")
+            .items(&labels)
+            .defaults(&vec![true; labels.len()])
+            .interact()
+            .map_err(io::Error::other)?
+    };
+
+    let cache = Confirm::with_theme(&SimpleTheme)
+        .with_prompt("Cache scan results so the next run starts instantly?")
+        .default(true)
+        .interact()
+        .map_err(io::Error::other)?;
+
+    Ok(Answers {
+        default_path: roots[root].clone(),
+        excludes: skipped.into_iter().map(|i| heavy[i].clone()).collect(),
+        cache,
+    })
+}
+
+/// The config file holding the answers.
+pub fn config_text(answers: &Answers) -> String {
+    let excludes: Vec<String> = answers.excludes.iter().map(|p| toml_string(&p.to_string_lossy())).collect();
+    format!(
+        "# Written by DevPurge on its first run; edit freely.\n\
+         default_path = {}\n\
+         excludes = [{}]\n\
+         cache = {}\n",
+        toml_string(&answers.default_path.to_string_lossy()),
+        excludes.join(", "),
+        answers.cache
+    )
+}
@@ -0,0 +1,97 @@
+use crate::CandidateDir;
+use std::cmp::Ordering;
+use std::path::Path;
+
+/// How paths are compared when two candidates have the same size.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PathOrder {
+    /// Raw bytes of the path, identical on every machine and locale.
+    #[default]
+    Bytewise,
+    /// Case-insensitive, with runs of digits compared by value (`v2` before `v10`).
+    Natural,
+}
+
+/// What the candidate list is sorted by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortKey {
+    /// Largest first.
+    #[default]
+    Size,
+    /// The full path, so folders of one project tree stay together.
+    Path,
+    /// The name of the project folder the candidate is in.
+    Name,
+    /// Least recently modified first; candidates whose age couldn't be read come last.
+    Age,
+}
+
+/// Sorts candidates by size, largest first, then by path ascending.
+///
+/// The order is fully determined by the candidates themselves, never by the order
+/// the scan produced them in, so repeated runs over the same tree list folders identically.
+pub fn sort_candidates(candidates: &mut [CandidateDir], order: PathOrder) {
+    sort_candidates_by(candidates, SortKey::Size, order);
+}
+
+/// Sorts candidates by `key`, with the path breaking ties.
+pub fn sort_candidates_by(candidates: &mut [CandidateDir], key: SortKey, order: PathOrder) {
+    candidates.sort_by(|a, b| {
+        let by_key = match key {
+            SortKey::Size => b.size.cmp(&a.size),
+            SortKey::Path => Ordering::Equal,
+            SortKey::Name => compare_paths(project_name(&a.path), project_name(&b.path), order),
+            SortKey::Age => a.modified.is_none().cmp(&b.modified.is_none()).then(a.modified.cmp(&b.modified)),
+        };
+        by_key.then_with(|| compare_paths(&a.path, &b.path, order))
+    });
+}
+
+fn project_name(path: &Path) -> &Path {
+    path.parent().and_then(Path::file_name).map_or(path, Path::new)
+}
+
+pub fn compare_paths(a: &Path, b: &Path, order: PathOrder) -> Ordering {
+    let a = a.as_os_str().as_encoded_bytes();
+    let b = b.as_os_str().as_encoded_bytes();
+    match order {
+        PathOrder::Bytewise => a.cmp(b),
+        // Fall back to bytes so paths differing only in case or leading zeros still have a fixed order.
+        PathOrder::Natural => natural_cmp(a, b).then_with(|| a.cmp(b)),
+    }
+}
+
+fn natural_cmp(a: &[u8], b: &[u8]) -> Ordering {
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i].is_ascii_digit() && b[j].is_ascii_digit() {
+            let start_a = i;
+            let start_b = j;
+            while i < a.len() && a[i].is_ascii_digit() {
+                i += 1;
+            }
+            while j < b.len() && b[j].is_ascii_digit() {
+                j += 1;
+            }
+            let digits_a = trim_zeros(&a[start_a..i]);
+            let digits_b = trim_zeros(&b[start_b..j]);
+            let ordering = digits_a.len().cmp(&digits_b.len()).then_with(|| digits_a.cmp(digits_b));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        } else {
+            let ordering = a[i].to_ascii_lowercase().cmp(&b[j].to_ascii_lowercase());
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            i += 1;
+            j += 1;
+        }
+    }
+    (a.len() - i).cmp(&(b.len() - j))
+}
+
+fn trim_zeros(digits: &[u8]) -> &[u8] {
+    let zeros = digits.iter().take_while(|&&d| d == b'0').count();
+    &digits[zeros..]
+}
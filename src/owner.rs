@@ -0,0 +1,94 @@
+//! Who owns the project trees under a shared scan root.
+//!
+//! On shared hosts each user's trees sit side by side below the root
+//! (`/srv/builds/alice/...`), so a candidate belongs to whoever owns its top-level
+//! directory. Lookups are cached per top-level directory and per user.
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// The directory directly below `root` that `path` lives in, or `path` itself when it
+/// isn't below `root`.
+pub fn top_level(root: &Path, path: &Path) -> PathBuf {
+    match path.strip_prefix(root).ok().and_then(|rel| rel.components().next()) {
+        Some(Component::Normal(first)) => root.join(first),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Owner of a path; `None` where ownership can't be read (and on Windows, where it isn't
+/// compared yet), which keeps the path.
+#[cfg(unix)]
+pub fn owner_of(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::symlink_metadata(path).ok().map(|m| m.uid())
+}
+
+#[cfg(not(unix))]
+pub fn owner_of(_path: &Path) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+pub fn current_user() -> Option<u32> {
+    Some(unsafe { libc::geteuid() })
+}
+
+#[cfg(not(unix))]
+pub fn current_user() -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn user_name(uid: u32) -> Option<String> {
+    let mut buf = vec![0 as libc::c_char; 1024];
+    let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut found: *mut libc::passwd = std::ptr::null_mut();
+    // SAFETY: every pointer references a live buffer of the stated size for the duration of the call.
+    let rc = unsafe { libc::getpwuid_r(uid, &mut entry, buf.as_mut_ptr(), buf.len(), &mut found) };
+    if rc != 0 || found.is_null() {
+        return None;
+    }
+    // SAFETY: on success pw_name points into `buf`, NUL-terminated.
+    Some(unsafe { std::ffi::CStr::from_ptr(entry.pw_name) }.to_string_lossy().into_owned())
+}
+
+#[cfg(not(unix))]
+fn user_name(_uid: u32) -> Option<String> {
+    None
+}
+
+/// Whether `--only-mine` applies without being asked for: the root is outside the home
+/// directory and owned by someone else, as shared build areas are.
+pub fn only_mine_by_default(root: &Path, home: Option<&Path>) -> bool {
+    let outside_home = home.is_none_or(|home| !root.starts_with(home));
+    outside_home && matches!((owner_of(root), current_user()), (Some(owner), Some(me)) if owner != me)
+}
+
+#[derive(Default)]
+pub struct OwnerCache {
+    owners: HashMap<PathBuf, Option<u32>>,
+    names: HashMap<u32, String>,
+}
+
+impl OwnerCache {
+    pub fn owner(&mut self, dir: &Path) -> Option<u32> {
+        *self.owners.entry(dir.to_path_buf()).or_insert_with(|| owner_of(dir))
+    }
+
+    /// Whether `dir` belongs to the current user; unknown owners count as ours.
+    pub fn is_mine(&mut self, dir: &Path) -> bool {
+        match (self.owner(dir), current_user()) {
+            (Some(owner), Some(me)) => owner == me,
+            _ => true,
+        }
+    }
+
+    /// The user name for display, or the numeric id when it has none.
+    pub fn name(&mut self, uid: Option<u32>) -> String {
+        match uid {
+            Some(uid) => self.names.entry(uid).or_insert_with(|| user_name(uid).unwrap_or_else(|| uid.to_string())).clone(),
+            None => "?".to_string(),
+        }
+    }
+}
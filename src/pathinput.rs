@@ -0,0 +1,85 @@
+use directories::BaseDirs;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Normalizes a path as typed or pasted by a user: trims whitespace and
+/// surrounding quotes, undoes shell escapes from drag-and-drop, and expands `~`.
+pub fn clean_path_input(input: &str) -> PathBuf {
+    let mut text = input.trim();
+    for quote in ['"', '\''] {
+        if text.len() >= 2 && text.starts_with(quote) && text.ends_with(quote) {
+            text = &text[1..text.len() - 1];
+        }
+    }
+    let text = text.strip_prefix("file://").unwrap_or(text);
+
+    let text = if cfg!(windows) { text.to_string() } else { unescape_shell(text) };
+    expand_tilde(&text)
+}
+
+fn unescape_shell(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(next) = chars.next() {
+                out.push(next);
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+pub fn expand_tilde(text: &str) -> PathBuf {
+    let rest = if text == "~" {
+        Some("")
+    } else {
+        text.strip_prefix("~/").or_else(|| text.strip_prefix("~\\"))
+    };
+    match (rest, BaseDirs::new()) {
+        (Some(rest), Some(dirs)) => dirs.home_dir().join(rest),
+        _ => PathBuf::from(text),
+    }
+}
+
+/// Sibling directories whose names are within a small edit distance of the
+/// missing path's last component, closest first.
+pub fn suggest_similar(path: &Path) -> Vec<PathBuf> {
+    let (parent, wanted) = match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => (parent, name.to_string_lossy().to_lowercase()),
+        _ => return Vec::new(),
+    };
+    let parent = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+    let max_distance = (wanted.chars().count() / 3).max(2);
+
+    let mut matches: Vec<(usize, PathBuf)> = fs::read_dir(parent)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            let distance = edit_distance(&wanted, &name);
+            (distance <= max_distance).then(|| (distance, entry.path()))
+        })
+        .collect();
+    matches.sort();
+    matches.into_iter().take(3).map(|(_, path)| path).collect()
+}
+
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
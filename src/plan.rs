@@ -0,0 +1,157 @@
+//! Deletion plans: candidates chosen on one machine, applied later, possibly on another.
+
+use crate::check::check_path;
+use crate::units::unix_secs;
+use crate::volume::{volume_id, VolumeTable};
+use crate::CandidateDir;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const PLAN_VERSION: u32 = 1;
+
+/// Rewrites paths starting with `from` to start with `to` instead.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathMapping {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// Parses `FROM=TO`.
+pub fn parse_path_mapping(input: &str) -> Result<PathMapping, String> {
+    match input.split_once('=') {
+        Some((from, to)) if !from.is_empty() && !to.is_empty() => Ok(PathMapping { from: from.into(), to: to.into() }),
+        _ => Err(format!("invalid mapping '{}': expected FROM=TO, e.g. /mnt/builds=/srv/builds", input)),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanEntry {
+    pub path: PathBuf,
+    pub size: u64,
+    #[serde(default)]
+    pub kind: String,
+    #[serde(default)]
+    pub is_file: bool,
+    /// [`volume_id`] of the volume the entry was found on.
+    #[serde(default)]
+    pub volume: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Plan {
+    pub version: u32,
+    /// Host the plan was made on.
+    pub host: String,
+    pub created: u64,
+    /// Applied at `--apply` time, the longest matching prefix winning.
+    #[serde(default)]
+    pub path_map: Vec<PathMapping>,
+    pub entries: Vec<PlanEntry>,
+}
+
+impl Plan {
+    pub fn new(candidates: &[CandidateDir], volumes: &VolumeTable, path_map: Vec<PathMapping>) -> Self {
+        Plan {
+            version: PLAN_VERSION,
+            host: hostname(),
+            created: unix_secs(SystemTime::now()),
+            path_map,
+            entries: candidates
+                .iter()
+                .map(|c| PlanEntry {
+                    path: c.path.clone(),
+                    size: c.size,
+                    kind: c.kind.clone(),
+                    is_file: c.is_file,
+                    volume: volumes.volume_of(&c.path).map(volume_id),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = fs::File::open(path).with_context(|| format!("could not open plan {}", path.display()))?;
+        let plan: Plan = serde_json::from_reader(file).with_context(|| format!("invalid plan {}", path.display()))?;
+        anyhow::ensure!(plan.version <= PLAN_VERSION, "plan {} needs a newer devpurge (format version {})", path.display(), plan.version);
+        Ok(plan)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let file = fs::File::create(path).with_context(|| format!("could not create {}", path.display()))?;
+        serde_json::to_writer_pretty(file, self).with_context(|| format!("could not write {}", path.display()))
+    }
+
+    /// Where an entry's path lives on this machine.
+    pub fn map_path(&self, path: &Path) -> PathBuf {
+        self.path_map
+            .iter()
+            .filter(|m| path.starts_with(&m.from))
+            .max_by_key(|m| m.from.components().count())
+            .map(|m| m.to.join(path.strip_prefix(&m.from).unwrap_or(path)))
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+
+    /// Checks one entry against this machine: `Ok` with its local path, or why it won't be applied.
+    ///
+    /// An entry applies on the planning host, or on any host that sees the same volume;
+    /// `ignore_host` skips those identity checks but never the existence check. Wherever a
+    /// mapping points it, the local path then has to pass every [`check_path`] check, as
+    /// a folder a scan found would.
+    pub fn verify(&self, entry: &PlanEntry, volumes: &VolumeTable, here: &str, ignore_host: bool, protected: &[PathBuf]) -> Result<PathBuf, String> {
+        let local = self.map_path(&entry.path);
+        if fs::symlink_metadata(&local).is_err() {
+            return Err(format!("not found at {}", local.display()));
+        }
+        if !ignore_host {
+            self.same_volume(entry, &local, volumes, here)?;
+        }
+        let verdict = check_path(&local, protected);
+        match verdict.ok {
+            true => Ok(local),
+            false => Err(format!("{} is not safe to delete: {}", local.display(), verdict.reasons.join("; "))),
+        }
+    }
+
+    /// Whether `local` is where `entry` was planned: the same volume, or, on the planning
+    /// host, a volume that couldn't be told apart.
+    fn same_volume(&self, entry: &PlanEntry, local: &Path, volumes: &VolumeTable, here: &str) -> Result<(), String> {
+        let local_volume = volumes.volume_of(local).map(volume_id);
+        match (&entry.volume, &local_volume) {
+            (Some(planned), Some(local_volume)) if planned == local_volume => Ok(()),
+            (None, _) | (_, None) if self.host == here => Ok(()),
+            (Some(planned), _) if self.host == here => {
+                Err(format!("was on volume {} when planned, now on {}", planned, local_volume.as_deref().unwrap_or("an unknown volume")))
+            }
+            (planned, _) => Err(format!(
+                "planned on host {} (volume {}), here it is on {}",
+                self.host,
+                planned.as_deref().unwrap_or("unknown"),
+                local_volume.as_deref().unwrap_or("an unknown volume")
+            )),
+        }
+    }
+}
+
+/// This machine's name, as recorded in plans.
+pub fn hostname() -> String {
+    platform_hostname().unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(unix)]
+fn platform_hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: the buffer is valid for its full length; gethostname NUL-terminates on success.
+    if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+        return None;
+    }
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    Some(String::from_utf8_lossy(&buf[..end]).into_owned())
+}
+
+#[cfg(not(unix))]
+fn platform_hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
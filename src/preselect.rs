@@ -0,0 +1,124 @@
+use crate::units::{age_of, format_duration, parse_duration, parse_size};
+use crate::CandidateDir;
+use human_bytes::human_bytes;
+use std::fmt;
+use std::time::Duration;
+
+/// What a preselection policy looks at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Time since the enclosing project changed, falling back to the folder's own age.
+    Idle,
+    /// Time since the folder itself was modified.
+    Age,
+    Size,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparator {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Threshold {
+    Duration(Duration),
+    Bytes(u64),
+}
+
+/// Which candidates start checked, e.g. `idle>90d` or `size>=1G`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Preselect {
+    pub metric: Metric,
+    pub comparator: Comparator,
+    pub threshold: Threshold,
+}
+
+/// Parses `<metric><comparator><value>` with metric `idle`, `age` or `size` and
+/// comparator `>`, `>=`, `<` or `<=`; ages take a duration and sizes a size.
+pub fn parse_preselect(input: &str) -> Result<Preselect, String> {
+    let input = input.trim();
+    let op_start = input
+        .find(['<', '>'])
+        .ok_or_else(|| format!("invalid preselection '{}': expected e.g. idle>90d or size>=1G", input))?;
+    let (metric, rest) = input.split_at(op_start);
+    let (comparator, value) = if let Some(value) = rest.strip_prefix(">=") {
+        (Comparator::GreaterOrEqual, value)
+    } else if let Some(value) = rest.strip_prefix("<=") {
+        (Comparator::LessOrEqual, value)
+    } else if let Some(value) = rest.strip_prefix('>') {
+        (Comparator::Greater, value)
+    } else {
+        (Comparator::Less, &rest[1..])
+    };
+
+    let metric = match metric.trim().to_lowercase().as_str() {
+        "idle" => Metric::Idle,
+        "age" => Metric::Age,
+        "size" => Metric::Size,
+        other => return Err(format!("unknown preselection metric '{}' (use idle, age or size)", other)),
+    };
+    let value = value.trim();
+    let threshold = match metric {
+        Metric::Idle | Metric::Age => Threshold::Duration(
+            parse_duration(value).map_err(|e| format!("{} compares against a duration like 90d: {}", metric, e))?,
+        ),
+        Metric::Size => Threshold::Bytes(parse_size(value).map_err(|e| format!("size compares against a size like 1G: {}", e))?),
+    };
+    Ok(Preselect { metric, comparator, threshold })
+}
+
+impl Preselect {
+    /// Whether the candidate starts checked; candidates whose age is unknown never do.
+    pub fn matches(&self, candidate: &CandidateDir) -> bool {
+        match self.threshold {
+            Threshold::Duration(threshold) => {
+                let modified = match self.metric {
+                    Metric::Idle => candidate.project_modified.or(candidate.fingerprint.map(|f| f.modified)),
+                    _ => candidate.fingerprint.map(|f| f.modified),
+                };
+                modified.is_some_and(|t| self.compare(age_of(t), threshold))
+            }
+            // A size we know is too low can't be trusted either way.
+            Threshold::Bytes(_) if candidate.is_incomplete() => false,
+            Threshold::Bytes(threshold) => self.compare(candidate.size, threshold),
+        }
+    }
+
+    fn compare<T: PartialOrd>(&self, value: T, threshold: T) -> bool {
+        match self.comparator {
+            Comparator::Greater => value > threshold,
+            Comparator::GreaterOrEqual => value >= threshold,
+            Comparator::Less => value < threshold,
+            Comparator::LessOrEqual => value <= threshold,
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Metric::Idle => "idle",
+            Metric::Age => "age",
+            Metric::Size => "size",
+        })
+    }
+}
+
+impl fmt::Display for Preselect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let comparator = match self.comparator {
+            Comparator::Greater => ">",
+            Comparator::GreaterOrEqual => ">=",
+            Comparator::Less => "<",
+            Comparator::LessOrEqual => "<=",
+        };
+        let threshold = match self.threshold {
+            Threshold::Duration(duration) => format_duration(duration),
+            Threshold::Bytes(bytes) => human_bytes(bytes as f64),
+        };
+        write!(f, "{} {} {}", self.metric, comparator, threshold)
+    }
+}
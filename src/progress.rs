@@ -0,0 +1,133 @@
+//! Machine-readable progress for wrappers (e.g. GUIs) on a channel of its own.
+//!
+//! Events are newline-delimited JSON objects with an `event` field. The first
+//! event is always `version`, so a reader can check the schema before trusting
+//! anything else; `heartbeat` events keep arriving while a single long operation
+//! (sizing or deleting one large folder) runs.
+
+use serde_json::{json, Value};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Bumped whenever an event changes incompatibly.
+pub const PROGRESS_SCHEMA_VERSION: u32 = 1;
+/// How often a `heartbeat` is sent while a single operation is running.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where `--progress` events go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressTarget {
+    /// An inherited file descriptor (unix) or handle (Windows), given as a number.
+    Inherited(u64),
+    /// A path to open for writing, e.g. a FIFO or `\\.\pipe\name` on Windows.
+    Path(PathBuf),
+}
+
+/// Parses `pipe:<fd|handle>`, `pipe:<named pipe>` or a plain file path.
+pub fn parse_progress_target(input: &str) -> Result<ProgressTarget, String> {
+    let target = match input.strip_prefix("pipe:") {
+        Some("") => return Err("expected a descriptor, handle or pipe name after 'pipe:'".to_string()),
+        Some(rest) => match rest.parse::<u64>() {
+            Ok(raw) => ProgressTarget::Inherited(raw),
+            Err(_) => ProgressTarget::Path(PathBuf::from(rest)),
+        },
+        None if input.is_empty() => return Err("expected a progress target".to_string()),
+        None => ProgressTarget::Path(PathBuf::from(input)),
+    };
+    Ok(target)
+}
+
+fn open_target(target: &ProgressTarget) -> io::Result<File> {
+    match target {
+        ProgressTarget::Path(path) => OpenOptions::new().write(true).create(true).truncate(true).open(path),
+        #[cfg(unix)]
+        ProgressTarget::Inherited(raw) => {
+            use std::os::unix::io::FromRawFd;
+            let fd = i32::try_from(*raw).map_err(|_| io::Error::other("file descriptor out of range"))?;
+            if fd <= 2 {
+                return Err(io::Error::other("progress must not share stdin, stdout or stderr"));
+            }
+            // SAFETY: the caller handed this descriptor to us to own for the rest of the run.
+            Ok(unsafe { File::from_raw_fd(fd) })
+        }
+        #[cfg(windows)]
+        ProgressTarget::Inherited(raw) => {
+            use std::os::windows::io::FromRawHandle;
+            // SAFETY: the caller handed this inherited handle to us to own for the rest of the run.
+            Ok(unsafe { File::from_raw_handle(*raw as usize as *mut std::ffi::c_void) })
+        }
+    }
+}
+
+/// A cheap, cloneable sink for progress events; does nothing when no target was given.
+#[derive(Clone, Default)]
+pub struct ProgressEvents {
+    /// Emptied by the first failed write, for every clone at once.
+    out: Option<Arc<Mutex<Option<File>>>>,
+}
+
+impl ProgressEvents {
+    pub fn disabled() -> Self {
+        ProgressEvents::default()
+    }
+
+    /// Opens the target and sends the `version` handshake.
+    pub fn open(target: &ProgressTarget) -> io::Result<Self> {
+        let events = ProgressEvents { out: Some(Arc::new(Mutex::new(Some(open_target(target)?)))) };
+        events.emit(
+            "version",
+            json!({ "schema": PROGRESS_SCHEMA_VERSION, "tool": "devpurge", "tool_version": env!("CARGO_PKG_VERSION") }),
+        );
+        Ok(events)
+    }
+
+    /// Whether a target was given and no write to it has failed yet.
+    pub fn is_enabled(&self) -> bool {
+        self.out.as_ref().is_some_and(|out| out.lock().is_ok_and(|file| file.is_some()))
+    }
+
+    /// Sends one event; `fields` must be a JSON object. Write errors turn the sink off.
+    pub fn emit(&self, event: &str, fields: Value) {
+        let Some(out) = &self.out else { return };
+        let mut object = match fields {
+            Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        };
+        object.insert("event".to_string(), Value::from(event));
+        let mut line = Value::Object(object).to_string();
+        line.push('\n');
+        let Ok(mut sink) = out.lock() else { return };
+        // A reader that went away won't come back; stop writing rather than fail every event.
+        if sink.as_mut().is_some_and(|file| file.write_all(line.as_bytes()).and_then(|_| file.flush()).is_err()) {
+            *sink = None;
+        }
+    }
+
+    /// Runs `work`, sending a `heartbeat` for `phase` and `path` every [`HEARTBEAT_INTERVAL`] meanwhile.
+    pub fn with_heartbeat<T>(&self, phase: &str, path: &std::path::Path, work: impl FnOnce() -> T) -> T {
+        if !self.is_enabled() {
+            return work();
+        }
+        let done = AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                let mut waited = Duration::ZERO;
+                while !done.load(Ordering::Relaxed) {
+                    std::thread::sleep(Duration::from_millis(50));
+                    waited += Duration::from_millis(50);
+                    if waited >= HEARTBEAT_INTERVAL {
+                        waited = Duration::ZERO;
+                        self.emit("heartbeat", json!({ "phase": phase, "path": path }));
+                    }
+                }
+            });
+            let result = work();
+            done.store(true, Ordering::Relaxed);
+            result
+        })
+    }
+}
@@ -0,0 +1,254 @@
+//! What kind of project each built-in target belongs to, and what has to be there for it
+//! to be safe to delete, as a table: adding an ecosystem is a row here, not a new branch.
+
+use crate::detect::{has_file_with_extension, is_default_derived_data, is_shadow_build};
+use crate::glob::glob_match;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use Marker::*;
+use ProjectKind::*;
+
+/// The ecosystem a target was made by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProjectKind {
+    Node,
+    TypeScript,
+    Angular,
+    NextJs,
+    NuxtJs,
+    Rust,
+    Gradle,
+    Maven,
+    CMake,
+    EspIdf,
+    PlatformIo,
+    Qt,
+    Php,
+    Go,
+    Ruby,
+    Python,
+    DotNet,
+    VisualStudio,
+    Dart,
+    CocoaPods,
+    Carthage,
+    Xcode,
+    Terraform,
+    Elixir,
+    Erlang,
+    Haskell,
+}
+
+impl ProjectKind {
+    /// The name shown for it, e.g. `next.js`.
+    pub fn label(self) -> &'static str {
+        match self {
+            ProjectKind::Node => "javascript",
+            ProjectKind::TypeScript => "typescript",
+            ProjectKind::Angular => "angular",
+            ProjectKind::NextJs => "next.js",
+            ProjectKind::NuxtJs => "nuxt.js",
+            ProjectKind::Rust => "rust",
+            ProjectKind::Gradle => "gradle",
+            ProjectKind::Maven => "maven",
+            ProjectKind::CMake => "c/c++",
+            ProjectKind::EspIdf => "esp-idf",
+            ProjectKind::PlatformIo => "platformio",
+            ProjectKind::Qt => "qt",
+            ProjectKind::Php => "php",
+            ProjectKind::Go => "go",
+            ProjectKind::Ruby => "ruby",
+            ProjectKind::Python => "python",
+            ProjectKind::DotNet => ".net",
+            ProjectKind::VisualStudio => "visual studio",
+            ProjectKind::Dart => "dart",
+            ProjectKind::CocoaPods => "cocoapods",
+            ProjectKind::Carthage => "carthage",
+            ProjectKind::Xcode => "xcode",
+            ProjectKind::Terraform => "terraform",
+            ProjectKind::Elixir => "elixir",
+            ProjectKind::Erlang => "erlang",
+            ProjectKind::Haskell => "haskell",
+        }
+    }
+}
+
+impl fmt::Display for ProjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// How sure DevPurge is that deleting a target loses nothing a reinstall or build won't
+/// bring back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Risk {
+    /// Always regenerated, like `__pycache__`.
+    #[default]
+    Safe,
+    /// Usually regenerated, but people sometimes put files there by hand: patched
+    /// packages in `vendor`, assets copied into a .NET `bin`.
+    Caution,
+}
+
+impl fmt::Display for Risk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Risk::Safe => "safe",
+            Risk::Caution => "caution",
+        })
+    }
+}
+
+/// Something that shows a target was made by its project.
+#[derive(Debug, Clone, Copy)]
+pub enum Marker {
+    /// A file or folder of this name next to the target.
+    Beside(&'static str),
+    /// Any of these next to it.
+    AnyBeside(&'static [&'static str]),
+    /// A file with this extension next to it, e.g. `csproj`.
+    Extension(&'static str),
+    /// A file of this name inside the target itself, for names too generic to go by
+    /// what is next to them.
+    Inside(&'static str),
+    /// Nothing: the name alone is enough.
+    Always,
+    /// A check the others can't express, given the target's path, and what it looks for.
+    Check(fn(&Path) -> bool, &'static str),
+}
+
+impl Marker {
+    /// Whether the target at `path`, whose folder is `parent`, has it.
+    pub fn is_met(&self, path: &Path, parent: &Path) -> bool {
+        match *self {
+            Marker::Beside(name) => parent.join(name).exists(),
+            Marker::AnyBeside(names) => names.iter().any(|name| parent.join(name).exists()),
+            Marker::Extension(extension) => has_file_with_extension(parent, extension),
+            Marker::Inside(name) => path.join(name).exists(),
+            Marker::Always => true,
+            Marker::Check(check, _) => check(path),
+        }
+    }
+
+    /// What it looks for, as in "found because of …".
+    pub fn describe(&self) -> String {
+        match *self {
+            Marker::Beside(name) => format!("{} next to it", name),
+            Marker::AnyBeside(names) => format!("{} next to it", names.join(" or ")),
+            Marker::Extension(extension) => format!("a .{} file next to it", extension),
+            Marker::Inside(name) => format!("{} inside it", name),
+            Marker::Always => "its name".to_string(),
+            Marker::Check(_, what) => what.to_string(),
+        }
+    }
+}
+
+/// One row of the table: targets named like `rule` belong to `kind` when any of
+/// `markers` is met.
+#[derive(Debug, Clone, Copy)]
+pub struct Detector {
+    /// A [`crate::detect::TARGET_DIRS`] or [`crate::detect::TARGET_FILES`] entry.
+    pub rule: &'static str,
+    pub kind: ProjectKind,
+    pub markers: &'static [Marker],
+    pub risk: Risk,
+}
+
+const fn row(rule: &'static str, kind: ProjectKind, markers: &'static [Marker]) -> Detector {
+    Detector { rule, kind, markers, risk: Risk::Safe }
+}
+
+/// A row whose targets may hold files put there by hand.
+const fn caution(rule: &'static str, kind: ProjectKind, markers: &'static [Marker]) -> Detector {
+    Detector { risk: Risk::Caution, ..row(rule, kind, markers) }
+}
+
+/// The built-in folder targets. A name may have several rows, tried in order, so a
+/// `build` next to an `sdkconfig` is ESP-IDF's before anything else's.
+pub const DIR_DETECTORS: &[Detector] = &[
+    row("node_modules", Node, &[Beside("package.json")]),
+    row("target", Rust, &[Beside("Cargo.toml")]),
+    row("build", EspIdf, &[Beside("sdkconfig")]),
+    row("build", Gradle, &[AnyBeside(&["build.gradle", "build.gradle.kts"])]),
+    row("build", Maven, &[Beside("pom.xml")]),
+    row("build", CMake, &[AnyBeside(&["CMakeLists.txt", "Makefile"])]),
+    row("build", Angular, &[Beside("angular.json")]),
+    row("dist", Angular, &[Beside("angular.json")]),
+    row("dist", Node, &[AnyBeside(&["package.json", "vite.config.js", "vite.config.ts"])]),
+    row("dist", TypeScript, &[Beside("tsconfig.json")]),
+    row(".gradle", Gradle, &[AnyBeside(&["build.gradle", "build.gradle.kts", "settings.gradle", "settings.gradle.kts"])]),
+    // Vendored dependencies are sometimes patched in place.
+    caution("vendor", Php, &[Beside("composer.json")]),
+    caution("vendor", Go, &[Beside("go.mod")]),
+    caution("vendor", Ruby, &[Beside("Gemfile")]),
+    row("__pycache__", Python, &[Always]),
+    caution(".venv", Python, &[Inside("pyvenv.cfg")]),
+    caution("venv", Python, &[Inside("pyvenv.cfg")]),
+    row(".tox", Python, &[AnyBeside(&["tox.ini", "pyproject.toml"])]),
+    row(".pytest_cache", Python, &[AnyBeside(&["pyproject.toml", "setup.py", "setup.cfg", "pytest.ini", "tox.ini"])]),
+    row(".mypy_cache", Python, &[AnyBeside(&["pyproject.toml", "setup.py", "setup.cfg", "mypy.ini"])]),
+    // Projects copy hand-placed assets and native libraries into bin.
+    caution("bin", DotNet, &[Extension("csproj"), Extension("fsproj"), Extension("sln")]),
+    row("obj", DotNet, &[Extension("csproj"), Extension("fsproj"), Extension("sln")]),
+    row(".dart_tool", Dart, &[Beside("pubspec.yaml")]),
+    row(".angular", Angular, &[Beside("angular.json")]),
+    row(".next", NextJs, &[AnyBeside(&["next.config.js", "next.config.ts"])]),
+    row(".nuxt", NuxtJs, &[AnyBeside(&["nuxt.config.js", "nuxt.config.ts"])]),
+    row(".vs", VisualStudio, &[Extension("sln"), Extension("vcxproj"), Extension("csproj")]),
+    row(".pio", PlatformIo, &[Beside("platformio.ini")]),
+    row("build-*", Qt, &[Check(is_shadow_build, "a CMakeCache.txt and a Qt or CMake project next to it, or a Makefile and Qt Creator's name for it")]),
+    // Some teams commit Pods and patch them.
+    caution("Pods", CocoaPods, &[Beside("Podfile")]),
+    // Carthage/Checkouts may be committed submodules; only the built frameworks go.
+    row("Build", Carthage, &[Check(is_carthage_build, "a Carthage folder next to a Cartfile")]),
+    row("DerivedData", Xcode, &[Check(is_default_derived_data, "Xcode's own location"), Extension("xcodeproj"), Extension("xcworkspace")]),
+    // Only the folder: .terraform.lock.hcl next to it pins the providers and is committed.
+    row(".terraform", Terraform, &[Extension("tf")]),
+    // rebar3 keeps Erlang dependencies inside _build, so only mix has a deps folder.
+    row("_build", Elixir, &[Beside("mix.exs")]),
+    row("_build", Erlang, &[Beside("rebar.config")]),
+    row("deps", Elixir, &[Beside("mix.exs")]),
+    row(".stack-work", Haskell, &[Beside("stack.yaml"), Extension("cabal")]),
+    row("dist-newstyle", Haskell, &[Beside("cabal.project"), Extension("cabal")]),
+];
+
+/// The built-in file targets.
+pub const FILE_DETECTORS: &[Detector] = &[
+    row(".eslintcache", Node, &[Beside("package.json")]),
+    row(".stylelintcache", Node, &[Beside("package.json")]),
+    row("*.tsbuildinfo", TypeScript, &[AnyBeside(&["package.json", "tsconfig.json"])]),
+    row(".phpunit.result.cache", Php, &[AnyBeside(&["composer.json", "phpunit.xml", "phpunit.xml.dist"])]),
+];
+
+/// The first row for a target named `name` at `path` whose markers are met, and the
+/// marker that was.
+pub fn detect(name: &str, path: &Path, is_file: bool) -> Option<(&'static Detector, &'static Marker)> {
+    let parent = path.parent()?;
+    let table = if is_file { FILE_DETECTORS } else { DIR_DETECTORS };
+    table
+        .iter()
+        .filter(|detector| glob_match(detector.rule, name))
+        .find_map(|detector| detector.markers.iter().find(|marker| marker.is_met(path, parent)).map(|marker| (detector, marker)))
+}
+
+/// The kind of project the target at `path` belongs to, if a built-in rule knows it.
+pub fn project_kind(path: &Path, is_file: bool) -> Option<ProjectKind> {
+    let name = path.file_name()?.to_str()?;
+    detect(name, path, is_file).map(|(detector, _)| detector.kind)
+}
+
+/// The risk of the row that found a target: `rule` is its [`crate::CandidateDir::kind`] and
+/// `project` what it was found for. Targets no row found, like custom ones, are safe.
+pub fn risk_of(rule: &str, project: Option<ProjectKind>, is_file: bool) -> Risk {
+    let table = if is_file { FILE_DETECTORS } else { DIR_DETECTORS };
+    table.iter().find(|detector| detector.rule == rule && Some(detector.kind) == project).map_or(Risk::Safe, |detector| detector.risk)
+}
+
+fn is_carthage_build(path: &Path) -> bool {
+    let Some(carthage) = path.parent() else { return false };
+    carthage.file_name().is_some_and(|n| n == "Carthage") && carthage.parent().is_some_and(|project| project.join("Cartfile").exists())
+}
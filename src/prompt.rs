@@ -0,0 +1,80 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" fn on_resize(_signal: libc::c_int) {
+    RESIZED.store(true, Ordering::Relaxed);
+}
+
+/// Notes terminal resizes from now on; a key read interrupted by one then fails with
+/// `Interrupted` instead of the resize going unnoticed until the next key.
+#[cfg(unix)]
+pub fn watch_resize() {
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_resize as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(libc::SIGWINCH, &action, std::ptr::null_mut());
+    }
+}
+
+#[cfg(not(unix))]
+pub fn watch_resize() {}
+
+/// Whether the terminal was resized since the last call.
+pub fn take_resize() -> bool {
+    RESIZED.swap(false, Ordering::Relaxed)
+}
+
+/// Waits until stdin has input available, returning `false` if the timeout passes first.
+#[cfg(unix)]
+pub fn wait_for_input(timeout: Duration) -> bool {
+    let mut fds = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+    let millis = timeout.as_millis().min(i32::MAX as u128) as i32;
+    loop {
+        let ready = unsafe { libc::poll(&mut fds, 1, millis) };
+        if ready < 0 && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+            continue;
+        }
+        return ready != 0;
+    }
+}
+
+#[cfg(windows)]
+pub fn wait_for_input(timeout: Duration) -> bool {
+    type Handle = *mut std::ffi::c_void;
+    const STD_INPUT_HANDLE: u32 = -10i32 as u32;
+    const WAIT_TIMEOUT: u32 = 0x102;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetStdHandle(which: u32) -> Handle;
+        fn WaitForSingleObject(handle: Handle, millis: u32) -> u32;
+    }
+
+    let millis = timeout.as_millis().min(u32::MAX as u128 - 1) as u32;
+    unsafe { WaitForSingleObject(GetStdHandle(STD_INPUT_HANDLE), millis) != WAIT_TIMEOUT }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn wait_for_input(_timeout: Duration) -> bool {
+    true
+}
+
+/// Reads one line from stdin, giving up once the deadline passes.
+///
+/// The whole line must arrive in time; a half-typed answer still times out.
+pub fn read_line_timeout(timeout: Duration) -> Option<io::Result<String>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut line = String::new();
+        let result = io::stdin().read_line(&mut line).map(|_| line);
+        let _ = tx.send(result);
+    });
+    rx.recv_timeout(timeout).ok()
+}
@@ -0,0 +1,109 @@
+//! Adjusting the minimum size, the kinds shown and the order after a scan, on the results
+//! already in memory, before the selection list opens.
+
+use crate::floors::{parse_size_floors, SizeFloors};
+use crate::order::{sort_candidates_by, PathOrder, SortKey};
+use crate::CandidateDir;
+use clap::ValueEnum;
+use dialoguer::{theme::SimpleTheme, Input, MultiSelect, Select};
+use human_bytes::human_bytes;
+use std::collections::BTreeSet;
+use std::io;
+
+/// The filters and order applied to the full scan results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refinement {
+    pub floors: SizeFloors,
+    /// Kinds left out, e.g. `vendor`.
+    pub hidden: BTreeSet<String>,
+    pub sort: SortKey,
+    pub reverse: bool,
+    pub path_order: PathOrder,
+}
+
+impl Refinement {
+    /// The candidates of `all` that pass, in order; `all` itself is never changed, so a
+    /// looser filter brings back what a stricter one dropped.
+    pub fn apply(&self, all: &[CandidateDir]) -> Vec<CandidateDir> {
+        let mut shown: Vec<CandidateDir> = all.iter().filter(|c| !self.hidden.contains(&c.kind) && self.floors.admits(c)).cloned().collect();
+        sort_candidates_by(&mut shown, self.sort, self.path_order);
+        if self.reverse {
+            shown.reverse();
+        }
+        shown
+    }
+}
+
+/// Every kind in `all` with its count and size, largest first.
+pub fn kinds(all: &[CandidateDir]) -> Vec<(String, usize, u64)> {
+    let mut kinds: Vec<(String, usize, u64)> = Vec::new();
+    for candidate in all {
+        match kinds.iter_mut().find(|(kind, _, _)| *kind == candidate.kind) {
+            Some((_, count, bytes)) => {
+                *count += 1;
+                *bytes += candidate.reclaimable();
+            }
+            None => kinds.push((candidate.kind.clone(), 1, candidate.reclaimable())),
+        }
+    }
+    kinds.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    kinds
+}
+
+/// Offers to change `refinement` until the user goes on to the selection, and returns
+/// what it lets through.
+pub fn ask(all: &[CandidateDir], refinement: &mut Refinement) -> io::Result<Vec<CandidateDir>> {
+    let available = kinds(all);
+    loop {
+        let shown = refinement.apply(all);
+        let total: u64 = shown.iter().map(|c| c.reclaimable()).sum();
+        let floors = match refinement.floors.is_empty() {
+            true => "none".to_string(),
+            false => refinement.floors.to_string(),
+        };
+        let kinds_shown = match refinement.hidden.len() {
+            0 => "all".to_string(),
+            hidden => format!("{} of {}", available.len() - hidden.min(available.len()), available.len()),
+        };
+        let items = [
+            format!("Go on to the selection ({} folders, {})", shown.len(), human_bytes(total as f64)),
+            format!("Minimum size: {}", floors),
+            format!("Kinds: {}", kinds_shown),
+            format!("Order: {}{}", sort_name(refinement.sort), if refinement.reverse { ", reversed" } else { "" }),
+        ];
+        let choice = Select::with_theme(&SimpleTheme).with_prompt("Refine the results, or go on").items(&items).default(0).interact()?;
+        match choice {
+            0 => return Ok(shown),
+            1 => {
+                let answer: String = Input::with_theme(&SimpleTheme)
+                    .with_prompt("Minimum size (e.g. 100M, or node_modules=200M,default=50M; 0 for none)")
+                    .with_initial_text(refinement.floors.rules())
+                    .validate_with(|input: &String| parse_size_floors(input).map(drop))
+                    .interact_text()?;
+                refinement.floors = parse_size_floors(&answer).unwrap_or_default();
+            }
+            2 => {
+                let labels: Vec<String> = available.iter().map(|(kind, count, bytes)| format!("{} ({} folders, {})", kind, count, human_bytes(*bytes as f64))).collect();
+                let checked: Vec<bool> = available.iter().map(|(kind, _, _)| !refinement.hidden.contains(kind)).collect();
+                let picked = MultiSelect::with_theme(&SimpleTheme).with_prompt("Kinds to show (space toggles)").items(&labels).defaults(&checked).interact()?;
+                refinement.hidden = available.iter().enumerate().filter(|(i, _)| !picked.contains(i)).map(|(_, (kind, _, _))| kind.clone()).collect();
+            }
+            _ => {
+                let keys = SortKey::value_variants();
+                let labels: Vec<&str> = keys.iter().map(|&key| sort_name(key)).collect();
+                let current = keys.iter().position(|&key| key == refinement.sort).unwrap_or(0);
+                refinement.sort = keys[Select::with_theme(&SimpleTheme).with_prompt("Order by").items(&labels).default(current).interact()?];
+                refinement.reverse = dialoguer::Confirm::with_theme(&SimpleTheme).with_prompt("Reversed?").default(refinement.reverse).interact()?;
+            }
+        }
+    }
+}
+
+fn sort_name(key: SortKey) -> &'static str {
+    match key {
+        SortKey::Size => "size",
+        SortKey::Path => "path",
+        SortKey::Name => "name",
+        SortKey::Age => "age",
+    }
+}
@@ -0,0 +1,285 @@
+use crate::detect::{hard_link_id, is_link_entry, native_clean, partial_paths};
+use crate::exec::{self, exec_allowed};
+use crate::failures::FailureLog;
+use crate::filesystem::{FileSystem, RealFs};
+use crate::glob::glob_match;
+use crate::longpath::extended;
+use crate::trash;
+use crate::CandidateDir;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+/// How long a native clean command may run before it is killed.
+const NATIVE_CLEAN_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// How a selected candidate is cleaned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Action {
+    /// Remove it entirely.
+    Delete,
+    /// Move it to the platform trash (see [`crate::trash`]).
+    Trash,
+    /// Remove only the caches inside that rebuild quickly (see [`partial_paths`]).
+    Partial,
+    /// Run the ecosystem's own clean command, e.g. `cargo clean`.
+    Native,
+    /// Empty it but keep the folder itself, with its permissions, mount or sync settings.
+    KeepDir,
+}
+
+impl Action {
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Delete => "delete",
+            Action::Trash => "trash",
+            Action::Partial => "partial",
+            Action::Native => "native",
+            Action::KeepDir => "keep-dir",
+        }
+    }
+
+    /// Whether the candidate is gone (or empty) afterwards, rather than cleaned in place.
+    pub fn removes_contents(self) -> bool {
+        matches!(self, Action::Delete | Action::Trash | Action::KeepDir)
+    }
+
+    /// What the action does, as in "Failed to {verb} ..."; actions that clean in place
+    /// are "clean (partial)" and so on.
+    pub fn verb(self) -> String {
+        match self {
+            Action::Delete => "delete".to_string(),
+            Action::Trash => "move to the trash".to_string(),
+            other => format!("clean ({})", other),
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// The actions that can be applied to `candidate`, plain deletion first.
+pub fn available_actions(candidate: &CandidateDir) -> Vec<Action> {
+    let mut actions = vec![Action::Delete];
+    if trash::supported() {
+        actions.push(Action::Trash);
+    }
+    if candidate.is_file {
+        return actions;
+    }
+    if !partial_targets(&RealFs, candidate).is_empty() {
+        actions.push(Action::Partial);
+    }
+    if exec_allowed() && native_clean(dir_name(&candidate.path), &candidate.path).is_some() {
+        actions.push(Action::Native);
+    }
+    if !candidate.is_link {
+        actions.push(Action::KeepDir);
+    }
+    actions
+}
+
+/// The caches inside `candidate` that [`Action::Partial`] removes, as they exist now, in
+/// the form [`extended`] gives.
+pub fn partial_targets(fs: &dyn FileSystem, candidate: &CandidateDir) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    for pattern in partial_paths(dir_name(&candidate.path)) {
+        let mut matches = vec![extended(&candidate.path).into_owned()];
+        for part in pattern.split('/') {
+            matches = matches
+                .iter()
+                .flat_map(|dir| fs.read_dir(dir).into_iter().flatten().filter_map(|e| e.ok()))
+                .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()) && glob_match(part, &entry.file_name().to_string_lossy()))
+                .map(|entry| entry.path())
+                .collect();
+        }
+        found.extend(matches);
+    }
+    found
+}
+
+/// Deletes a candidate without ever touching what links inside it point to.
+///
+/// Symbolic links and junctions met inside are removed instead of followed; a candidate
+/// that is itself a link or junction only loses the link.
+pub fn remove_candidate(candidate: &CandidateDir) -> io::Result<()> {
+    remove_candidate_with(&RealFs, candidate)
+}
+
+/// [`remove_candidate`] through any [`FileSystem`].
+pub fn remove_candidate_with(fs: &dyn FileSystem, candidate: &CandidateDir) -> io::Result<()> {
+    apply_action_with_progress(fs, candidate, Action::Delete, false, &mut |_| {})
+}
+
+/// Applies `action` to a candidate; everything but running a native clean command or
+/// moving to the trash goes through `fs`.
+pub fn apply_action(fs: &dyn FileSystem, candidate: &CandidateDir, action: Action) -> io::Result<()> {
+    apply_action_with_progress(fs, candidate, action, false, &mut |_| {})
+}
+
+/// [`apply_action`], calling `freed` with the bytes each removed file gave back as it
+/// goes. A file with other hard links left frees nothing. Moving to the trash and native
+/// clean commands report nothing.
+///
+/// Removal goes through the [`extended`] form of the path, so entries deeper than
+/// Windows' 260-character limit are reached too.
+///
+/// With `force` (`--force`), an entry Unix refuses to remove gets owner write permission
+/// on its folder and is tried again. Windows always clears the read-only attribute instead.
+pub fn apply_action_with_progress(fs: &dyn FileSystem, candidate: &CandidateDir, action: Action, force: bool, freed: &mut dyn FnMut(u64)) -> io::Result<()> {
+    let path = extended(&candidate.path);
+    match action {
+        Action::Delete if candidate.is_file => {
+            remove_or_retry(fs, &path, false, force)?;
+            freed(candidate.size);
+            Ok(())
+        }
+        Action::Delete => remove_entry(fs, &path, force, freed),
+        Action::Trash => trash::move_to_trash(&candidate.path),
+        Action::Partial => partial_targets(fs, candidate).iter().try_for_each(|cache| remove_entry(fs, cache, force, freed)),
+        Action::Native => run_native_clean(&candidate.path),
+        Action::KeepDir => remove_contents(fs, &path, force, freed),
+    }
+}
+
+/// Deletes a candidate and records the outcome, so one that failed is offered again by
+/// `--retry-failed` until an attempt succeeds.
+pub fn delete_recorded(fs: &dyn FileSystem, candidate: &CandidateDir, failures: &mut FailureLog) -> io::Result<()> {
+    apply_recorded(fs, candidate, Action::Delete, failures)
+}
+
+/// [`delete_recorded`] for any action.
+pub fn apply_recorded(fs: &dyn FileSystem, candidate: &CandidateDir, action: Action, failures: &mut FailureLog) -> io::Result<()> {
+    let removed = apply_action_with_progress(fs, candidate, action, false, &mut |_| {});
+    match &removed {
+        Ok(()) => failures.record_success(&candidate.path),
+        Err(e) => failures.record_failure(&candidate.path, e),
+    }
+    removed
+}
+
+/// Removes a file, a folder with everything in it, or only the link if `path` is one.
+///
+/// Folders are emptied bottom-up one file at a time, so progress can be shown within a
+/// large one. The first failure stops the removal, leaving the rest of the tree as it was.
+fn remove_entry(fs: &dyn FileSystem, path: &Path, force: bool, freed: &mut dyn FnMut(u64)) -> io::Result<()> {
+    let metadata = fs.symlink_metadata(path)?;
+    if is_link_entry(metadata.file_type(), &metadata) {
+        return remove_link(fs, path);
+    }
+    if metadata.is_dir() {
+        remove_contents(fs, path, force, freed)?;
+        return remove_or_retry(fs, path, true, force);
+    }
+    // Read before unlinking, while the other names still count.
+    let last_name = hard_link_id(path, &metadata).is_none();
+    remove_or_retry(fs, path, false, force)?;
+    freed(if last_name { metadata.len() } else { 0 });
+    Ok(())
+}
+
+/// Removes everything inside the folder at `path`, in name order.
+fn remove_contents(fs: &dyn FileSystem, path: &Path, force: bool, freed: &mut dyn FnMut(u64)) -> io::Result<()> {
+    let listing = match fs.read_dir(path) {
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied && make_listable(fs, path, force) => fs.read_dir(path),
+        listing => listing,
+    };
+    let mut entries = listing?.map(|entry| entry.map(|e| e.path())).collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+    entries.iter().try_for_each(|entry| remove_entry(fs, entry, force, freed))
+}
+
+/// Removes a file, or an empty folder with `is_dir`. When that is denied and the entry
+/// can be made removable, it is tried once more; a second denial names the entry, so the
+/// failure summary says which one held the folder back.
+fn remove_or_retry(fs: &dyn FileSystem, path: &Path, is_dir: bool, force: bool) -> io::Result<()> {
+    let remove = || if is_dir { fs.remove_dir(path) } else { fs.remove_file(path) };
+    match remove() {
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied && make_removable(fs, path, force) => remove().map_err(|e| {
+            io::Error::new(e.kind(), format!("{} at {}, even after making it writable", crate::failures::describe_error(&e), path.display()))
+        }),
+        removed => removed,
+    }
+}
+
+/// Clears the read-only attribute, which Windows won't delete through; whether it was set.
+#[cfg(windows)]
+fn make_removable(fs: &dyn FileSystem, path: &Path, _force: bool) -> bool {
+    let Ok(metadata) = fs.symlink_metadata(path) else { return false };
+    let mut permissions = metadata.permissions();
+    if !permissions.readonly() {
+        return false;
+    }
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs.set_permissions(path, permissions).is_ok()
+}
+
+/// Unix decides by the folder an entry is in; with `force`, gives its owner write
+/// permission there. Whether anything changed.
+#[cfg(unix)]
+fn make_removable(fs: &dyn FileSystem, path: &Path, force: bool) -> bool {
+    path.parent().is_some_and(|parent| force && add_owner_mode(fs, parent, 0o300))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn make_removable(_fs: &dyn FileSystem, _path: &Path, _force: bool) -> bool {
+    false
+}
+
+/// With `force`, lets the owner list a folder Unix won't open, so it can be emptied.
+#[cfg(unix)]
+fn make_listable(fs: &dyn FileSystem, path: &Path, force: bool) -> bool {
+    force && add_owner_mode(fs, path, 0o700)
+}
+
+#[cfg(not(unix))]
+fn make_listable(_fs: &dyn FileSystem, _path: &Path, _force: bool) -> bool {
+    false
+}
+
+/// Adds the `bits` of the owner's permissions that `dir` lacks; whether there were any.
+#[cfg(unix)]
+fn add_owner_mode(fs: &dyn FileSystem, dir: &Path, bits: u32) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    let Ok(metadata) = fs.symlink_metadata(dir) else { return false };
+    let mode = metadata.permissions().mode();
+    if mode & bits == bits {
+        return false;
+    }
+    fs.set_permissions(dir, std::fs::Permissions::from_mode(mode | bits)).is_ok()
+}
+
+fn run_native_clean(path: &Path) -> io::Result<()> {
+    let command = native_clean(dir_name(path), path).ok_or_else(|| io::Error::other("no native clean command for this folder"))?;
+    let project = path.parent().unwrap_or(path);
+    let output = exec::run(Command::new(command[0]).args(&command[1..]).current_dir(project), NATIVE_CLEAN_TIMEOUT)
+        .map_err(|e| io::Error::other(format!("{}: {}", command.join(" "), e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr.lines().rfind(|line| !line.trim().is_empty()).unwrap_or("no output");
+        return Err(io::Error::other(format!("{} failed ({}): {}", command.join(" "), output.status, reason.trim())));
+    }
+    Ok(())
+}
+
+fn dir_name(path: &Path) -> &str {
+    path.file_name().and_then(|n| n.to_str()).unwrap_or("")
+}
+
+/// Directory symlinks and junctions are directories to Windows; everywhere else a link is a file.
+#[cfg(windows)]
+fn remove_link(fs: &dyn FileSystem, path: &Path) -> io::Result<()> {
+    fs.remove_dir(path).or_else(|_| fs.remove_file(path))
+}
+
+#[cfg(not(windows))]
+fn remove_link(fs: &dyn FileSystem, path: &Path) -> io::Result<()> {
+    fs.remove_file(path)
+}
@@ -0,0 +1,70 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The human-readable report sections of a run: printed as they happen and, with
+/// `--output`, also collected as plain text and written to a file at the end.
+///
+/// Prompts and progress bars don't go through here, so they never end up in the file.
+#[derive(Debug, Default)]
+pub struct Report {
+    output: Option<PathBuf>,
+    lines: Vec<String>,
+    /// Print to stderr, keeping stdout for machine-readable output.
+    to_stderr: bool,
+}
+
+impl Report {
+    pub fn new(output: Option<PathBuf>) -> Self {
+        Report { output, lines: Vec::new(), to_stderr: false }
+    }
+
+    /// Prints report lines to stderr from now on.
+    pub fn use_stderr(&mut self) {
+        self.to_stderr = true;
+    }
+
+    /// Prints a report line and records it without styling.
+    pub fn line(&mut self, text: impl AsRef<str>) {
+        match self.to_stderr {
+            true => eprintln!("{}", text.as_ref()),
+            false => println!("{}", text.as_ref()),
+        }
+        self.record(text);
+    }
+
+    /// Whether lines are being collected for `--output`, so callers can skip formatting
+    /// ones that are only recorded.
+    pub fn records(&self) -> bool {
+        self.output.is_some()
+    }
+
+    /// Records a line that was already shown some other way, e.g. above a progress bar.
+    pub fn record(&mut self, text: impl AsRef<str>) {
+        if self.output.is_some() {
+            self.lines.push(console::strip_ansi_codes(text.as_ref()).into_owned());
+        }
+    }
+
+    /// Writes the collected lines to the output file, if one was requested.
+    pub fn finish(&self) -> io::Result<()> {
+        match &self.output {
+            Some(path) => write_atomically(path, &self.lines),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Writes next to `path` and renames over it, so readers never see a half-written report.
+fn write_atomically(path: &Path, lines: &[String]) -> io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    let mut file = io::BufWriter::new(fs::File::create(&tmp)?);
+    for line in lines {
+        writeln!(file, "{}", line)?;
+    }
+    file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    fs::rename(&tmp, path)
+}
@@ -0,0 +1,102 @@
+use crate::units::unix_secs;
+use crate::CandidateDir;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Sizes may drift this much (as a fraction) before a saved selection no longer applies.
+pub const MAX_SIZE_DRIFT: f64 = 0.1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedItem {
+    pub path: PathBuf,
+    pub size: u64,
+    pub checked: bool,
+}
+
+/// The checkboxes of a selection that was never carried out, kept for the next run in
+/// the same root.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSelection {
+    pub root: PathBuf,
+    pub saved_at: u64,
+    pub items: Vec<SavedItem>,
+}
+
+impl SavedSelection {
+    pub fn new(root: &Path, candidates: &[CandidateDir], checked: &[bool]) -> Self {
+        SavedSelection {
+            root: root.to_path_buf(),
+            saved_at: unix_secs(SystemTime::now()),
+            items: candidates
+                .iter()
+                .zip(checked)
+                .map(|(c, &checked)| SavedItem { path: c.path.clone(), size: c.size, checked })
+                .collect(),
+        }
+    }
+
+    pub fn selected_count(&self) -> usize {
+        self.items.iter().filter(|i| i.checked).count()
+    }
+
+    /// The saved checkbox for each candidate, or `None` when the candidates changed too much
+    /// for it to mean the same thing: another root, saved paths missing, or sizes drifted.
+    /// Candidates that are new since then keep their entry in `defaults`.
+    pub fn restore(&self, root: &Path, candidates: &[CandidateDir], defaults: &[bool]) -> Option<Vec<bool>> {
+        if self.root != root {
+            return None;
+        }
+        let mut checked = defaults.to_vec();
+        for item in &self.items {
+            let idx = candidates.iter().position(|c| c.path == item.path)?;
+            let drift = (candidates[idx].size as f64 - item.size as f64).abs() / (item.size.max(1) as f64);
+            if drift > MAX_SIZE_DRIFT {
+                return None;
+            }
+            checked[idx] = item.checked;
+        }
+        Some(checked)
+    }
+}
+
+/// The saved selections, at most one per root, all in one file so a run in one root
+/// never reads or clears another's.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SavedSelections {
+    pub selections: Vec<SavedSelection>,
+}
+
+impl SavedSelections {
+    /// Reads `file`; a missing or unreadable one (including the single selection older
+    /// versions wrote) holds nothing.
+    pub fn load(file: &Path) -> Self {
+        fs::File::open(file).ok().and_then(|f| serde_json::from_reader(f).ok()).unwrap_or_default()
+    }
+
+    pub fn save(&self, file: &Path) -> io::Result<()> {
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        serde_json::to_writer(fs::File::create(file)?, self).map_err(io::Error::other)
+    }
+
+    pub fn get(&self, root: &Path) -> Option<&SavedSelection> {
+        self.selections.iter().find(|s| s.root == root)
+    }
+
+    /// Keeps `selection`, replacing whatever was saved for its root.
+    pub fn put(&mut self, selection: SavedSelection) {
+        self.remove(&selection.root);
+        self.selections.push(selection);
+    }
+
+    /// Drops the selection saved for `root`; false when there was none.
+    pub fn remove(&mut self, root: &Path) -> bool {
+        let before = self.selections.len();
+        self.selections.retain(|s| s.root != root);
+        self.selections.len() != before
+    }
+}
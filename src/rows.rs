@@ -0,0 +1,218 @@
+//! The selection list's rows and detail lines, formatted from the candidates only when
+//! they are drawn, for the terminal width at that moment.
+
+use crate::detect::{deletion_warning, shows_ecosystem};
+use crate::display::{badge_explanations, badges, fit_row, PathDisplay};
+use crate::failures::FailedDeletion;
+use crate::gitignore::repository_of;
+use crate::projects::{detect, Risk};
+use crate::severity::Severity;
+use crate::snapshot::Change;
+use crate::ui::{RenderRow, Rescan};
+use crate::units::{age_of, format_age, format_timestamp};
+use crate::CandidateDir;
+use human_bytes::human_bytes;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// Workspace members named in the details before the rest are summed up as "…".
+const MAX_MEMBERS_SHOWN: usize = 5;
+
+pub struct CandidateRows {
+    /// Shared with the caller, which sees a rescanned candidate's new measurements.
+    candidates: Rc<RefCell<Vec<CandidateDir>>>,
+    /// Candidates measured again by a rescan, until the list takes them in.
+    remeasured: Arc<Mutex<HashMap<usize, CandidateDir>>>,
+    paths: PathDisplay,
+    severity: Severity,
+    unicode: bool,
+    /// Show how long each project has been idle.
+    pub show_idle: bool,
+    /// Shown in brackets before a path, e.g. its volume when grouping by volume.
+    pub labels: HashMap<PathBuf, String>,
+    /// How each candidate compares to the previous scan, with `--diff-previous`.
+    pub changes: HashMap<PathBuf, Change>,
+    /// Earlier failed attempts to delete a candidate.
+    pub failed: HashMap<PathBuf, FailedDeletion>,
+    /// Candidates inside a git repository that doesn't ignore them.
+    pub not_ignored: HashSet<PathBuf>,
+    /// Candidates modified so recently that a build may still be writing to them.
+    pub active: HashSet<PathBuf>,
+}
+
+impl CandidateRows {
+    pub fn new(candidates: Rc<RefCell<Vec<CandidateDir>>>, paths: PathDisplay, severity: Severity, unicode: bool) -> Self {
+        CandidateRows {
+            candidates,
+            remeasured: Arc::default(),
+            paths,
+            severity,
+            unicode,
+            show_idle: false,
+            labels: HashMap::new(),
+            changes: HashMap::new(),
+            failed: HashMap::new(),
+            not_ignored: HashSet::new(),
+            active: HashSet::new(),
+        }
+    }
+
+    /// The path as shown, with its label, and everything shown after it.
+    fn parts(&self, c: &CandidateDir) -> (String, String) {
+        let severity = &self.severity;
+        let mut details = vec![match (c.physical_size, c.is_incomplete()) {
+            _ if c.size_unknown => "size unknown".to_string(),
+            (_, true) => format!("≥ {} (incomplete)", severity.size(c.size, &human_bytes(c.size as f64))),
+            (Some(physical), false) => format!("logical {} / will free ≈{}", human_bytes(c.size as f64), severity.size(physical, &human_bytes(physical as f64))),
+            (None, false) => severity.size(c.size, &human_bytes(c.size as f64)),
+        }];
+        // How long ago it was modified, so an abandoned project stands out from today's.
+        details.push(match c.modified {
+            Some(t) => severity.age(age_of(t), &format_age(age_of(t))),
+            None => "?".to_string(),
+        });
+        if c.is_file {
+            details.push("file".to_string());
+        }
+        let name = dir_name(&c.path);
+        if let Some(project) = c.project.filter(|_| shows_ecosystem(name)) {
+            details.push(project.to_string());
+        }
+        match self.changes.get(&c.path) {
+            Some(Change::New) => details.push("new".to_string()),
+            Some(Change::Grown(by)) => details.push(format!("grown +{}", human_bytes(*by as f64))),
+            Some(Change::Known) => details.push("known".to_string()),
+            None => {}
+        }
+        if self.show_idle {
+            details.push(match c.project_modified {
+                Some(t) => severity.age(age_of(t), &format!("idle {}", format_age(age_of(t)))),
+                None => "idle ?".to_string(),
+            });
+        }
+        if let Some(failed) = self.failed.get(&c.path) {
+            details.push(format!("failed last time: {}", failed.error));
+        }
+        let mut suffix = format!(" ({})", details.join(", "));
+        for badge in badges(c, self.unicode) {
+            suffix.push_str("  ");
+            suffix.push_str(badge);
+        }
+        if let Some(root) = &c.workspace {
+            suffix.push_str(&format!("  workspace {}", self.paths.show(root)));
+        }
+        if self.not_ignored.contains(&c.path) {
+            suffix.push_str(if self.unicode { "  ⚠ not gitignored" } else { "  [!] not gitignored" });
+        }
+        if c.risk == Risk::Caution {
+            suffix.push_str(if self.unicode { "  ⚠ caution" } else { "  [!] caution" });
+        }
+        if self.active.contains(&c.path) {
+            suffix.push_str(if self.unicode { "  ⚠ recently modified" } else { "  [!] recently modified" });
+        }
+        let path = match self.labels.get(&c.path) {
+            Some(label) => format!("[{}] {}", label, self.paths.show(&c.path)),
+            None => self.paths.show(&c.path),
+        };
+        (path, suffix)
+    }
+}
+
+impl RenderRow for CandidateRows {
+    fn render(&self, item: usize, width: usize) -> String {
+        let (path, suffix) = self.parts(&self.candidates.borrow()[item]);
+        fit_row(&path, &suffix, width)
+    }
+
+    fn details(&self, item: usize) -> Vec<String> {
+        let candidates = self.candidates.borrow();
+        let c = &candidates[item];
+        let mut lines = vec![
+            format!("Path: {}", c.path.display()),
+            match c.size_unknown {
+                true => "Size: not measured yet (--fast); press r to measure it".to_string(),
+                false => format!("Size: {}{}", if c.is_incomplete() { "≥ " } else { "" }, human_bytes(c.size as f64)),
+            },
+        ];
+        if c.is_incomplete() {
+            lines.push(format!("Incomplete: {} entries could not be read; their size is unknown and not included", c.unreadable));
+        }
+        if c.links_inside > 0 {
+            lines.push(format!("Links: {} links or junctions inside; their targets are not counted and will not be deleted", c.links_inside));
+        }
+        if let Some(physical) = c.physical_size {
+            lines.push(format!("Will free: ≈{} (shares blocks with clones elsewhere)", human_bytes(physical as f64)));
+        }
+        if c.is_file {
+            lines.push("Type: file".to_string());
+        }
+        match c.modified {
+            Some(t) => lines.push(format!("Last modified: {} ({} ago)", format_timestamp(t), format_age(age_of(t)))),
+            None => lines.push("Last modified: unknown (could not be read)".to_string()),
+        }
+        if !c.serves.is_empty() {
+            let names: Vec<String> = c.serves.iter().take(MAX_MEMBERS_SHOWN).map(|m| self.paths.show(m)).collect();
+            let more = if c.serves.len() > MAX_MEMBERS_SHOWN { ", …" } else { "" };
+            lines.push(format!("Workspace: serves {} packages ({}{})", c.serves.len(), names.join(", "), more));
+        }
+        if let Some(root) = &c.workspace {
+            lines.push(format!("Workspace: a member's folder in the workspace at {}; reinstall from there, and w selects the whole workspace", self.paths.show(root)));
+        }
+        if let Some(t) = c.project_modified {
+            lines.push(format!("Project last changed: {} ({} ago)", format_timestamp(t), format_age(age_of(t))));
+        }
+        let name = dir_name(&c.path);
+        if let Some((detector, marker)) = detect(name, &c.path, c.is_file) {
+            lines.push(format!("Ecosystem: {} (found by {})", detector.kind, marker.describe()));
+        }
+        if let Some(hint) = c.restore_hint() {
+            lines.push(format!("Restore: {}", hint));
+        }
+        if self.not_ignored.contains(&c.path) {
+            let repository = repository_of(&c.path).map(|r| self.paths.show(r)).unwrap_or_default();
+            lines.push(format!("Git: not ignored by the repository at {}; it may be committed on purpose", repository));
+        }
+        if let Some(warning) = deletion_warning(name) {
+            lines.push(format!("Warning: {}", warning));
+        }
+        if c.risk == Risk::Caution {
+            lines.push(format!("Risk: caution; {} folders sometimes hold files put there by hand, so it isn't preselected. Look inside before deleting", c.kind));
+        }
+        if self.active.contains(&c.path) {
+            lines.push("In use: modified moments ago, so a build or install may still be writing to it; not preselected".to_string());
+        }
+        if let Some(failed) = self.failed.get(&c.path) {
+            lines.push(format!("Failed last time: {} ({} attempts)", failed.error, failed.attempts));
+        }
+        for (badge, explanation) in badges(c, self.unicode).into_iter().zip(badge_explanations(c)) {
+            lines.push(format!("{}  {}", badge, explanation));
+        }
+        lines
+    }
+
+    fn rescan(&self, item: usize) -> Option<Rescan> {
+        let candidate = self.candidates.borrow()[item].clone();
+        let remeasured = Arc::clone(&self.remeasured);
+        Some(Box::new(move || {
+            let fresh = candidate.remeasure()?;
+            let size = fresh.reclaimable();
+            remeasured.lock().unwrap_or_else(|e| e.into_inner()).insert(item, fresh);
+            Some(size)
+        }))
+    }
+
+    /// A gone candidate stays in the list as it was, so every index keeps meaning the same
+    /// folder; the selection no longer offers it.
+    fn rescanned(&self, item: usize, _size: Option<u64>) {
+        if let Some(fresh) = self.remeasured.lock().unwrap_or_else(|e| e.into_inner()).remove(&item) {
+            self.candidates.borrow_mut()[item] = fresh;
+        }
+    }
+}
+
+fn dir_name(path: &Path) -> &str {
+    path.file_name().and_then(|n| n.to_str()).unwrap_or("")
+}
@@ -0,0 +1,665 @@
+use crate::detect::{self, custom_target_specs, target_dirs, target_files};
+use crate::glob::{glob_match, path_glob_match};
+use crate::projects::{project_kind, risk_of, ProjectKind, Risk};
+use crate::longpath::extended;
+use crate::limits::retry_exhausted;
+use crate::sizing::SizingPool;
+use crate::volume::{annotate_location, VolumeTable};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use crate::walk::{WalkError, Walker};
+use crate::watchdog::Watchdog;
+use std::time::Duration;
+
+/// A folder, or a single file, that a scan found safe to delete, with what was measured
+/// about it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CandidateDir {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Found by a scan that deferred sizing (`--fast`), so `size` is 0 until
+    /// [`CandidateDir::remeasure`] fills it in.
+    #[serde(default)]
+    pub size_unknown: bool,
+    /// The rule that matched, e.g. `node_modules` or `build-*`.
+    #[serde(default)]
+    pub kind: String,
+    /// The ecosystem a built-in rule found it belongs to; `None` for configured targets,
+    /// and in caches written before it was recorded until [`CandidateDir::ensure_kind`].
+    #[serde(default)]
+    pub project: Option<ProjectKind>,
+    /// Whether it may hold files put there by hand, from the row that found it; set again
+    /// by [`CandidateDir::ensure_kind`] for cached entries.
+    #[serde(default)]
+    pub risk: Risk,
+    /// Last source-file modification in the enclosing project (unix seconds).
+    #[serde(default)]
+    pub project_modified: Option<u64>,
+    /// Newest modification of the candidate or its direct entries (unix seconds); `None`
+    /// when it couldn't be read.
+    #[serde(default)]
+    pub modified: Option<u64>,
+    /// Reached through a symbolic link.
+    #[serde(default)]
+    pub is_link: bool,
+    /// Lives on a network filesystem.
+    #[serde(default)]
+    pub on_network: bool,
+    /// Is itself a mount point.
+    #[serde(default)]
+    pub is_mount_point: bool,
+    /// Lives inside a cloud-synced folder.
+    #[serde(default)]
+    pub cloud_synced: bool,
+    /// A single regenerable file (e.g. `.eslintcache`) rather than a directory.
+    #[serde(default)]
+    pub is_file: bool,
+    /// Symbolic links and junctions inside; their targets are neither counted nor deleted.
+    #[serde(default)]
+    pub links_inside: u64,
+    /// The volume it was found on (see [`crate::volume::volume_id`]), to tell an unplugged
+    /// disk from a deleted folder later.
+    #[serde(default)]
+    pub volume: Option<String>,
+    /// Entries that couldn't be read while sizing, so `size` is only a lower bound.
+    #[serde(default)]
+    pub unreadable: u64,
+    /// What deleting it would free when that differs from `size`, e.g. APFS clones sharing blocks.
+    #[serde(default)]
+    pub physical_size: Option<u64>,
+    /// Member packages, when this is the shared `target` or `node_modules` of a workspace
+    /// (see [`crate::monorepo`]).
+    #[serde(default)]
+    pub serves: Vec<PathBuf>,
+    /// The workspace root, when this is the `node_modules` of one of its member packages.
+    #[serde(default)]
+    pub workspace: Option<PathBuf>,
+    /// Quick snapshot taken when the candidate was sized, to notice later changes cheaply.
+    #[serde(default)]
+    pub fingerprint: Option<Fingerprint>,
+}
+
+/// The modification time and number of direct entries of a candidate.
+///
+/// Both change when something is added or removed at the top level, which is how
+/// builds and installs usually touch these folders, without walking the tree again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub modified: u64,
+    pub entries: u64,
+}
+
+/// How far modification times may drift without counting as a change: filesystems like
+/// FAT and some network shares only keep them to the nearest two seconds.
+const MODIFIED_SLACK_SECS: u64 = 2;
+
+impl Fingerprint {
+    /// `None` when the path is gone or can't be read.
+    pub fn of(path: &Path) -> Option<Self> {
+        Self::read(path).ok()
+    }
+
+    /// Like [`Fingerprint::of`], but says why it couldn't be taken, so a folder that is
+    /// gone (`NotFound`) can be told from one that is only unreadable right now.
+    pub fn read(path: &Path) -> std::io::Result<Self> {
+        let path = extended(path);
+        let metadata = std::fs::symlink_metadata(&path)?;
+        let modified = metadata.modified().map(crate::units::unix_secs).unwrap_or(0);
+        let entries = if metadata.is_dir() { retry_exhausted(|| std::fs::read_dir(&path))?.count() as u64 } else { 0 };
+        Ok(Fingerprint { modified, entries })
+    }
+
+    /// Whether the folder changed enough since `before` to be worth measuring again:
+    /// entries were added or removed, or it was modified beyond timestamp precision.
+    pub fn changed_since(&self, before: &Fingerprint) -> bool {
+        self.entries != before.entries || self.modified.abs_diff(before.modified) > MODIFIED_SLACK_SECS
+    }
+}
+
+impl CandidateDir {
+    pub fn new(path: PathBuf, size: u64) -> Self {
+        let (kind, project) = (kind_of(&path, false), project_kind(&path, false));
+        CandidateDir {
+            fingerprint: Fingerprint::of(&path),
+            modified: crate::activity::last_modified(&path),
+            serves: Vec::new(),
+            workspace: None,
+            physical_size: None,
+            links_inside: 0,
+            unreadable: 0,
+            volume: None,
+            risk: risk_of(&kind, project, false),
+            kind,
+            project,
+            path,
+            size,
+            size_unknown: false,
+            project_modified: None,
+            is_link: false,
+            on_network: false,
+            is_mount_point: false,
+            cloud_synced: false,
+            is_file: false,
+        }
+    }
+
+    /// A single-file candidate.
+    pub fn file(path: PathBuf, size: u64) -> Self {
+        let (kind, project) = (kind_of(&path, true), project_kind(&path, true));
+        CandidateDir { risk: risk_of(&kind, project, true), kind, project, is_file: true, ..CandidateDir::new(path, size) }
+    }
+
+    /// Bytes deleting this candidate is expected to free.
+    pub fn reclaimable(&self) -> u64 {
+        self.physical_size.unwrap_or(self.size)
+    }
+
+    /// Measures it again as it is on disk now, keeping what the scan learned about where
+    /// it lives; `None` when it is gone.
+    pub fn remeasure(&self) -> Option<CandidateDir> {
+        let metadata = std::fs::symlink_metadata(extended(&self.path)).ok()?;
+        let fresh = match self.is_file {
+            true => CandidateDir::file(self.path.clone(), metadata.len()),
+            false => size_candidate(&self.path, |_, _| {}),
+        };
+        Some(CandidateDir {
+            size: fresh.size,
+            size_unknown: false,
+            physical_size: fresh.physical_size,
+            links_inside: fresh.links_inside,
+            unreadable: fresh.unreadable,
+            serves: fresh.serves,
+            modified: fresh.modified,
+            fingerprint: fresh.fingerprint,
+            ..self.clone()
+        })
+    }
+
+    /// Some of it couldn't be read, so its size is a lower bound.
+    pub fn is_incomplete(&self) -> bool {
+        self.unreadable > 0
+    }
+
+    /// The workspace root this belongs to: its own folder when this is the shared folder at
+    /// the root, or the root of the workspace it is a member's folder of.
+    pub fn workspace_root(&self) -> Option<&Path> {
+        match &self.workspace {
+            Some(root) => Some(root),
+            None if !self.serves.is_empty() => self.path.parent(),
+            None => None,
+        }
+    }
+
+    /// What brings it back once deleted, e.g. `npm install`, going by its kind.
+    pub fn restore_hint(&self) -> Option<&'static str> {
+        detect::restore_hint(&self.kind, &self.path)
+    }
+
+    /// Its ecosystem's name, or its kind when no built-in rule knows one.
+    pub fn ecosystem(&self) -> &str {
+        match self.project {
+            Some(project) => project.label(),
+            None => &self.kind,
+        }
+    }
+
+    /// Fills in the kind, ecosystem and risk of entries cached before candidates recorded them.
+    pub fn ensure_kind(&mut self) {
+        if self.kind.is_empty() {
+            self.kind = kind_of(&self.path, self.is_file);
+        }
+        if self.project.is_none() {
+            self.project = project_kind(&self.path, self.is_file);
+        }
+        self.risk = risk_of(&self.kind, self.project, self.is_file);
+    }
+}
+
+fn kind_of(path: &Path, is_file: bool) -> String {
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    detect::target_kind(&name, is_file).map(str::to_string).unwrap_or_else(|| name.into_owned())
+}
+
+/// Options shared by every way of scanning a tree.
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Directory names, or `*`/`?` patterns, that are considered candidates.
+    pub targets: Vec<String>,
+    /// File names or patterns that are considered candidates.
+    pub target_files: Vec<String>,
+    /// Directories (and everything below them) that are never walked.
+    pub excludes: Vec<PathBuf>,
+    /// Glob patterns for paths that are never walked, like `excludes` (see [`path_glob_match`]).
+    pub exclude_patterns: Vec<String>,
+    /// Directory names, or `*`/`?` patterns, never walked into wherever they are, because
+    /// nothing inside them can be a candidate (see [`DEFAULT_PRUNED`]).
+    pub pruned: Vec<String>,
+    /// Maximum depth of the discovery walk; `None` walks the whole tree.
+    pub max_depth: Option<usize>,
+    /// Walk level by level instead of depth-first, so a scan that stops early has
+    /// looked at every part of the tree a little rather than one part completely.
+    pub breadth_first: bool,
+    /// Directories that don't open within this long are skipped with [`ScanError::Slow`];
+    /// `None` waits forever.
+    pub dir_timeout: Option<Duration>,
+    /// Threads sizing candidates while the walk goes on; 1 sizes each one before walking further.
+    pub size_threads: usize,
+    /// Don't size folders at all: they come out with [`CandidateDir::size_unknown`] set.
+    pub defer_sizing: bool,
+}
+
+/// A file in the scan root listing exclude patterns, one per line.
+pub const IGNORE_FILE: &str = ".devpurgeignore";
+
+/// The patterns in the scan root's [`IGNORE_FILE`]; blank lines and `#` comments are
+/// skipped. A missing file means none.
+pub fn ignore_patterns(root: &Path) -> std::io::Result<Vec<String>> {
+    match std::fs::read_to_string(root.join(IGNORE_FILE)) {
+        Ok(text) => Ok(text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Version control stores: often millions of files, none of them ever a candidate.
+pub const DEFAULT_PRUNED: &[&str] = &[".git", ".hg", ".svn"];
+
+/// Generous enough for a spun-down disk or a slow network share to wake up.
+pub const DEFAULT_DIR_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            targets: target_dirs().to_vec(),
+            target_files: target_files().to_vec(),
+            excludes: Vec::new(),
+            exclude_patterns: Vec::new(),
+            pruned: DEFAULT_PRUNED.iter().map(|name| name.to_string()).collect(),
+            max_depth: None,
+            breadth_first: false,
+            dir_timeout: Some(DEFAULT_DIR_TIMEOUT),
+            size_threads: 1,
+            defer_sizing: false,
+        }
+    }
+}
+
+/// What a scan looked for and where, stored with cached results so a later run can tell
+/// whether the cache can answer its question.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanScope {
+    pub root: PathBuf,
+    pub targets: Vec<String>,
+    pub target_files: Vec<String>,
+    pub excludes: Vec<PathBuf>,
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+    #[serde(default)]
+    pub pruned: Vec<String>,
+    pub max_depth: Option<usize>,
+    /// The configured and `--target` rules in effect, which decide what a name finds.
+    #[serde(default)]
+    pub custom_targets: Vec<String>,
+}
+
+impl ScanScope {
+    pub fn new(root: &Path, options: &ScanOptions) -> Self {
+        ScanScope {
+            root: std::path::absolute(root).unwrap_or_else(|_| root.to_path_buf()),
+            targets: options.targets.clone(),
+            target_files: options.target_files.clone(),
+            excludes: options.excludes.clone(),
+            exclude_patterns: options.exclude_patterns.clone(),
+            pruned: options.pruned.clone(),
+            max_depth: options.max_depth,
+            custom_targets: custom_target_specs(),
+        }
+    }
+
+    /// Whether results of a scan with this scope are a superset of what `wanted` would find,
+    /// so `wanted` can be answered by filtering them. The error says what is missing.
+    pub fn answers(&self, wanted: &ScanScope) -> Result<(), String> {
+        if !wanted.root.starts_with(&self.root) {
+            return Err(format!("it covers {}, not {}", self.root.display(), wanted.root.display()));
+        }
+        if let Some(kind) = wanted.targets.iter().chain(&wanted.target_files).find(|k| !self.targets.contains(k) && !self.target_files.contains(k)) {
+            return Err(format!("it was built without '{}' folders", kind));
+        }
+        if let Some(exclude) = self.excludes.iter().find(|e| !wanted.excludes.iter().any(|w| e.starts_with(w))) {
+            return Err(format!("it skipped {}", exclude.display()));
+        }
+        if let Some(pattern) = self.exclude_patterns.iter().find(|p| !wanted.exclude_patterns.contains(p)) {
+            return Err(format!("it skipped paths matching '{}'", pattern));
+        }
+        if let Some(name) = self.pruned.iter().find(|p| !wanted.pruned.contains(p)) {
+            return Err(format!("it didn't look inside '{}' folders", name));
+        }
+        if let Some(extra) = self.custom_targets.iter().find(|t| !wanted.custom_targets.contains(t)) {
+            return Err(format!("it was built with the extra target '{}'", extra));
+        }
+        if let Some(missing) = wanted.custom_targets.iter().find(|t| !self.custom_targets.contains(t)) {
+            return Err(format!("it was built without the target '{}'", missing));
+        }
+        if let Some(cached_depth) = self.max_depth {
+            let offset = wanted.root.components().count() - self.root.components().count();
+            if wanted.max_depth.is_none_or(|depth| depth + offset > cached_depth) {
+                return Err(format!("it only went {} levels deep", cached_depth));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a scan with this scope would have found `candidate`.
+    pub fn includes(&self, candidate: &CandidateDir) -> bool {
+        let path = std::path::absolute(&candidate.path).unwrap_or_else(|_| candidate.path.clone());
+        let Ok(relative) = path.strip_prefix(&self.root) else { return false };
+        let kinds = if candidate.is_file { &self.target_files } else { &self.targets };
+        kinds.contains(&candidate.kind)
+            && !self.excludes.iter().any(|e| path.starts_with(e))
+            && !self.exclude_patterns.iter().any(|p| path_glob_match(p, &path))
+            && !relative.parent().is_some_and(|dirs| dirs.components().any(|c| self.pruned.iter().any(|p| glob_match(p, &c.as_os_str().to_string_lossy()))))
+            && self.max_depth.is_none_or(|depth| relative.components().count() <= depth)
+    }
+}
+
+#[derive(Debug)]
+pub enum ScanError {
+    Walk(walkdir::Error),
+    Io { path: PathBuf, error: std::io::Error },
+    /// The directory didn't open within [`ScanOptions::dir_timeout`]; it and everything
+    /// below it were skipped.
+    Slow { path: PathBuf, timeout: Duration },
+}
+
+impl ScanError {
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            ScanError::Walk(e) => e.path(),
+            ScanError::Io { path, .. } | ScanError::Slow { path, .. } => Some(path),
+        }
+    }
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::Walk(e) => match e.path() {
+                Some(path) => write!(f, "failed to read {}: {}", path.display(), e),
+                None => write!(f, "{}", e),
+            },
+            ScanError::Io { path, error } => write!(f, "failed to read {}: {}", path.display(), error),
+            ScanError::Slow { path, timeout } => {
+                write!(f, "skipped {}: it did not respond within {:.0}s", path.display(), timeout.as_secs_f64())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ScanError::Walk(e) => Some(e),
+            ScanError::Io { error, .. } => Some(error),
+            ScanError::Slow { .. } => None,
+        }
+    }
+}
+
+/// Measures a candidate directory: its size, the links and unreadable entries inside,
+/// and what deleting it would really free.
+pub(crate) fn size_candidate(path: &Path, progress: impl FnMut(u64, u64)) -> CandidateDir {
+    let measured = detect::measure(path, progress);
+    let mut candidate = CandidateDir::new(path.to_path_buf(), measured.bytes);
+    candidate.links_inside = measured.links;
+    candidate.unreadable = measured.unreadable;
+    candidate.physical_size = detect::private_size(path).filter(|&physical| physical < measured.bytes);
+    candidate.serves = crate::monorepo::shared_by(path).map(|workspace| workspace.members).unwrap_or_default();
+    candidate.workspace = crate::monorepo::member_of(path);
+    candidate
+}
+
+/// A candidate directory as found, without measuring it.
+fn unsized_candidate(path: &Path) -> CandidateDir {
+    let mut candidate = CandidateDir::new(path.to_path_buf(), 0);
+    candidate.size_unknown = true;
+    candidate.serves = crate::monorepo::shared_by(path).map(|workspace| workspace.members).unwrap_or_default();
+    candidate.workspace = crate::monorepo::member_of(path);
+    candidate
+}
+
+/// The [`detect::SYSTEM_CACHES`] under `home` that exist, sized; they are found by
+/// location rather than by walking, since they lie outside any scan root.
+pub fn system_caches(home: &Path, mut progress: impl FnMut(&Path, u64, u64)) -> Vec<CandidateDir> {
+    detect::SYSTEM_CACHES
+        .iter()
+        .map(|relative| home.join(relative))
+        .filter(|path| path.is_dir() && path.file_name().and_then(|n| n.to_str()).is_some_and(|name| detect::is_safe_to_delete(name, path)))
+        .map(|path| size_candidate(&path, |bytes, files| progress(&path, bytes, files)))
+        .collect()
+}
+
+/// How many found candidates may wait for sizing before the walk pauses, per thread.
+const QUEUED_PER_THREAD: usize = 4;
+
+type DirCallback = Box<dyn FnMut(&Path)>;
+type SizingCallback = Box<dyn FnMut(&Path, u64, u64)>;
+type StopCheck = Box<dyn Fn() -> bool>;
+
+/// Scans the whole tree under `root` and returns every candidate, sized, in the order
+/// they were found. Entries that couldn't be read are left out; [`ScanIter`] reports them
+/// and can stop early.
+pub fn scan(root: impl AsRef<Path>, options: ScanOptions) -> Vec<CandidateDir> {
+    ScanIter::new(root, options).filter_map(Result::ok).collect()
+}
+
+/// Lazily walks a tree and yields candidates as they are found.
+///
+/// Nothing is read until the iterator is advanced, and matched candidates are
+/// never descended into while everything outside them is. Dropping the iterator
+/// stops the walk and closes any directory handles it still holds.
+///
+/// With [`ScanOptions::size_threads`] above 1, candidates are sized on worker threads
+/// while the walk continues, and still come out in the order they were found; with
+/// [`ScanOptions::defer_sizing`] they aren't sized at all.
+///
+/// ```no_run
+/// use devpurge::{ScanIter, ScanOptions};
+///
+/// // Only walks as far as needed to find five candidates.
+/// let first: Vec<_> = ScanIter::new("/", ScanOptions::default())
+///     .filter_map(Result::ok)
+///     .take(5)
+///     .collect();
+/// assert!(first.len() <= 5);
+/// ```
+pub struct ScanIter {
+    it: Walker,
+    root: PathBuf,
+    options: ScanOptions,
+    on_dir: Option<DirCallback>,
+    on_sizing: Option<SizingCallback>,
+    stop_when: Option<StopCheck>,
+    volumes: Option<VolumeTable>,
+    watchdog: Option<Watchdog>,
+    pool: Option<SizingPool>,
+    walk_done: bool,
+}
+
+/// What the walk turned up next.
+enum Found {
+    Error(ScanError),
+    File(CandidateDir),
+    Dir(PathBuf),
+}
+
+impl ScanIter {
+    pub fn new(root: impl AsRef<Path>, options: ScanOptions) -> Self {
+        ScanIter {
+            it: Walker::new(root.as_ref(), options.max_depth, options.breadth_first),
+            root: root.as_ref().to_path_buf(),
+            watchdog: options.dir_timeout.map(Watchdog::new),
+            pool: (options.size_threads > 1 && !options.defer_sizing).then(|| SizingPool::new(options.size_threads)),
+            walk_done: false,
+            options,
+            on_dir: None,
+            on_sizing: None,
+            stop_when: None,
+            volumes: None,
+        }
+    }
+
+    /// Registers a callback invoked for every directory visited, e.g. to drive a spinner.
+    pub fn on_dir(mut self, callback: impl FnMut(&Path) + 'static) -> Self {
+        self.on_dir = Some(Box::new(callback));
+        self
+    }
+
+    /// Registers a callback receiving `(candidate, bytes, files)` while a large candidate is sized.
+    pub fn on_sizing(mut self, callback: impl FnMut(&Path, u64, u64) + 'static) -> Self {
+        self.on_sizing = Some(Box::new(callback));
+        self
+    }
+
+    /// Ends the scan early once `stop` returns true, e.g. after Ctrl-C. It is asked before
+    /// every entry; candidates still being sized when it does are left out.
+    pub fn stop_when(mut self, stop: impl Fn() -> bool + 'static) -> Self {
+        self.stop_when = Some(Box::new(stop));
+        self
+    }
+
+    fn stopped(&self) -> bool {
+        self.stop_when.as_ref().is_some_and(|stop| stop())
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        if self.options.excludes.iter().any(|e| path.starts_with(e)) {
+            return true;
+        }
+        if self.options.exclude_patterns.is_empty() {
+            return false;
+        }
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        self.options.exclude_patterns.iter().any(|p| path_glob_match(p, &path))
+    }
+
+    fn is_target(&self, name: &str) -> bool {
+        self.options.targets.iter().any(|t| glob_match(t, name))
+    }
+
+    fn is_target_file(&self, name: &str) -> bool {
+        self.options.target_files.iter().any(|t| glob_match(t, name))
+    }
+
+    fn located(&mut self, mut candidate: CandidateDir) -> CandidateDir {
+        annotate_location(&mut candidate, self.volumes.get_or_insert_with(VolumeTable::load));
+        candidate
+    }
+
+    /// Walks on to the next candidate or error, skipping everything below a candidate.
+    fn walk(&mut self) -> Option<Found> {
+        loop {
+            if self.stopped() {
+                return None;
+            }
+            let entry = match self.it.next_entry()? {
+                // Running out of descriptors was waited out and counted by the walk already.
+                Err(WalkError::Walk(e)) => return Some(Found::Error(ScanError::Walk(e))),
+                Err(WalkError::Io { path, error }) => return Some(Found::Error(ScanError::Io { path, error })),
+                Ok(entry) => entry,
+            };
+            let path = entry.path.as_path();
+
+            if entry.file_type.is_file() {
+                let file_name = entry.file_name();
+                if self.is_target_file(&file_name) && detect::is_safe_to_delete_file(&file_name, path) && !self.is_excluded(path) {
+                    let size = std::fs::symlink_metadata(path).map(|m| m.len()).unwrap_or(0);
+                    return Some(Found::File(CandidateDir::file(path.to_path_buf(), size)));
+                }
+                continue;
+            }
+
+            if !entry.file_type.is_dir() {
+                continue;
+            }
+
+            let file_name = entry.file_name();
+            // The root is walked even when its own name is pruned: it was asked for.
+            if (path != self.root && self.options.pruned.iter().any(|p| glob_match(p, &file_name))) || self.is_excluded(path) {
+                self.it.skip_current_dir();
+                continue;
+            }
+
+            if let (Some(watchdog), Some(timeout)) = (self.watchdog.as_mut(), self.options.dir_timeout) {
+                if !watchdog.responds(path) {
+                    self.it.skip_current_dir();
+                    return Some(Found::Error(ScanError::Slow { path: path.to_path_buf(), timeout }));
+                }
+            }
+
+            if let Some(callback) = self.on_dir.as_mut() {
+                callback(path);
+            }
+
+            if self.is_target(&file_name) && detect::is_safe_to_delete(&file_name, path) {
+                self.it.skip_current_dir();
+                return Some(Found::Dir(entry.path));
+            }
+        }
+    }
+}
+
+impl Iterator for ScanIter {
+    type Item = Result<CandidateDir, ScanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.stopped() {
+                return None;
+            }
+            if let Some(pool) = self.pool.as_mut() {
+                let mut ignore = |_: &Path, _, _| {};
+                let progress: &mut dyn FnMut(&Path, u64, u64) = match self.on_sizing.as_mut() {
+                    Some(callback) => callback,
+                    None => &mut ignore,
+                };
+                if let Some(candidate) = pool.take_ready() {
+                    return Some(Ok(self.located(candidate)));
+                }
+                if self.walk_done || pool.in_flight() >= self.options.size_threads * QUEUED_PER_THREAD {
+                    if !pool.wait(progress) {
+                        return None;
+                    }
+                    continue;
+                }
+                pool.poll(progress);
+            }
+
+            let Some(found) = self.walk() else {
+                // Sized one by one, nothing is left to hand out; otherwise drain the pool.
+                self.pool.as_ref()?;
+                self.walk_done = true;
+                continue;
+            };
+            match (found, self.pool.as_mut()) {
+                (Found::Error(e), _) => return Some(Err(e)),
+                (Found::File(candidate), Some(pool)) => pool.push(candidate),
+                (Found::Dir(path), Some(pool)) => pool.submit(path),
+                (Found::File(candidate), None) => return Some(Ok(self.located(candidate))),
+                (Found::Dir(path), None) if self.options.defer_sizing => return Some(Ok(self.located(unsized_candidate(&path)))),
+                (Found::Dir(path), None) => {
+                    let candidate = match self.on_sizing.as_mut() {
+                        Some(callback) => size_candidate(&path, |bytes, files| callback(&path, bytes, files)),
+                        None => size_candidate(&path, |_, _| {}),
+                    };
+                    return Some(Ok(self.located(candidate)));
+                }
+            }
+        }
+    }
+}
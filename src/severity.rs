@@ -0,0 +1,51 @@
+use console::style;
+use std::time::Duration;
+
+pub const DEFAULT_SIZE_THRESHOLDS: [u64; 3] = [100 << 20, 1 << 30, 10 << 30];
+pub const DEFAULT_AGE_THRESHOLDS: [Duration; 2] = [Duration::from_secs(7 * 86_400), Duration::from_secs(90 * 86_400)];
+
+/// Colors sizes by magnitude and ages by staleness so the big, old entries stand out.
+///
+/// Styling goes through `console`, which drops it for NO_COLOR and non-terminal output.
+#[derive(Debug, Clone, Copy)]
+pub struct Severity {
+    /// Sizes below the first are green, below the second yellow, below the third red
+    /// and anything larger bold red.
+    pub size: [u64; 3],
+    /// Ages below the first are dimmed; at or above the second they are green.
+    pub age: [Duration; 2],
+}
+
+impl Default for Severity {
+    fn default() -> Self {
+        Severity { size: DEFAULT_SIZE_THRESHOLDS, age: DEFAULT_AGE_THRESHOLDS }
+    }
+}
+
+impl Severity {
+    /// `text` styled for a size of `bytes`.
+    pub fn size(&self, bytes: u64, text: &str) -> String {
+        let styled = style(text);
+        let styled = if bytes < self.size[0] {
+            styled.green()
+        } else if bytes < self.size[1] {
+            styled.yellow()
+        } else if bytes < self.size[2] {
+            styled.red()
+        } else {
+            styled.red().bold()
+        };
+        styled.to_string()
+    }
+
+    /// `text` styled for something last touched `age` ago.
+    pub fn age(&self, age: Duration, text: &str) -> String {
+        if age < self.age[0] {
+            style(text).dim().to_string()
+        } else if age >= self.age[1] {
+            style(text).green().to_string()
+        } else {
+            text.to_string()
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Sizes candidates on worker threads while the walk goes on, handing them back in the
+//! order they were found so results don't depend on which worker finished first.
+
+use crate::limits::throttled;
+use crate::scanner::{size_candidate, CandidateDir};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+struct Job {
+    seq: u64,
+    path: PathBuf,
+}
+
+enum Event {
+    Progress { path: PathBuf, bytes: u64, files: u64 },
+    Done { seq: u64, candidate: CandidateDir },
+}
+
+pub(crate) struct SizingPool {
+    threads: usize,
+    /// Started with the first directory to size.
+    workers: Option<(Sender<Job>, Receiver<Event>)>,
+    /// Sequence number of the next candidate found, and of the next one handed out.
+    next_seq: u64,
+    next_out: u64,
+    finished: BTreeMap<u64, CandidateDir>,
+}
+
+impl SizingPool {
+    pub fn new(threads: usize) -> Self {
+        SizingPool { threads, workers: None, next_seq: 0, next_out: 0, finished: BTreeMap::new() }
+    }
+
+    /// Candidates found but not handed out yet, sized or not.
+    pub fn in_flight(&self) -> usize {
+        (self.next_seq - self.next_out) as usize
+    }
+
+    /// Queues a directory to be sized by the next free worker.
+    pub fn submit(&mut self, path: PathBuf) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let threads = self.threads;
+        let (jobs, _) = self.workers.get_or_insert_with(|| spawn_workers(threads));
+        if let Err(mpsc::SendError(job)) = jobs.send(Job { seq, path }) {
+            // Every worker is gone (one panicked); size it here instead.
+            self.finished.insert(seq, size_candidate(&job.path, |_, _| {}));
+        }
+    }
+
+    /// Queues a candidate that needs no sizing, keeping its place in the order.
+    pub fn push(&mut self, candidate: CandidateDir) {
+        self.finished.insert(self.next_seq, candidate);
+        self.next_seq += 1;
+    }
+
+    /// The next candidate in the order they were found, once it has been sized.
+    pub fn take_ready(&mut self) -> Option<CandidateDir> {
+        let candidate = self.finished.remove(&self.next_out)?;
+        self.next_out += 1;
+        Some(candidate)
+    }
+
+    /// Handles whatever the workers reported since the last call, without waiting.
+    pub fn poll(&mut self, progress: &mut dyn FnMut(&Path, u64, u64)) {
+        while let Some(event) = self.workers.as_ref().and_then(|(_, events)| events.try_recv().ok()) {
+            self.handle(event, progress);
+        }
+    }
+
+    /// Waits for the next report from a worker. `false` when nothing is being sized.
+    pub fn wait(&mut self, progress: &mut dyn FnMut(&Path, u64, u64)) -> bool {
+        if self.in_flight() == self.finished.len() {
+            return false;
+        }
+        match self.workers.as_ref().map(|(_, events)| events.recv()) {
+            Some(Ok(event)) => {
+                self.handle(event, progress);
+                true
+            }
+            // Every worker is gone, so the rest will never be sized.
+            _ => false,
+        }
+    }
+
+    fn handle(&mut self, event: Event, progress: &mut dyn FnMut(&Path, u64, u64)) {
+        match event {
+            Event::Progress { path, bytes, files } => progress(&path, bytes, files),
+            Event::Done { seq, candidate } => {
+                self.finished.insert(seq, candidate);
+            }
+        }
+    }
+}
+
+fn spawn_workers(threads: usize) -> (Sender<Job>, Receiver<Event>) {
+    let (jobs, incoming) = mpsc::channel::<Job>();
+    let (events_tx, events) = mpsc::channel();
+    let incoming = Arc::new(Mutex::new(incoming));
+    for _ in 0..threads {
+        let (incoming, events) = (incoming.clone(), events_tx.clone());
+        thread::spawn(move || loop {
+            // The lock is only held while taking a job, not while sizing it.
+            let job = match incoming.lock() {
+                Ok(receiver) => receiver.recv(),
+                Err(_) => return,
+            };
+            let Ok(Job { seq, path }) = job else { return };
+            // Fewer at once after one ran out of descriptors, so the others can finish.
+            let candidate = throttled(|| {
+                size_candidate(&path, |bytes, files| {
+                    let _ = events.send(Event::Progress { path: path.clone(), bytes, files });
+                })
+            });
+            if events.send(Event::Done { seq, candidate }).is_err() {
+                return;
+            }
+        });
+    }
+    (jobs, events)
+}
@@ -0,0 +1,110 @@
+//! What earlier scans of a root found, so a run can say what changed since the last one.
+//!
+//! Each root has its own file in the snapshot directory holding its two most recent
+//! scans: a run loaded from the cache still has the scan before the cached one to
+//! compare against.
+
+use crate::history::checksum;
+use crate::CandidateDir;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Scans kept per root.
+const KEPT: usize = 2;
+/// Growth below this fraction of the earlier size still counts as the same folder.
+const GROWN_THRESHOLD: f64 = 0.1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// The candidates one scan found, with when it ran (unix seconds).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub taken: u64,
+    pub entries: Vec<SnapshotEntry>,
+}
+
+impl Snapshot {
+    pub fn new(taken: u64, candidates: &[CandidateDir]) -> Self {
+        let entries = candidates.iter().map(|c| SnapshotEntry { path: c.path.clone(), size: c.size }).collect();
+        Snapshot { taken, entries }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RootSnapshots {
+    root: PathBuf,
+    scans: Vec<Snapshot>,
+}
+
+fn snapshot_file(dir: &Path, root: &Path) -> PathBuf {
+    dir.join(format!("{}.json", checksum(root.as_os_str().as_encoded_bytes())))
+}
+
+fn load(dir: &Path, root: &Path) -> RootSnapshots {
+    fs::read(snapshot_file(dir, root))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<RootSnapshots>(&bytes).ok())
+        .filter(|stored| stored.root == root)
+        .unwrap_or_else(|| RootSnapshots { root: root.to_path_buf(), scans: Vec::new() })
+}
+
+/// Records a scan of `root`, dropping all but the most recent ones.
+pub fn record_snapshot(dir: &Path, root: &Path, snapshot: Snapshot) -> io::Result<()> {
+    let mut stored = load(dir, root);
+    stored.scans.retain(|s| s.taken != snapshot.taken);
+    stored.scans.push(snapshot);
+    stored.scans.sort_by_key(|s| s.taken);
+    let excess = stored.scans.len().saturating_sub(KEPT);
+    stored.scans.drain(..excess);
+
+    fs::create_dir_all(dir)?;
+    let file = snapshot_file(dir, root);
+    let tmp = file.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec(&stored).map_err(io::Error::other)?)?;
+    fs::rename(&tmp, &file)
+}
+
+/// The most recent scan of `root` taken before `before`.
+pub fn previous_snapshot(dir: &Path, root: &Path, before: u64) -> Option<Snapshot> {
+    load(dir, root).scans.into_iter().filter(|s| s.taken < before).max_by_key(|s| s.taken)
+}
+
+/// How a candidate compares to the previous scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    New,
+    /// Grew by this many bytes.
+    Grown(u64),
+    Known,
+}
+
+impl Change {
+    /// New folders first, then grown ones, then the rest.
+    pub fn rank(self) -> u8 {
+        match self {
+            Change::New => 0,
+            Change::Grown(_) => 1,
+            Change::Known => 2,
+        }
+    }
+}
+
+/// The change of every candidate (in order) and the earlier entries no longer found.
+pub fn diff<'a>(previous: &'a Snapshot, candidates: &[CandidateDir]) -> (Vec<Change>, Vec<&'a SnapshotEntry>) {
+    let changes = candidates
+        .iter()
+        .map(|c| match previous.entries.iter().find(|e| e.path == c.path) {
+            None => Change::New,
+            Some(e) if c.size as f64 > e.size as f64 * (1.0 + GROWN_THRESHOLD) => Change::Grown(c.size - e.size),
+            Some(_) => Change::Known,
+        })
+        .collect();
+    let gone = previous.entries.iter().filter(|e| !candidates.iter().any(|c| c.path == e.path)).collect();
+    (changes, gone)
+}
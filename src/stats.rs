@@ -0,0 +1,73 @@
+//! Lifetime totals, updated under an exclusive lock so parallel runs add up.
+
+use crate::history::{RunRecord, Totals};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub runs: u64,
+    pub folders_deleted: u64,
+    pub bytes_reclaimed: u64,
+    pub first_run: Option<u64>,
+    pub last_run: Option<u64>,
+    pub by_ecosystem: BTreeMap<String, Totals>,
+}
+
+impl Stats {
+    pub fn add(&mut self, run: &RunRecord) {
+        self.runs += 1;
+        self.folders_deleted += run.folders_deleted;
+        self.bytes_reclaimed += run.bytes_reclaimed;
+        self.first_run = Some(self.first_run.map_or(run.timestamp, |first| first.min(run.timestamp)));
+        self.last_run = self.last_run.max(Some(run.timestamp));
+        for (ecosystem, totals) in &run.by_ecosystem {
+            let sum = self.by_ecosystem.entry(ecosystem.clone()).or_default();
+            sum.folders += totals.folders;
+            sum.bytes += totals.bytes;
+        }
+    }
+}
+
+/// Reads the totals; a missing or unreadable file counts as no runs yet.
+pub fn load_stats(file: &Path) -> Stats {
+    read_stats(file).unwrap_or_default()
+}
+
+/// Reads the totals, saying why when the file exists but can't be used; a missing
+/// file is no runs yet.
+pub fn read_stats(file: &Path) -> Result<Stats, String> {
+    match fs::read(file) {
+        Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| format!("{} is damaged ({})", file.display(), e)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Stats::default()),
+        Err(e) => Err(format!("could not read {}: {}", file.display(), e)),
+    }
+}
+
+/// Adds a run to the totals in `file`.
+///
+/// A lock file next to it is held for the whole read-modify-write, so a run finishing
+/// at the same moment waits instead of overwriting this one's update, and the new
+/// totals replace the old ones by rename so a crash never leaves half a file.
+pub fn record_run(file: &Path, run: &RunRecord) -> io::Result<Stats> {
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let lock = OpenOptions::new().write(true).create(true).truncate(false).open(file.with_extension("lock"))?;
+    lock.lock()?;
+
+    let mut stats = load_stats(file);
+    stats.add(run);
+
+    let tmp = file.with_extension("tmp");
+    let mut out = fs::File::create(&tmp)?;
+    out.write_all(&serde_json::to_vec(&stats).map_err(io::Error::other)?)?;
+    out.sync_data()?;
+    fs::rename(&tmp, file)?;
+    lock.unlock()?;
+    Ok(stats)
+}
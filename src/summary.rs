@@ -0,0 +1,64 @@
+//! What a scan found, added up by kind: the table under "Found N folders" and the
+//! `summary` object of `--format json`.
+
+use crate::CandidateDir;
+use serde::Serialize;
+
+/// The candidates of one kind, e.g. every `target` a Rust project made.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KindTotal {
+    pub kind: String,
+    /// The ecosystem that made them; `None` for configured targets. A kind several
+    /// ecosystems use, like `build`, gets a group for each.
+    pub ecosystem: Option<&'static str>,
+    pub folders: usize,
+    pub size: u64,
+    pub reclaimable: u64,
+}
+
+impl KindTotal {
+    /// The name shown for the group, e.g. `target (rust)`.
+    pub fn label(&self) -> String {
+        match self.ecosystem {
+            Some(ecosystem) => format!("{} ({})", self.kind, ecosystem),
+            None => self.kind.clone(),
+        }
+    }
+}
+
+/// The totals of a scan, overall and by kind.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    pub folders: usize,
+    pub size: u64,
+    pub reclaimable: u64,
+    /// Largest first; a kind with a single folder still has its own group.
+    pub by_kind: Vec<KindTotal>,
+}
+
+/// Adds up `candidates` by kind and ecosystem.
+pub fn summarize(candidates: &[CandidateDir]) -> Summary {
+    let mut summary = Summary::default();
+    for candidate in candidates {
+        summary.folders += 1;
+        summary.size += candidate.size;
+        summary.reclaimable += candidate.reclaimable();
+        let ecosystem = candidate.project.map(|project| project.label());
+        match summary.by_kind.iter_mut().find(|group| group.kind == candidate.kind && group.ecosystem == ecosystem) {
+            Some(group) => {
+                group.folders += 1;
+                group.size += candidate.size;
+                group.reclaimable += candidate.reclaimable();
+            }
+            None => summary.by_kind.push(KindTotal {
+                kind: candidate.kind.clone(),
+                ecosystem,
+                folders: 1,
+                size: candidate.size,
+                reclaimable: candidate.reclaimable(),
+            }),
+        }
+    }
+    summary.by_kind.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.label().cmp(&b.label())));
+    summary
+}
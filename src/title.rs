@@ -0,0 +1,59 @@
+use console::Term;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// xterm's title stack: push the current title on start, pop it to restore.
+const PUSH_TITLE: &str = "\x1b[22;0t";
+const POP_TITLE: &str = "\x1b[23;0t";
+
+static TITLE_PUSHED: AtomicBool = AtomicBool::new(false);
+
+/// Owns the terminal title for the duration of a run and restores it on drop.
+pub struct TerminalTitle {
+    term: Option<Term>,
+}
+
+impl TerminalTitle {
+    pub fn new(enabled: bool) -> Self {
+        let term = Term::stdout();
+        if !enabled || !term.is_term() {
+            return TerminalTitle { term: None };
+        }
+        let _ = term.write_str(PUSH_TITLE);
+        TITLE_PUSHED.store(true, Ordering::SeqCst);
+        TerminalTitle { term: Some(term) }
+    }
+
+    pub fn set(&self, title: &str) {
+        if let Some(term) = &self.term {
+            term.set_title(title);
+        }
+    }
+}
+
+impl Drop for TerminalTitle {
+    fn drop(&mut self) {
+        if let Some(term) = &self.term {
+            let _ = term.write_str(POP_TITLE);
+            TITLE_PUSHED.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Pops the title if a run still owns it; only a raw write, so a signal handler can call it.
+#[cfg(unix)]
+pub(crate) fn restore_from_signal() {
+    if TITLE_PUSHED.load(Ordering::SeqCst) {
+        unsafe {
+            libc::write(libc::STDOUT_FILENO, POP_TITLE.as_ptr() as *const libc::c_void, POP_TITLE.len());
+        }
+    }
+}
+
+/// Compact counter for titles, e.g. 42k.
+pub fn short_count(n: usize) -> String {
+    match n {
+        0..=999 => n.to_string(),
+        1_000..=999_999 => format!("{}k", n / 1_000),
+        _ => format!("{:.1}M", n as f64 / 1_000_000.0),
+    }
+}
@@ -0,0 +1,298 @@
+//! Moving candidates to the platform trash instead of deleting them: the freedesktop
+//! trash on Linux and other unix systems, `~/.Trash` on macOS and the Recycle Bin on
+//! Windows. Nothing here ever falls back to deleting; a folder that can't be trashed
+//! stays where it is and the error says why.
+
+use std::io;
+use std::path::Path;
+
+/// Whether this platform has a trash DevPurge knows how to use.
+pub fn supported() -> bool {
+    platform::trash_root().is_some()
+}
+
+/// Checks that there is a trash to move things into, creating its folders if needed,
+/// so `--trash` can refuse up front instead of failing on every folder.
+pub fn check_available() -> Result<(), String> {
+    platform::check_available()
+}
+
+/// Moves `path`, a file or a whole folder, to the trash.
+pub fn move_to_trash(path: &Path) -> io::Result<()> {
+    let path = std::path::absolute(path)?;
+    platform::move_to_trash(&path)
+}
+
+/// Percent-encodes a path for the `Path=` line of a `.trashinfo` file.
+pub fn encode_trash_path(path: &[u8]) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for &byte in path {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use super::encode_trash_path;
+    use std::fs::{self, OpenOptions};
+    use std::io::{self, Write};
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+    use std::path::{Path, PathBuf};
+
+    /// `$XDG_DATA_HOME/Trash`, or `~/.local/share/Trash`.
+    pub fn trash_root() -> Option<PathBuf> {
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| std::env::var_os("HOME").filter(|home| !home.is_empty()).map(|home| Path::new(&home).join(".local/share")))?;
+        Some(data_home.join("Trash"))
+    }
+
+    pub fn check_available() -> Result<(), String> {
+        let root = trash_root().ok_or("no freedesktop trash: neither XDG_DATA_HOME nor HOME is set")?;
+        prepare(&root).map_err(|e| format!("no freedesktop trash at {}: {}", root.display(), e))
+    }
+
+    /// Creates `files` and `info` under a trash directory, private to the user.
+    fn prepare(root: &Path) -> io::Result<()> {
+        for sub in ["files", "info"] {
+            fs::DirBuilder::new().recursive(true).mode(0o700).create(root.join(sub))?;
+        }
+        Ok(())
+    }
+
+    pub fn move_to_trash(path: &Path) -> io::Result<()> {
+        let parent = path.parent().ok_or_else(|| io::Error::other("cannot trash a filesystem root"))?;
+        let device = fs::metadata(parent)?.dev();
+        let home = trash_root().ok_or_else(|| io::Error::other("no freedesktop trash: neither XDG_DATA_HOME nor HOME is set"))?;
+        let home_device = home.ancestors().find_map(|dir| fs::metadata(dir).ok()).map(|m| m.dev());
+
+        // The home trash takes what lives on its own filesystem; anything else goes to the
+        // trash at the top of the filesystem it is on, since trashing must never copy.
+        let (root, info_path) = if home_device == Some(device) {
+            prepare(&home)?;
+            (home, path.to_path_buf())
+        } else {
+            let top = top_dir(parent, device);
+            let root = volume_trash(&top).map_err(|e| io::Error::new(e.kind(), format!("this volume has no usable trash ({})", e)))?;
+            (root, path.strip_prefix(&top).unwrap_or(path).to_path_buf())
+        };
+
+        let name = path.file_name().ok_or_else(|| io::Error::other("cannot trash a path without a name"))?;
+        let (info_file, target) = reserve(&root, name, &info_path)?;
+        if let Err(e) = fs::rename(path, &target) {
+            let _ = fs::remove_file(&info_file);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// The highest ancestor of `dir` still on `device`: the mount point it lives under.
+    fn top_dir(dir: &Path, device: u64) -> PathBuf {
+        let mut top = dir.to_path_buf();
+        for ancestor in dir.ancestors().skip(1) {
+            match fs::metadata(ancestor) {
+                Ok(metadata) if metadata.dev() == device => top = ancestor.to_path_buf(),
+                _ => break,
+            }
+        }
+        top
+    }
+
+    /// `$topdir/.Trash/$uid` when an administrator set up a shared, sticky `.Trash`,
+    /// otherwise `$topdir/.Trash-$uid`.
+    fn volume_trash(top: &Path) -> io::Result<PathBuf> {
+        let uid = unsafe { libc::getuid() };
+        let shared = top.join(".Trash");
+        if let Ok(metadata) = fs::symlink_metadata(&shared) {
+            if metadata.is_dir() && metadata.permissions().mode() & 0o1000 != 0 {
+                let root = shared.join(uid.to_string());
+                if prepare(&root).is_ok() {
+                    return Ok(root);
+                }
+            }
+        }
+        let root = top.join(format!(".Trash-{}", uid));
+        prepare(&root)?;
+        Ok(root)
+    }
+
+    /// Writes the `.trashinfo` file under the first free name, which reserves that name,
+    /// and returns it with where the item goes.
+    fn reserve(root: &Path, name: &std::ffi::OsStr, original: &Path) -> io::Result<(PathBuf, PathBuf)> {
+        let contents = format!(
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            encode_trash_path(original.as_os_str().as_bytes()),
+            local_timestamp()
+        );
+        for n in 1.. {
+            let mut candidate = name.to_os_string();
+            if n > 1 {
+                candidate.push(format!(".{}", n));
+            }
+            let target = root.join("files").join(&candidate);
+            let mut info_name = candidate.clone();
+            info_name.push(".trashinfo");
+            let info = root.join("info").join(info_name);
+            if fs::symlink_metadata(&target).is_ok() {
+                continue;
+            }
+            match OpenOptions::new().write(true).create_new(true).open(&info) {
+                Ok(mut file) => {
+                    file.write_all(contents.as_bytes())?;
+                    return Ok((info, target));
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        unreachable!("the loop only ends by returning")
+    }
+
+    /// Local time as `YYYY-MM-DDThh:mm:ss`, the format the trash spec asks for.
+    fn local_timestamp() -> String {
+        let now = unsafe { libc::time(std::ptr::null_mut()) };
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        unsafe { libc::localtime_r(&now, &mut tm) };
+        format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}", tm.tm_year + 1900, tm.tm_mon + 1, tm.tm_mday, tm.tm_hour, tm.tm_min, tm.tm_sec)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::fs;
+    use std::io;
+    use std::os::unix::fs::MetadataExt;
+    use std::path::{Path, PathBuf};
+
+    pub fn trash_root() -> Option<PathBuf> {
+        std::env::var_os("HOME").filter(|home| !home.is_empty()).map(|home| Path::new(&home).join(".Trash"))
+    }
+
+    pub fn check_available() -> Result<(), String> {
+        match trash_root() {
+            Some(root) if root.is_dir() => Ok(()),
+            Some(root) => Err(format!("no trash folder at {}", root.display())),
+            None => Err("no trash folder: HOME is not set".to_string()),
+        }
+    }
+
+    /// Renames into `~/.Trash` the way Finder names duplicates ("name 2"); items on other
+    /// volumes are refused rather than copied.
+    pub fn move_to_trash(path: &Path) -> io::Result<()> {
+        let root = trash_root().ok_or_else(|| io::Error::other("no trash folder: HOME is not set"))?;
+        let parent = path.parent().ok_or_else(|| io::Error::other("cannot trash a volume root"))?;
+        if fs::metadata(parent)?.dev() != fs::metadata(&root)?.dev() {
+            return Err(io::Error::other("it is on another volume than ~/.Trash"));
+        }
+        let name = path.file_name().ok_or_else(|| io::Error::other("cannot trash a path without a name"))?;
+        let mut target = root.join(name);
+        let mut n = 2;
+        while fs::symlink_metadata(&target).is_ok() {
+            let mut numbered = name.to_os_string();
+            numbered.push(format!(" {}", n));
+            target = root.join(numbered);
+            n += 1;
+        }
+        fs::rename(path, target)
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ffi::c_void;
+    use std::io;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+
+    const FO_DELETE: u32 = 3;
+    const FOF_SILENT: u16 = 0x0004;
+    const FOF_NOCONFIRMATION: u16 = 0x0010;
+    const FOF_ALLOWUNDO: u16 = 0x0040;
+    const FOF_NOERRORUI: u16 = 0x0400;
+
+    #[repr(C)]
+    struct ShFileOpStruct {
+        hwnd: *mut c_void,
+        func: u32,
+        from: *const u16,
+        to: *const u16,
+        flags: u16,
+        any_aborted: i32,
+        name_mappings: *mut c_void,
+        progress_title: *const u16,
+    }
+
+    #[repr(C)]
+    struct ShQueryRbInfo {
+        size: u32,
+        bytes: i64,
+        items: i64,
+    }
+
+    #[link(name = "shell32")]
+    extern "system" {
+        fn SHFileOperationW(op: *mut ShFileOpStruct) -> i32;
+        fn SHQueryRecycleBinW(root: *const u16, info: *mut ShQueryRbInfo) -> i32;
+    }
+
+    pub fn trash_root() -> Option<PathBuf> {
+        Some(PathBuf::new())
+    }
+
+    pub fn check_available() -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Sends `path` to the Recycle Bin. Drives without one (network shares, some USB
+    /// sticks) are refused first, because there the shell would delete permanently.
+    pub fn move_to_trash(path: &Path) -> io::Result<()> {
+        let root = path.ancestors().last().unwrap_or(path);
+        let wide_root: Vec<u16> = root.as_os_str().encode_wide().chain(Some(0)).collect();
+        let mut info = ShQueryRbInfo { size: std::mem::size_of::<ShQueryRbInfo>() as u32, bytes: 0, items: 0 };
+        if unsafe { SHQueryRecycleBinW(wide_root.as_ptr(), &mut info) } != 0 {
+            return Err(io::Error::other(format!("{} has no Recycle Bin", root.display())));
+        }
+
+        // The source list is double-null terminated.
+        let from: Vec<u16> = path.as_os_str().encode_wide().chain([0, 0]).collect();
+        let mut op = ShFileOpStruct {
+            hwnd: std::ptr::null_mut(),
+            func: FO_DELETE,
+            from: from.as_ptr(),
+            to: std::ptr::null(),
+            flags: FOF_ALLOWUNDO | FOF_NOCONFIRMATION | FOF_NOERRORUI | FOF_SILENT,
+            any_aborted: 0,
+            name_mappings: std::ptr::null_mut(),
+            progress_title: std::ptr::null(),
+        };
+        match unsafe { SHFileOperationW(&mut op) } {
+            0 if op.any_aborted == 0 => Ok(()),
+            0 => Err(io::Error::other("moving to the Recycle Bin was aborted")),
+            code => Err(io::Error::other(format!("moving to the Recycle Bin failed (code {:#x})", code))),
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    pub fn trash_root() -> Option<PathBuf> {
+        None
+    }
+
+    pub fn check_available() -> Result<(), String> {
+        Err("this platform has no trash DevPurge can use".to_string())
+    }
+
+    pub fn move_to_trash(_path: &Path) -> io::Result<()> {
+        Err(io::Error::other("this platform has no trash DevPurge can use"))
+    }
+}
@@ -0,0 +1,699 @@
+use console::{Key, Term};
+use human_bytes::human_bytes;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Duration;
+
+use crate::notes::{note_label, MAX_NOTE_CHARS};
+use crate::prompt::{take_resize, wait_for_input, watch_resize};
+
+/// Interrupted key reads in a row that are retried before giving up.
+const MAX_INTERRUPTIONS: u32 = 5;
+
+/// How often the list checks for finished rescans while any are running.
+const RESCAN_POLL: Duration = Duration::from_millis(100);
+
+/// Measures an item again off the UI thread: its new size, or `None` if it is gone.
+pub type Rescan = Box<dyn FnOnce() -> Option<u64> + Send>;
+
+pub enum SelectOutcome {
+    Selected(Vec<usize>),
+    Cancelled,
+    TimedOut,
+}
+
+/// A line of the list: a single item, or the small items of one kind collapsed together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Item(usize),
+    /// A member of an expanded group, drawn indented below it.
+    Member(usize),
+    Group(usize),
+}
+
+struct SmallGroup {
+    kind: String,
+    members: Vec<usize>,
+    expanded: bool,
+}
+
+/// Formats the items of a list when they are drawn, so rows that are never shown are
+/// never formatted and every row fits the terminal as it is at that moment.
+pub trait RenderRow {
+    /// The row for `item`, fitted to `width` columns.
+    fn render(&self, item: usize, width: usize) -> String;
+
+    /// Extra lines shown for the highlighted item when details are toggled on.
+    fn details(&self, _item: usize) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The work `r` runs in the background to measure `item` again; `None` when items
+    /// can't be rescanned.
+    fn rescan(&self, _item: usize) -> Option<Rescan> {
+        None
+    }
+
+    /// Takes in what a rescan of `item` found (`None` if it is gone), before its row is
+    /// drawn again.
+    fn rescanned(&self, _item: usize, _size: Option<u64>) {}
+}
+
+/// Rows formatted up front, for short lists.
+impl RenderRow for Vec<String> {
+    fn render(&self, item: usize, _width: usize) -> String {
+        self[item].clone()
+    }
+}
+
+/// Checkbox list with a pinned header and a live total of the current selection.
+pub struct Selection {
+    pub header: Vec<String>,
+    pub prompt: String,
+    rows: Box<dyn RenderRow>,
+    /// Rows drawn so far, with the width each was fitted to.
+    rendered: RefCell<HashMap<usize, (usize, String)>>,
+    pub sizes: Vec<u64>,
+    pub checked: Vec<bool>,
+    /// Kind of each item; when set, `t` switches to a view toggling whole kinds at once.
+    pub kinds: Vec<String>,
+    /// The workspace each item belongs to, if any; `w` toggles every item of the
+    /// highlighted one's workspace at once.
+    pub workspaces: Vec<Option<String>>,
+    /// Items of one kind smaller than this are collapsed into a single row; `0` disables it.
+    pub small_below: u64,
+    pub page_size: usize,
+    /// Resolve as timed out if no key is pressed before this passes.
+    pub idle_timeout: Option<Duration>,
+    /// Note on each item, shown after its row; `n` edits the highlighted one.
+    pub notes: Vec<Option<String>>,
+    /// The item whose note is being typed, and the text so far.
+    editing: Option<(usize, String)>,
+    /// The actions each item can get, in the order `a` cycles through them. The first is
+    /// the plain one; any other is shown after the row, e.g. `[partial]`.
+    pub actions: Vec<Vec<String>>,
+    /// Index into `actions` of each item's current action.
+    chosen_actions: Vec<usize>,
+    /// Rescans still running, by item.
+    rescanning: HashMap<usize, Receiver<Option<u64>>>,
+    /// Items a rescan found gone; they are no longer listed, selected or counted.
+    removed: Vec<bool>,
+}
+
+impl Selection {
+    pub fn new(items: Vec<String>, sizes: Vec<u64>, checked: Vec<bool>) -> Self {
+        Selection::with_rows(Box::new(items), sizes, checked)
+    }
+
+    /// A list of `sizes.len()` items whose rows `rows` formats as they are drawn.
+    pub fn with_rows(rows: Box<dyn RenderRow>, sizes: Vec<u64>, checked: Vec<bool>) -> Self {
+        Selection {
+            header: Vec::new(),
+            prompt: "Select folders to DELETE (Up/Down to move, Space to toggle, d for details, t for kinds, e to expand, n for a note, a for the action, r to rescan, +/-/i to select all/none/the rest, Enter to confirm)".to_string(),
+            rows,
+            rendered: RefCell::default(),
+            sizes,
+            checked,
+            kinds: Vec::new(),
+            workspaces: Vec::new(),
+            small_below: 0,
+            page_size: 8,
+            idle_timeout: None,
+            notes: Vec::new(),
+            editing: None,
+            actions: Vec::new(),
+            chosen_actions: Vec::new(),
+            rescanning: HashMap::new(),
+            removed: Vec::new(),
+        }
+    }
+
+    pub fn interact(&mut self) -> io::Result<SelectOutcome> {
+        let term = Term::buffered_stdout();
+        if !term.is_term() {
+            return Err(io::Error::other("the selection list needs an interactive terminal"));
+        }
+
+        watch_resize();
+        term.clear_screen()?;
+        term.hide_cursor()?;
+        let result = self.run(&term);
+        // Leave a clean screen behind even when the list failed half-drawn.
+        let restored = term.clear_screen().and_then(|_| term.show_cursor()).and_then(|_| term.flush());
+        let outcome = result?;
+        restored?;
+        Ok(outcome)
+    }
+
+    fn run(&mut self, term: &Term) -> io::Result<SelectOutcome> {
+        let mut cursor = 0usize;
+        let mut offset = 0usize;
+        let mut pressed = false;
+        let mut show_details = false;
+        let kinds = self.kind_names();
+        let mut groups = self.small_groups(&kinds);
+        let mut kind_view = false;
+        let mut kind_cursor = 0usize;
+        let mut size = term.size();
+        let mut interruptions = 0;
+
+        let result = loop {
+            if take_resize() || term.size() != size {
+                size = term.size();
+                term.clear_screen()?;
+            }
+            self.finish_rescans(&mut groups);
+            let rows = visible_rows(self.len(), &groups, &self.removed);
+            cursor = cursor.min(rows.len().saturating_sub(1));
+            if cursor < offset {
+                offset = cursor;
+            } else if cursor >= offset + self.page_size {
+                offset = cursor + 1 - self.page_size;
+            }
+            if kind_view {
+                self.draw_kinds(term, &kinds, kind_cursor)?;
+            } else {
+                self.draw(term, &rows, &groups, cursor, offset, show_details)?;
+            }
+
+            if let (false, Some(timeout)) = (pressed, self.idle_timeout) {
+                if !wait_for_input(timeout) {
+                    break SelectOutcome::TimedOut;
+                }
+            }
+            pressed = true;
+            // Redraw as rescans finish, until a key comes in.
+            if !self.rescanning.is_empty() && !wait_for_input(RESCAN_POLL) {
+                continue;
+            }
+
+            // Raw reads report Ctrl-C as a key, and also a read interrupted by a signal such
+            // as a resize; only the latter leaves the resize flag set.
+            let key = match term.read_key_raw() {
+                Ok(Key::CtrlC) if take_resize() => {
+                    term.clear_screen()?;
+                    continue;
+                }
+                Ok(Key::CtrlC) => break SelectOutcome::Cancelled,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted && interruptions < MAX_INTERRUPTIONS => {
+                    interruptions += 1;
+                    continue;
+                }
+                key => key?,
+            };
+            interruptions = 0;
+            if let Some((item, text)) = self.editing.as_mut() {
+                match key {
+                    Key::Char(c) if !c.is_control() && text.chars().count() < MAX_NOTE_CHARS => text.push(c),
+                    Key::Backspace => {
+                        text.pop();
+                    }
+                    Key::Enter => {
+                        let (item, text) = (*item, text.trim().to_string());
+                        self.set_note(item, (!text.is_empty()).then_some(text));
+                        self.editing = None;
+                    }
+                    Key::Escape => self.editing = None,
+                    _ => {}
+                }
+                continue;
+            }
+            if kind_view {
+                let last = kinds.len().saturating_sub(1);
+                match key {
+                    Key::ArrowUp | Key::Char('k') => kind_cursor = if kind_cursor == 0 { last } else { kind_cursor - 1 },
+                    Key::ArrowDown | Key::Char('j') => kind_cursor = if kind_cursor >= last { 0 } else { kind_cursor + 1 },
+                    Key::Char(' ') => {
+                        if let Some(kind) = kinds.get(kind_cursor) {
+                            self.toggle_kind(kind);
+                        }
+                    }
+                    Key::Char('t') | Key::Escape | Key::Enter => kind_view = false,
+                    Key::Char('q') => break SelectOutcome::Cancelled,
+                    _ => {}
+                }
+                continue;
+            }
+
+            let last = rows.len().saturating_sub(1);
+            match key {
+                Key::ArrowUp | Key::Char('k') => cursor = if cursor == 0 { last } else { cursor - 1 },
+                Key::ArrowDown | Key::Char('j') => cursor = if cursor >= last { 0 } else { cursor + 1 },
+                Key::PageUp => cursor = cursor.saturating_sub(self.page_size),
+                Key::PageDown => cursor = (cursor + self.page_size).min(last),
+                Key::Home => cursor = 0,
+                Key::End => cursor = last,
+                Key::Char(' ') => match rows.get(cursor) {
+                    Some(Row::Item(i) | Row::Member(i)) => self.checked[*i] = !self.checked[*i],
+                    Some(Row::Group(g)) => self.toggle_all(&groups[*g].members),
+                    None => {}
+                },
+                Key::Char('e') | Key::ArrowRight | Key::ArrowLeft => {
+                    let group = match rows.get(cursor) {
+                        Some(Row::Group(g)) => Some(*g),
+                        Some(Row::Member(i)) => groups.iter().position(|g| g.members.contains(i)),
+                        _ => None,
+                    };
+                    if let Some(g) = group {
+                        groups[g].expanded = match key {
+                            Key::ArrowRight => true,
+                            Key::ArrowLeft => false,
+                            _ => !groups[g].expanded,
+                        };
+                        cursor = visible_rows(self.len(), &groups, &self.removed).iter().position(|r| *r == Row::Group(g)).unwrap_or(cursor);
+                    }
+                }
+                Key::Enter => {
+                    break SelectOutcome::Selected(
+                        self.checked.iter().enumerate().filter(|(_, &c)| c).map(|(i, _)| i).collect(),
+                    )
+                }
+                Key::Char('d') => show_details = !show_details,
+                Key::Char('n') => {
+                    if let Some(Row::Item(i) | Row::Member(i)) = rows.get(cursor) {
+                        self.editing = Some((*i, self.note(*i).unwrap_or_default().to_string()));
+                    }
+                }
+                Key::Char('a') => {
+                    if let Some(Row::Item(i) | Row::Member(i)) = rows.get(cursor) {
+                        let count = self.actions.get(*i).map_or(0, Vec::len);
+                        if count > 1 {
+                            self.set_action(*i, (self.action(*i) + 1) % count);
+                        }
+                    }
+                }
+                Key::Char('r') => {
+                    if let Some(Row::Item(i) | Row::Member(i)) = rows.get(cursor) {
+                        self.start_rescan(*i);
+                    }
+                }
+                Key::Char('w') => {
+                    if let Some(Row::Item(i) | Row::Member(i)) = rows.get(cursor) {
+                        self.toggle_workspace(*i);
+                    }
+                }
+                Key::Char('+') => self.select_all(),
+                Key::Char('-') => self.select_none(),
+                Key::Char('i') => self.invert_selection(),
+                Key::Char('t') if !kinds.is_empty() => kind_view = true,
+                Key::Escape | Key::Char('q') => break SelectOutcome::Cancelled,
+                _ => {}
+            }
+        };
+        Ok(result)
+    }
+
+    /// Plain fallback for terminals the interactive list can't drive: prints the items
+    /// numbered and reads the ones to delete as numbers and ranges, e.g. `1,3,5-7`.
+    pub fn interact_numbered(&mut self, mut input: impl BufRead, mut output: impl Write) -> io::Result<SelectOutcome> {
+        for line in &self.header {
+            writeln!(output, "{}", line)?;
+        }
+        for i in 0..self.len() {
+            writeln!(output, "{:>4}. {} {}{}", i + 1, self.check_mark(&[i]), self.row(i, usize::MAX), self.note_suffix(i))?;
+        }
+        loop {
+            write!(output, "Folders to delete, e.g. 1,3,5-7 (Enter keeps the [x] ones, 'all', 'none', 'invert', q to cancel): ")?;
+            output.flush()?;
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 {
+                return Ok(SelectOutcome::Cancelled);
+            }
+            let chosen = match line.trim() {
+                "" => (0..self.len()).filter(|&i| self.checked[i]).collect(),
+                "q" => return Ok(SelectOutcome::Cancelled),
+                "none" => Vec::new(),
+                "all" => (0..self.len()).collect(),
+                "invert" => (0..self.len()).filter(|&i| !self.checked[i]).collect(),
+                answer => match parse_indices(answer, self.len()) {
+                    Ok(chosen) => chosen,
+                    Err(e) => {
+                        writeln!(output, "{}", e)?;
+                        continue;
+                    }
+                },
+            };
+            for (i, checked) in self.checked.iter_mut().enumerate() {
+                *checked = chosen.contains(&i);
+            }
+            return Ok(SelectOutcome::Selected(chosen));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sizes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+
+    /// The row for `item` fitted to `width`; rendered again only when the width changes.
+    pub fn row(&self, item: usize, width: usize) -> String {
+        let mut rendered = self.rendered.borrow_mut();
+        match rendered.get(&item) {
+            Some((fitted, row)) if *fitted == width => row.clone(),
+            _ => {
+                let row = self.rows.render(item, width);
+                rendered.insert(item, (width, row.clone()));
+                row
+            }
+        }
+    }
+
+    pub fn note(&self, item: usize) -> Option<&str> {
+        self.notes.get(item).and_then(|n| n.as_deref())
+    }
+
+    /// Index into `actions` of the item's current action.
+    pub fn action(&self, item: usize) -> usize {
+        self.chosen_actions.get(item).copied().unwrap_or(0)
+    }
+
+    pub fn set_action(&mut self, item: usize, action: usize) {
+        if self.chosen_actions.len() < self.len() {
+            self.chosen_actions.resize(self.len(), 0);
+        }
+        self.chosen_actions[item] = action;
+    }
+
+    /// Whether a rescan found the item gone.
+    pub fn is_removed(&self, item: usize) -> bool {
+        self.removed.get(item).copied().unwrap_or(false)
+    }
+
+    /// Items still listed, in order.
+    fn listed(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len()).filter(|&i| !self.is_removed(i))
+    }
+
+    /// Checks every listed item.
+    pub fn select_all(&mut self) {
+        for i in self.listed().collect::<Vec<_>>() {
+            self.checked[i] = true;
+        }
+    }
+
+    pub fn select_none(&mut self) {
+        self.checked.fill(false);
+    }
+
+    /// Checks the listed items that aren't checked and unchecks the rest.
+    pub fn invert_selection(&mut self) {
+        for i in self.listed().collect::<Vec<_>>() {
+            self.checked[i] = !self.checked[i];
+        }
+    }
+
+    /// The footer line: how many items are checked and what they add up to.
+    pub fn summary(&self) -> String {
+        let selected: Vec<usize> = (0..self.len()).filter(|&i| self.checked[i]).collect();
+        let selected_size: u64 = selected.iter().map(|&i| self.sizes[i]).sum();
+        format!("Selected: {} of {} folders, {}", selected.len(), self.listed().count(), human_bytes(selected_size as f64))
+    }
+
+    /// Measures `item` again on a background thread, unless it already is being measured.
+    fn start_rescan(&mut self, item: usize) {
+        if self.rescanning.contains_key(&item) {
+            return;
+        }
+        let Some(work) = self.rows.rescan(item) else {
+            return;
+        };
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(work());
+        });
+        self.rescanning.insert(item, receiver);
+    }
+
+    /// Takes in finished rescans: an item that is gone leaves the list and the selection,
+    /// and one whose size changed counts with its new size.
+    fn finish_rescans(&mut self, groups: &mut [SmallGroup]) {
+        let items: Vec<usize> = self.rescanning.keys().copied().collect();
+        for item in items {
+            let found = match self.rescanning[&item].try_recv() {
+                Ok(found) => found,
+                Err(TryRecvError::Empty) => continue,
+                // The rescan panicked; the item stays as it was.
+                Err(TryRecvError::Disconnected) => {
+                    self.rescanning.remove(&item);
+                    continue;
+                }
+            };
+            self.rescanning.remove(&item);
+            self.rows.rescanned(item, found);
+            self.rendered.borrow_mut().remove(&item);
+            match found {
+                Some(size) => self.sizes[item] = size,
+                None => {
+                    self.removed.resize(self.len(), false);
+                    self.removed[item] = true;
+                    self.checked[item] = false;
+                    for group in groups.iter_mut() {
+                        group.members.retain(|&m| m != item);
+                    }
+                }
+            }
+        }
+    }
+
+    fn set_note(&mut self, item: usize, note: Option<String>) {
+        if self.notes.len() < self.len() {
+            self.notes.resize(self.len(), None);
+        }
+        self.notes[item] = note;
+    }
+
+    /// The row for `item` in what's left of `width` after `indent` columns and its note.
+    fn fitted_row(&self, item: usize, indent: usize, width: usize) -> String {
+        let note = console::measure_text_width(&self.note_suffix(item));
+        self.row(item, width.saturating_sub(indent + note + 1))
+    }
+
+    /// A placeholder while the item is rescanned, its action unless it is the plain one,
+    /// then its note.
+    fn note_suffix(&self, item: usize) -> String {
+        let mut suffix = if self.rescanning.contains_key(&item) { "  sizing…".to_string() } else { String::new() };
+        if let (chosen, Some(actions)) = (self.action(item), self.actions.get(item)) {
+            if chosen > 0 {
+                suffix.push_str(&format!("  [{}]", actions[chosen]));
+            }
+        }
+        if let Some(note) = self.note(item) {
+            suffix.push_str(&format!("  {}", note_label(note)));
+        }
+        suffix
+    }
+
+    /// Distinct kinds, in order of first appearance.
+    fn kind_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for kind in &self.kinds {
+            if !names.contains(kind) {
+                names.push(kind.clone());
+            }
+        }
+        names
+    }
+
+    /// Checks every item of `kind`, or unchecks them all if they already are.
+    fn toggle_kind(&mut self, kind: &str) {
+        let members: Vec<usize> = self.listed().filter(|&i| self.kinds.get(i).is_some_and(|k| k == kind)).collect();
+        self.toggle_all(&members);
+    }
+
+    fn toggle_workspace(&mut self, item: usize) {
+        let Some(workspace) = self.workspaces.get(item).cloned().flatten() else {
+            return;
+        };
+        let members: Vec<usize> = self.listed().filter(|&i| self.workspaces.get(i).is_some_and(|w| w.as_ref() == Some(&workspace))).collect();
+        self.toggle_all(&members);
+    }
+
+    fn toggle_all(&mut self, members: &[usize]) {
+        let all_checked = members.iter().all(|&i| self.checked[i]);
+        for &i in members {
+            self.checked[i] = !all_checked;
+        }
+    }
+
+    /// Per kind, the items below [`Selection::small_below`], when there are at least two.
+    fn small_groups(&self, kinds: &[String]) -> Vec<SmallGroup> {
+        if self.small_below == 0 {
+            return Vec::new();
+        }
+        kinds
+            .iter()
+            .map(|kind| SmallGroup {
+                kind: kind.clone(),
+                members: (0..self.len()).filter(|&i| &self.kinds[i] == kind && self.sizes[i] < self.small_below).collect(),
+                expanded: false,
+            })
+            .filter(|group| group.members.len() > 1)
+            .collect()
+    }
+
+    fn check_mark(&self, members: &[usize]) -> &'static str {
+        match members.iter().filter(|&&i| self.checked[i]).count() {
+            0 => "[ ]",
+            n if n == members.len() => "[x]",
+            _ => "[-]",
+        }
+    }
+
+    fn draw_header(&self, term: &Term) -> io::Result<()> {
+        term.move_cursor_to(0, 0)?;
+        term.clear_to_end_of_screen()?;
+        for line in &self.header {
+            write_fitted(term, line)?;
+        }
+        if !self.header.is_empty() {
+            write_fitted(term, "")?;
+        }
+        Ok(())
+    }
+
+    fn draw_kinds(&self, term: &Term, kinds: &[String], cursor: usize) -> io::Result<()> {
+        self.draw_header(term)?;
+        write_fitted(term, "Toggle whole kinds (Up/Down to move, Space to toggle, t or Enter to return to folders)")?;
+        for (idx, kind) in kinds.iter().enumerate() {
+            let members: Vec<usize> = self.listed().filter(|&i| &self.kinds[i] == kind).collect();
+            let selected: Vec<usize> = members.iter().copied().filter(|&i| self.checked[i]).collect();
+            let check = match selected.len() {
+                0 => "[ ]",
+                n if n == members.len() => "[x]",
+                _ => "[-]",
+            };
+            let total: u64 = members.iter().map(|&i| self.sizes[i]).sum();
+            let selected_size: u64 = selected.iter().map(|&i| self.sizes[i]).sum();
+            let marker = if idx == cursor { ">" } else { " " };
+            write_fitted(term, &format!(
+                "{} {} {:<16} {}/{} selected, {} of {}",
+                marker,
+                check,
+                kind,
+                selected.len(),
+                members.len(),
+                human_bytes(selected_size as f64),
+                human_bytes(total as f64)
+            ))?;
+        }
+        self.draw_footer(term)?;
+        term.flush()
+    }
+
+    fn draw_footer(&self, term: &Term) -> io::Result<()> {
+        write_fitted(term, &self.summary())
+    }
+
+    fn draw(&self, term: &Term, rows: &[Row], groups: &[SmallGroup], cursor: usize, offset: usize, show_details: bool) -> io::Result<()> {
+        self.draw_header(term)?;
+        write_fitted(term, &self.prompt)?;
+
+        let width = term.size().1 as usize;
+        let end = (offset + self.page_size).min(rows.len());
+        for (idx, row) in rows.iter().enumerate().take(end).skip(offset) {
+            let marker = if idx == cursor { ">" } else { " " };
+            let line = match *row {
+                Row::Item(i) => format!("{} {} {}{}", marker, self.check_mark(&[i]), self.fitted_row(i, 6, width), self.note_suffix(i)),
+                Row::Member(i) => format!("{}     {} {}{}", marker, self.check_mark(&[i]), self.fitted_row(i, 10, width), self.note_suffix(i)),
+                Row::Group(g) => {
+                    let group = &groups[g];
+                    let total: u64 = group.members.iter().map(|&i| self.sizes[i]).sum();
+                    format!(
+                        "{} {} {} small {} folders — {} total {}",
+                        marker,
+                        self.check_mark(&group.members),
+                        group.members.len(),
+                        group.kind,
+                        human_bytes(total as f64),
+                        if group.expanded { "(e to collapse)" } else { "(e to expand)" }
+                    )
+                }
+            };
+            write_fitted(term, &line)?;
+        }
+        if rows.len() > self.page_size {
+            write_fitted(term, &format!("  ({}-{} of {})", offset + 1, end, rows.len()))?;
+        }
+
+        self.draw_footer(term)?;
+
+        if let Some((_, text)) = &self.editing {
+            write_fitted(term, &format!("Note (Enter to save, empty to remove, Esc to cancel): {}_", text))?;
+        }
+
+        if show_details {
+            write_fitted(term, "")?;
+            match rows.get(cursor) {
+                Some(Row::Item(i) | Row::Member(i)) => {
+                    for line in self.rows.details(*i) {
+                        write_fitted(term, &format!("  {}", line))?;
+                    }
+                }
+                Some(Row::Group(g)) => {
+                    for &i in &groups[*g].members {
+                        write_fitted(term, &format!("  {}", self.row(i, width.saturating_sub(2))))?;
+                    }
+                }
+                None => {}
+            }
+        }
+        term.flush()
+    }
+}
+
+/// Parses 1-based numbers and ranges separated by commas or spaces (`1,3 5-7`) into
+/// sorted, distinct 0-based indices below `count`.
+pub fn parse_indices(input: &str, count: usize) -> Result<Vec<usize>, String> {
+    let mut chosen = Vec::new();
+    for part in input.split([',', ' ']).map(str::trim).filter(|p| !p.is_empty()) {
+        let number = |text: &str| match text.trim().parse::<usize>() {
+            Ok(n) if (1..=count).contains(&n) => Ok(n - 1),
+            Ok(n) => Err(format!("{} is not between 1 and {}", n, count)),
+            Err(_) => Err(format!("'{}' is not a number or a range like 5-7", part)),
+        };
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (number(first)?, number(last)?);
+                if first > last {
+                    return Err(format!("range {} runs backwards", part));
+                }
+                chosen.extend(first..=last);
+            }
+            None => chosen.push(number(part)?),
+        }
+    }
+    chosen.sort_unstable();
+    chosen.dedup();
+    Ok(chosen)
+}
+
+/// Writes one line cut to the terminal width, so a narrower terminal doesn't wrap
+/// rows and push the list out of place.
+fn write_fitted(term: &Term, line: &str) -> io::Result<()> {
+    let width = term.size().1 as usize;
+    term.write_line(&console::truncate_str(line, width.max(10), "…"))
+}
+
+/// The rows currently shown: each group sits where its first member would be and
+/// lists its members below it only while expanded. Removed items are left out.
+fn visible_rows(item_count: usize, groups: &[SmallGroup], removed: &[bool]) -> Vec<Row> {
+    let mut rows = Vec::with_capacity(item_count);
+    for i in (0..item_count).filter(|&i| !removed.get(i).copied().unwrap_or(false)) {
+        match groups.iter().position(|g| g.members.contains(&i)) {
+            None => rows.push(Row::Item(i)),
+            Some(g) if groups[g].members[0] == i => {
+                rows.push(Row::Group(g));
+                if groups[g].expanded {
+                    rows.extend(groups[g].members.iter().map(|&m| Row::Member(m)));
+                }
+            }
+            Some(_) => {}
+        }
+    }
+    rows
+}
@@ -0,0 +1,121 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+const MONTH: u64 = 30 * DAY;
+const YEAR: u64 = 365 * DAY;
+
+/// Parses durations like `90d`, `12h`, `6mo` or `1y`.
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let split = input.find(|c: char| !c.is_ascii_digit()).unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let value: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}': expected a number followed by a unit like 30d", input))?;
+    let unit_secs = match unit.trim().to_lowercase().as_str() {
+        "s" | "sec" | "secs" => 1,
+        "m" | "min" | "mins" => MINUTE,
+        "h" | "hour" | "hours" => HOUR,
+        "" | "d" | "day" | "days" => DAY,
+        "w" | "week" | "weeks" => WEEK,
+        "mo" | "month" | "months" => MONTH,
+        "y" | "year" | "years" => YEAR,
+        other => return Err(format!("invalid duration unit '{}' (use s, m, h, d, w, mo or y)", other)),
+    };
+    Ok(Duration::from_secs(value.saturating_mul(unit_secs)))
+}
+
+/// [`parse_duration`], except that a bare number means hours.
+pub fn parse_hours(input: &str) -> Result<Duration, String> {
+    match input.trim().parse::<u64>() {
+        Ok(hours) => Ok(Duration::from_secs(hours.saturating_mul(HOUR))),
+        Err(_) => parse_duration(input),
+    }
+}
+
+/// Parses sizes like `500M`, `1.5G` or `20GB` (binary units); a bare number means megabytes.
+pub fn parse_size(input: &str) -> Result<u64, String> {
+    let input = input.trim();
+    let split = input.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{}': expected a number followed by a unit like 500M", input))?;
+    let multiplier: u64 = match unit.trim().to_lowercase().trim_end_matches("ib").trim_end_matches('b') {
+        "" if unit.trim().eq_ignore_ascii_case("b") => 1,
+        "" => 1 << 20,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        "t" => 1 << 40,
+        other => return Err(format!("invalid size unit '{}' (use B, K, M, G or T)", other)),
+    };
+    Ok((value * multiplier as f64) as u64)
+}
+
+/// Formats an elapsed duration compactly, e.g. "3 days" or "8 months".
+pub fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    let (value, unit) = if secs < MINUTE {
+        return "just now".to_string();
+    } else if secs < HOUR {
+        (secs / MINUTE, "minute")
+    } else if secs < DAY {
+        (secs / HOUR, "hour")
+    } else if secs < MONTH {
+        (secs / DAY, "day")
+    } else if secs < YEAR {
+        (secs / MONTH, "month")
+    } else {
+        (secs / YEAR, "year")
+    };
+    if value == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", value, unit)
+    }
+}
+
+/// Formats a configured duration; unlike [`format_age`] it never rounds to "just now".
+pub fn format_duration(duration: Duration) -> String {
+    match duration.as_secs() {
+        1 => "1 second".to_string(),
+        secs if secs < MINUTE => format!("{} seconds", secs),
+        _ => format_age(duration),
+    }
+}
+
+/// Formats a unix timestamp as `YYYY-MM-DD HH:MM UTC`.
+pub fn format_timestamp(timestamp: u64) -> String {
+    // Days-to-civil conversion from Howard Hinnant's date algorithms.
+    let days = (timestamp / DAY) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    let secs_of_day = timestamp % DAY;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        secs_of_day / HOUR,
+        (secs_of_day % HOUR) / MINUTE
+    )
+}
+
+pub fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Time elapsed since the given unix timestamp, clamped at zero.
+pub fn age_of(timestamp: u64) -> Duration {
+    Duration::from_secs(unix_secs(SystemTime::now()).saturating_sub(timestamp))
+}
@@ -0,0 +1,290 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mount {
+    pub mount_point: PathBuf,
+    pub device: String,
+    pub fs_type: String,
+}
+
+/// Snapshot of the mounted volumes, loaded once per run.
+#[derive(Debug, Clone, Default)]
+pub struct VolumeTable {
+    mounts: Vec<Mount>,
+    /// [`volume_id`] per mount point, looked up once.
+    ids: RefCell<HashMap<PathBuf, String>>,
+}
+
+impl VolumeTable {
+    pub fn load() -> Self {
+        Self::from_mounts(platform_mounts())
+    }
+
+    pub fn from_mounts(mut mounts: Vec<Mount>) -> Self {
+        // Longest mount point first so the first prefix match is the most specific one.
+        mounts.sort_by_key(|m| std::cmp::Reverse(m.mount_point.as_os_str().len()));
+        VolumeTable { mounts, ids: RefCell::default() }
+    }
+
+    pub fn mounts(&self) -> &[Mount] {
+        &self.mounts
+    }
+
+    /// The volume a path lives on, resolved by mount-point prefix.
+    pub fn volume_of(&self, path: &Path) -> Option<&Mount> {
+        let path = std::path::absolute(path).ok()?;
+        self.mounts.iter().find(|m| path.starts_with(&m.mount_point))
+    }
+
+    /// The [`volume_id`] of the volume a path lives on.
+    pub fn id_of(&self, path: &Path) -> Option<String> {
+        let mount = self.volume_of(path)?;
+        Some(self.ids.borrow_mut().entry(mount.mount_point.clone()).or_insert_with(|| volume_id(mount)).clone())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn platform_mounts() -> Vec<Mount> {
+    std::fs::read_to_string("/proc/self/mounts")
+        .map(|table| parse_proc_mounts(&table))
+        .unwrap_or_default()
+}
+
+/// Parses the `/proc/mounts` format, decoding the octal escapes used for spaces.
+pub fn parse_proc_mounts(table: &str) -> Vec<Mount> {
+    table
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let device = unescape_mount_field(fields.next()?);
+            let mount_point = unescape_mount_field(fields.next()?);
+            let fs_type = fields.next()?.to_string();
+            Some(Mount { mount_point: PathBuf::from(mount_point), device, fs_type })
+        })
+        .collect()
+}
+
+fn unescape_mount_field(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            let code: String = chars.by_ref().take(3).collect();
+            match u8::from_str_radix(&code, 8) {
+                Ok(byte) => out.push(byte as char),
+                Err(_) => {
+                    out.push('\\');
+                    out.push_str(&code);
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(target_os = "macos")]
+fn platform_mounts() -> Vec<Mount> {
+    use std::ffi::CStr;
+
+    let mut buf: *mut libc::statfs = std::ptr::null_mut();
+    let count = unsafe { libc::getmntinfo(&mut buf, libc::MNT_NOWAIT) };
+    if count <= 0 || buf.is_null() {
+        return Vec::new();
+    }
+    let entries = unsafe { std::slice::from_raw_parts(buf, count as usize) };
+    entries
+        .iter()
+        .map(|fs| unsafe {
+            Mount {
+                mount_point: PathBuf::from(CStr::from_ptr(fs.f_mntonname.as_ptr()).to_string_lossy().into_owned()),
+                device: CStr::from_ptr(fs.f_mntfromname.as_ptr()).to_string_lossy().into_owned(),
+                fs_type: CStr::from_ptr(fs.f_fstypename.as_ptr()).to_string_lossy().into_owned(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+fn platform_mounts() -> Vec<Mount> {
+    (b'A'..=b'Z')
+        .map(|letter| format!("{}:\\", letter as char))
+        .filter(|root| Path::new(root).exists())
+        .map(|root| Mount { mount_point: PathBuf::from(&root), device: root, fs_type: String::new() })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn platform_mounts() -> Vec<Mount> {
+    Vec::new()
+}
+
+/// A stable name for a volume that is the same on every machine seeing it: the filesystem
+/// UUID where Linux exposes one, otherwise the device (e.g. `server:/export` for NFS).
+pub fn volume_id(mount: &Mount) -> String {
+    filesystem_uuid(&mount.device).unwrap_or_else(|| mount.device.clone())
+}
+
+#[cfg(target_os = "linux")]
+fn filesystem_uuid(device: &str) -> Option<String> {
+    let device = std::fs::canonicalize(device).ok()?;
+    std::fs::read_dir("/dev/disk/by-uuid")
+        .ok()?
+        .flatten()
+        .find(|entry| std::fs::canonicalize(entry.path()).is_ok_and(|target| target == device))
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn filesystem_uuid(_device: &str) -> Option<String> {
+    None
+}
+
+/// Identifiers of every volume mounted right now.
+pub fn mounted_ids(table: &VolumeTable) -> HashSet<String> {
+    table.mounts().iter().filter_map(|m| table.id_of(&m.mount_point)).collect()
+}
+
+/// Whether a cached candidate can still be trusted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Presence {
+    Present,
+    /// Its volume isn't mounted; it may well still exist, so keep it but don't offer it.
+    Offline,
+    /// Its volume is mounted but the path is gone (or now belongs to another volume).
+    Stale,
+}
+
+pub fn presence(candidate: &crate::CandidateDir, table: &VolumeTable, mounted: &HashSet<String>) -> Presence {
+    let Some(recorded) = &candidate.volume else {
+        return if candidate.path.exists() { Presence::Present } else { Presence::Stale };
+    };
+    if !mounted.contains(recorded) {
+        return Presence::Offline;
+    }
+    // An empty mount point left by an unplugged disk can make the path resolve on the
+    // parent volume; only the recorded volume counts.
+    let here = table.id_of(&candidate.path);
+    match candidate.path.exists() && here.as_ref() == Some(recorded) {
+        true => Presence::Present,
+        false => Presence::Stale,
+    }
+}
+
+/// Free space available to the current user on the volume holding `path`.
+#[cfg(unix)]
+pub fn free_space(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(windows)]
+pub fn free_space(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetDiskFreeSpaceExW(dir: *const u16, available: *mut u64, total: *mut u64, free: *mut u64) -> i32;
+    }
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut available = 0u64;
+    let ok = unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, std::ptr::null_mut(), std::ptr::null_mut()) };
+    if ok != 0 { Some(available) } else { None }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn free_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+#[derive(Debug, Clone)]
+pub struct VolumeUsage {
+    pub mount_point: Option<PathBuf>,
+    pub count: usize,
+    pub bytes: u64,
+    pub free: Option<u64>,
+}
+
+/// Totals candidates per volume, largest purgeable total first.
+pub fn group_by_volume<'a>(
+    table: &VolumeTable,
+    candidates: impl IntoIterator<Item = &'a crate::CandidateDir>,
+) -> Vec<VolumeUsage> {
+    let mut groups: Vec<VolumeUsage> = Vec::new();
+    for candidate in candidates {
+        let mount_point = table.volume_of(&candidate.path).map(|m| m.mount_point.clone());
+        match groups.iter_mut().find(|g| g.mount_point == mount_point) {
+            Some(group) => {
+                group.count += 1;
+                group.bytes += candidate.reclaimable();
+            }
+            None => groups.push(VolumeUsage {
+                free: mount_point.as_deref().and_then(free_space),
+                mount_point,
+                count: 1,
+                bytes: candidate.reclaimable(),
+            }),
+        }
+    }
+    groups.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.mount_point.cmp(&b.mount_point)));
+    groups
+}
+
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb", "smbfs", "smb3", "afpfs", "webdav", "davfs", "ncpfs",
+    "9p", "glusterfs", "ceph", "fuse.sshfs", "sshfs", "fuse.rclone", "fuse.s3fs",
+];
+
+const CLOUD_FOLDERS: &[&str] = &[
+    "Dropbox", "OneDrive", "Google Drive", "iCloud Drive", "Mobile Documents",
+    "CloudStorage", "Nextcloud", "pCloudDrive", "MEGA",
+];
+
+pub fn is_network_filesystem(fs_type: &str) -> bool {
+    NETWORK_FILESYSTEMS.contains(&fs_type)
+}
+
+/// Fills in the location flags (link, network, mount point, cloud) of a candidate.
+pub fn annotate_location(candidate: &mut crate::CandidateDir, table: &VolumeTable) {
+    let Ok(path) = std::path::absolute(&candidate.path) else { return };
+
+    if candidate.volume.is_none() {
+        candidate.volume = table.id_of(&path);
+    }
+    candidate.is_link = path
+        .ancestors()
+        .any(|p| std::fs::symlink_metadata(p).map(|m| m.file_type().is_symlink()).unwrap_or(false));
+    candidate.on_network = table.volume_of(&path).is_some_and(|m| is_network_filesystem(&m.fs_type));
+    candidate.is_mount_point = table.mounts().iter().any(|m| m.mount_point == path) || crosses_device(&path);
+    candidate.cloud_synced = path.components().any(|c| {
+        let name = c.as_os_str().to_string_lossy();
+        CLOUD_FOLDERS.contains(&name.as_ref()) || name.starts_with("OneDrive - ") || name.starts_with("Dropbox (")
+    });
+}
+
+#[cfg(unix)]
+fn crosses_device(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (path.metadata(), path.parent().map(|p| p.metadata())) {
+        (Ok(own), Some(Ok(parent))) => own.dev() != parent.dev(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn crosses_device(_path: &Path) -> bool {
+    false
+}
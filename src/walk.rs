@@ -0,0 +1,195 @@
+//! The directory walks behind [`crate::ScanIter`]: depth-first through `walkdir`, or
+//! breadth-first for scans that may stop early and should sample the tree broadly.
+
+use crate::limits::{is_fd_exhaustion, note_fd_exhaustion, retry_exhausted};
+use std::collections::VecDeque;
+use std::fs::{self, FileType};
+use std::io;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// One entry of a walk.
+pub(crate) struct Entry {
+    pub path: PathBuf,
+    pub file_type: FileType,
+}
+
+impl Entry {
+    pub fn file_name(&self) -> std::borrow::Cow<'_, str> {
+        self.path.file_name().unwrap_or(self.path.as_os_str()).to_string_lossy()
+    }
+}
+
+pub(crate) enum WalkError {
+    Walk(walkdir::Error),
+    Io { path: PathBuf, error: io::Error },
+}
+
+pub(crate) enum Walker {
+    Depth(DepthFirst),
+    Breadth(BreadthFirst),
+}
+
+impl Walker {
+    pub fn new(root: &Path, max_depth: Option<usize>, breadth_first: bool) -> Self {
+        if breadth_first {
+            return Walker::Breadth(BreadthFirst::new(root, max_depth));
+        }
+        Walker::Depth(DepthFirst { walk: depth_first(root, max_depth), max_depth, retried: Vec::new() })
+    }
+
+    pub fn next_entry(&mut self) -> Option<Result<Entry, WalkError>> {
+        match self {
+            Walker::Depth(it) => it.next_entry(),
+            Walker::Breadth(it) => it.next_entry(),
+        }
+    }
+
+    /// Don't descend into the directory just returned.
+    pub fn skip_current_dir(&mut self) {
+        match self {
+            Walker::Depth(it) => it.retried.last_mut().map_or(&mut it.walk, |(walk, _)| walk).skip_current_dir(),
+            Walker::Breadth(it) => it.pending_dir = None,
+        }
+    }
+}
+
+fn depth_first(root: &Path, max_depth: Option<usize>) -> walkdir::IntoIter {
+    // Sorted so the results never depend on the order the filesystem lists entries in.
+    // Links are listed but never entered, so the scan stays inside the tree it was given.
+    let mut walker = WalkDir::new(root).follow_links(false).sort_by_file_name();
+    if let Some(depth) = max_depth {
+        walker = walker.max_depth(depth);
+    }
+    walker.into_iter()
+}
+
+/// `walkdir`'s walk, where a directory that couldn't be opened because every descriptor
+/// was taken is walked on its own, in its place, once one frees up.
+pub(crate) struct DepthFirst {
+    walk: walkdir::IntoIter,
+    max_depth: Option<usize>,
+    /// Those directories' walks with the depth they start at, innermost last; each is
+    /// walked to the end before the one it came from goes on.
+    retried: Vec<(walkdir::IntoIter, usize)>,
+}
+
+impl DepthFirst {
+    fn next_entry(&mut self) -> Option<Result<Entry, WalkError>> {
+        loop {
+            let (walk, base) = self.retried.last_mut().map_or((&mut self.walk, 0), |(walk, base)| (walk, *base));
+            let e = match walk.next() {
+                Some(Ok(entry)) => return Some(Ok(Entry { file_type: entry.file_type(), path: entry.into_path() })),
+                Some(Err(e)) => e,
+                None if self.retried.pop().is_some() => continue,
+                None => return None,
+            };
+            if !e.io_error().is_some_and(is_fd_exhaustion) {
+                return Some(Err(WalkError::Walk(e)));
+            }
+            // Walked on its own already, and still out of descriptors: give it up.
+            let second_try = base > 0 && e.depth() == 0;
+            match e.path().filter(|_| !second_try) {
+                Some(dir) if retry_exhausted(|| fs::read_dir(dir).map(drop)).is_ok() => {
+                    let depth = base + e.depth();
+                    let walk = depth_first(dir, self.max_depth.map(|max| max - depth));
+                    self.retried.push((walk, depth));
+                }
+                Some(_) => return Some(Err(WalkError::Walk(e))),
+                None => {
+                    note_fd_exhaustion();
+                    return Some(Err(WalkError::Walk(e)));
+                }
+            }
+        }
+    }
+}
+
+/// Visits every directory of one depth before any of the next, each sorted by name.
+pub(crate) struct BreadthFirst {
+    queue: VecDeque<(PathBuf, usize)>,
+    current: VecDeque<Entry>,
+    /// Entries of the directory just read that couldn't be looked at, reported before the rest.
+    failed: VecDeque<WalkError>,
+    current_depth: usize,
+    /// The directory returned last, queued on the next call unless it was skipped.
+    pending_dir: Option<(PathBuf, usize)>,
+    root: Option<PathBuf>,
+    max_depth: Option<usize>,
+}
+
+impl BreadthFirst {
+    fn new(root: &Path, max_depth: Option<usize>) -> Self {
+        BreadthFirst {
+            queue: VecDeque::new(),
+            current: VecDeque::new(),
+            failed: VecDeque::new(),
+            current_depth: 0,
+            pending_dir: None,
+            root: Some(root.to_path_buf()),
+            max_depth,
+        }
+    }
+
+    fn next_entry(&mut self) -> Option<Result<Entry, WalkError>> {
+        if let Some(dir) = self.pending_dir.take() {
+            if self.max_depth.is_none_or(|max| dir.1 < max) {
+                self.queue.push_back(dir);
+            }
+        }
+
+        if let Some(root) = self.root.take() {
+            return Some(match fs::metadata(&root) {
+                Ok(metadata) => {
+                    if metadata.is_dir() {
+                        self.pending_dir = Some((root.clone(), 0));
+                    }
+                    Ok(Entry { path: root, file_type: metadata.file_type() })
+                }
+                Err(error) => Err(WalkError::Io { path: root, error }),
+            });
+        }
+
+        loop {
+            if let Some(error) = self.failed.pop_front() {
+                return Some(Err(error));
+            }
+            if let Some(entry) = self.current.pop_front() {
+                if entry.file_type.is_dir() {
+                    self.pending_dir = Some((entry.path.clone(), self.current_depth));
+                }
+                return Some(Ok(entry));
+            }
+            let (dir, depth) = self.queue.pop_front()?;
+            self.current_depth = depth + 1;
+            match retry_exhausted(|| read_sorted(&dir)) {
+                Ok((entries, failed)) => {
+                    self.current = entries.into();
+                    self.failed = failed.into();
+                }
+                Err(error) => return Some(Err(WalkError::Io { path: dir, error })),
+            }
+        }
+    }
+}
+
+/// The entries of `dir` sorted by name, and the errors of those that couldn't be read;
+/// like `walkdir`, one bad entry doesn't lose the others.
+fn read_sorted(dir: &Path) -> io::Result<(Vec<Entry>, Vec<WalkError>)> {
+    let (mut entries, mut failed) = (Vec::new(), Vec::new());
+    for entry in fs::read_dir(dir)? {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(error) => {
+                failed.push(WalkError::Io { path: dir.to_path_buf(), error });
+                continue;
+            }
+        };
+        match entry.file_type() {
+            Ok(file_type) => entries.push(Entry { file_type, path: entry.path() }),
+            Err(error) => failed.push(WalkError::Io { path: entry.path(), error }),
+        }
+    }
+    entries.sort_by(|a, b| a.path.file_name().cmp(&b.path.file_name()));
+    Ok((entries, failed))
+}
@@ -0,0 +1,61 @@
+//! Opens directories on a helper thread so one that never answers (a dead network
+//! mount, a hung FUSE filesystem) costs a timeout instead of the whole scan.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+struct Worker {
+    requests: Sender<PathBuf>,
+    replies: Receiver<()>,
+}
+
+pub(crate) struct Watchdog {
+    timeout: Duration,
+    worker: Option<Worker>,
+}
+
+impl Watchdog {
+    pub fn new(timeout: Duration) -> Self {
+        Watchdog { timeout, worker: None }
+    }
+
+    /// Opens `dir` and reads its first entry, giving up after the timeout. Errors other
+    /// than slowness are left for the walk itself to report.
+    pub fn responds(&mut self, dir: &Path) -> bool {
+        let worker = self.worker.get_or_insert_with(spawn_worker);
+        if worker.requests.send(dir.to_path_buf()).is_err() {
+            self.worker = None;
+            return true;
+        }
+        match worker.replies.recv_timeout(self.timeout) {
+            Ok(()) => true,
+            Err(RecvTimeoutError::Disconnected) => {
+                self.worker = None;
+                true
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                // The worker is stuck in the kernel; abandon it rather than wait. It exits
+                // on its own if the read ever returns and finds nobody listening.
+                self.worker = None;
+                false
+            }
+        }
+    }
+}
+
+fn spawn_worker() -> Worker {
+    let (requests, incoming) = mpsc::channel::<PathBuf>();
+    let (outgoing, replies) = mpsc::channel();
+    thread::spawn(move || {
+        for dir in incoming {
+            let _ = fs::read_dir(&dir).map(|mut entries| entries.next());
+            if outgoing.send(()).is_err() {
+                break;
+            }
+        }
+    });
+    Worker { requests, replies }
+}
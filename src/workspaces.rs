@@ -0,0 +1,96 @@
+//! CI agent workspace roots (`/var/lib/jenkins/workspace`), where every directory is a
+//! separate checkout named after its job: `<job>`, `<job>@2`, `<job>@tmp`.
+
+use crate::units::age_of;
+use crate::CandidateDir;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Workspaces whose sources haven't changed for this long are idle unless told otherwise.
+pub const DEFAULT_WORKSPACE_IDLE: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+/// The job a workspace directory belongs to: its name up to the first `@`.
+pub fn job_name(dir_name: &str) -> &str {
+    dir_name.split('@').next().unwrap_or(dir_name)
+}
+
+/// Job names from a list with one per line; blank lines and `#` comments are skipped.
+pub fn parse_job_names(text: &str) -> HashSet<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// The directories directly below `root`, sorted by name. Links are skipped: what they
+/// point to isn't part of this root.
+pub fn list_workspaces(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(root)?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|entry| entry.path())
+        .collect();
+    dirs.sort();
+    Ok(dirs)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WorkspaceState {
+    /// Worked in recently; left alone.
+    Active,
+    /// Not worked in for longer than the idle threshold; its artifacts are offered.
+    Idle,
+    /// Its job is not in the list of active jobs; the whole directory is offered.
+    Orphaned,
+}
+
+impl WorkspaceState {
+    pub fn label(self) -> &'static str {
+        match self {
+            WorkspaceState::Active => "active",
+            WorkspaceState::Idle => "idle",
+            WorkspaceState::Orphaned => "orphaned",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub dir: PathBuf,
+    /// When its sources last changed (unix seconds), if any were found.
+    pub last_activity: Option<u64>,
+    /// The dependency and build folders found inside it.
+    pub artifacts: Vec<CandidateDir>,
+}
+
+impl Workspace {
+    pub fn name(&self) -> String {
+        self.dir.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+    }
+
+    pub fn job(&self) -> String {
+        job_name(&self.name()).to_string()
+    }
+
+    /// What deleting all of its artifacts would free.
+    pub fn purgeable(&self) -> u64 {
+        self.artifacts.iter().map(CandidateDir::reclaimable).sum()
+    }
+
+    /// Orphaned when a list of active jobs is given and its job isn't in it; otherwise idle
+    /// when it hasn't been worked in for `idle`. A workspace without any sources counts as
+    /// idle, since nothing in it is being worked on.
+    pub fn state(&self, idle: Duration, active_jobs: Option<&HashSet<String>>) -> WorkspaceState {
+        if active_jobs.is_some_and(|jobs| !jobs.contains(&self.job())) {
+            return WorkspaceState::Orphaned;
+        }
+        match self.last_activity {
+            Some(t) if age_of(t) < idle => WorkspaceState::Active,
+            _ => WorkspaceState::Idle,
+        }
+    }
+}
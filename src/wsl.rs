@@ -0,0 +1,27 @@
+use crate::volume::{Mount, VolumeTable};
+use std::path::Path;
+
+/// Filesystems WSL uses to expose Windows drives: `drvfs` on WSL 1, `9p` on WSL 2.
+const WINDOWS_DRIVE_FS: &[&str] = &["drvfs", "9p", "v9fs"];
+
+/// Whether a `/proc/version` string comes from a WSL kernel.
+pub fn is_wsl_kernel(proc_version: &str) -> bool {
+    proc_version.to_lowercase().contains("microsoft")
+}
+
+/// Whether this process runs inside WSL.
+pub fn running_in_wsl() -> bool {
+    if std::env::var_os("WSL_DISTRO_NAME").is_some_and(|name| !name.is_empty()) {
+        return true;
+    }
+    std::fs::read_to_string("/proc/version").is_ok_and(|version| is_wsl_kernel(&version))
+}
+
+pub fn is_windows_drive(mount: &Mount) -> bool {
+    WINDOWS_DRIVE_FS.contains(&mount.fs_type.as_str())
+}
+
+/// The Windows drive mount `path` lives on, when it is reached through WSL.
+pub fn windows_drive_of<'a>(volumes: &'a VolumeTable, path: &Path) -> Option<&'a Mount> {
+    volumes.volume_of(path).filter(|mount| is_windows_drive(mount))
+}
@@ -0,0 +1,40 @@
+//! `--target name:markers` adds a rule for one run. The rules are fixed for the whole
+//! process once configured, so the scan is checked in one test.
+
+use devpurge::detect::{configure_targets, parse_ad_hoc_target, parse_target};
+use devpurge::fixtures::Tree;
+use devpurge::{ScanIter, ScanOptions, ScanScope};
+
+#[test]
+fn ad_hoc_targets_are_found_like_built_in_ones() {
+    let tree = Tree::new("ad-hoc-targets");
+    tree.file("web/package.json").file("web/coverage/lcov.info").file("docs/coverage/index.html").file("web/node_modules/pkg/index.js");
+
+    let coverage = parse_ad_hoc_target("coverage:package.json, pyproject.toml", false).unwrap();
+    assert_eq!(coverage.markers, ["package.json", "pyproject.toml"]);
+    configure_targets(&[coverage], &[]).unwrap();
+
+    let mut found: Vec<String> = ScanIter::new(&tree.root, ScanOptions::default()).filter_map(Result::ok).map(|c| tree.relative(&c.path)).collect();
+    found.sort();
+    assert_eq!(found, ["web/coverage", "web/node_modules"]);
+    assert_eq!(parse_target("coverage"), Ok("coverage".to_string()));
+
+    // A cache built with it doesn't answer a run without it, nor the other way round.
+    let with = ScanScope::new(&tree.root, &ScanOptions::default());
+    assert_eq!(with.custom_targets, ["coverage:package.json,pyproject.toml"]);
+    let without = ScanScope { custom_targets: Vec::new(), ..with.clone() };
+    assert!(with.answers(&without).unwrap_err().contains("extra target 'coverage:package.json,pyproject.toml'"));
+    assert!(without.answers(&with).is_err());
+    assert!(with.answers(&with).is_ok());
+}
+
+#[test]
+fn a_bare_name_needs_allow_unmarked() {
+    let refused = parse_ad_hoc_target("coverage", false).unwrap_err();
+    assert!(refused.contains("--allow-unmarked"), "{}", refused);
+    assert!(parse_ad_hoc_target("coverage:", false).is_err());
+
+    let unmarked = parse_ad_hoc_target("coverage", true).unwrap();
+    assert!(unmarked.unmarked && unmarked.markers.is_empty());
+    assert_eq!(unmarked.spec(), "coverage");
+}
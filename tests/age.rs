@@ -0,0 +1,98 @@
+//! Candidate ages, shown in the list and used by `--older-than`: the newest of the
+//! folder and its direct entries.
+
+use devpurge::activity::{last_modified, recently_modified, DEFAULT_ACTIVE_WITHIN};
+use devpurge::display::{fit_row, PathDisplay, PathStyle};
+use devpurge::fixtures::{parse_spec, Tree};
+use devpurge::rows::CandidateRows;
+use devpurge::severity::Severity;
+use devpurge::ui::RenderRow;
+use devpurge::units::{parse_hours, unix_secs};
+use devpurge::{CandidateDir, ScanIter, ScanOptions};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fs::File;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+
+const DAY: u64 = 24 * 60 * 60;
+
+fn backdate(path: &std::path::Path, days: u64) {
+    File::open(path).unwrap().set_modified(SystemTime::now() - Duration::from_secs(days * DAY)).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn the_newest_top_level_entry_sets_the_age() {
+    let mut tree = Tree::new("age");
+    let spec = parse_spec("[[projects]]\npath = \"app\"\necosystem = \"node\"\nfiles = 2").unwrap();
+    tree.generate(&spec);
+    let modules = tree.root.join("app/node_modules");
+    backdate(&modules.join("f0.bin"), 40);
+    backdate(&modules.join("f1.bin"), 30);
+    backdate(&modules, 50);
+
+    let found: Vec<_> = ScanIter::new(&tree.root, ScanOptions::default()).filter_map(Result::ok).collect();
+    let age = |modified: u64| unix_secs(SystemTime::now()).saturating_sub(modified) / DAY;
+    assert_eq!(age(found[0].modified.unwrap()), 30);
+
+    File::create(modules.join("fresh.bin")).unwrap();
+    assert_eq!(age(last_modified(&modules).unwrap()), 0);
+}
+
+#[test]
+fn a_missing_folder_has_no_age() {
+    let tree = Tree::new("age-missing");
+    assert_eq!(last_modified(&tree.root.join("gone")), None);
+}
+
+#[test]
+fn a_bare_cache_age_is_in_hours() {
+    assert_eq!(parse_hours("24"), Ok(Duration::from_secs(DAY)));
+    assert_eq!(parse_hours("2d"), Ok(Duration::from_secs(2 * DAY)));
+    assert_eq!(parse_hours("0"), Ok(Duration::ZERO));
+}
+
+#[test]
+fn rows_show_the_age_after_the_size_and_a_question_mark_when_unknown() {
+    let mut old = CandidateDir::new("/work/app/node_modules".into(), 1000);
+    old.modified = Some(unix_secs(SystemTime::now()) - 240 * DAY);
+    let mut unknown = CandidateDir::new("/work/lib/target".into(), 1000);
+    unknown.modified = None;
+    let rows = CandidateRows::new(Rc::new(RefCell::new(vec![old, unknown])), PathDisplay::new(PathStyle::Relative, Path::new("/work")), Severity::default(), false);
+
+    let render = |item| console::strip_ansi_codes(&rows.render(item, 120)).to_string();
+    assert!(render(0).ends_with("(1000 B, 8 months)"), "{}", render(0));
+    assert!(render(1).ends_with("(1000 B, ?)"), "{}", render(1));
+}
+
+#[test]
+fn rows_never_outgrow_the_terminal() {
+    let path = "/home/me/projects/clients/acme/web/packages/storefront/node_modules";
+    let suffix = " (1.2 GB, 8 months, javascript, failed last time: permission denied)";
+    for width in [20, 40, 60, 80, 200] {
+        let row = fit_row(path, suffix, width);
+        assert!(console::measure_text_width(&row) <= width, "{} > {}: {}", console::measure_text_width(&row), width, row);
+    }
+    assert_eq!(fit_row(path, suffix, 200), format!("{}{}", path, suffix));
+    assert!(fit_row(path, suffix, 100).ends_with(suffix));
+}
+
+#[test]
+fn folders_written_moments_ago_are_flagged_as_active() {
+    let tree = Tree::new("age-active");
+    tree.file("app/package.json").file("app/node_modules/pkg/index.js");
+    let mut found: Vec<_> = ScanIter::new(&tree.root, ScanOptions::default()).filter_map(Result::ok).collect();
+    assert!(recently_modified(&found[0], DEFAULT_ACTIVE_WITHIN));
+
+    found[0].modified = Some(unix_secs(SystemTime::now()) - DAY);
+    assert!(!recently_modified(&found[0], DEFAULT_ACTIVE_WITHIN));
+    found[0].modified = None;
+    assert!(!recently_modified(&found[0], DEFAULT_ACTIVE_WITHIN));
+
+    let path = found[0].path.clone();
+    let mut rows = CandidateRows::new(Rc::new(RefCell::new(found)), PathDisplay::new(PathStyle::Relative, &tree.root), Severity::default(), false);
+    rows.active = HashSet::from([path]);
+    assert!(rows.render(0, 120).ends_with("[!] recently modified"), "{}", rows.render(0, 120));
+}
@@ -0,0 +1,43 @@
+//! CocoaPods, Carthage and Xcode: each folder is offered only next to its project, and
+//! Xcode's shared DerivedData is found at its default location.
+
+use devpurge::fixtures::Tree;
+use devpurge::scanner::system_caches;
+
+#[test]
+fn project_folders_need_their_manifest() {
+    let tree = Tree::new("apple");
+    tree.file("app/Podfile")
+        .file("app/Pods/Alamofire/Source.swift")
+        .file("app/Cartfile")
+        .file("app/Carthage/Build/iOS/Kit.framework/Kit")
+        .file("app/Carthage/Checkouts/Kit/README.md")
+        .dir("app/App.xcodeproj")
+        .file("app/DerivedData/App/Build/Products/App.app/App")
+        // Without a Podfile, Cartfile or Xcode project next to them.
+        .file("loose/Pods/notes.txt")
+        .file("loose/Carthage/Build/x")
+        .file("loose/DerivedData/x");
+
+    assert_eq!(tree.found(), ["app/Carthage/Build", "app/DerivedData", "app/Pods"]);
+}
+
+#[test]
+fn default_derived_data_is_found_by_location() {
+    let tree = Tree::new("apple-home");
+    tree.file("Library/Developer/Xcode/DerivedData/App-abc/Build/Intermediates.noindex/x.o");
+
+    let found = system_caches(&tree.root, |_, _, _| {});
+    let paths: Vec<String> = found.iter().map(|c| tree.relative(&c.path)).collect();
+    assert_eq!(paths, ["Library/Developer/Xcode/DerivedData"]);
+    assert!(found[0].size > 0);
+    // A scan of the home folder finds it too.
+    assert_eq!(tree.found(), ["Library/Developer/Xcode/DerivedData"]);
+}
+
+#[test]
+fn missing_system_caches_are_skipped() {
+    let tree = Tree::new("apple-empty");
+    tree.file("Library/Developer/notes.txt");
+    assert!(system_caches(&tree.root, |_, _, _| {}).is_empty());
+}
@@ -0,0 +1,74 @@
+//! The scan cache keeps each root's results and reads back exactly what was written.
+
+use devpurge::cache::{cache_key, load_cache, write_cache, CachedScan, ScanCache};
+use devpurge::fixtures::Tree;
+use devpurge::projects::ProjectKind;
+use devpurge::{scan, ScanOptions, ScanScope};
+
+#[test]
+fn a_written_cache_reads_back_unchanged() {
+    let tree = Tree::new("cache-roundtrip");
+    tree.file("web/package.json").file("web/node_modules/x/index.js").file("api/Cargo.toml").file("api/target/debug/api");
+    let options = ScanOptions::default();
+    let candidates = scan(&tree.root, options.clone());
+    assert_eq!(candidates.len(), 2);
+
+    let mut cache = ScanCache::default();
+    let scope = ScanScope::new(&tree.root, &options);
+    cache.roots.insert(cache_key(&tree.root), CachedScan { scanned_at: 1_700_000_000, partial: false, scope: Some(scope.clone()), candidates: candidates.clone() });
+    let file = tree.root.join("scan_cache.json");
+    write_cache(&file, &cache).unwrap();
+
+    let loaded = load_cache(&file).unwrap();
+    let cached = &loaded.roots[&cache_key(&tree.root)];
+    assert_eq!(cached.candidates, candidates);
+    assert_eq!(cached.scope.as_ref(), Some(&scope));
+    assert_eq!(cached.scanned_at, 1_700_000_000);
+}
+
+#[test]
+fn the_closest_scan_above_a_root_answers_for_it() {
+    let tree = Tree::new("cache-entry");
+    tree.dir("work/clients/acme");
+    let mut cache = ScanCache::default();
+    for root in ["work", "work/clients"] {
+        let root = tree.root.join(root);
+        cache.roots.insert(cache_key(&root), CachedScan { scanned_at: 0, partial: false, scope: None, candidates: Vec::new() });
+    }
+    let wanted = |root: &str| ScanScope::new(&tree.root.join(root), &ScanOptions::default());
+
+    assert_eq!(cache.entry_for(&wanted("work/clients/acme")), Some(cache_key(&tree.root.join("work/clients"))));
+    assert_eq!(cache.entry_for(&wanted("work")), Some(cache_key(&tree.root.join("work"))));
+    assert_eq!(cache.entry_for(&wanted("")), None);
+}
+
+#[test]
+fn caches_from_before_per_root_scans_read_as_empty() {
+    let tree = Tree::new("cache-legacy");
+    let file = tree.root.join("scan_cache.json");
+    std::fs::write(&file, r#"[{"path":"/w/node_modules","size":10}]"#).unwrap();
+    assert!(load_cache(&file).is_none());
+}
+
+#[test]
+fn entries_cached_before_the_ecosystem_was_recorded_get_it_back() {
+    let tree = Tree::new("cache-project");
+    tree.file("api/Cargo.toml").dir("api/target");
+    let options = ScanOptions::default();
+    let mut cache = ScanCache::default();
+    cache.roots.insert(cache_key(&tree.root), CachedScan { scanned_at: 0, partial: false, scope: None, candidates: scan(&tree.root, options) });
+    let mut old = serde_json::to_value(&cache).unwrap();
+    for roots in old["roots"].as_object_mut().unwrap().values_mut() {
+        for candidate in roots["candidates"].as_array_mut().unwrap() {
+            candidate.as_object_mut().unwrap().remove("project");
+        }
+    }
+    let file = tree.root.join("scan_cache.json");
+    std::fs::write(&file, old.to_string()).unwrap();
+
+    let mut candidate = load_cache(&file).unwrap().roots[&cache_key(&tree.root)].candidates[0].clone();
+    assert_eq!(candidate.project, None);
+    candidate.ensure_kind();
+    assert_eq!(candidate.project, Some(ProjectKind::Rust));
+    assert_eq!(candidate.ecosystem(), "rust");
+}
@@ -0,0 +1,51 @@
+//! Targets declared in the config next to the built-in ones. The merged rules are fixed
+//! for the whole process, so everything that needs them configured is in one test.
+
+use devpurge::config::parse_config;
+use devpurge::detect::{configure_targets, is_safe_to_delete, parse_target, target_kind};
+use devpurge::fixtures::Tree;
+use devpurge::{ScanIter, ScanOptions};
+
+#[test]
+fn configured_targets_are_found_and_disabled_ones_are_not() {
+    let tree = Tree::new("custom-targets");
+    tree.dir("svc/out/gen").dir("lib/out/gen").dir("app/vendor/pkg").file("svc/BUILD.bzl").file("app/go.mod");
+
+    let config = parse_config(
+        r#"
+        disabled_targets = ["vendor"]
+
+        [[targets]]
+        name = "out"
+        markers = ["BUILD.bzl", "BUILD"]
+        "#,
+    )
+    .unwrap();
+    configure_targets(&config.targets, &config.disabled_targets).unwrap();
+
+    let found: Vec<_> = ScanIter::new(&tree.root, ScanOptions::default()).filter_map(Result::ok).collect();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].path, tree.root.join("svc/out"));
+    assert_eq!(found[0].kind, "out");
+
+    assert!(!is_safe_to_delete("out", &tree.root.join("lib/out")), "no marker next to it");
+    assert!(!is_safe_to_delete("vendor", &tree.root.join("app/vendor")), "disabled");
+    assert_eq!(target_kind("out", false), Some("out"));
+    assert_eq!(parse_target("out"), Ok("out".to_string()));
+    assert!(parse_target("vendor").is_err());
+    assert!(configure_targets(&[], &[]).is_err(), "rules are fixed once configured");
+}
+
+#[test]
+fn invalid_target_config_is_an_error() {
+    let unknown = parse_config("disabled_targets = [\"vendr\"]").unwrap_err();
+    assert!(format!("{:#}", unknown).contains("cannot disable 'vendr'"));
+
+    let no_markers = parse_config("[[targets]]\nname = \"out\"\nmarkers = []").unwrap_err();
+    assert!(format!("{:#}", no_markers).contains("at least one marker"));
+
+    let nested = parse_config("[[targets]]\nname = \"a/out\"\nmarkers = [\"BUILD\"]").unwrap_err();
+    assert!(format!("{:#}", nested).contains("single file or folder name"));
+
+    assert!(parse_config("[[targets]]\nname = \"out\"\nmarker = [\"BUILD\"]").is_err(), "unknown field");
+}
@@ -0,0 +1,207 @@
+//! Deletion failures injected through `FaultyFs`: partial deletes, busy folders, and how
+//! they end up in the failure log.
+
+use devpurge::failures::{describe_error, FailureLog};
+use devpurge::filesystem::{FaultyFs, Op};
+use devpurge::fixtures::Tree;
+use devpurge::remove::{apply_action, apply_action_with_progress, available_actions, delete_recorded, partial_targets, remove_candidate_with, Action};
+use devpurge::CandidateDir;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A node_modules with three one-byte files.
+fn fixture(name: &str) -> Tree {
+    let tree = Tree::new(&format!("deletion-{}", name));
+    tree.file("app/node_modules/a/1.js").file("app/node_modules/a/2.js").file("app/node_modules/a/3.js");
+    tree
+}
+
+fn remaining(dir: &Path) -> usize {
+    fs::read_dir(dir).map(|entries| entries.count()).unwrap_or(0)
+}
+
+#[test]
+fn a_denied_file_stops_the_delete_partway_and_is_recorded() {
+    let tree = fixture("denied");
+    let candidate = CandidateDir::new(tree.root.join("app/node_modules"), 3);
+    let faulty = FaultyFs::new().deny_nth_file(2);
+    let mut failures = FailureLog::default();
+
+    let error = delete_recorded(&faulty, &candidate, &mut failures).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+    assert_eq!(remaining(&tree.root.join("app/node_modules/a")), 2);
+    let failed = failures.get(&candidate.path).unwrap();
+    assert_eq!((failed.error.as_str(), failed.attempts), ("permission denied", 1));
+
+    // The fault is gone, so a retry finishes the job and clears the entry.
+    delete_recorded(&faulty, &candidate, &mut failures).unwrap();
+    assert!(!candidate.path.exists());
+    assert!(failures.entries().is_empty());
+}
+
+#[test]
+fn a_busy_folder_fails_once_then_deletes() {
+    let tree = fixture("busy");
+    let candidate = CandidateDir::new(tree.root.join("app/node_modules"), 3);
+    let faulty = FaultyFs::new().busy_once(&candidate.path);
+    let mut failures = FailureLog::default();
+
+    let error = delete_recorded(&faulty, &candidate, &mut failures).unwrap_err();
+    assert!(["resource busy", "sharing violation"].contains(&describe_error(&error).as_str()));
+    assert_eq!(remaining(&candidate.path.join("a")), 3);
+    assert_eq!(failures.get(&candidate.path).unwrap().attempts, 1);
+
+    delete_recorded(&faulty, &candidate, &mut failures).unwrap();
+    assert!(!candidate.path.exists());
+    assert!(failures.get(&candidate.path).is_none());
+}
+
+#[test]
+fn a_folder_that_cannot_be_listed_stops_the_delete() {
+    let tree = fixture("unlisted");
+    let candidate = CandidateDir::new(tree.root.join("app/node_modules"), 3);
+    let inner = candidate.path.join("a");
+    let faulty = FaultyFs::new().deny_listing(&inner);
+    let mut failures = FailureLog::default();
+
+    let error = delete_recorded(&faulty, &candidate, &mut failures).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+    assert!(faulty.calls().contains(&(Op::ReadDir, inner.clone())));
+    assert_eq!(remaining(&inner), 3);
+    assert_eq!(failures.get(&candidate.path).unwrap().attempts, 1);
+}
+
+#[test]
+fn partial_finds_nothing_in_a_folder_that_cannot_be_listed() {
+    let tree = fixture("partial-unlisted");
+    tree.file("app/package.json").dir("app/node_modules/.cache/babel");
+    let candidate = CandidateDir::new(tree.root.join("app/node_modules"), 3);
+
+    assert!(partial_targets(&FaultyFs::new().deny_listing(&candidate.path), &candidate).is_empty());
+    let found = partial_targets(&FaultyFs::new(), &candidate);
+    assert!(found.len() == 1 && found[0].ends_with("node_modules/.cache"), "{:?}", found);
+}
+
+#[test]
+fn repeated_failures_count_attempts() {
+    let tree = fixture("attempts");
+    let candidate = CandidateDir::new(tree.root.join("app/node_modules"), 3);
+    let faulty = FaultyFs::new().busy_once(&candidate.path).deny_nth_file(1);
+    let mut failures = FailureLog::default();
+
+    assert!(delete_recorded(&faulty, &candidate, &mut failures).is_err());
+    assert!(delete_recorded(&faulty, &candidate, &mut failures).is_err());
+    let failed = failures.get(&candidate.path).unwrap();
+    assert_eq!((failed.error.as_str(), failed.attempts), ("permission denied", 2));
+}
+
+#[test]
+fn a_vanished_candidate_fails_as_not_found() {
+    let tree = fixture("vanished");
+    let candidate = CandidateDir::new(tree.root.join("app/gone"), 3);
+    let mut failures = FailureLog::default();
+
+    let error = delete_recorded(&FaultyFs::new(), &candidate, &mut failures).unwrap_err();
+    assert_eq!(describe_error(&error), "not found");
+}
+
+#[test]
+fn file_candidates_are_removed_as_files() {
+    let tree = fixture("file");
+    tree.file("app/.eslintcache");
+    let candidate = CandidateDir::file(tree.root.join("app/.eslintcache"), 1);
+    let faulty = FaultyFs::new();
+
+    remove_candidate_with(&faulty, &candidate).unwrap();
+    assert_eq!(faulty.calls(), vec![(Op::RemoveFile, candidate.path.clone())]);
+}
+
+#[cfg(unix)]
+#[test]
+fn a_candidate_that_is_a_link_is_never_walked() {
+    let tree = fixture("link");
+    std::os::unix::fs::symlink(tree.root.join("app/node_modules"), tree.root.join("app/linked")).unwrap();
+    let candidate = CandidateDir::new(tree.root.join("app/linked"), 0);
+    let faulty = FaultyFs::new();
+
+    remove_candidate_with(&faulty, &candidate).unwrap();
+    assert!(faulty.calls().iter().all(|(_, path)| *path == candidate.path), "nothing behind the link is touched");
+    assert_eq!(remaining(&tree.root.join("app/node_modules/a")), 3);
+}
+
+#[test]
+fn keep_dir_empties_the_folder_but_keeps_it() {
+    let tree = fixture("keep-dir");
+    let candidate = CandidateDir::new(tree.root.join("app/node_modules"), 3);
+    tree.file("app/node_modules/.package-lock.json");
+
+    apply_action(&FaultyFs::new(), &candidate, Action::KeepDir).unwrap();
+    assert!(candidate.path.is_dir());
+    assert_eq!(remaining(&candidate.path), 0);
+}
+
+#[test]
+fn partial_removes_only_the_caches_inside() {
+    let tree = fixture("partial");
+    tree.file("app/package.json").dir("app/node_modules/.cache/babel");
+    let candidate = CandidateDir::new(tree.root.join("app/node_modules"), 3);
+    let trash: &[Action] = if devpurge::trash::supported() { &[Action::Trash] } else { &[] };
+    assert_eq!(available_actions(&candidate), [&[Action::Delete], trash, &[Action::Partial, Action::KeepDir]].concat());
+
+    apply_action(&FaultyFs::new(), &candidate, Action::Partial).unwrap();
+    assert!(!candidate.path.join(".cache").exists());
+    assert_eq!(remaining(&candidate.path.join("a")), 3);
+
+    let without_caches = available_actions(&candidate);
+    assert!(!without_caches.contains(&Action::Partial), "nothing left to clean partially");
+    let file = CandidateDir::file(tree.root.join("app/package.json"), 2);
+    assert_eq!(available_actions(&file), [&[Action::Delete], trash].concat());
+}
+
+#[test]
+fn progress_reports_each_file_as_it_goes() {
+    let tree = fixture("progress");
+    fs::write(tree.root.join("app/node_modules/big.bin"), vec![0u8; 1000]).unwrap();
+    let candidate = CandidateDir::new(tree.root.join("app/node_modules"), 1003);
+    let mut reported = Vec::new();
+
+    apply_action_with_progress(&FaultyFs::new(), &candidate, Action::Delete, false, &mut |bytes| reported.push(bytes)).unwrap();
+    assert_eq!(reported, [1, 1, 1, 1000]);
+    assert!(!candidate.path.exists());
+}
+
+#[test]
+fn progress_counts_what_went_before_a_failure() {
+    let tree = fixture("progress-denied");
+    let candidate = CandidateDir::new(tree.root.join("app/node_modules"), 3);
+    let mut freed = 0;
+
+    let result = apply_action_with_progress(&FaultyFs::new().deny_nth_file(3), &candidate, Action::Delete, false, &mut |bytes| freed += bytes);
+    assert!(result.is_err());
+    assert_eq!(freed, 2);
+}
+
+#[cfg(unix)]
+#[test]
+fn a_file_with_another_name_left_frees_nothing() {
+    let tree = fixture("progress-hard-link");
+    fs::hard_link(tree.root.join("app/node_modules/a/1.js"), tree.root.join("app/kept.js")).unwrap();
+    let candidate = CandidateDir::new(tree.root.join("app/node_modules"), 3);
+    let mut freed = 0;
+
+    apply_action_with_progress(&FaultyFs::new(), &candidate, Action::Delete, false, &mut |bytes| freed += bytes).unwrap();
+    assert_eq!(freed, 2);
+    assert!(tree.root.join("app/kept.js").is_file());
+}
+
+#[test]
+fn a_candidate_measured_after_a_failure_shows_what_is_left() {
+    let tree = fixture("remaining");
+    let candidate = CandidateDir::new(tree.root.join("app/node_modules"), 3);
+    let mut freed = 0;
+
+    assert!(apply_action_with_progress(&FaultyFs::new().deny_nth_file(2), &candidate, Action::Delete, false, &mut |bytes| freed += bytes).is_err());
+    let remaining = candidate.remeasure().unwrap();
+    assert_eq!((freed, remaining.size), (1, 2));
+}
@@ -0,0 +1,195 @@
+//! Every built-in rule: offered with its project's files, refused without them.
+
+use devpurge::detect::{is_safe_to_delete, is_safe_to_delete_file, TARGET_DIRS, TARGET_FILES};
+use devpurge::fixtures::Tree;
+use devpurge::projects::{detect, Marker, ProjectKind, Risk, DIR_DETECTORS, FILE_DETECTORS};
+use devpurge::{scan, ScanOptions};
+
+/// The rule, the candidate's path and the files that make it safe. Paths are relative
+/// to a fresh tree; markers may sit inside the candidate, next to it or above it.
+const DIRS: &[(&str, &str, &[&str])] = &[
+    ("node_modules", "p/node_modules", &["p/package.json"]),
+    ("target", "p/target", &["p/Cargo.toml"]),
+    ("build", "p/build", &["p/build.gradle"]),
+    ("dist", "p/dist", &["p/vite.config.ts"]),
+    (".gradle", "p/.gradle", &["p/settings.gradle.kts"]),
+    ("vendor", "p/vendor", &["p/go.mod"]),
+    ("__pycache__", "p/__pycache__", &[]),
+    (".venv", "p/.venv", &["p/.venv/pyvenv.cfg"]),
+    ("venv", "p/venv", &["p/venv/pyvenv.cfg"]),
+    (".tox", "p/.tox", &["p/tox.ini"]),
+    (".pytest_cache", "p/.pytest_cache", &["p/pytest.ini"]),
+    (".mypy_cache", "p/.mypy_cache", &["p/mypy.ini"]),
+    ("bin", "p/bin", &["p/App.csproj"]),
+    ("obj", "p/obj", &["p/App.fsproj"]),
+    (".dart_tool", "p/.dart_tool", &["p/pubspec.yaml"]),
+    (".angular", "p/.angular", &["p/angular.json"]),
+    (".next", "p/.next", &["p/next.config.ts"]),
+    (".nuxt", "p/.nuxt", &["p/nuxt.config.js"]),
+    (".vs", "p/.vs", &["p/App.sln"]),
+    (".pio", "p/.pio", &["p/platformio.ini"]),
+    ("build-*", "build-app-Desktop-Debug", &["build-app-Desktop-Debug/Makefile", "app/CMakeLists.txt"]),
+    ("Pods", "p/Pods", &["p/Podfile"]),
+    ("Build", "p/Carthage/Build", &["p/Cartfile"]),
+    ("DerivedData", "p/DerivedData", &["p/App.xcodeproj"]),
+    (".terraform", "p/.terraform", &["p/main.tf"]),
+    ("_build", "p/_build", &["p/rebar.config"]),
+    ("deps", "p/deps", &["p/mix.exs"]),
+    (".stack-work", "p/.stack-work", &["p/stack.yaml"]),
+    ("dist-newstyle", "p/dist-newstyle", &["p/app.cabal"]),
+];
+
+const FILES: &[(&str, &str, &[&str])] = &[
+    (".eslintcache", "p/.eslintcache", &["p/package.json"]),
+    (".stylelintcache", "p/.stylelintcache", &["p/package.json"]),
+    ("*.tsbuildinfo", "p/tsconfig.tsbuildinfo", &["p/tsconfig.json"]),
+    (".phpunit.result.cache", "p/.phpunit.result.cache", &["p/phpunit.xml"]),
+];
+
+fn name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap()
+}
+
+#[test]
+fn every_rule_is_covered() {
+    let rules: Vec<&str> = DIRS.iter().chain(FILES).map(|(rule, _, _)| *rule).collect();
+    for rule in TARGET_DIRS.iter().chain(TARGET_FILES) {
+        assert!(rules.contains(rule), "no case for {}", rule);
+    }
+}
+
+#[test]
+fn folders_need_their_markers() {
+    for (rule, candidate, markers) in DIRS {
+        let tree = Tree::new("detection-dir");
+        tree.dir(candidate);
+        let path = tree.root.join(candidate);
+        // Only __pycache__ is safe anywhere.
+        assert_eq!(is_safe_to_delete(name(candidate), &path), markers.is_empty(), "{} without markers", rule);
+        for marker in *markers {
+            tree.file(marker);
+        }
+        assert!(is_safe_to_delete(name(candidate), &path), "{} with {:?}", rule, markers);
+    }
+}
+
+#[test]
+fn files_need_their_markers() {
+    for (rule, candidate, markers) in FILES {
+        let tree = Tree::new("detection-file");
+        tree.file(candidate);
+        let path = tree.root.join(candidate);
+        assert!(!is_safe_to_delete_file(name(candidate), &path), "{} without markers", rule);
+        for marker in *markers {
+            tree.file(marker);
+        }
+        assert!(is_safe_to_delete_file(name(candidate), &path), "{} with {:?}", rule, markers);
+    }
+}
+
+#[test]
+fn every_rule_has_a_detector_and_every_detector_a_rule() {
+    for (rules, detectors) in [(TARGET_DIRS, DIR_DETECTORS), (TARGET_FILES, FILE_DETECTORS)] {
+        for rule in rules {
+            assert!(detectors.iter().any(|d| d.rule == *rule), "no detector for {}", rule);
+        }
+        for detector in detectors {
+            assert!(rules.contains(&detector.rule), "{} is not a built-in target", detector.rule);
+        }
+    }
+}
+
+#[test]
+fn each_marker_alone_detects_its_kind() {
+    for (detector, is_file) in DIR_DETECTORS.iter().map(|d| (d, false)).chain(FILE_DETECTORS.iter().map(|d| (d, true))) {
+        for marker in detector.markers {
+            let tree = Tree::new("detection-marker");
+            let name = detector.rule.replace('*', "app");
+            let candidate = format!("p/{}", name);
+            match is_file {
+                true => tree.file(&candidate),
+                false => tree.dir(&candidate),
+            };
+            match *marker {
+                Marker::Beside(file) => tree.file(&format!("p/{}", file)),
+                Marker::AnyBeside(files) => tree.file(&format!("p/{}", files[files.len() - 1])),
+                Marker::Extension(extension) => tree.file(&format!("p/App.{}", extension)),
+                Marker::Inside(file) => tree.file(&format!("{}/{}", candidate, file)),
+                Marker::Always => &tree,
+                // Covered with their own layouts by `folders_need_their_markers`.
+                Marker::Check(..) => continue,
+            };
+            let found = detect(&name, &tree.root.join(&candidate), is_file).map(|(d, _)| d.kind);
+            assert_eq!(found, Some(detector.kind), "{} with {}", detector.rule, marker.describe());
+        }
+    }
+}
+
+#[test]
+fn the_scan_records_the_first_matching_kind() {
+    let tree = Tree::new("detection-kind");
+    tree.file("ng/angular.json").file("ng/package.json").dir("ng/dist");
+    tree.file("java/pom.xml").dir("java/build");
+    tree.file("esp/sdkconfig").file("esp/CMakeLists.txt").dir("esp/build");
+    tree.file("lib/go.mod").dir("lib/vendor");
+    let found = |project: &str| scan(&tree.root, ScanOptions::default()).into_iter().find(|c| c.path.starts_with(tree.root.join(project))).and_then(|c| c.project);
+
+    assert_eq!(found("ng"), Some(ProjectKind::Angular));
+    assert_eq!(found("java"), Some(ProjectKind::Maven));
+    assert_eq!(found("esp"), Some(ProjectKind::EspIdf));
+    assert_eq!(found("lib"), Some(ProjectKind::Go));
+}
+
+#[test]
+fn targets_that_may_hold_hand_placed_files_are_cautious() {
+    let tree = Tree::new("detection-risk");
+    tree.file("lib/go.mod").dir("lib/vendor");
+    tree.file("app/package.json").dir("app/node_modules");
+    tree.file("api/Api.csproj").dir("api/bin");
+    let mut found = scan(&tree.root, ScanOptions::default());
+    found.sort_by(|a, b| a.path.cmp(&b.path));
+    let risks: Vec<(String, Risk)> = found.iter().map(|c| (tree.relative(&c.path), c.risk)).collect();
+    assert_eq!(risks, [("api/bin".to_string(), Risk::Caution), ("app/node_modules".to_string(), Risk::Safe), ("lib/vendor".to_string(), Risk::Caution)]);
+
+    // Cached before risks were recorded: the row that found it says again.
+    let mut cached = found[2].clone();
+    cached.risk = Risk::Safe;
+    cached.ensure_kind();
+    assert_eq!(cached.risk, Risk::Caution);
+}
+
+#[test]
+fn shadow_builds_need_a_cmake_cache_or_qt_creators_name() {
+    let refused: &[(&str, &[&str])] = &[
+        // A Makefile next to a project, under a name Qt Creator doesn't give.
+        ("build-tools", &["build-tools/Makefile", "app/CMakeLists.txt"]),
+        // Named after a project that isn't there.
+        ("build-app-Desktop-Debug", &["build-app-Desktop-Debug/Makefile", "other/CMakeLists.txt"]),
+        // Not a build configuration.
+        ("build-app-Desktop-Nightly", &["build-app-Desktop-Nightly/Makefile", "app/CMakeLists.txt"]),
+        // No kit.
+        ("build-app-Debug", &["build-app-Debug/Makefile", "app/CMakeLists.txt"]),
+        // Neither build file.
+        ("build-app-Desktop-Debug", &["build-app-Desktop-Debug/notes.txt", "app/CMakeLists.txt"]),
+    ];
+    for (candidate, files) in refused {
+        let tree = Tree::new("detection-shadow");
+        for file in *files {
+            tree.file(file);
+        }
+        assert!(!is_safe_to_delete(candidate, &tree.root.join(candidate)), "{} with {:?}", candidate, files);
+    }
+
+    let accepted: &[(&str, &[&str])] = &[
+        ("build-tools", &["build-tools/CMakeCache.txt", "app/CMakeLists.txt"]),
+        ("build-my-app-Desktop_Qt_6_5_GCC_64bit-Release", &["build-my-app-Desktop_Qt_6_5_GCC_64bit-Release/Makefile", "my-app/app.pro"]),
+        ("build-app-Android-RelWithDebInfo", &["build-app-Android-RelWithDebInfo/Makefile", "app/CMakeLists.txt"]),
+    ];
+    for (candidate, files) in accepted {
+        let tree = Tree::new("detection-shadow");
+        for file in *files {
+            tree.file(file);
+        }
+        assert!(is_safe_to_delete(candidate, &tree.root.join(candidate)), "{} with {:?}", candidate, files);
+    }
+}
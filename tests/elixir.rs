@@ -0,0 +1,29 @@
+//! Elixir's `_build` and `deps`: offered only next to a mix.exs, since `deps` in
+//! particular is a common name for other things.
+
+use devpurge::fixtures::Tree;
+
+#[test]
+fn mix_project_folders_are_found() {
+    let tree = Tree::new("elixir");
+    tree.file("app/mix.exs")
+        .file("app/_build/dev/lib/app/ebin/app.app")
+        .file("app/deps/jason/mix.exs")
+        .file("erl/rebar.config")
+        .file("erl/_build/default/lib/cowboy/ebin/cowboy.beam");
+
+    assert_eq!(tree.found(), ["app/_build", "app/deps", "erl/_build"]);
+}
+
+#[test]
+fn deps_without_mix_exs_is_skipped() {
+    let tree = Tree::new("elixir-false-positive");
+    // A C project's vendored dependencies, and a rebar3 project, which has no deps folder of its own.
+    tree.file("native/Makefile")
+        .file("native/deps/zlib/zlib.h")
+        .file("erl/rebar.config")
+        .file("erl/deps/notes.txt")
+        .file("docs/_build/html/index.html");
+
+    assert!(tree.found().is_empty());
+}
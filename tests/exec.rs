@@ -0,0 +1,45 @@
+use devpurge::exec::{run, set_no_exec, ExecError};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+fn shell(script: &str) -> Command {
+    let mut command;
+    if cfg!(windows) {
+        command = Command::new("cmd");
+        command.args(["/C", script]);
+    } else {
+        command = Command::new("sh");
+        command.args(["-c", script]);
+    }
+    command
+}
+
+// One test so the process-wide switch isn't flipped under a parallel test.
+#[test]
+fn commands_run_time_out_and_can_be_disabled() {
+    let out = run(&mut shell("echo hello"), Duration::from_secs(10)).unwrap();
+    assert!(out.status.success());
+    assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "hello");
+
+    if cfg!(unix) {
+        let started = Instant::now();
+        let result = run(&mut shell("sleep 5"), Duration::from_millis(200));
+        assert!(matches!(result, Err(ExecError::TimedOut(_))), "{:?}", result);
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    set_no_exec(true);
+    let result = run(&mut shell("echo hello"), Duration::from_secs(10));
+    assert!(matches!(result, Err(ExecError::Disabled)), "{:?}", result);
+
+    let dir = std::env::temp_dir().join(format!("devpurge-exec-{}", std::process::id()));
+    let verdict = devpurge::check::check_path(&dir.join("node_modules"), &[]);
+    assert!(verdict.notes.is_empty(), "a missing path is never handed to git");
+    std::fs::create_dir_all(dir.join("node_modules")).unwrap();
+    std::fs::write(dir.join("package.json"), b"{}").unwrap();
+    let verdict = devpurge::check::check_path(&dir.join("node_modules"), &[]);
+    assert!(verdict.ok);
+    assert!(verdict.notes[0].contains("--no-exec"), "{:?}", verdict.notes);
+    set_no_exec(false);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
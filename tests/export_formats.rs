@@ -0,0 +1,54 @@
+use devpurge::export::{write_csv, write_json};
+use devpurge::CandidateDir;
+use std::path::PathBuf;
+
+fn candidates() -> Vec<CandidateDir> {
+    let mut incomplete = CandidateDir::new(PathBuf::from("/p/web, \"new\"/node_modules"), 2048);
+    incomplete.unreadable = 3;
+    vec![CandidateDir::new(PathBuf::from("/p/api/target"), 4096), incomplete]
+}
+
+#[test]
+fn csv_has_a_header_and_quotes_awkward_paths() {
+    let mut out = Vec::new();
+    write_csv(&candidates(), &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "path,kind,size,reclaimable,is_file,incomplete\n\
+         /p/api/target,target,4096,4096,false,false\n\
+         \"/p/web, \"\"new\"\"/node_modules\",node_modules,2048,2048,false,true\n"
+    );
+}
+
+#[test]
+fn json_lists_the_candidates_as_objects() {
+    let mut out = Vec::new();
+    write_json(&candidates(), &mut out).unwrap();
+    let export: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let rows = &export["candidates"];
+    assert_eq!(rows[0]["path"], "/p/api/target");
+    assert_eq!(rows[0]["kind"], "target");
+    assert_eq!(rows[0]["size"], 4096);
+    assert_eq!(rows[1]["incomplete"], true);
+    assert_eq!(rows[0]["risk"], "safe");
+    assert_eq!(rows.as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn json_sums_up_the_candidates_by_kind() {
+    let mut all = candidates();
+    all.push(CandidateDir::new(PathBuf::from("/p/cli/target"), 1024));
+    let mut out = Vec::new();
+    write_json(&all, &mut out).unwrap();
+    let summary = &serde_json::from_slice::<serde_json::Value>(&out).unwrap()["summary"];
+    assert_eq!(summary["folders"], 3);
+    assert_eq!(summary["size"], 7168);
+    let groups = summary["by_kind"].as_array().unwrap();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0]["kind"], "target");
+    assert_eq!(groups[0]["folders"], 2);
+    assert_eq!(groups[0]["size"], 5120);
+    // A kind with a single folder still gets its own group.
+    assert_eq!(groups[1]["kind"], "node_modules");
+    assert_eq!(groups[1]["folders"], 1);
+}
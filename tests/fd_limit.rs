@@ -0,0 +1,57 @@
+//! Scans under a lowered open-file limit: a sizing thread that runs out of descriptors
+//! waits for the others to close theirs instead of leaving part of a folder out.
+#![cfg(unix)]
+
+use devpurge::fixtures::{parse_spec, Tree};
+use devpurge::limits::fd_exhaustion_count;
+use devpurge::{ScanIter, ScanOptions};
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Far below what sixteen sizing threads, each holding a walk several levels deep, open at once.
+const LIMIT: libc::rlim_t = 20;
+/// Set for the child process, to the tree it scans.
+const CHILD_ROOT: &str = "DEVPURGE_FD_LIMIT_ROOT";
+
+#[test]
+fn a_tree_wider_than_the_limit_is_scanned_in_full() {
+    let mut tree = Tree::new("fd-limit");
+    let spec = "[[projects]]\npath = \"app-{n}\"\necosystem = \"node\"\nsize = \"6B\"\nfiles = 40\ndepth = 10\ncopies = 120";
+    let expected = tree.generate(&parse_spec(spec).unwrap());
+    assert!(expected.len() as u64 > LIMIT);
+
+    let mut child = Command::new(std::env::current_exe().unwrap());
+    child.args(["--exact", "scan_under_the_lowered_limit", "--nocapture", "--test-threads=1"]).env(CHILD_ROOT, &tree.root);
+    // SAFETY: setrlimit is async-signal-safe, and nothing else runs between fork and exec.
+    unsafe {
+        child.pre_exec(|| {
+            let limit = libc::rlimit { rlim_cur: LIMIT, rlim_max: LIMIT };
+            if libc::setrlimit(libc::RLIMIT_NOFILE, &limit) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    let output = child.output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}\n{}", stdout, String::from_utf8_lossy(&output.stderr));
+
+    let mut found: Vec<String> = stdout.lines().filter_map(|line| line.split_once("found ").map(|(_, rest)| rest)).map(String::from).collect();
+    found.sort();
+    let wanted: Vec<String> = expected.iter().map(|e| format!("{} {} 0", tree.relative(&e.path), e.size)).collect();
+    assert_eq!(found, wanted);
+    assert!(stdout.contains("exhausted 0"), "{}", stdout);
+}
+
+/// The child half of the test above; does nothing in a normal test run.
+#[test]
+fn scan_under_the_lowered_limit() {
+    let Some(root) = std::env::var_os(CHILD_ROOT).map(PathBuf::from) else { return };
+    let options = ScanOptions { size_threads: 16, ..ScanOptions::default() };
+    for candidate in ScanIter::new(&root, options) {
+        let candidate = candidate.unwrap();
+        println!("found {} {} {}", candidate.path.strip_prefix(&root).unwrap().display(), candidate.size, candidate.unreadable);
+    }
+    println!("exhausted {}", fd_exhaustion_count());
+}
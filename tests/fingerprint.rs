@@ -0,0 +1,37 @@
+//! The quick look taken at a folder right before deleting it: only a missing folder counts
+//! as gone, and only a significant change means measuring it again.
+
+use devpurge::fixtures::Tree;
+use devpurge::Fingerprint;
+use std::io::ErrorKind;
+
+#[test]
+fn only_significant_changes_count() {
+    let before = Fingerprint { modified: 1_000, entries: 10 };
+    assert!(!before.changed_since(&before));
+    // Within the two seconds FAT and some shares round to.
+    assert!(!Fingerprint { modified: 1_002, ..before }.changed_since(&before));
+    assert!(Fingerprint { modified: 1_003, ..before }.changed_since(&before));
+    assert!(Fingerprint { modified: 997, ..before }.changed_since(&before));
+    assert!(Fingerprint { entries: 11, ..before }.changed_since(&before));
+}
+
+#[test]
+fn a_missing_folder_is_told_apart_from_an_unreadable_one() {
+    let tree = Tree::new("fingerprint");
+    tree.file("app/node_modules/pkg/index.js");
+    let folder = tree.root.join("app/node_modules");
+    assert_eq!(Fingerprint::read(&folder).unwrap().entries, 1);
+    assert_eq!(Fingerprint::read(&tree.root.join("app/target")).unwrap_err().kind(), ErrorKind::NotFound);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&folder, std::fs::Permissions::from_mode(0o000)).unwrap();
+        // Root reads it anyway, so there is nothing to tell apart.
+        if std::fs::read_dir(&folder).is_err() {
+            assert_eq!(Fingerprint::read(&folder).unwrap_err().kind(), ErrorKind::PermissionDenied);
+        }
+        std::fs::set_permissions(&folder, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+}
@@ -0,0 +1,31 @@
+use devpurge::floors::parse_size_floors;
+
+#[test]
+fn bare_numbers_stay_megabytes() {
+    let floors = parse_size_floors("500").unwrap();
+    assert_eq!(floors.default, 500 << 20);
+    assert!(floors.kinds.is_empty());
+}
+
+#[test]
+fn per_kind_rules_override_the_default() {
+    let floors = parse_size_floors("node_modules=200M, target=1G,default=50M").unwrap();
+    assert_eq!(floors.rule_for("target"), ("target", 1 << 30));
+    assert_eq!(floors.rule_for("node_modules"), ("node_modules", 200 << 20));
+    assert_eq!(floors.rule_for("__pycache__"), ("default", 50 << 20));
+}
+
+#[test]
+fn unknown_kinds_and_bad_sizes_are_named() {
+    let err = parse_size_floors("node_module=200M").unwrap_err();
+    assert!(err.contains("unknown target 'node_module'"), "{}", err);
+    let err = parse_size_floors("target=1Q").unwrap_err();
+    assert!(err.contains("target=1Q"), "{}", err);
+}
+
+#[test]
+fn rules_read_back_as_the_same_floors() {
+    let floors = parse_size_floors("node_modules=200M,target=1.5G,default=1000B").unwrap();
+    assert_eq!(floors.rules(), "node_modules=200M,target=1536M,default=1000B");
+    assert_eq!(parse_size_floors(&floors.rules()).unwrap(), floors);
+}
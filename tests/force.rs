@@ -0,0 +1,58 @@
+//! Entries the filesystem refuses to remove are made removable and tried once more: the
+//! read-only attribute on Windows, the folder's permissions with `--force` on Unix.
+
+use devpurge::failures::describe_error;
+use devpurge::filesystem::{FaultyFs, Op};
+use devpurge::fixtures::Tree;
+use devpurge::remove::{apply_action_with_progress, remove_candidate, Action};
+use devpurge::CandidateDir;
+use std::fs;
+use std::path::PathBuf;
+
+/// A node_modules with a file in a subfolder.
+fn fixture(name: &str) -> Tree {
+    let tree = Tree::new(name);
+    tree.file("app/package.json").file("app/node_modules/pkg/index.js");
+    tree
+}
+
+#[test]
+fn read_only_files_are_deleted() {
+    let tree = fixture("force-read-only");
+    let file = tree.root.join("app/node_modules/pkg/index.js");
+    let mut permissions = fs::metadata(&file).unwrap().permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(&file, permissions).unwrap();
+
+    remove_candidate(&CandidateDir::new(tree.root.join("app/node_modules"), 1)).unwrap();
+    assert!(!tree.root.join("app/node_modules").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn force_makes_the_folder_writable_and_retries_only_the_refused_entry() {
+    use std::os::unix::fs::PermissionsExt;
+    let delete = |fs: &FaultyFs, candidate: &CandidateDir, force: bool| apply_action_with_progress(fs, candidate, Action::Delete, force, &mut |_| {});
+
+    let tree = fixture("force-unix");
+    let folder = tree.root.join("app/node_modules/pkg");
+    let candidate = CandidateDir::new(tree.root.join("app/node_modules"), 1);
+    fs::set_permissions(&folder, fs::Permissions::from_mode(0o555)).unwrap();
+
+    let refused = delete(&FaultyFs::new().deny_nth_file(1), &candidate, false).unwrap_err();
+    assert_eq!(describe_error(&refused), "permission denied");
+
+    let faulty = FaultyFs::new().deny_nth_file(1);
+    delete(&faulty, &candidate, true).unwrap();
+    assert!(!candidate.path.exists());
+    let changed: Vec<PathBuf> = faulty.calls().into_iter().filter(|(op, _)| *op == Op::SetPermissions).map(|(_, path)| path).collect();
+    assert_eq!(changed, [folder]);
+
+    // Refused again after the retry: the summary names the entry.
+    let tree = fixture("force-unix-still");
+    let folder = tree.root.join("app/node_modules/pkg");
+    fs::set_permissions(&folder, fs::Permissions::from_mode(0o555)).unwrap();
+    let error = delete(&FaultyFs::new().deny_nth_file(1).deny_nth_file(2), &CandidateDir::new(tree.root.join("app/node_modules"), 1), true).unwrap_err();
+    assert!(describe_error(&error).starts_with(&format!("permission denied at {}", folder.join("index.js").display())), "{}", describe_error(&error));
+    fs::set_permissions(&folder, fs::Permissions::from_mode(0o755)).unwrap();
+}
@@ -0,0 +1,38 @@
+//! `--gitignored-only` and the "not gitignored" marker: one `git check-ignore` per repository.
+
+use devpurge::fixtures::Tree;
+use devpurge::gitignore::{repository_of, statuses, GitStatus};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn git_init(dir: &Path, ignore: &str) {
+    let status = Command::new("git").arg("init").arg("-q").arg(dir).status().expect("git is installed");
+    assert!(status.success());
+    fs::write(dir.join(".gitignore"), ignore).unwrap();
+}
+
+#[test]
+fn ignored_committed_nested_and_outside() {
+    let tree = Tree::new("gitignore");
+    tree.dir("repo/app/node_modules").dir("repo/vendor").dir("repo/inner/target").dir("loose/node_modules");
+    git_init(&tree.root.join("repo"), "node_modules/\n");
+    // A nested repository answers for its own folders.
+    git_init(&tree.root.join("repo/inner"), "/target\n");
+
+    let paths = ["repo/app/node_modules", "repo/vendor", "repo/inner/target", "loose/node_modules"].map(|p| tree.root.join(p));
+    let found = statuses(paths.iter().map(|p| p.as_path()));
+    assert_eq!(found.get(&paths[0]), Some(&GitStatus::Ignored));
+    assert_eq!(found.get(&paths[1]), Some(&GitStatus::NotIgnored));
+    assert_eq!(found.get(&paths[2]), Some(&GitStatus::Ignored));
+    assert_eq!(found.get(&paths[3]), None);
+    assert_eq!(repository_of(&paths[2]), Some(tree.root.join("repo/inner").as_path()));
+}
+
+#[test]
+fn a_worktree_is_its_own_repository() {
+    let tree = Tree::new("gitignore-worktree");
+    tree.dir("wt/node_modules");
+    fs::write(tree.root.join("wt/.git"), "gitdir: /elsewhere/.git/worktrees/wt\n").unwrap();
+    assert_eq!(repository_of(&tree.root.join("wt/node_modules")), Some(tree.root.join("wt").as_path()));
+}
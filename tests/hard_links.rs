@@ -0,0 +1,22 @@
+//! Hard links inside a candidate: each file counts once, however many names it has.
+
+use devpurge::detect::measure;
+use devpurge::fixtures::{parse_spec, Tree};
+use devpurge::{ScanIter, ScanOptions};
+use std::fs;
+
+const MB: u64 = 1024 * 1024;
+
+#[test]
+fn a_file_with_two_names_counts_once() {
+    let mut tree = Tree::new("hard-links");
+    tree.generate(&parse_spec("[[projects]]\npath = \"app\"\necosystem = \"rust\"\nsize = \"0B\"").unwrap());
+    let target = tree.root.join("app/target");
+    fs::write(target.join("big.bin"), vec![1u8; MB as usize]).unwrap();
+    fs::create_dir(target.join("deps")).unwrap();
+    fs::hard_link(target.join("big.bin"), target.join("deps/big-copy.bin")).unwrap();
+
+    assert_eq!(measure(&target, |_, _| {}).bytes, MB);
+    let found: Vec<_> = ScanIter::new(&tree.root, ScanOptions::default()).filter_map(Result::ok).collect();
+    assert_eq!(found[0].size, MB);
+}
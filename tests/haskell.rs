@@ -0,0 +1,28 @@
+//! Haskell build output from stack and cabal, each next to its own project files.
+
+use devpurge::fixtures::Tree;
+
+#[test]
+fn stack_and_cabal_layouts_are_found() {
+    let tree = Tree::new("haskell");
+    tree.file("stacked/stack.yaml")
+        .file("stacked/.stack-work/install/x86_64-linux/ghc-9.6/lib/Main.o")
+        .file("cabaled/cabal.project")
+        .file("cabaled/dist-newstyle/build/x86_64-linux/ghc-9.6/app-0.1/build/Main.o")
+        // A lone package with only its .cabal file works for both tools.
+        .file("pkg/pkg.cabal")
+        .file("pkg/.stack-work/dist/x")
+        .file("pkg/dist-newstyle/cache/plan.json")
+        // Build output inside is never walked, even when it looks like a project.
+        .file("pkg/dist-newstyle/src/dep-1.0/dep.cabal")
+        .file("pkg/dist-newstyle/src/dep-1.0/dist-newstyle/x");
+
+    assert_eq!(tree.found(), ["cabaled/dist-newstyle", "pkg/.stack-work", "pkg/dist-newstyle", "stacked/.stack-work"]);
+}
+
+#[test]
+fn folders_without_project_files_are_skipped() {
+    let tree = Tree::new("haskell-unmarked");
+    tree.file("other/.stack-work/x").file("other/dist-newstyle/x");
+    assert!(tree.found().is_empty());
+}
@@ -0,0 +1,30 @@
+//! Restore hints after a cleanup, one line per project.
+
+use devpurge::fixtures::Tree;
+use devpurge::hints::regeneration_hints;
+use devpurge::{ScanIter, ScanOptions};
+
+#[test]
+fn hints_are_grouped_by_project_and_follow_the_kind() {
+    let tree = Tree::new("hints");
+    tree.file("api/package.json")
+        .file("api/Cargo.toml")
+        .file("api/node_modules/x/index.js")
+        .file("api/target/debug/api")
+        .file("app/CMakeLists.txt")
+        .file("app/build-desktop-Debug/CMakeCache.txt")
+        .file("web/package.json")
+        .file("web/node_modules/y/index.js");
+    let found: Vec<_> = ScanIter::new(&tree.root, ScanOptions::default()).filter_map(Result::ok).collect();
+
+    let hints = regeneration_hints(&found);
+    let lines: Vec<(String, Vec<(String, &str)>)> = hints.into_iter().map(|h| (tree.relative(&h.project), h.restores)).collect();
+    assert_eq!(
+        lines,
+        [
+            ("api".to_string(), vec![("node_modules".to_string(), "npm install"), ("target".to_string(), "cargo build")]),
+            ("app".to_string(), vec![("build-*".to_string(), "rebuild in Qt Creator")]),
+            ("web".to_string(), vec![("node_modules".to_string(), "npm install")]),
+        ]
+    );
+}
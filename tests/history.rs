@@ -0,0 +1,160 @@
+use devpurge::history::{append_record, checksum, common_root, read_records, DeletionRecord, RunRecord, Totals};
+use devpurge::stats::{load_stats, read_stats, record_run};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("devpurge-history-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run(thread: u64, n: u64) -> RunRecord {
+    RunRecord {
+        timestamp: 1_700_000_000 + thread * 1000 + n,
+        root: PathBuf::from(format!("/projects/{}", thread)),
+        folders_deleted: n + 1,
+        bytes_reclaimed: (thread + 1) * 1_000_000 + n,
+        by_ecosystem: BTreeMap::from([("rust".to_string(), Totals { folders: n + 1, bytes: (thread + 1) * 1_000_000 + n })]),
+    }
+}
+
+#[test]
+fn parallel_writers_lose_nothing() {
+    const THREADS: u64 = 8;
+    const RUNS: u64 = 50;
+    let dir = scratch("parallel");
+    let (log, totals) = (dir.join("history.jsonl"), dir.join("stats.json"));
+
+    std::thread::scope(|scope| {
+        for thread in 0..THREADS {
+            let (log, totals) = (&log, &totals);
+            scope.spawn(move || {
+                for n in 0..RUNS {
+                    append_record(log, &run(thread, n)).unwrap();
+                    record_run(totals, &run(thread, n)).unwrap();
+                }
+            });
+        }
+    });
+
+    let (records, damaged): (Vec<RunRecord>, usize) = read_records(&log).unwrap();
+    assert_eq!(damaged, 0);
+    assert_eq!(records.len() as u64, THREADS * RUNS);
+
+    let stats = load_stats(&totals);
+    assert_eq!(stats.runs, THREADS * RUNS);
+    assert_eq!(stats.bytes_reclaimed, records.iter().map(|r| r.bytes_reclaimed).sum::<u64>());
+    assert_eq!(stats.folders_deleted, records.iter().map(|r| r.folders_deleted).sum::<u64>());
+    assert_eq!(stats.by_ecosystem["rust"], Totals { folders: stats.folders_deleted, bytes: stats.bytes_reclaimed });
+    assert_eq!(stats.first_run, Some(1_700_000_000));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn damaged_lines_are_skipped() {
+    let dir = scratch("damaged");
+    let log = dir.join("history.jsonl");
+    append_record(&log, &run(0, 0)).unwrap();
+    append_record(&log, &run(0, 1)).unwrap();
+
+    let mut file = OpenOptions::new().append(true).open(&log).unwrap();
+    // A record whose numbers were altered after it was written, then a torn final line.
+    let tampered = fs::read_to_string(&log).unwrap().lines().next().unwrap().replace("\"folders_deleted\":1", "\"folders_deleted\":9");
+    writeln!(file, "{}", tampered).unwrap();
+    write!(file, "{{\"sum\":\"0123\",\"entry\":{{\"timest").unwrap();
+    drop(file);
+    append_record(&log, &run(0, 2)).unwrap();
+
+    let (records, damaged): (Vec<RunRecord>, usize) = read_records(&log).unwrap();
+    assert_eq!(records, [run(0, 0), run(0, 1), run(0, 2)]);
+    assert_eq!(damaged, 2);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn deletion_records_keep_the_error_of_a_failed_folder() {
+    let dir = scratch("deletions");
+    let log = dir.join("deletions.jsonl");
+    let record = |path: &str, complete: bool, error: Option<&str>| DeletionRecord {
+        timestamp: 1_700_000_000,
+        path: PathBuf::from(path),
+        kind: "node_modules".to_string(),
+        action: "delete".to_string(),
+        size: 4096,
+        complete,
+        error: error.map(str::to_string),
+    };
+    append_record(&log, &record("/work/a/node_modules", true, None)).unwrap();
+    append_record(&log, &record("/work/b/node_modules", false, Some("permission denied"))).unwrap();
+
+    assert!(!fs::read_to_string(&log).unwrap().lines().next().unwrap().contains("error"));
+    let (records, damaged): (Vec<DeletionRecord>, usize) = read_records(&log).unwrap();
+    assert_eq!(records, [record("/work/a/node_modules", true, None), record("/work/b/node_modules", false, Some("permission denied"))]);
+    assert_eq!(damaged, 0);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn runs_recorded_before_the_ecosystem_breakdown_still_read() {
+    let dir = scratch("old-runs");
+    let log = dir.join("history.jsonl");
+    let body = r#"{"timestamp":1600000000,"root":"/projects/old","folders_deleted":3,"bytes_reclaimed":42}"#;
+    fs::write(&log, format!("{{\"sum\":\"{}\",\"entry\":{}}}\n", checksum(body.as_bytes()), body)).unwrap();
+
+    let (records, damaged): (Vec<RunRecord>, usize) = read_records(&log).unwrap();
+    assert_eq!(damaged, 0);
+    assert_eq!(records[0].bytes_reclaimed, 42);
+    assert!(records[0].by_ecosystem.is_empty());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn damaged_totals_start_again() {
+    let dir = scratch("damaged-totals");
+    let totals = dir.join("stats.json");
+    fs::write(&totals, "{\"runs\": 4, \"bytes_recl").unwrap();
+
+    assert!(read_stats(&totals).unwrap_err().contains("damaged"));
+    let stats = record_run(&totals, &run(0, 0)).unwrap();
+    assert_eq!(stats.runs, 1);
+    assert_eq!(load_stats(&totals), stats);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn the_checksum_covers_the_entry_as_written() {
+    let dir = scratch("as-written");
+    let log = dir.join("history.jsonl");
+    // Written by another version: fields in another order, spaces, and a field this one
+    // doesn't know. Serializing it again would give other text, but it is intact.
+    let body = r#"{ "root": "/projects/new", "timestamp": 1700000000, "bytes_reclaimed": 7, "folders_deleted": 1, "host": "builder" }"#;
+    fs::write(&log, format!("{{\"sum\":\"{}\",\"entry\":{}}}\n", checksum(body.as_bytes()), body)).unwrap();
+    // The same entry with its text changed after the sum was taken.
+    let changed = body.replace("\"bytes_reclaimed\": 7", "\"bytes_reclaimed\": 7 ");
+    OpenOptions::new().append(true).open(&log).unwrap().write_all(format!("{{\"sum\":\"{}\",\"entry\":{}}}\n", checksum(body.as_bytes()), changed).as_bytes()).unwrap();
+
+    let (records, damaged): (Vec<RunRecord>, usize) = read_records(&log).unwrap();
+    assert_eq!(damaged, 1);
+    assert_eq!(records.len(), 1);
+    assert_eq!((records[0].root.clone(), records[0].bytes_reclaimed), (PathBuf::from("/projects/new"), 7));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn runs_without_a_scan_root_record_the_folder_holding_what_they_cleaned() {
+    let paths = [Path::new("/work/api/node_modules"), Path::new("/work/web/app/target"), Path::new("/work/api/target")];
+    assert_eq!(common_root(paths), Path::new("/work"));
+    assert_eq!(common_root([Path::new("/work/api/node_modules")]), Path::new("/work/api"));
+    assert_eq!(common_root([Path::new("/work/api/target"), Path::new("/home/me/app/target")]), Path::new("/"));
+    assert_eq!(common_root([]), PathBuf::new());
+}
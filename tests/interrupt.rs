@@ -0,0 +1,50 @@
+//! Ctrl-C during a scan stops the walk where it is and keeps what was already found.
+
+use devpurge::fixtures::Tree;
+use devpurge::{ScanIter, ScanOptions};
+use std::cell::Cell;
+use std::rc::Rc;
+
+fn tree() -> Tree {
+    let tree = Tree::new("interrupt");
+    for n in 0..6 {
+        tree.file(&format!("app-{}/package.json", n)).file(&format!("app-{}/node_modules/pkg/index.js", n));
+    }
+    tree
+}
+
+/// What a scan with `size_threads` finds when it is stopped after `dirs` directories.
+fn stopped_after(tree: &Tree, dirs: usize, size_threads: usize) -> Vec<String> {
+    let seen = Rc::new(Cell::new(0));
+    let (counted, checked) = (seen.clone(), seen.clone());
+    let options = ScanOptions { size_threads, ..ScanOptions::default() };
+    let mut found: Vec<String> = ScanIter::new(&tree.root, options)
+        .on_dir(move |_| counted.set(counted.get() + 1))
+        .stop_when(move || checked.get() >= dirs)
+        .filter_map(Result::ok)
+        .map(|c| tree.relative(&c.path))
+        .collect();
+    found.sort();
+    found
+}
+
+#[test]
+fn a_stopped_scan_ends_early_with_what_it_found() {
+    let tree = tree();
+    let all = stopped_after(&tree, usize::MAX, 1);
+    assert_eq!(all.len(), 6);
+
+    // The root, then the first project and its node_modules.
+    let early = stopped_after(&tree, 3, 1);
+    assert_eq!(early.len(), 1);
+    assert!(all.contains(&early[0]));
+    assert_eq!(stopped_after(&tree, 0, 1), Vec::<String>::new());
+}
+
+#[test]
+fn a_stopped_scan_sizing_on_threads_ends_too() {
+    let tree = tree();
+    let early = stopped_after(&tree, 3, 4);
+    assert!(early.len() <= 1, "{:?}", early);
+    assert_eq!(stopped_after(&tree, usize::MAX, 4).len(), 6);
+}
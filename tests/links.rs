@@ -0,0 +1,101 @@
+//! Links and junctions inside candidates: never counted, never followed when deleting.
+
+use devpurge::detect::measure;
+use devpurge::fixtures::{parse_spec, Tree};
+use devpurge::remove::remove_candidate;
+use devpurge::{CandidateDir, ScanIter, ScanOptions};
+use std::fs;
+use std::path::Path;
+
+/// A 64 KiB `shared` folder, and an app whose node_modules holds 4 bytes of its own.
+fn fixture(name: &str) -> Tree {
+    let mut tree = Tree::new(&format!("links-{}", name));
+    let spec = parse_spec(
+        r#"
+        [[projects]]
+        path = "shared"
+        ecosystem = "rust"
+        size = "64K"
+
+        [[projects]]
+        path = "app"
+        ecosystem = "node"
+        size = "4B"
+        "#,
+    )
+    .unwrap();
+    tree.generate(&spec);
+    tree
+}
+
+/// Bytes of every file in the tree, counted without following any link.
+fn bytes_on_disk(root: &Path) -> u64 {
+    walkdir::WalkDir::new(root)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.metadata().unwrap().len())
+        .sum()
+}
+
+#[test]
+fn sizing_skips_link_targets() {
+    let tree = fixture("size");
+    tree.link("app/node_modules/linked", "shared");
+
+    let measured = measure(&tree.root.join("app/node_modules"), |_, _| {});
+    assert_eq!(measured.bytes, 4);
+    assert_eq!(measured.links, 1);
+}
+
+#[test]
+fn deleting_a_candidate_keeps_link_targets() {
+    let tree = fixture("delete");
+    tree.link("app/node_modules/linked", "shared");
+
+    remove_candidate(&CandidateDir::new(tree.root.join("app/node_modules"), 4)).unwrap();
+    assert!(!tree.root.join("app/node_modules").exists());
+    assert!(tree.root.join("shared/target/f0.bin").exists());
+}
+
+#[test]
+fn a_candidate_that_is_a_link_only_loses_the_link() {
+    let tree = fixture("self");
+    tree.dir("other").link("other/node_modules", "shared");
+
+    remove_candidate(&CandidateDir::new(tree.root.join("other/node_modules"), 0)).unwrap();
+    assert!(fs::symlink_metadata(tree.root.join("other/node_modules")).is_err());
+    assert!(tree.root.join("shared/target/f0.bin").exists());
+}
+
+#[test]
+fn the_measured_size_is_what_deleting_frees() {
+    let tree = fixture("freed");
+    tree.link("app/node_modules/linked", "shared");
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(tree.root.join("shared/target/f0.bin"), tree.root.join("app/node_modules/file-link")).unwrap();
+
+    let measured = measure(&tree.root.join("app/node_modules"), |_, _| {});
+    let before = bytes_on_disk(&tree.root);
+    remove_candidate(&CandidateDir::new(tree.root.join("app/node_modules"), measured.bytes)).unwrap();
+    assert_eq!(before - bytes_on_disk(&tree.root), measured.bytes);
+}
+
+#[test]
+fn a_candidate_that_is_a_link_measures_nothing() {
+    let tree = fixture("self-size");
+    tree.dir("other").link("other/node_modules", "shared");
+
+    let measured = measure(&tree.root.join("other/node_modules"), |_, _| {});
+    assert_eq!((measured.bytes, measured.links), (0, 0));
+}
+
+#[test]
+fn the_scan_does_not_follow_links() {
+    let tree = fixture("scan");
+    tree.link("mirror", "shared").link("app/loop", ".");
+
+    let mut found: Vec<String> = ScanIter::new(&tree.root, ScanOptions::default()).filter_map(Result::ok).map(|c| tree.relative(&c.path)).collect();
+    found.sort();
+    assert_eq!(found, ["app/node_modules", "shared/target"]);
+}
@@ -0,0 +1,44 @@
+//! Trees deeper than Windows' 260-character path limit are sized and deleted like any
+//! other, and the extended-length form never reaches what is shown.
+
+use devpurge::detect::calculate_size;
+use devpurge::fixtures::Tree;
+use devpurge::longpath::{extended, friendly};
+use devpurge::remove::remove_candidate;
+use devpurge::CandidateDir;
+use std::path::Path;
+
+#[test]
+fn a_tree_past_max_path_is_sized_and_deleted() {
+    let tree = Tree::new("long-paths");
+    tree.file("app/package.json");
+    let nested: Vec<String> = (0..16).map(|level| format!("package-with-a-long-name-{:02}", level)).collect();
+    let deepest = tree.root.join(format!("app/node_modules/{}/index.js", nested.join("/node_modules/")));
+    assert!(deepest.as_os_str().len() > 260);
+    // Made the way DevPurge reaches it, since Windows refuses the plain form.
+    std::fs::create_dir_all(extended(deepest.parent().unwrap())).unwrap();
+    std::fs::write(extended(&deepest), vec![0u8; 1000]).unwrap();
+
+    let candidate_path = tree.root.join("app/node_modules");
+    assert_eq!(calculate_size(&candidate_path), 1000);
+    remove_candidate(&CandidateDir::new(candidate_path.clone(), 1000)).unwrap();
+    assert!(!candidate_path.exists());
+    assert!(tree.root.join("app/package.json").exists());
+}
+
+#[test]
+fn the_extended_prefix_is_never_shown() {
+    assert_eq!(friendly(Path::new(r"\\?\C:\work\app")), Path::new(r"C:\work\app"));
+    assert_eq!(friendly(Path::new(r"\\?\UNC\server\share\app")), Path::new(r"\\server\share\app"));
+    assert_eq!(friendly(Path::new("/home/me/app")), Path::new("/home/me/app"));
+}
+
+#[cfg(windows)]
+#[test]
+fn windows_paths_are_made_absolute_and_extended() {
+    let relative = extended(Path::new(r"work\app"));
+    assert!(relative.to_str().unwrap().starts_with(r"\\?\"), "{}", relative.display());
+    assert!(relative.ends_with(r"work\app"));
+    assert_eq!(extended(Path::new(r"\\server\share\app")), Path::new(r"\\?\UNC\server\share\app"));
+    assert_eq!(extended(Path::new(r"\\?\C:\app")), Path::new(r"\\?\C:\app"));
+}
@@ -0,0 +1,39 @@
+//! `--max-depth` bounds where folders are looked for, never how they are sized.
+
+use devpurge::fixtures::Tree;
+use devpurge::{ScanIter, ScanOptions, ScanScope};
+
+fn scan(tree: &Tree, max_depth: Option<usize>) -> Vec<(String, u64)> {
+    let options = ScanOptions { max_depth, ..ScanOptions::default() };
+    let mut found: Vec<(String, u64)> = ScanIter::new(&tree.root, options).filter_map(Result::ok).map(|c| (tree.relative(&c.path), c.size)).collect();
+    found.sort();
+    found
+}
+
+#[test]
+fn folders_below_the_depth_are_not_found_but_found_ones_are_sized_fully() {
+    let tree = Tree::new("max-depth");
+    tree.file("app/package.json")
+        .file("app/node_modules/a/b/c/d/e/index.js")
+        .file("clients/acme/web/package.json")
+        .file("clients/acme/web/node_modules/x/index.js");
+
+    assert_eq!(scan(&tree, Some(0)), []);
+    assert_eq!(scan(&tree, Some(1)), []);
+    let shallow = scan(&tree, Some(2));
+    assert_eq!(shallow.len(), 1);
+    assert_eq!(shallow[0].0, "app/node_modules");
+    assert_eq!(shallow, scan(&tree, None)[..1]);
+    assert_eq!(scan(&tree, Some(4)).len(), 2);
+}
+
+#[test]
+fn a_shallow_cache_does_not_answer_a_deeper_scan() {
+    let tree = Tree::new("max-depth-cache");
+    let scope = |max_depth| ScanScope::new(&tree.root, &ScanOptions { max_depth, ..ScanOptions::default() });
+
+    assert!(scope(Some(2)).answers(&scope(Some(3))).is_err());
+    assert!(scope(Some(2)).answers(&scope(None)).is_err());
+    assert!(scope(Some(3)).answers(&scope(Some(2))).is_ok());
+    assert!(scope(None).answers(&scope(Some(2))).is_ok());
+}
@@ -0,0 +1,159 @@
+//! The TOML subset config files and fixture specs are read with, and the in-place edits
+//! made to config files.
+
+use devpurge::config::{add_to_list, parse_config, toml_string};
+use devpurge::minitoml::parse;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[test]
+fn strings_keep_what_is_quoted() {
+    let text = r#"
+basic = "a # not a comment = still the value"
+literal = 'C:\Users\me # kept'
+empty = ""
+"quoted key" = 1
+'literal key' = 2
+"#;
+    assert_eq!(
+        parse(text).unwrap(),
+        json!({
+            "basic": "a # not a comment = still the value",
+            "literal": r"C:\Users\me # kept",
+            "empty": "",
+            "quoted key": 1,
+            "literal key": 2,
+        })
+    );
+}
+
+#[test]
+fn escapes_in_basic_strings() {
+    let cases = [
+        (r#"s = "tab\there""#, "tab\there"),
+        (r#"s = "line\nbreak\r""#, "line\nbreak\r"),
+        (r#"s = "say \"hi\"""#, "say \"hi\""),
+        (r#"s = "back\\slash\\""#, "back\\slash\\"),
+        (r#"s = "caf\u00e9""#, "café"),
+        (r#"s = "\U0001F600""#, "\u{1F600}"),
+        (r##"s = "\"#quoted hash\"" # comment"##, "\"#quoted hash\""),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(parse(text).unwrap(), json!({ "s": expected }), "{}", text);
+    }
+    for text in [r#"s = "\q""#, r#"s = "\u12""#, r#"s = "\uD800""#, r#"s = "open"#, "s = 'open"] {
+        assert!(parse(text).is_err(), "{}", text);
+    }
+}
+
+#[test]
+fn arrays_may_span_lines_with_comments() {
+    let text = "
+paths = [
+  \"/a\",   # first
+  '/b # not a comment',
+  # a line of its own
+  \"/c ]\",
+]
+nested = [[1, 2], [\"x\"], []]
+after = true
+";
+    assert_eq!(
+        parse(text).unwrap(),
+        json!({ "paths": ["/a", "/b # not a comment", "/c ]"], "nested": [[1, 2], ["x"], []], "after": true })
+    );
+    assert_eq!(parse("paths = [\n  \"/a\",\n").unwrap_err(), "line 1: unterminated array");
+    assert!(parse("paths = [\"/a\" \"/b\"]").is_err());
+}
+
+#[test]
+fn values_tables_and_keys() {
+    let text = "
+# top-level values
+count = 1_000
+ratio = 1.5
+negative = -3
+enabled = false
+colors.size = \"red\"
+
+[scan]
+max_depth = 4
+point = { x = 1, y.z = \"deep\" }
+
+[[projects]]
+path = \"a\"
+
+[[projects]]
+path = \"b\"
+";
+    assert_eq!(
+        parse(text).unwrap(),
+        json!({
+            "count": 1000,
+            "ratio": 1.5,
+            "negative": -3,
+            "enabled": false,
+            "colors": { "size": "red" },
+            "scan": { "max_depth": 4, "point": { "x": 1, "y": { "z": "deep" } } },
+            "projects": [{ "path": "a" }, { "path": "b" }],
+        })
+    );
+}
+
+#[test]
+fn mistakes_name_their_line() {
+    let cases = [
+        ("a = 1\na = 2", "line 2: duplicate key 'a'"),
+        ("a = 1\n[a]", "line 2: 'a' is not a table"),
+        ("[table", "line 1: unterminated table header"),
+        ("\n\njust words", "line 3: expected 'key = value'"),
+        ("a = 1 2", "line 1: unexpected trailing characters"),
+        ("a = yes", "line 1: invalid value 'yes'"),
+        ("a =", "line 1: missing value"),
+    ];
+    for (text, error) in cases {
+        assert_eq!(parse(text).unwrap_err(), error, "{:?}", text);
+    }
+}
+
+#[test]
+fn quoted_strings_read_back_unchanged() {
+    for text in ["plain", r"C:\Users\me", "say \"hi\"", "tab\tand\nnewline", "# hash = equals", "it's [bracketed]", "café \u{1F600}"] {
+        assert_eq!(parse(&format!("s = {}", toml_string(text))).unwrap(), json!({ "s": text }), "{}", text);
+    }
+}
+
+#[test]
+fn added_paths_read_back_and_the_rest_is_kept() {
+    let text = "\
+# My settings
+protected = [ # things I keep
+  \"/keep [old]\", # a bracket in a string
+  '/literal',
+] # end of the list
+set_title = true
+
+[colors]
+size = [\"1M\", \"2M\", \"3M\"]
+";
+    let added = [PathBuf::from("/with \"quotes\""), PathBuf::from(r"C:\back\slash"), PathBuf::from("/has # hash")];
+    let updated = add_to_list(text, "protected", &added).unwrap();
+    let config = parse_config(&updated).unwrap();
+    let mut expected = vec![PathBuf::from("/keep [old]"), PathBuf::from("/literal")];
+    expected.extend(added.iter().cloned());
+    assert_eq!(config.protected, expected);
+    assert!(config.set_title);
+    // Only the new items were written; every other line is as it was.
+    assert!(updated.starts_with("# My settings\nprotected = [ # things I keep\n  \"/keep [old]\", # a bracket in a string\n  '/literal',\n"));
+    assert!(updated.ends_with("] # end of the list\nset_title = true\n\n[colors]\nsize = [\"1M\", \"2M\", \"3M\"]\n"));
+
+    // Adding again appends to what was added.
+    let again = add_to_list(&updated, "protected", &[PathBuf::from("/d")]).unwrap();
+    assert_eq!(parse_config(&again).unwrap().protected.last(), Some(&PathBuf::from("/d")));
+
+    // A list that isn't there yet goes above the first table.
+    let excluded = add_to_list(&updated, "excludes", &[PathBuf::from("/e")]).unwrap();
+    assert!(excluded.contains("set_title = true\n\nexcludes = [\"/e\"]\n[colors]"));
+    assert_eq!(parse_config(&excluded).unwrap().excludes, [PathBuf::from("/e")]);
+    assert_eq!(parse_config(&add_to_list("", "excludes", &[PathBuf::from("/e")]).unwrap()).unwrap().excludes, [PathBuf::from("/e")]);
+}
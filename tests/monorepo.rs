@@ -0,0 +1,93 @@
+//! Shared `target` and `node_modules` folders at workspace roots, and the members they serve.
+
+use devpurge::activity::candidate_activity;
+use devpurge::fixtures::Tree;
+use devpurge::monorepo::shared_by;
+use devpurge::{ScanIter, ScanOptions};
+use std::fs;
+
+fn names(tree: &Tree, members: &[std::path::PathBuf]) -> Vec<String> {
+    members.iter().map(|m| tree.relative(m)).collect()
+}
+
+#[test]
+fn cargo_members_come_from_globs_minus_excludes() {
+    let tree = Tree::new("monorepo-cargo");
+    tree.file("crates/core/Cargo.toml").file("crates/cli/Cargo.toml").file("crates/old/Cargo.toml");
+    tree.file("crates/notes/README.md").file("tools/gen/Cargo.toml").dir("target");
+    fs::write(
+        tree.root.join("Cargo.toml"),
+        "[package]\nname = \"app\"\n\n[workspace]\nmembers = [\n    \"crates/*\", # every crate\n    \"tools/gen\",\n]\nexclude = [\"crates/old\"]\n\n[workspace.dependencies]\nserde = \"1\"\n",
+    )
+    .unwrap();
+
+    let workspace = shared_by(&tree.root.join("target")).unwrap();
+    assert_eq!(workspace.tool, "cargo");
+    assert_eq!(names(&tree, &workspace.members), ["", "crates/cli", "crates/core", "tools/gen"]);
+    assert_eq!(shared_by(&tree.root.join("crates/core/target")), None, "not a workspace root");
+}
+
+#[test]
+fn npm_and_pnpm_workspaces_are_read() {
+    let tree = Tree::new("monorepo-js");
+    tree.file("web/packages/ui/package.json").file("web/packages/api/package.json").file("web/apps/site/package.json");
+    tree.file("web/apps/demo/package.json").dir("web/node_modules");
+    fs::write(tree.root.join("web/package.json"), r#"{ "workspaces": { "packages": ["packages/*", "apps/**", "!apps/demo"] } }"#).unwrap();
+    let npm = shared_by(&tree.root.join("web/node_modules")).unwrap();
+    assert_eq!((npm.tool, names(&tree, &npm.members)), ("npm", vec!["web/apps/site".to_string(), "web/packages/api".to_string(), "web/packages/ui".to_string()]));
+
+    fs::write(tree.root.join("web/pnpm-workspace.yaml"), "packages:\n  - 'packages/*'\n  - \"!packages/api\"\ncatalog:\n  - react\n").unwrap();
+    let pnpm = shared_by(&tree.root.join("web/node_modules")).unwrap();
+    assert_eq!((pnpm.tool, names(&tree, &pnpm.members)), ("pnpm", vec!["web/packages/ui".to_string()]));
+}
+
+#[test]
+fn scans_attach_members_and_idle_counts_every_member() {
+    let tree = Tree::new("monorepo-scan");
+    tree.file("app/src/main.rs").file("app/target/debug/app").file("lib/Cargo.toml").file("lib/src/lib.rs");
+    fs::write(tree.root.join("app/Cargo.toml"), "[package]\nname = \"app\"\n\n[workspace]\nmembers = [\"../lib\"]\n").unwrap();
+    let old = std::time::SystemTime::now() - std::time::Duration::from_secs(90 * 24 * 60 * 60);
+    for file in ["app/Cargo.toml", "app/src/main.rs"] {
+        fs::File::options().write(true).open(tree.root.join(file)).unwrap().set_modified(old).unwrap();
+    }
+
+    let found: Vec<_> = ScanIter::new(tree.root.join("app"), ScanOptions::default()).filter_map(Result::ok).collect();
+    assert_eq!(found.len(), 1);
+    assert_eq!(names(&tree, &found[0].serves), ["app", "lib"]);
+
+    // The member outside the scanned tree was edited just now, so the workspace isn't idle.
+    let newest = fs::metadata(tree.root.join("lib/src/lib.rs")).unwrap().modified().unwrap();
+    assert_eq!(candidate_activity(&found[0]), Some(devpurge::units::unix_secs(newest)));
+}
+
+#[cfg(unix)]
+#[test]
+fn member_folders_name_their_workspace_and_count_only_what_they_hold() {
+    use std::os::unix::fs::symlink;
+
+    let tree = Tree::new("monorepo-members");
+    tree.file("web/packages/ui/package.json").file("web/node_modules/.pnpm/react@18/node_modules/react/index.js");
+    tree.file("web/packages/ui/node_modules/.bin/tool");
+    fs::write(tree.root.join("web/package.json"), "{}").unwrap();
+    fs::write(tree.root.join("web/pnpm-workspace.yaml"), "packages:\n  - 'packages/*'\n").unwrap();
+    symlink(tree.root.join("web/node_modules/.pnpm/react@18/node_modules/react"), tree.root.join("web/packages/ui/node_modules/react")).unwrap();
+    // A standalone project beside the workspace isn't part of it.
+    tree.file("solo/package.json").file("solo/node_modules/x/index.js");
+
+    let mut found: Vec<_> = ScanIter::new(&tree.root, ScanOptions::default()).filter_map(Result::ok).collect();
+    found.sort_by(|a, b| a.path.cmp(&b.path));
+    let roots: Vec<(String, Option<String>)> = found.iter().map(|c| (tree.relative(&c.path), c.workspace_root().map(|r| tree.relative(r)))).collect();
+    assert_eq!(
+        roots,
+        [
+            ("solo/node_modules".to_string(), None),
+            ("web/node_modules".to_string(), Some("web".to_string())),
+            ("web/packages/ui/node_modules".to_string(), Some("web".to_string())),
+        ]
+    );
+
+    // The member's folder holds a link into the store; only its own file is counted.
+    let member = &found[2];
+    assert_eq!(member.size, fs::metadata(tree.root.join("web/packages/ui/node_modules/.bin/tool")).unwrap().len());
+    assert_eq!(member.links_inside, 1);
+}
@@ -0,0 +1,89 @@
+use devpurge::config::{add_to_list, parse_config};
+use devpurge::moves::{KnownProjects, Migration};
+use devpurge::CandidateDir;
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn rules_follow_a_moved_project() {
+    let dir = std::env::temp_dir().join(format!("devpurge-moves-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let (old, new) = (dir.join("old/app"), dir.join("new/app"));
+    fs::create_dir_all(old.join("node_modules")).unwrap();
+    fs::write(old.join("package.json"), br#"{"name": "app"}"#).unwrap();
+    fs::create_dir_all(dir.join("other")).unwrap();
+    fs::write(dir.join("other/package.json"), br#"{"name": "other"}"#).unwrap();
+
+    let (protected, excluded) = (old.clone(), old.join("node_modules"));
+    let rules = [("protected", protected.as_path()), ("excludes", excluded.as_path())];
+    let mut known = KnownProjects::default();
+    assert!(known.follow(&rules, &[]).is_empty());
+
+    fs::create_dir_all(dir.join("new")).unwrap();
+    fs::rename(&old, &new).unwrap();
+    let candidates = [CandidateDir::new(new.join("node_modules"), 1), CandidateDir::new(dir.join("other/node_modules"), 1)];
+    assert_eq!(
+        known.follow(&rules, &candidates),
+        vec![
+            Migration { list: "protected", from: protected.clone(), to: new.clone() },
+            Migration { list: "excludes", from: excluded.clone(), to: new.join("node_modules") },
+        ]
+    );
+
+    // A copy of the project makes the match ambiguous, so nothing is suggested.
+    fs::create_dir_all(dir.join("copy")).unwrap();
+    fs::copy(new.join("package.json"), dir.join("copy/package.json")).unwrap();
+    let with_copy = [candidates[0].clone(), CandidateDir::new(dir.join("copy/node_modules"), 1)];
+    assert!(known.follow(&rules, &with_copy).is_empty());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn moved_projects_stay_out_of_the_run_unless_the_config_takes_them() {
+    let dir = std::env::temp_dir().join(format!("devpurge-moves-settle-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let (old, new) = (dir.join("old/app"), dir.join("new/app"));
+    fs::create_dir_all(&old).unwrap();
+    fs::write(old.join("package.json"), br#"{"name": "app"}"#).unwrap();
+    let rules = [("protected", old.as_path())];
+    let mut known = KnownProjects::default();
+    known.follow(&rules, &[]);
+    fs::create_dir_all(dir.join("new")).unwrap();
+    fs::rename(&old, &new).unwrap();
+
+    let found = vec![CandidateDir::new(new.join("node_modules"), 1), CandidateDir::new(dir.join("other/node_modules"), 1)];
+    let migrations = known.follow(&rules, &found);
+    assert_eq!(migrations.len(), 1);
+
+    // Nobody to ask (no terminal, or --yes): left out, and offered again next run.
+    let mut candidates = found.clone();
+    assert_eq!(known.settle(&migrations, None, &mut candidates), 1);
+    assert_eq!(candidates, [found[1].clone()]);
+    assert_eq!(known.follow(&rules, &found), migrations);
+
+    // Declined: still left out this time, but not asked about again.
+    let mut candidates = found.clone();
+    assert_eq!(known.settle(&migrations, Some(false), &mut candidates), 1);
+    assert_eq!(candidates, [found[1].clone()]);
+    assert!(known.follow(&rules, &found).is_empty());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn paths_are_added_to_config_lists_in_place() {
+    let text = "# mine\nprotected = [\n  \"/a\", # keep\n  \"/b\",\n]\nset_title = true\n\n[colors]\nsize = [\"1M\", \"2M\", \"3M\"]\n";
+    let updated = add_to_list(text, "protected", &[PathBuf::from("/c")]).unwrap();
+    assert!(updated.starts_with("# mine\nprotected = [\n  \"/a\", # keep\n  \"/b\",\n\"/c\"]"));
+    let updated = add_to_list(&updated, "excludes", &[PathBuf::from(r"C:\x")]).unwrap();
+    assert!(updated.contains("excludes = [\"C:\\\\x\"]\n[colors]"));
+
+    let config = parse_config(&updated).unwrap();
+    assert_eq!(config.protected, ["/a", "/b", "/c"].map(PathBuf::from));
+    assert_eq!(config.excludes, [PathBuf::from(r"C:\x")]);
+    assert!(config.set_title);
+
+    let config = parse_config(&add_to_list("cache = false", "protected", &[PathBuf::from("/d")]).unwrap()).unwrap();
+    assert_eq!(config.protected, [PathBuf::from("/d")]);
+}
@@ -0,0 +1,47 @@
+use devpurge::notes::{Notes, MAX_NOTE_CHARS};
+use devpurge::CandidateDir;
+use std::fs;
+
+#[test]
+fn notes_persist_and_blank_ones_are_removed() {
+    let dir = std::env::temp_dir().join(format!("devpurge-notes-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let file = dir.join("notes.json");
+    let folder = dir.join("app/node_modules");
+
+    let mut notes = Notes::load(Some(file.clone()));
+    assert!(notes.set(&folder, "  ask Sam before deleting "));
+    assert!(!notes.set(&folder, "ask Sam before deleting"));
+    assert!(notes.set(&dir.join("other"), &"x".repeat(200)));
+    notes.save().unwrap();
+
+    let mut notes = Notes::load(Some(file.clone()));
+    assert_eq!(notes.get(&folder), Some("ask Sam before deleting"));
+    assert_eq!(notes.get(&dir.join("other")).map(str::len), Some(MAX_NOTE_CHARS));
+    assert!(notes.set(&folder, "   "));
+    assert_eq!(notes.get(&folder), None);
+    assert_eq!(notes.clear(), 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn notes_move_with_their_project() {
+    let dir = std::env::temp_dir().join(format!("devpurge-notes-move-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let (old, new) = (dir.join("old/app"), dir.join("new/app"));
+    fs::create_dir_all(old.join("target")).unwrap();
+    fs::write(old.join("Cargo.toml"), "[package]\nname = \"app\"\n").unwrap();
+
+    let mut notes = Notes::load(None);
+    notes.set(&old.join("target"), "keep until the release branch merges");
+    fs::create_dir_all(dir.join("new")).unwrap();
+    fs::rename(&old, &new).unwrap();
+
+    let moved = notes.follow_moves(&[CandidateDir::new(new.join("target"), 1)]);
+    assert_eq!(moved, vec![(old.join("target"), new.join("target"))]);
+    assert_eq!(notes.get(&new.join("target")), Some("keep until the release branch merges"));
+    assert_eq!(notes.get(&old.join("target")), None);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
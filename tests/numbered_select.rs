@@ -0,0 +1,125 @@
+use devpurge::ui::{parse_indices, RenderRow, SelectOutcome, Selection};
+use std::cell::Cell;
+use std::io::Cursor;
+use std::rc::Rc;
+
+fn selection() -> Selection {
+    let items = vec!["a/node_modules".to_string(), "b/target".to_string(), "c/dist".to_string()];
+    Selection::new(items, vec![300, 200, 100], vec![true, false, true])
+}
+
+fn answer(selection: &mut Selection, input: &str) -> (SelectOutcome, String) {
+    let mut output = Vec::new();
+    let outcome = selection.interact_numbered(Cursor::new(input.to_string()), &mut output).unwrap();
+    (outcome, String::from_utf8(output).unwrap())
+}
+
+fn selected(outcome: SelectOutcome) -> Vec<usize> {
+    match outcome {
+        SelectOutcome::Selected(indices) => indices,
+        _ => panic!("expected a selection"),
+    }
+}
+
+#[test]
+fn numbers_and_ranges_are_parsed() {
+    assert_eq!(parse_indices("3, 1-2 2", 5), Ok(vec![0, 1, 2]));
+    assert_eq!(parse_indices("4-5", 5), Ok(vec![3, 4]));
+    assert!(parse_indices("0", 5).unwrap_err().contains("between 1 and 5"));
+    assert!(parse_indices("6", 5).is_err());
+    assert!(parse_indices("3-1", 5).unwrap_err().contains("backwards"));
+    assert!(parse_indices("x", 5).unwrap_err().contains("'x'"));
+}
+
+#[test]
+fn enter_keeps_the_checked_items() {
+    let mut selection = selection();
+    let (outcome, output) = answer(&mut selection, "\n");
+    assert_eq!(selected(outcome), vec![0, 2]);
+    assert!(output.contains("   1. [x] a/node_modules"));
+    assert!(output.contains("   2. [ ] b/target"));
+}
+
+#[test]
+fn typed_numbers_replace_the_selection() {
+    let mut selection = selection();
+    let (outcome, _) = answer(&mut selection, "2-3\n");
+    assert_eq!(selected(outcome), vec![1, 2]);
+    assert_eq!(selection.checked, vec![false, true, true]);
+}
+
+#[test]
+fn invalid_answers_ask_again() {
+    let mut selection = selection();
+    let (outcome, output) = answer(&mut selection, "7\nnone\n");
+    assert!(selected(outcome).is_empty());
+    assert!(output.contains("7 is not between 1 and 3"));
+    assert_eq!(output.matches("Folders to delete").count(), 2);
+}
+
+#[test]
+fn q_or_end_of_input_cancels() {
+    assert!(matches!(answer(&mut selection(), "q\n").0, SelectOutcome::Cancelled));
+    assert!(matches!(answer(&mut selection(), "").0, SelectOutcome::Cancelled));
+}
+
+#[test]
+fn notes_follow_their_items() {
+    let mut selection = selection();
+    selection.notes = vec![None, Some("ask Sam".to_string()), None];
+    let (_, output) = answer(&mut selection, "\n");
+    assert!(output.contains("2. [ ] b/target  [note: ask Sam]"));
+    assert_eq!(selection.note(1), Some("ask Sam"));
+}
+
+#[test]
+fn actions_other_than_the_first_are_shown() {
+    let mut selection = selection();
+    selection.actions = vec![vec!["delete".to_string(), "partial".to_string()]; 3];
+    selection.set_action(0, 1);
+    selection.notes = vec![Some("ask Sam".to_string()), None, None];
+    let (_, output) = answer(&mut selection, "\n");
+    assert!(output.contains("1. [x] a/node_modules  [partial]  [note: ask Sam]"));
+    assert!(output.contains("2. [ ] b/target\n"));
+    assert_eq!((selection.action(0), selection.action(1)), (1, 0));
+}
+
+struct CountingRows(Rc<Cell<usize>>);
+
+impl RenderRow for CountingRows {
+    fn render(&self, item: usize, width: usize) -> String {
+        self.0.set(self.0.get() + 1);
+        format!("{}/{}", item, width)
+    }
+}
+
+#[test]
+fn rows_are_rendered_when_needed_and_again_for_a_new_width() {
+    let calls = Rc::new(Cell::new(0));
+    let selection = Selection::with_rows(Box::new(CountingRows(calls.clone())), vec![1; 1000], vec![false; 1000]);
+    assert_eq!(calls.get(), 0);
+    assert_eq!(selection.row(3, 80), "3/80");
+    assert_eq!(selection.row(3, 80), "3/80");
+    assert_eq!(calls.get(), 1);
+    assert_eq!(selection.row(3, 120), "3/120");
+    assert_eq!(calls.get(), 2);
+}
+
+#[test]
+fn invert_picks_the_unchecked_items() {
+    let mut selection = selection();
+    let (outcome, _) = answer(&mut selection, "invert\n");
+    assert_eq!(selected(outcome), vec![1]);
+}
+
+#[test]
+fn bulk_changes_update_the_summary() {
+    let mut selection = selection();
+    assert_eq!(selection.summary(), "Selected: 2 of 3 folders, 400 B");
+    selection.invert_selection();
+    assert_eq!((selection.checked.clone(), selection.summary()), (vec![false, true, false], "Selected: 1 of 3 folders, 200 B".to_string()));
+    selection.select_all();
+    assert_eq!(selection.summary(), "Selected: 3 of 3 folders, 600 B");
+    selection.select_none();
+    assert_eq!(selection.summary(), "Selected: 0 of 3 folders, 0 B");
+}
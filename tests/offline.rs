@@ -0,0 +1,33 @@
+use devpurge::volume::{mounted_ids, presence, Mount, Presence, VolumeTable};
+use devpurge::CandidateDir;
+use std::fs;
+use std::path::PathBuf;
+
+fn mount(point: &str, device: &str) -> Mount {
+    Mount { mount_point: PathBuf::from(point), device: device.to_string(), fs_type: "ext4".to_string() }
+}
+
+fn on_volume(path: PathBuf, volume: &str) -> CandidateDir {
+    CandidateDir { volume: Some(volume.to_string()), ..CandidateDir::new(path, 1) }
+}
+
+#[test]
+fn cached_entries_are_present_offline_or_stale() {
+    let dir = std::env::temp_dir().join(format!("devpurge-offline-{}", std::process::id()));
+    fs::create_dir_all(dir.join("node_modules")).unwrap();
+    let root = if cfg!(windows) { "C:\\" } else { "/" };
+    let table = VolumeTable::from_mounts(vec![mount(root, "rootdev"), mount("/media/backup", "backupdev")]);
+    let mounted = mounted_ids(&table);
+
+    assert_eq!(presence(&on_volume(dir.join("node_modules"), "rootdev"), &table, &mounted), Presence::Present);
+    assert_eq!(presence(&on_volume(dir.join("target"), "rootdev"), &table, &mounted), Presence::Stale);
+    // Unplugged: kept whether or not something answers at that path.
+    assert_eq!(presence(&on_volume(dir.join("node_modules"), "ssd"), &table, &mounted), Presence::Offline);
+    assert_eq!(presence(&on_volume(dir.join("target"), "ssd"), &table, &mounted), Presence::Offline);
+    // The recorded volume is mounted elsewhere, so this path now belongs to another one.
+    assert_eq!(presence(&on_volume(dir.join("node_modules"), "backupdev"), &table, &mounted), Presence::Stale);
+    // Entries cached before volumes were recorded fall back to checking the path.
+    assert_eq!(presence(&CandidateDir::new(dir.join("node_modules"), 1), &table, &mounted), Presence::Present);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,28 @@
+use devpurge::config::parse_config;
+use devpurge::onboarding::{config_text, suggested_roots, Answers};
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn answers_round_trip_through_the_config_file() {
+    let answers = Answers {
+        default_path: PathBuf::from(r#"C:\Users\me\My "code""#),
+        excludes: vec![PathBuf::from("/home/me/.cache"), PathBuf::from(r"D:\AppData")],
+        cache: false,
+    };
+    let config = parse_config(&config_text(&answers)).unwrap();
+    assert_eq!(config.default_path, Some(answers.default_path));
+    assert_eq!(config.excludes, answers.excludes);
+    assert_eq!(config.cache, Some(false));
+}
+
+#[test]
+fn project_folders_are_suggested_before_home() {
+    let home = std::env::temp_dir().join(format!("devpurge-onboarding-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&home);
+    fs::create_dir_all(home.join("code")).unwrap();
+    fs::write(home.join("src"), b"not a directory").unwrap();
+
+    assert_eq!(suggested_roots(&home), vec![home.join("code"), home.clone()]);
+    fs::remove_dir_all(&home).unwrap();
+}
@@ -0,0 +1,33 @@
+use devpurge::owner::{only_mine_by_default, top_level, OwnerCache};
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn candidates_belong_to_the_directory_below_the_root() {
+    let root = Path::new("/srv/builds");
+    assert_eq!(top_level(root, Path::new("/srv/builds/alice/app/node_modules")), root.join("alice"));
+    assert_eq!(top_level(root, Path::new("/srv/builds/target")), root.join("target"));
+    assert_eq!(top_level(root, Path::new("/elsewhere/target")), Path::new("/elsewhere/target"));
+}
+
+#[test]
+fn own_trees_are_mine_and_roots_in_home_are_not_filtered() {
+    let dir = std::env::temp_dir().join(format!("devpurge-owner-{}", std::process::id()));
+    fs::create_dir_all(dir.join("me")).unwrap();
+
+    let mut owners = OwnerCache::default();
+    assert!(owners.is_mine(&dir.join("me")));
+    assert!(!only_mine_by_default(&dir, None));
+    assert!(!only_mine_by_default(&dir, Some(&dir)));
+
+    #[cfg(unix)]
+    {
+        // Only root can hand a directory to someone else.
+        fs::create_dir_all(dir.join("other")).unwrap();
+        if std::os::unix::fs::chown(dir.join("other"), Some(65534), None).is_ok() {
+            assert!(!owners.is_mine(&dir.join("other")));
+            assert_eq!(owners.owner(&dir.join("other")), Some(65534));
+        }
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
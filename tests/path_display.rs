@@ -0,0 +1,39 @@
+//! `--relative` (`--paths relative`): rows show paths below the scan root without it.
+
+use devpurge::display::{PathDisplay, PathStyle};
+use devpurge::rows::CandidateRows;
+use devpurge::severity::Severity;
+use devpurge::ui::RenderRow;
+use devpurge::CandidateDir;
+use directories::BaseDirs;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+fn root() -> PathBuf {
+    Path::new("/work/clients/bigcorp/monorepo").to_path_buf()
+}
+
+#[test]
+fn paths_below_the_root_lose_it() {
+    let paths = PathDisplay::new(PathStyle::Relative, &root());
+    assert_eq!(paths.show(&root().join("packages/web/node_modules")), Path::new("packages/web/node_modules").display().to_string());
+    assert_eq!(paths.show(&root()), ".");
+    assert_eq!(paths.show(Path::new("/srv/other/target")), Path::new("/srv/other/target").display().to_string());
+
+    let home = BaseDirs::new().unwrap().home_dir().to_path_buf();
+    let cache = PathDisplay::new(PathStyle::Relative, &root()).show(&home.join(".cache/pip"));
+    assert!(cache.starts_with('~'), "{}", cache);
+}
+
+#[test]
+fn rows_are_fitted_from_the_relative_path() {
+    let candidate = CandidateDir::new(root().join("packages/storefront-checkout/node_modules"), 1000);
+    let rows = |style| CandidateRows::new(Rc::new(RefCell::new(vec![candidate.clone()])), PathDisplay::new(style, &root()), Severity::default(), false);
+
+    // Wide enough for the relative path, too narrow for the absolute one.
+    let relative = console::strip_ansi_codes(&rows(PathStyle::Relative).render(0, 60)).to_string();
+    assert!(relative.starts_with(&Path::new("packages/storefront-checkout/node_modules").display().to_string()), "{}", relative);
+    let absolute = console::strip_ansi_codes(&rows(PathStyle::Absolute).render(0, 60)).to_string();
+    assert!(absolute.contains("..."), "{}", absolute);
+}
@@ -0,0 +1,86 @@
+//! Plans applied on this machine: where each entry is mapped to, and the checks it has to
+//! pass there before anything is deleted.
+
+use devpurge::fixtures::Tree;
+use devpurge::plan::{hostname, parse_path_mapping, Plan, PlanEntry, PLAN_VERSION};
+use devpurge::volume::{Mount, VolumeTable};
+use std::path::{Path, PathBuf};
+
+fn plan(host: &str, path_map: &[&str]) -> Plan {
+    Plan {
+        version: PLAN_VERSION,
+        host: host.to_string(),
+        created: 0,
+        path_map: path_map.iter().map(|m| parse_path_mapping(m).unwrap()).collect(),
+        entries: Vec::new(),
+    }
+}
+
+fn entry(path: &Path, volume: &str) -> PlanEntry {
+    PlanEntry { path: path.to_path_buf(), size: 1, kind: "node_modules".to_string(), is_file: false, volume: Some(volume.to_string()) }
+}
+
+/// One volume holding everything, whose id is its device name.
+fn volumes() -> VolumeTable {
+    VolumeTable::from_mounts(vec![Mount { mount_point: PathBuf::from("/"), device: "dev-here".to_string(), fs_type: "ext4".to_string() }])
+}
+
+#[test]
+fn the_longest_whole_component_prefix_wins() {
+    let plan = plan("builder", &["/mnt=/srv", "/mnt/builds=/data/builds", "/mnt/build=/wrong"]);
+    assert_eq!(plan.map_path(Path::new("/mnt/builds/app/node_modules")), Path::new("/data/builds/app/node_modules"));
+    assert_eq!(plan.map_path(Path::new("/mnt/other/target")), Path::new("/srv/other/target"));
+    // `/mnt/build` is not a prefix of `/mnt/buildsX`: components are compared whole.
+    assert_eq!(plan.map_path(Path::new("/mnt/buildsX/target")), Path::new("/srv/buildsX/target"));
+    assert_eq!(plan.map_path(Path::new("/home/me/app/target")), Path::new("/home/me/app/target"));
+}
+
+#[test]
+fn entries_apply_on_the_same_host_or_volume() {
+    let tree = Tree::new("plan-volumes");
+    tree.file("app/package.json").dir("app/node_modules/pkg");
+    let folder = tree.root.join("app/node_modules");
+    let (volumes, here) = (volumes(), hostname());
+
+    let home = plan(&here, &[]);
+    assert_eq!(home.verify(&entry(&folder, "dev-here"), &volumes, &here, false, &[]), Ok(folder.clone()));
+    assert_eq!(
+        home.verify(&entry(&folder, "dev-old"), &volumes, &here, false, &[]),
+        Err("was on volume dev-old when planned, now on dev-here".to_string())
+    );
+
+    let away = plan("builder", &[]);
+    assert_eq!(away.verify(&entry(&folder, "dev-here"), &volumes, &here, false, &[]), Ok(folder.clone()));
+    assert_eq!(
+        away.verify(&entry(&folder, "dev-old"), &volumes, &here, false, &[]),
+        Err("planned on host builder (volume dev-old), here it is on dev-here".to_string())
+    );
+    assert_eq!(away.verify(&entry(&folder, "dev-old"), &volumes, &here, true, &[]), Ok(folder.clone()));
+
+    let missing = tree.root.join("gone/node_modules");
+    assert_eq!(away.verify(&entry(&missing, "dev-here"), &volumes, &here, true, &[]), Err(format!("not found at {}", missing.display())));
+}
+
+#[test]
+fn mapped_paths_must_pass_the_safety_checks() {
+    let tree = Tree::new("plan-safety");
+    tree.file("app/package.json").dir("app/node_modules/pkg").file("app/src/main.js").dir("loose/app/node_modules/pkg");
+    let (volumes, here) = (volumes(), hostname());
+    let planned = Path::new("/planned/app/node_modules");
+    let refused = |mapping: &str, protected: &[PathBuf]| {
+        let plan = plan(&here, &[mapping]);
+        plan.verify(&entry(planned, "dev-here"), &volumes, &here, true, protected).unwrap_err()
+    };
+
+    let to_sources = refused(&format!("/planned/app/node_modules={}", tree.root.join("app/src").display()), &[]);
+    assert!(to_sources.contains("is not safe to delete: 'src' is not a known target name"), "{}", to_sources);
+
+    let unmarked = refused(&format!("/planned={}", tree.root.join("loose").display()), &[]);
+    assert!(unmarked.contains("no matching project file next to it"), "{}", unmarked);
+
+    let guarded = refused(&format!("/planned={}", tree.root.display()), &[tree.root.join("app")]);
+    assert!(guarded.contains(&format!("protected by {}", tree.root.join("app").display())), "{}", guarded);
+
+    let plan = plan(&here, &[&format!("/planned={}", tree.root.display())]);
+    assert_eq!(plan.verify(&entry(planned, "dev-here"), &volumes, &here, true, &[]), Ok(tree.root.join("app/node_modules")));
+}
@@ -0,0 +1,58 @@
+use devpurge::CandidateDir;
+use devpurge::preselect::{parse_preselect, Comparator, Metric, Threshold};
+use std::path::PathBuf;
+use std::time::Duration;
+
+const DAY: u64 = 86_400;
+
+#[test]
+fn parses_age_and_size_policies() {
+    let policy = parse_preselect("idle>90d").unwrap();
+    assert_eq!(policy.metric, Metric::Idle);
+    assert_eq!(policy.comparator, Comparator::Greater);
+    assert_eq!(policy.threshold, Threshold::Duration(Duration::from_secs(90 * DAY)));
+
+    let policy = parse_preselect(" size >= 1G ").unwrap();
+    assert_eq!(policy.metric, Metric::Size);
+    assert_eq!(policy.comparator, Comparator::GreaterOrEqual);
+    assert_eq!(policy.threshold, Threshold::Bytes(1 << 30));
+
+    let policy = parse_preselect("AGE<=2w").unwrap();
+    assert_eq!(policy.metric, Metric::Age);
+    assert_eq!(policy.comparator, Comparator::LessOrEqual);
+
+    assert_eq!(parse_preselect("size<500M").unwrap().comparator, Comparator::Less);
+}
+
+#[test]
+fn rejects_mismatched_units() {
+    assert!(parse_preselect("idle>10GB").is_err());
+    assert!(parse_preselect("age<5G").is_err());
+    assert!(parse_preselect("size>90d").is_err());
+}
+
+#[test]
+fn rejects_malformed_expressions() {
+    assert!(parse_preselect("").is_err());
+    assert!(parse_preselect("idle").is_err());
+    assert!(parse_preselect("idle=90d").is_err());
+    assert!(parse_preselect("weight>3").is_err());
+    assert!(parse_preselect("idle>").is_err());
+    assert!(parse_preselect(">90d").is_err());
+}
+
+#[test]
+fn displays_readably_for_the_header() {
+    assert_eq!(parse_preselect("idle>90d").unwrap().to_string(), "idle > 3 months");
+    assert_eq!(parse_preselect("size>=1G").unwrap().to_string(), "size >= 1 GiB");
+}
+
+#[test]
+fn incomplete_sizes_never_match_size_policies() {
+    let mut candidate = CandidateDir::new(PathBuf::from("/nonexistent/node_modules"), 2 << 30);
+    let policy = parse_preselect("size>=1G").unwrap();
+    assert!(policy.matches(&candidate));
+    candidate.unreadable = 3;
+    assert!(!policy.matches(&candidate));
+    assert!(!parse_preselect("size<10G").unwrap().matches(&candidate));
+}
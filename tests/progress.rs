@@ -0,0 +1,62 @@
+//! `--progress` targets and the events written to them.
+
+use devpurge::fixtures::Tree;
+use devpurge::progress::{parse_progress_target, ProgressEvents, ProgressTarget, PROGRESS_SCHEMA_VERSION};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn targets_are_descriptors_pipes_or_paths() {
+    let cases = [
+        ("pipe:3", Ok(ProgressTarget::Inherited(3))),
+        ("pipe:1234", Ok(ProgressTarget::Inherited(1234))),
+        (r"pipe:\\.\pipe\devpurge", Ok(ProgressTarget::Path(PathBuf::from(r"\\.\pipe\devpurge")))),
+        ("pipe:/tmp/events.fifo", Ok(ProgressTarget::Path(PathBuf::from("/tmp/events.fifo")))),
+        ("pipe:-1", Ok(ProgressTarget::Path(PathBuf::from("-1")))),
+        ("events.jsonl", Ok(ProgressTarget::Path(PathBuf::from("events.jsonl")))),
+        ("pipe:", Err("expected a descriptor, handle or pipe name after 'pipe:'".to_string())),
+        ("", Err("expected a progress target".to_string())),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(parse_progress_target(input), expected, "{:?}", input);
+    }
+}
+
+#[test]
+fn events_are_json_lines_after_the_version() {
+    let tree = Tree::new("progress-file");
+    let file = tree.root.join("events.jsonl");
+    let events = ProgressEvents::open(&ProgressTarget::Path(file.clone())).unwrap();
+    assert!(events.is_enabled());
+    events.emit("scan_start", json!({ "root": "/work" }));
+    events.clone().emit("scan_done", json!({ "found": 2 }));
+
+    let lines: Vec<Value> = fs::read_to_string(&file).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!((lines[0]["event"].clone(), lines[0]["schema"].clone()), (json!("version"), json!(PROGRESS_SCHEMA_VERSION)));
+    assert_eq!(lines[1], json!({ "event": "scan_start", "root": "/work" }));
+    assert_eq!(lines[2], json!({ "event": "scan_done", "found": 2 }));
+
+    let disabled = ProgressEvents::disabled();
+    assert!(!disabled.is_enabled());
+    disabled.emit("scan_start", json!({}));
+}
+
+#[cfg(unix)]
+#[test]
+fn a_failed_write_turns_the_sink_off() {
+    use std::os::unix::io::IntoRawFd;
+
+    let (reader, writer) = std::io::pipe().unwrap();
+    let events = ProgressEvents::open(&ProgressTarget::Inherited(writer.into_raw_fd() as u64)).unwrap();
+    let clone = events.clone();
+    assert!(events.is_enabled());
+
+    // The reader goes away: the next write fails, and every clone stops writing.
+    drop(reader);
+    events.emit("scan_start", json!({}));
+    assert!(!events.is_enabled());
+    assert!(!clone.is_enabled());
+    clone.emit("scan_done", json!({}));
+}
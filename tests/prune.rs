@@ -0,0 +1,59 @@
+//! Version control stores and configured folder names are never walked into.
+
+use devpurge::fixtures::Tree;
+use devpurge::{ScanIter, ScanOptions, ScanScope};
+
+fn scan(tree: &Tree, options: ScanOptions) -> Vec<String> {
+    let mut paths: Vec<String> = ScanIter::new(&tree.root, options).filter_map(Result::ok).map(|c| tree.relative(&c.path)).collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn version_control_stores_are_not_walked() {
+    let tree = Tree::new("prune-vcs");
+    tree.file("app/package.json")
+        .file("app/node_modules/x/index.js")
+        // A checked-out copy kept inside .git, as some tools do.
+        .file("app/.git/modules/sub/package.json")
+        .file("app/.git/modules/sub/node_modules/y/index.js")
+        .file("old/.svn/pristine/package.json")
+        .file("old/.svn/pristine/node_modules/z/index.js");
+
+    assert_eq!(scan(&tree, ScanOptions::default()), ["app/node_modules"]);
+}
+
+#[test]
+fn configured_names_are_pruned_too() {
+    let tree = Tree::new("prune-config");
+    tree.file("Photos Library.photoslibrary/resources/package.json")
+        .file("Photos Library.photoslibrary/resources/node_modules/x/index.js")
+        .file("vm.vmwarevm/package.json")
+        .file("vm.vmwarevm/node_modules/x/index.js")
+        .file("web/package.json")
+        .file("web/node_modules/x/index.js");
+
+    let mut options = ScanOptions::default();
+    options.pruned.extend(["Photos Library.photoslibrary".to_string(), "*.vmwarevm".to_string()]);
+    assert_eq!(scan(&tree, options), ["web/node_modules"]);
+}
+
+#[test]
+fn a_pruned_root_is_still_scanned() {
+    let tree = Tree::new("prune-root");
+    tree.file(".git/hooks/package.json").file(".git/hooks/node_modules/x/index.js");
+
+    let found: Vec<_> = ScanIter::new(tree.root.join(".git"), ScanOptions::default()).filter_map(Result::ok).collect();
+    assert_eq!(found.len(), 1);
+}
+
+#[test]
+fn a_cache_that_pruned_more_does_not_answer() {
+    let tree = Tree::new("prune-cache");
+    let mut wider = ScanOptions::default();
+    wider.pruned.push("archive".to_string());
+    let cached = ScanScope::new(&tree.root, &wider);
+
+    assert!(cached.answers(&ScanScope::new(&tree.root, &ScanOptions::default())).is_err());
+    assert!(ScanScope::new(&tree.root, &ScanOptions::default()).answers(&cached).is_ok());
+}
@@ -0,0 +1,32 @@
+//! Python virtualenvs and tool caches: each needs its own marker, and a folder that is
+//! merely named `venv` is never offered.
+
+use devpurge::fixtures::Tree;
+
+#[test]
+fn marked_environments_and_caches_are_found() {
+    let tree = Tree::new("python");
+    tree.file("app/.venv/pyvenv.cfg")
+        .file("app/.venv/lib/site.py")
+        .file("tool/venv/pyvenv.cfg")
+        .file("app/tox.ini")
+        .file("app/.tox/py312/bin/python")
+        .file("lib/pyproject.toml")
+        .file("lib/.pytest_cache/v/cache/nodeids")
+        .file("lib/.mypy_cache/3.12/x.json");
+
+    assert_eq!(tree.found(), ["app/.tox", "app/.venv", "lib/.mypy_cache", "lib/.pytest_cache", "tool/venv"]);
+}
+
+#[test]
+fn unmarked_folders_are_left_alone() {
+    let tree = Tree::new("python-unmarked");
+    // A project's own `venv` package, and caches with no Python project beside them.
+    tree.file("app/venv/__init__.py")
+        .file("app/.venv/notes.txt")
+        .file("loose/.tox/log.txt")
+        .file("loose/.pytest_cache/v/cache/nodeids")
+        .file("loose/.mypy_cache/3.12/x.json");
+
+    assert!(tree.found().is_empty());
+}
@@ -0,0 +1,60 @@
+//! Refining scan results in memory: filters can be loosened again without a rescan.
+
+use devpurge::floors::parse_size_floors;
+use devpurge::order::{PathOrder, SortKey};
+use devpurge::refine::{kinds, Refinement};
+use devpurge::CandidateDir;
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+fn candidate(path: &str, kind: &str, size: u64) -> CandidateDir {
+    CandidateDir { kind: kind.to_string(), ..CandidateDir::new(PathBuf::from(path), size) }
+}
+
+fn all() -> Vec<CandidateDir> {
+    vec![
+        candidate("/w/a/node_modules", "node_modules", 300 << 20),
+        candidate("/w/a/target", "target", 2 << 30),
+        candidate("/w/b/node_modules", "node_modules", 10 << 20),
+        candidate("/w/c/vendor", "vendor", 50 << 20),
+    ]
+}
+
+fn paths(candidates: &[CandidateDir]) -> Vec<String> {
+    candidates.iter().map(|c| c.path.display().to_string()).collect()
+}
+
+#[test]
+fn a_looser_floor_brings_dropped_folders_back() {
+    let all = all();
+    let mut refinement = Refinement {
+        floors: parse_size_floors("100M").unwrap(),
+        hidden: BTreeSet::new(),
+        sort: SortKey::Size,
+        reverse: false,
+        path_order: PathOrder::Bytewise,
+    };
+    assert_eq!(paths(&refinement.apply(&all)), ["/w/a/target", "/w/a/node_modules"]);
+
+    refinement.floors = parse_size_floors("0").unwrap();
+    refinement.hidden.insert("vendor".to_string());
+    refinement.sort = SortKey::Path;
+    assert_eq!(paths(&refinement.apply(&all)), ["/w/a/node_modules", "/w/a/target", "/w/b/node_modules"]);
+
+    refinement.reverse = true;
+    assert_eq!(paths(&refinement.apply(&all)), ["/w/b/node_modules", "/w/a/target", "/w/a/node_modules"]);
+    assert_eq!(all.len(), 4);
+}
+
+#[test]
+fn kinds_are_counted_largest_first() {
+    let kinds = kinds(&all());
+    assert_eq!(
+        kinds,
+        [
+            ("target".to_string(), 1, 2 << 30),
+            ("node_modules".to_string(), 2, 310 << 20),
+            ("vendor".to_string(), 1, 50 << 20),
+        ]
+    );
+}
@@ -0,0 +1,35 @@
+//! `r` in the selection list: measuring one candidate again while the list stays open.
+
+use devpurge::display::{PathDisplay, PathStyle};
+use devpurge::fixtures::{parse_spec, Tree};
+use devpurge::rows::CandidateRows;
+use devpurge::severity::Severity;
+use devpurge::ui::RenderRow;
+use devpurge::{ScanIter, ScanOptions};
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+
+#[test]
+fn a_rescan_replaces_only_the_rescanned_candidate() {
+    let mut tree = Tree::new("rescan");
+    let spec = parse_spec("[[projects]]\npath = \"a\"\necosystem = \"node\"\n\n[[projects]]\npath = \"b\"\necosystem = \"node\"").unwrap();
+    tree.generate(&spec);
+    let mut found: Vec<_> = ScanIter::new(&tree.root, ScanOptions::default()).filter_map(Result::ok).collect();
+    found.sort_by(|a, b| a.path.cmp(&b.path));
+    let before = found.clone();
+    let candidates = Rc::new(RefCell::new(found));
+    let rows = CandidateRows::new(candidates.clone(), PathDisplay::new(PathStyle::Relative, &tree.root), Severity::default(), false);
+
+    fs::write(tree.root.join("a/node_modules/big.bin"), vec![0u8; 64 * 1024]).unwrap();
+    let size = rows.rescan(0).unwrap()().unwrap();
+    rows.rescanned(0, Some(size));
+    assert!(candidates.borrow()[0].size >= 64 * 1024);
+    assert_eq!(candidates.borrow()[0].path, before[0].path);
+    assert_eq!(candidates.borrow()[1].size, before[1].size);
+
+    fs::remove_dir_all(tree.root.join("b/node_modules")).unwrap();
+    assert_eq!(rows.rescan(1).unwrap()(), None);
+    rows.rescanned(1, None);
+    assert_eq!(candidates.borrow()[1].path, before[1].path);
+}
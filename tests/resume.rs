@@ -0,0 +1,65 @@
+//! Selections that were never carried out, offered again to the next run in the same root.
+
+use devpurge::fixtures::Tree;
+use devpurge::resume::{SavedSelection, SavedSelections};
+use devpurge::CandidateDir;
+use std::path::Path;
+
+fn candidates(root: &Path, sizes: &[(&str, u64)]) -> Vec<CandidateDir> {
+    sizes.iter().map(|&(rel, size)| CandidateDir::new(root.join(rel), size)).collect()
+}
+
+#[test]
+fn selections_are_kept_per_root() {
+    let tree = Tree::new("resume-roots");
+    let file = tree.root.join("cache/selection.json");
+    let (work, play) = (Path::new("/work"), Path::new("/play"));
+    let mut saved = SavedSelections::load(&file);
+    assert!(saved.selections.is_empty());
+
+    saved.put(SavedSelection::new(work, &candidates(work, &[("a/target", 10), ("b/target", 20)]), &[true, false]));
+    saved.put(SavedSelection::new(play, &candidates(play, &[("c/node_modules", 5)]), &[true]));
+    // A second save in the same root replaces the first.
+    saved.put(SavedSelection::new(work, &candidates(work, &[("a/target", 10), ("b/target", 20)]), &[false, false]));
+    saved.save(&file).unwrap();
+
+    let mut loaded = SavedSelections::load(&file);
+    assert_eq!(loaded.selections.len(), 2);
+    assert_eq!(loaded.get(work).unwrap().selected_count(), 0);
+    assert_eq!(loaded.get(play).unwrap().selected_count(), 1);
+    assert!(loaded.get(Path::new("/elsewhere")).is_none());
+
+    assert!(!loaded.remove(Path::new("/elsewhere")));
+    assert!(loaded.remove(work));
+    assert!(loaded.get(work).is_none());
+    assert!(loaded.get(play).is_some());
+}
+
+#[test]
+fn an_old_or_broken_file_holds_nothing() {
+    let tree = Tree::new("resume-old");
+    std::fs::write(tree.root.join("selection.json"), r#"{"root":"/work","saved_at":0,"items":[]}"#).unwrap();
+    assert!(SavedSelections::load(&tree.root.join("selection.json")).selections.is_empty());
+    assert!(SavedSelections::load(&tree.root.join("missing.json")).selections.is_empty());
+}
+
+#[test]
+fn restoring_checks_the_root_paths_and_sizes() {
+    let root = Path::new("/work");
+    let then = candidates(root, &[("a/target", 1000), ("b/target", 1000)]);
+    let saved = SavedSelection::new(root, &then, &[false, true]);
+
+    // New candidates keep their default; saved ones get their checkbox back.
+    let now = candidates(root, &[("a/target", 1000), ("b/target", 1000), ("c/target", 1)]);
+    assert_eq!(saved.restore(root, &now, &[true, false, true]), Some(vec![false, true, true]));
+
+    // Within the allowed drift, and just past it.
+    let drifted = candidates(root, &[("a/target", 1100), ("b/target", 900)]);
+    assert_eq!(saved.restore(root, &drifted, &[true, true]), Some(vec![false, true]));
+    let grown = candidates(root, &[("a/target", 1101), ("b/target", 1000)]);
+    assert_eq!(saved.restore(root, &grown, &[true, true]), None);
+
+    let missing = candidates(root, &[("a/target", 1000)]);
+    assert_eq!(saved.restore(root, &missing, &[true]), None);
+    assert_eq!(saved.restore(Path::new("/play"), &now, &[true, true, true]), None);
+}
@@ -0,0 +1,238 @@
+//! Fixture trees checking which directories the scanner descends into: matched
+//! candidates never, everything outside them always.
+
+use devpurge::glob::path_glob_match;
+use devpurge::scanner::{ignore_patterns, IGNORE_FILE};
+use devpurge::fixtures::{parse_spec, Expected, Tree};
+use devpurge::{CandidateDir, ScanIter, ScanOptions, ScanScope};
+use std::fs;
+use std::path::Path;
+
+/// Every directory the walk reported visiting, relative to the root.
+fn visited(tree: &Tree) -> Vec<String> {
+    let visited = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let (sink, root) = (visited.clone(), tree.root.clone());
+    ScanIter::new(&tree.root, ScanOptions::default())
+        .on_dir(move |dir| sink.borrow_mut().push(dir.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/")))
+        .for_each(drop);
+    let visited = visited.borrow().clone();
+    visited
+}
+
+#[test]
+fn monorepo_packages_are_found_once_beside_hoisted_root() {
+    let fx = Tree::new("monorepo");
+    fx.file("package.json")
+        .file("node_modules/left-pad/package.json")
+        .file("node_modules/left-pad/node_modules/dep/index.js")
+        .file("packages/a/package.json")
+        .file("packages/a/node_modules/x/index.js")
+        .file("packages/b/package.json")
+        .file("packages/b/node_modules/y/index.js")
+        .file("packages/c/package.json");
+
+    assert_eq!(fx.found(), ["node_modules", "packages/a/node_modules", "packages/b/node_modules"]);
+}
+
+#[test]
+fn candidates_are_never_descended() {
+    let fx = Tree::new("nested");
+    fx.file("Cargo.toml")
+        .file("target/debug/build/Cargo.toml")
+        .dir("target/debug/build/target/release")
+        .file("target/package.json")
+        .dir("target/node_modules/z");
+
+    assert_eq!(fx.found(), ["target"]);
+    assert!(visited(&fx).iter().all(|dir| !dir.starts_with("target/")));
+}
+
+#[test]
+fn siblings_after_a_leading_candidate_are_walked() {
+    // `.next` sorts before every sibling, so it is the first entry of its directory.
+    let fx = Tree::new("leading");
+    fx.file("next.config.js")
+        .dir(".next/cache")
+        .file("a/package.json")
+        .dir("a/node_modules/q")
+        .file("z/deep/er/Cargo.toml")
+        .dir("z/deep/er/target/debug");
+
+    assert_eq!(fx.found(), [".next", "a/node_modules", "z/deep/er/target"]);
+}
+
+#[test]
+fn unverified_target_names_are_walked_like_any_other_directory() {
+    let fx = Tree::new("unverified");
+    fx.dir("build/docs")
+        .file("build/app/package.json")
+        .dir("build/app/node_modules/m");
+
+    assert_eq!(fx.found(), ["build/app/node_modules"]);
+}
+
+#[test]
+fn results_do_not_depend_on_creation_order() {
+    let forward = Tree::new("forward");
+    let backward = Tree::new("backward");
+    let projects = ["p1", "p2", "p3", "p4", "p5"];
+    for project in projects {
+        forward.file(&format!("{}/package.json", project)).dir(&format!("{}/node_modules/d", project));
+    }
+    for project in projects.iter().rev() {
+        backward.file(&format!("{}/package.json", project)).dir(&format!("{}/node_modules/d", project));
+    }
+
+    let found: Vec<String> = ScanIter::new(&forward.root, ScanOptions::default())
+        .filter_map(Result::ok)
+        .map(|c| forward.relative(&c.path))
+        .collect();
+    let found_backward: Vec<String> = ScanIter::new(&backward.root, ScanOptions::default())
+        .filter_map(Result::ok)
+        .map(|c| backward.relative(&c.path))
+        .collect();
+    assert_eq!(found, found_backward);
+    assert_eq!(found.len(), projects.len());
+}
+
+#[test]
+fn breadth_first_finds_the_same_candidates_shallowest_first() {
+    let fx = Tree::new("breadth");
+    fx.file("a/b/c/package.json")
+        .dir("a/b/c/node_modules/x")
+        .file("z/Cargo.toml")
+        .dir("z/target/debug")
+        .file("package.json")
+        .dir("node_modules/y");
+
+    let options = ScanOptions { breadth_first: true, ..ScanOptions::default() };
+    let found: Vec<String> = ScanIter::new(&fx.root, options)
+        .filter_map(Result::ok)
+        .map(|c| fx.relative(&c.path))
+        .collect();
+    assert_eq!(found, ["node_modules", "z/target", "a/b/c/node_modules"]);
+
+    let mut sorted = found.clone();
+    sorted.sort();
+    assert_eq!(sorted, fx.found());
+}
+
+const MIXED: &str = r#"
+[[projects]]
+path = "web/app{n}"
+ecosystem = "node"
+size = "3M"
+files = 7
+depth = 2
+copies = 6
+
+[[projects]]
+path = "svc/api{n}"
+ecosystem = "rust"
+size = "40M"
+files = 3
+copies = 3
+
+[[projects]]
+path = "tools/old"
+ecosystem = "gradle"
+unmarked = true
+
+[[projects]]
+path = "scripts"
+ecosystem = "python"
+unmarked = true
+
+[[projects]]
+path = "web/app1/node_modules/pkg"
+ecosystem = "rust"
+size = "1M"
+
+[[links]]
+path = "web/app2/node_modules/shared"
+target = "svc/api1"
+"#;
+
+#[test]
+fn scans_find_exactly_the_generated_candidates() {
+    let mut fx = Tree::new("generated");
+    let expected = fx.generate(&parse_spec(MIXED).unwrap());
+    assert_eq!(expected.len(), 10, "six node, three rust and the python cache");
+
+    let scan = |options: ScanOptions| -> Vec<Expected> {
+        let mut found: Vec<Expected> = ScanIter::new(&fx.root, options)
+            .filter_map(Result::ok)
+            .map(|c| Expected { path: c.path, size: c.size })
+            .collect();
+        found.sort();
+        found
+    };
+    assert_eq!(scan(ScanOptions::default()), expected);
+    assert_eq!(scan(ScanOptions { size_threads: 3, ..ScanOptions::default() }), expected);
+    assert_eq!(scan(ScanOptions { breadth_first: true, ..ScanOptions::default() }), expected);
+}
+
+#[test]
+fn deferred_sizing_finds_the_same_folders_and_measures_them_on_request() {
+    let mut fx = Tree::new("deferred");
+    let expected = fx.generate(&parse_spec(MIXED).unwrap());
+
+    let options = ScanOptions { defer_sizing: true, size_threads: 3, ..ScanOptions::default() };
+    let found: Vec<CandidateDir> = ScanIter::new(&fx.root, options).filter_map(Result::ok).collect();
+    assert!(found.iter().all(|c| c.size_unknown && c.size == 0));
+
+    let mut measured: Vec<Expected> = found
+        .iter()
+        .map(|c| c.remeasure().unwrap())
+        .inspect(|c| assert!(!c.size_unknown))
+        .map(|c| Expected { path: c.path, size: c.size })
+        .collect();
+    measured.sort();
+    assert_eq!(measured, expected);
+}
+
+#[test]
+fn excluded_patterns_prune_valid_candidates() {
+    let fx = Tree::new("exclude");
+    fx.file("keep/package.json")
+        .dir("keep/node_modules/x")
+        .file("vendor/legacy/app/package.json")
+        .dir("vendor/legacy/app/node_modules/x")
+        .file("share/Cargo.toml")
+        .dir("share/target/debug");
+    fs::write(fx.root.join(IGNORE_FILE), "# mounted from the NAS\nshare\n").unwrap();
+
+    let mut exclude_patterns = ignore_patterns(&fx.root).unwrap();
+    assert_eq!(exclude_patterns, ["share"]);
+    exclude_patterns.push("**/legacy/**".to_string());
+    let options = ScanOptions { exclude_patterns, ..ScanOptions::default() };
+    let visited = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let (sink, root) = (visited.clone(), fx.root.clone());
+    let found: Vec<String> = ScanIter::new(&fx.root, options.clone())
+        .on_dir(move |dir| sink.borrow_mut().push(dir.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/")))
+        .filter_map(Result::ok)
+        .map(|c| fx.relative(&c.path))
+        .collect();
+    assert_eq!(found, ["keep/node_modules"]);
+    assert!(!visited.borrow().iter().any(|dir| dir.starts_with("vendor/legacy") || dir.starts_with("share")));
+
+    // A cache built without the patterns still answers, but drops what they exclude.
+    let cached = ScanScope::new(&fx.root, &ScanOptions::default());
+    let wanted = ScanScope::new(&fx.root, &options);
+    assert!(cached.answers(&wanted).is_ok());
+    assert!(wanted.answers(&cached).is_err());
+    let legacy = CandidateDir::new(fx.root.join("vendor/legacy/app/node_modules"), 0);
+    assert!(cached.includes(&legacy) && !wanted.includes(&legacy));
+}
+
+#[test]
+fn exclude_patterns_match_absolute_paths_and_components() {
+    let path = Path::new("/home/me/work/legacy/app/node_modules");
+    assert!(path_glob_match("**/legacy/**", path));
+    assert!(path_glob_match("legacy", path));
+    assert!(path_glob_match("/home/me/work", path) && path_glob_match("/home/*/work/**", path));
+    assert!(path_glob_match("work/leg*", path));
+    assert!(!path_glob_match("/work", path));
+    assert!(!path_glob_match("**/vendor/**", path));
+    assert!(!path_glob_match("lega", path));
+}
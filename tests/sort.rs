@@ -0,0 +1,85 @@
+//! `--sort` keys and their tie-breaking by path.
+
+use devpurge::order::{compare_paths, sort_candidates_by, PathOrder, SortKey};
+use devpurge::CandidateDir;
+use std::cmp::Ordering::{self, Equal, Greater, Less};
+use std::path::{Path, PathBuf};
+
+fn candidate(path: &str, size: u64, modified: Option<u64>) -> CandidateDir {
+    CandidateDir { modified, ..CandidateDir::new(PathBuf::from(path), size) }
+}
+
+fn sorted(key: SortKey) -> Vec<String> {
+    let mut candidates = vec![
+        candidate("/w/zeta/node_modules", 100, Some(300)),
+        candidate("/w/alpha/target", 300, None),
+        candidate("/w/aaa/beta/dist", 200, Some(100)),
+        candidate("/w/alpha/node_modules", 100, Some(200)),
+    ];
+    sort_candidates_by(&mut candidates, key, PathOrder::Bytewise);
+    candidates.iter().map(|c| c.path.display().to_string()).collect()
+}
+
+#[test]
+fn each_key_orders_the_list() {
+    assert_eq!(sorted(SortKey::Size), ["/w/alpha/target", "/w/aaa/beta/dist", "/w/alpha/node_modules", "/w/zeta/node_modules"]);
+    assert_eq!(sorted(SortKey::Path), ["/w/aaa/beta/dist", "/w/alpha/node_modules", "/w/alpha/target", "/w/zeta/node_modules"]);
+    // By the project folder's name, `beta`, not where it sits.
+    assert_eq!(sorted(SortKey::Name), ["/w/alpha/node_modules", "/w/alpha/target", "/w/aaa/beta/dist", "/w/zeta/node_modules"]);
+    // Oldest first, unknown ages last.
+    assert_eq!(sorted(SortKey::Age), ["/w/aaa/beta/dist", "/w/alpha/node_modules", "/w/zeta/node_modules", "/w/alpha/target"]);
+}
+
+/// A candidate's path, size and modification time.
+type Row = (&'static str, u64, Option<u64>);
+
+#[test]
+fn ties_are_broken_by_path() {
+    // Each key with candidates it can't tell apart, listed out of order.
+    let cases: &[(SortKey, &[Row], &[&str])] = &[
+        (SortKey::Size, &[("/w/b/target", 5, None), ("/w/a/target", 5, None), ("/w/c/target", 9, None)], &["/w/c/target", "/w/a/target", "/w/b/target"]),
+        (SortKey::Name, &[("/x/app/target", 1, None), ("/w/app/node_modules", 2, None)], &["/w/app/node_modules", "/x/app/target"]),
+        (SortKey::Age, &[("/w/b/dist", 1, Some(50)), ("/w/a/dist", 2, Some(50)), ("/w/d/dist", 3, None), ("/w/c/dist", 4, None)], &["/w/a/dist", "/w/b/dist", "/w/c/dist", "/w/d/dist"]),
+        (SortKey::Path, &[("/w/b", 1, None), ("/w/a/z", 1, None), ("/w/a", 1, None)], &["/w/a", "/w/a/z", "/w/b"]),
+    ];
+    for (key, candidates, expected) in cases {
+        let mut candidates: Vec<CandidateDir> = candidates.iter().map(|&(path, size, modified)| candidate(path, size, modified)).collect();
+        sort_candidates_by(&mut candidates, *key, PathOrder::Bytewise);
+        let paths: Vec<String> = candidates.iter().map(|c| c.path.display().to_string()).collect();
+        assert_eq!(paths, *expected, "{:?}", key);
+    }
+}
+
+#[test]
+fn natural_and_bytewise_orders_differ_on_digits_and_case() {
+    let cases: &[(&str, &str, Ordering, Ordering)] = &[
+        // (a, b, bytewise, natural)
+        ("v2", "v10", Greater, Less),
+        ("v10", "v10", Equal, Equal),
+        ("app9/target", "app10/target", Greater, Less),
+        ("Beta", "alpha", Less, Greater),
+        ("alpha", "Alpha", Greater, Greater),
+        // Same value: leading zeros fall back to the bytes, so the order is still fixed.
+        ("v007", "v7", Less, Less),
+        ("v1.2.10", "v1.2.9", Less, Greater),
+        ("a", "a1", Less, Less),
+        ("a1b", "a1", Greater, Greater),
+        ("99999999999999999999999", "100000000000000000000000", Greater, Less),
+    ];
+    for &(a, b, bytewise, natural) in cases {
+        assert_eq!(compare_paths(Path::new(a), Path::new(b), PathOrder::Bytewise), bytewise, "{} vs {} bytewise", a, b);
+        assert_eq!(compare_paths(Path::new(a), Path::new(b), PathOrder::Natural), natural, "{} vs {} natural", a, b);
+        assert_eq!(compare_paths(Path::new(b), Path::new(a), PathOrder::Natural), natural.reverse(), "{} vs {} natural", b, a);
+    }
+}
+
+#[test]
+fn the_path_order_applies_to_the_tie_break() {
+    let sorted = |order| {
+        let mut candidates = vec![candidate("/w/app10/target", 1, None), candidate("/w/App2/target", 1, None), candidate("/w/app9/target", 1, None)];
+        sort_candidates_by(&mut candidates, SortKey::Size, order);
+        candidates.iter().map(|c| c.path.display().to_string()).collect::<Vec<_>>()
+    };
+    assert_eq!(sorted(PathOrder::Bytewise), ["/w/App2/target", "/w/app10/target", "/w/app9/target"]);
+    assert_eq!(sorted(PathOrder::Natural), ["/w/App2/target", "/w/app9/target", "/w/app10/target"]);
+}
@@ -0,0 +1,27 @@
+//! `.terraform` provider caches: offered next to `.tf` files, never walked into, and
+//! deleted without the lock file beside them.
+
+use devpurge::fixtures::Tree;
+use devpurge::remove::remove_candidate;
+use devpurge::{ScanIter, ScanOptions};
+
+#[test]
+fn only_the_provider_cache_is_offered_and_removed() {
+    let tree = Tree::new("terraform");
+    tree.file("infra/main.tf")
+        .file("infra/.terraform.lock.hcl")
+        .file("infra/.terraform/providers/registry.terraform.io/hashicorp/aws/5.0.0/linux_amd64/terraform-provider-aws")
+        // Matched folders are never descended into, so nothing inside is offered separately.
+        .file("infra/.terraform/modules/web/package.json")
+        .file("infra/.terraform/modules/web/node_modules/x/index.js")
+        .file("notes/.terraform/x");
+
+    let found: Vec<_> = ScanIter::new(&tree.root, ScanOptions::default()).filter_map(Result::ok).collect();
+    let paths: Vec<String> = found.iter().map(|c| tree.relative(&c.path)).collect();
+    assert_eq!(paths, ["infra/.terraform"]);
+
+    remove_candidate(&found[0]).unwrap();
+    assert!(!tree.root.join("infra/.terraform").exists());
+    assert!(tree.root.join("infra/.terraform.lock.hcl").is_file());
+    assert!(tree.root.join("infra/main.tf").is_file());
+}
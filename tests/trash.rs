@@ -0,0 +1,37 @@
+//! Moving to the freedesktop trash: what lands in `files` and `info`, and how clashing
+//! names are resolved. Runs against a trash under a temporary `XDG_DATA_HOME`.
+
+use devpurge::fixtures::Tree;
+use devpurge::trash::{check_available, encode_trash_path, move_to_trash};
+use std::fs;
+
+#[test]
+fn paths_are_percent_encoded() {
+    assert_eq!(encode_trash_path(b"/home/me/my app/node_modules"), "/home/me/my%20app/node_modules");
+    assert_eq!(encode_trash_path("/tmp/ä".as_bytes()), "/tmp/%C3%A4");
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn trashed_folders_keep_where_they_came_from() {
+    let tree = Tree::new("trash");
+    tree.file("one/node_modules/a.js").file("two/node_modules/b.js");
+    std::env::set_var("XDG_DATA_HOME", tree.root.join("data"));
+    check_available().unwrap();
+
+    move_to_trash(&tree.root.join("one/node_modules")).unwrap();
+    move_to_trash(&tree.root.join("two/node_modules")).unwrap();
+
+    let trash = tree.root.join("data/Trash");
+    assert!(!tree.root.join("one/node_modules").exists());
+    assert!(trash.join("files/node_modules/a.js").exists());
+    assert!(trash.join("files/node_modules.2/b.js").exists());
+    let info = fs::read_to_string(trash.join("info/node_modules.2.trashinfo")).unwrap();
+    assert!(info.starts_with("[Trash Info]\n"));
+    assert!(info.contains(&format!("Path={}\n", tree.root.join("two/node_modules").display())));
+    assert!(info.lines().any(|line| line.starts_with("DeletionDate=") && line.len() == "DeletionDate=2024-01-31T12:00:00".len()));
+
+    let missing = move_to_trash(&tree.root.join("gone"));
+    assert!(missing.is_err());
+    assert!(!trash.join("info/gone.trashinfo").exists(), "a failed move leaves no info file behind");
+}
@@ -0,0 +1,48 @@
+use devpurge::units::unix_secs;
+use devpurge::workspaces::{job_name, list_workspaces, parse_job_names, Workspace, WorkspaceState};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn workspace(name: &str, idle_days: Option<u64>) -> Workspace {
+    let now = unix_secs(SystemTime::now());
+    Workspace {
+        dir: PathBuf::from("/var/lib/jenkins/workspace").join(name),
+        last_activity: idle_days.map(|days| now - days * DAY.as_secs()),
+        artifacts: Vec::new(),
+    }
+}
+
+#[test]
+fn workspaces_belong_to_the_job_before_the_at() {
+    assert_eq!(job_name("deploy"), "deploy");
+    assert_eq!(job_name("deploy@2"), "deploy");
+    assert_eq!(job_name("deploy@2@tmp"), "deploy");
+    assert_eq!(parse_job_names("# jobs\ndeploy\n\n  build-web  \n"), ["deploy", "build-web"].map(String::from).into());
+}
+
+#[test]
+fn workspaces_are_active_idle_or_orphaned() {
+    let idle = 14 * DAY;
+    assert_eq!(workspace("web@2", Some(3)).state(idle, None), WorkspaceState::Active);
+    assert_eq!(workspace("web", Some(30)).state(idle, None), WorkspaceState::Idle);
+    assert_eq!(workspace("empty", None).state(idle, None), WorkspaceState::Idle);
+
+    let jobs = parse_job_names("web\n");
+    assert_eq!(workspace("web@2", Some(3)).state(idle, Some(&jobs)), WorkspaceState::Active);
+    assert_eq!(workspace("old-job@tmp", Some(3)).state(idle, Some(&jobs)), WorkspaceState::Orphaned);
+}
+
+#[test]
+fn only_directories_directly_below_the_root_are_workspaces() {
+    let root = std::env::temp_dir().join(format!("devpurge-workspaces-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("b@2/src")).unwrap();
+    fs::create_dir_all(root.join("a")).unwrap();
+    fs::write(root.join("notes.txt"), b"").unwrap();
+
+    assert_eq!(list_workspaces(&root).unwrap(), vec![root.join("a"), root.join("b@2")]);
+    fs::remove_dir_all(&root).unwrap();
+}
@@ -0,0 +1,38 @@
+use devpurge::volume::{parse_proc_mounts, VolumeTable};
+use devpurge::wsl::{is_wsl_kernel, windows_drive_of};
+use std::path::Path;
+
+const WSL2_MOUNTS: &str = "\
+/dev/sdc / ext4 rw,relatime 0 0
+C:\\134 /mnt/c 9p rw,noatime,dirsync,aname=drvfs;path=C:\\;uid=1000 0 0
+D:\\134 /mnt/d 9p rw,noatime,aname=drvfs;path=D:\\ 0 0
+tmpfs /mnt/wsl tmpfs rw,relatime 0 0
+";
+
+const WSL1_MOUNTS: &str = "\
+rootfs / lxfs rw,noatime 0 0
+C: /mnt/c drvfs rw,noatime,uid=1000 0 0
+";
+
+#[test]
+fn recognizes_wsl_kernels() {
+    assert!(is_wsl_kernel("Linux version 5.15.133.1-microsoft-standard-WSL2 (root@1c602f52c2e4)"));
+    assert!(is_wsl_kernel("Linux version 4.4.0-19041-Microsoft (Microsoft@Microsoft.com)"));
+    assert!(!is_wsl_kernel("Linux version 6.8.0-45-generic (buildd@lcy02-amd64-075)"));
+}
+
+#[test]
+fn windows_drives_through_9p_are_detected() {
+    let volumes = VolumeTable::from_mounts(parse_proc_mounts(WSL2_MOUNTS));
+    let drive = windows_drive_of(&volumes, Path::new("/mnt/c/Users/me/src")).unwrap();
+    assert_eq!(drive.mount_point, Path::new("/mnt/c"));
+    assert!(windows_drive_of(&volumes, Path::new("/home/me/src")).is_none());
+    assert!(windows_drive_of(&volumes, Path::new("/mnt/wsl/shared")).is_none());
+}
+
+#[test]
+fn windows_drives_through_drvfs_are_detected() {
+    let volumes = VolumeTable::from_mounts(parse_proc_mounts(WSL1_MOUNTS));
+    assert!(windows_drive_of(&volumes, Path::new("/mnt/c/projects")).is_some());
+    assert!(windows_drive_of(&volumes, Path::new("/home/me")).is_none());
+}